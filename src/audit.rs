@@ -0,0 +1,137 @@
+//! Append-only JSON lines audit log of executed statements.
+//!
+//! Every line carries the SHA-256 of the line before it (`prev`) and of its
+//! own content (`hash`), so editing, removing or reordering lines breaks the
+//! chain from that point on, which [`verify`] reports.
+
+use crate::error::{Result, RusqlError};
+use chrono::Local;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+/// `prev` of the first line of a log.
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// One executed statement.
+pub struct AuditEntry<'a> {
+    pub user: &'a str,
+    pub host: &'a str,
+    pub database: Option<&'a str>,
+    pub statement: &'a str,
+    pub duration: Duration,
+    pub rows_affected: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// An audit log opened for appending.
+pub struct AuditLog {
+    file: File,
+    /// Hash of the last line, chained into the next one.
+    previous: String,
+    /// Record the SHA-256 of statements instead of their text.
+    hash_statements: bool,
+}
+
+impl AuditLog {
+    /// Open `path`, creating it if needed and continuing the chain of the
+    /// lines already in it.
+    pub fn open(path: &Path, hash_statements: bool) -> Result<Self> {
+        let previous = match fs::read_to_string(path) {
+            Ok(contents) => match contents.lines().rfind(|line| !line.trim().is_empty()) {
+                Some(line) => serde_json::from_str::<Value>(line).ok()
+                    .and_then(|line| line.get("hash")?.as_str().map(str::to_string))
+                    .ok_or_else(|| RusqlError::Data(format!("The last line of {} is not an audit record", path.display())))?,
+                None => GENESIS.to_string(),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => GENESIS.to_string(),
+            Err(e) => return Err(e.into()),
+        };
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(AuditLog { file, previous, hash_statements })
+    }
+
+    /// Append a line for `entry`, flushed before returning.
+    pub fn record(&mut self, entry: &AuditEntry) -> Result<()> {
+        let mut line = json!({
+            "timestamp": Local::now().to_rfc3339(),
+            "user": entry.user,
+            "host": entry.host,
+            "database": entry.database,
+            "duration_ms": entry.duration.as_secs_f64() * 1000.0,
+            "rows_affected": entry.rows_affected,
+            "error": entry.error,
+            "prev": self.previous,
+        });
+        let Value::Object(fields) = &mut line else {
+            return Ok(());
+        };
+        match self.hash_statements {
+            true => fields.insert("statement_sha256".into(), sha256(entry.statement.as_bytes()).into()),
+            false => fields.insert("statement".into(), entry.statement.into()),
+        };
+        let hash = content_hash(fields);
+        fields.insert("hash".into(), hash.clone().into());
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()?;
+        self.previous = hash;
+        Ok(())
+    }
+}
+
+/// Check the chain of an audit log, returning the number of records.
+///
+/// ```
+/// use rusql::audit::{verify, AuditEntry, AuditLog};
+/// use std::time::Duration;
+///
+/// let path = std::env::temp_dir().join(format!("rusql-audit-{}.jsonl", std::process::id()));
+/// let mut log = AuditLog::open(&path, false)?;
+/// for statement in ["SELECT 1", "DELETE FROM t WHERE id = 7"] {
+///     log.record(&AuditEntry {
+///         user: "ops", host: "db1", database: Some("shop"), statement,
+///         duration: Duration::from_millis(3), rows_affected: Some(1), error: None,
+///     })?;
+/// }
+/// assert_eq!(verify(&path)?, 2);
+///
+/// let edited = std::fs::read_to_string(&path)?.replace("id = 7", "id = 8");
+/// std::fs::write(&path, edited)?;
+/// assert!(verify(&path).is_err());
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), rusql::error::RusqlError>(())
+/// ```
+pub fn verify(path: &Path) -> Result<usize> {
+    let contents = fs::read_to_string(path)?;
+    let mut previous = GENESIS.to_string();
+    let mut records = 0;
+    for (number, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let broken = |reason: &str| RusqlError::Data(format!("{} line {}: {}", path.display(), number + 1, reason));
+        let Ok(Value::Object(mut fields)) = serde_json::from_str::<Value>(line) else {
+            return Err(broken("not an audit record"));
+        };
+        let hash = fields.remove("hash").and_then(|hash| hash.as_str().map(str::to_string));
+        if fields.get("prev").and_then(Value::as_str) != Some(previous.as_str()) {
+            return Err(broken("does not follow the line before it; lines were removed, added or reordered"));
+        }
+        if hash.as_deref() != Some(content_hash(&fields).as_str()) {
+            return Err(broken("its content does not match its hash; the line was edited"));
+        }
+        previous = hash.unwrap_or_default();
+        records += 1;
+    }
+    Ok(records)
+}
+
+/// Hash of a record's fields other than `hash`. The map keeps its keys
+/// sorted, so the serialization is the same when read back.
+fn content_hash(fields: &Map<String, Value>) -> String {
+    sha256(Value::Object(fields.clone()).to_string().as_bytes())
+}
+
+fn sha256(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
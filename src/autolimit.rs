@@ -0,0 +1,48 @@
+use crate::{MySQLClient, QueryResult};
+use rusql::error::{Result, RusqlError};
+use rusql::statements::keywords;
+
+/// Rows fetched by interactive bare SELECTs unless `auto_limit` says otherwise.
+pub const DEFAULT_AUTO_LIMIT: u64 = 1000;
+
+/// `query` with `LIMIT n` appended if it is a SELECT without a LIMIT of its own.
+///
+/// Statements where a trailing LIMIT would be invalid or change their meaning
+/// (`INTO`, locking reads, `PROCEDURE`) are left alone.
+pub fn apply(query: &str, limit: u64) -> Option<String> {
+    let words = keywords(query);
+    if words.first().map(String::as_str) != Some("SELECT") {
+        return None;
+    }
+    if words.iter().any(|w| matches!(w.as_str(), "LIMIT" | "INTO" | "FOR" | "LOCK" | "PROCEDURE")) {
+        return None;
+    }
+    // On its own line so a trailing `-- comment` cannot swallow it
+    let query = query.trim_end().trim_end_matches(';').trim_end();
+    Some(format!("{}\nLIMIT {}", query, limit))
+}
+
+/// Shown when a limited SELECT returned exactly `limit` rows, so more may exist.
+pub fn note(limit: usize) -> String {
+    format!("Stopped at the auto limit of {} rows; add a LIMIT clause or run \\autolimit off to see more", limit)
+}
+
+/// `\autolimit [n|off]`: show or change the row cap for bare SELECTs.
+pub fn set(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    client.auto_limit = match args.to_lowercase().as_str() {
+        "" => {
+            match client.auto_limit {
+                Some(limit) => client.notice(&format!("Bare SELECTs are limited to {} rows", limit)),
+                None => client.notice("Auto limit is off"),
+            }
+            return Ok(None);
+        }
+        "off" | "0" => None,
+        n => Some(n.parse().map_err(|_| RusqlError::Command("Usage: \\autolimit [rows|off]".into()))?),
+    };
+    match client.auto_limit {
+        Some(limit) => client.notice(&format!("Bare SELECTs are now limited to {} rows", limit)),
+        None => client.notice("Auto limit disabled"),
+    }
+    Ok(None)
+}
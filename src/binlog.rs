@@ -0,0 +1,222 @@
+//! Reading row changes from the binary log over the replication protocol.
+//!
+//! A [`BinlogReader`] registers as a replica on its own connection and turns
+//! the event stream into [`Entry`] values: one per inserted, updated or
+//! deleted row, plus statement-based queries and commits. Row images carry
+//! column names when the server logs them (`binlog_row_metadata=FULL`);
+//! otherwise columns are named by position from zero, `@0`, `@1`, ...
+
+use crate::error::{Result, RusqlError};
+use chrono::{DateTime, Utc};
+use mysql::binlog::events::{EventData, RowsEventData};
+use mysql::binlog::row::BinlogRow;
+use mysql::prelude::*;
+use mysql::{BinlogDumpFlags, BinlogRequest, BinlogStream, Conn, Opts, Value};
+use std::collections::VecDeque;
+
+/// Seconds between heartbeats sent by the server while nothing is logged.
+const HEARTBEAT_SECS: u64 = 1;
+
+/// Column values of one row image, by column name.
+pub type Image = Vec<(String, Value)>;
+
+/// What a binlog event did.
+#[derive(Debug, Clone)]
+pub enum Change {
+    Insert { db: String, table: String, after: Image },
+    Update { db: String, table: String, before: Image, after: Image },
+    Delete { db: String, table: String, before: Image },
+    /// A statement logged as text: DDL, or DML under statement-based logging.
+    Query { db: String, query: String },
+    Commit,
+    /// Nothing was logged for a while; lets a follower check for Ctrl-C.
+    Idle,
+}
+
+impl Change {
+    /// Database and table of a row change.
+    pub fn table(&self) -> Option<(&str, &str)> {
+        match self {
+            Change::Insert { db, table, .. } | Change::Update { db, table, .. } | Change::Delete { db, table, .. } => {
+                Some((db, table))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// One change with where and when it was logged.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub time: DateTime<Utc>,
+    pub file: String,
+    /// Position of the event following this one in `file`.
+    pub position: u64,
+    pub change: Change,
+}
+
+/// Binlog events from a starting file and position, as row changes.
+///
+/// ```no_run
+/// # fn run(opts: mysql::Opts) -> rusql::error::Result<()> {
+/// use rusql::binlog::{BinlogReader, Change};
+/// for entry in BinlogReader::open(opts, "binlog.000042", 4, false)? {
+///     if let Change::Delete { db, table, before } = entry?.change {
+///         println!("{}.{}: {:?}", db, table, before);
+///     }
+/// }
+/// # Ok(()) }
+/// ```
+pub struct BinlogReader {
+    stream: BinlogStream,
+    file: String,
+    follow: bool,
+    pending: VecDeque<Entry>,
+}
+
+impl BinlogReader {
+    /// Start reading `file` at `position` (4 is the first event). Without
+    /// `follow`, reading stops at the end of `file`; with it, the reader
+    /// waits for new events and continues into the following files.
+    pub fn open<O: Into<Opts>>(opts: O, file: &str, position: u64, follow: bool) -> Result<BinlogReader> {
+        Ok(BinlogReader {
+            stream: request(opts, file, position, follow)?,
+            file: file.to_string(),
+            follow,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Entries of one event; `None` once a non-following reader leaves its file.
+    fn read_event(&mut self) -> Option<Result<Vec<Entry>>> {
+        let event = match self.stream.next()? {
+            Ok(event) => event,
+            Err(e) => return Some(Err(e.into())),
+        };
+        let header = event.header();
+        let time = DateTime::from_timestamp(header.timestamp() as i64, 0).unwrap_or_default();
+        let position = header.log_pos() as u64;
+        let entry = |file: &str, change| Entry { time, file: file.to_string(), position, change };
+
+        let data = match event.read_data() {
+            Ok(Some(data)) => data,
+            Ok(None) => return Some(Ok(Vec::new())),
+            Err(e) => return Some(Err(e.into())),
+        };
+        let entries = match data {
+            EventData::RotateEvent(rotate) => {
+                let name = rotate.name().into_owned();
+                if name != self.file {
+                    if !self.follow {
+                        return None;
+                    }
+                    self.file = name;
+                }
+                Vec::new()
+            }
+            EventData::HeartbeatEvent => vec![entry(&self.file, Change::Idle)],
+            EventData::QueryEvent(query) => {
+                let change = Change::Query { db: query.schema().into_owned(), query: query.query().into_owned() };
+                vec![entry(&self.file, change)]
+            }
+            EventData::XidEvent(_) => vec![entry(&self.file, Change::Commit)],
+            EventData::RowsEvent(rows) => match self.rows(&rows) {
+                Ok(changes) => changes.into_iter().map(|change| entry(&self.file, change)).collect(),
+                Err(e) => return Some(Err(e)),
+            },
+            _ => Vec::new(),
+        };
+        Some(Ok(entries))
+    }
+
+    /// One change per row of a rows event.
+    fn rows(&self, rows: &RowsEventData) -> Result<Vec<Change>> {
+        let tme = self.stream.get_tme(rows.table_id())
+            .ok_or_else(|| RusqlError::Data(format!("No table map for table id {} in the binlog", rows.table_id())))?;
+        let db = tme.database_name().into_owned();
+        let table = tme.table_name().into_owned();
+
+        let mut changes = Vec::new();
+        for row in rows.rows(tme) {
+            let (before, after) = row?;
+            let (db, table) = (db.clone(), table.clone());
+            changes.push(match (before, after) {
+                (None, Some(after)) => Change::Insert { db, table, after: image(after)? },
+                (Some(before), Some(after)) => Change::Update { db, table, before: image(before)?, after: image(after)? },
+                (Some(before), None) => Change::Delete { db, table, before: image(before)? },
+                (None, None) => continue,
+            });
+        }
+        Ok(changes)
+    }
+}
+
+impl Iterator for BinlogReader {
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            match self.read_event()? {
+                Ok(entries) => self.pending.extend(entries),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+fn image(row: BinlogRow) -> Result<Image> {
+    let names: Vec<String> = row.columns_ref().iter().map(|column| column.name_str().into_owned()).collect();
+    names.into_iter()
+        .zip(row.unwrap())
+        .map(|(name, value)| {
+            let value = Value::try_from(value)
+                .map_err(|e| RusqlError::Data(format!("Column {} in the binlog: {}", name, e)))?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// Register as a replica and ask for the events of `file` from `position`.
+fn request<O: Into<Opts>>(opts: O, file: &str, position: u64, follow: bool) -> Result<BinlogStream> {
+    let mut conn = Conn::new(opts)?;
+    // Heartbeats keep a follower responsive while nothing is logged (nanoseconds)
+    conn.query_drop(format!("SET @master_heartbeat_period = {}", HEARTBEAT_SECS * 1_000_000_000))?;
+
+    let mut request = BinlogRequest::new(dump_server_id())
+        .with_filename(file.as_bytes().to_vec())
+        .with_pos(position);
+    if !follow {
+        request = request.with_flags(BinlogDumpFlags::BINLOG_DUMP_NON_BLOCK);
+    }
+    Ok(conn.get_binlog_stream(request)?)
+}
+
+/// When the server started writing `file`: the time of its first event.
+pub fn started_at<O: Into<Opts>>(opts: O, file: &str) -> Result<Option<DateTime<Utc>>> {
+    for event in request(opts, file, 4, false)? {
+        // The rotate event announcing the file carries no time
+        let timestamp = event?.header().timestamp();
+        if timestamp > 0 {
+            return Ok(DateTime::from_timestamp(timestamp as i64, 0));
+        }
+    }
+    Ok(None)
+}
+
+/// Server id the reader registers with; it must differ from every real replica's.
+fn dump_server_id() -> u32 {
+    0xFFFF_0000 | (std::process::id() & 0xFFFF)
+}
+
+/// File and position the server is currently writing, for readers that start
+/// at the end of the log.
+pub fn current_position(conn: &mut Conn) -> Result<(String, u64)> {
+    // SHOW MASTER STATUS was renamed in MySQL 8.4
+    let status: Option<mysql::Row> = conn.query_first("SHOW BINARY LOG STATUS")
+        .or_else(|_| conn.query_first("SHOW MASTER STATUS"))?;
+    let status = status.ok_or_else(|| RusqlError::Command("Binary logging is disabled on this server".into()))?;
+    let file = status.get_opt::<String, _>(0).and_then(|file| file.ok()).unwrap_or_default();
+    let position = status.get_opt::<u64, _>(1).and_then(|position| position.ok()).unwrap_or(4);
+    Ok((file, position))
+}
@@ -0,0 +1,220 @@
+use crate::{history, interrupt, MySQLClient, QueryResult};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use mysql::{Params, Value};
+use rusql::binlog::{self, BinlogReader, Change, Entry, Image};
+use rusql::error::{Result, RusqlError};
+
+const USAGE: &str = "Usage: \\binlog [tail | show <file>] [--since <datetime>] [--until <datetime>] \
+                     [--db <name>] [--table <[db.]name>] [--limit <events>]";
+
+/// Events listed by `\binlog show` unless `--limit` says otherwise.
+const DEFAULT_LIMIT: usize = 1000;
+
+/// Options shared by the binlog commands.
+#[derive(Default)]
+pub struct Filter {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub db: Option<String>,
+    pub table: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl Filter {
+    /// Whether an entry is in the time window and touches the chosen database
+    /// and table. Statements are only matched by database, commits only
+    /// without a filter.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        if self.since.is_some_and(|since| entry.time < since) || self.until.is_some_and(|until| entry.time > until) {
+            return false;
+        }
+        match &entry.change {
+            Change::Idle => false,
+            Change::Commit => self.db.is_none() && self.table.is_none(),
+            Change::Query { db, .. } => self.table.is_none() && self.db.as_ref().is_none_or(|name| name == db),
+            change => change.table().is_some_and(|(db, table)| {
+                self.db.as_ref().is_none_or(|name| name == db) && self.table.as_ref().is_none_or(|name| name == table)
+            }),
+        }
+    }
+}
+
+/// `\binlog`: list the binary logs; `\binlog tail`: print new events as they
+/// are logged, until Ctrl-C; `\binlog show <file>`: events of one log file.
+pub fn binlog(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let (action, rest) = args.split_once(char::is_whitespace)
+        .map(|(action, rest)| (action, rest.trim()))
+        .unwrap_or((args, ""));
+
+    match action {
+        "" => client.query_result("SHOW BINARY LOGS", Params::Empty).map(Some),
+        "tail" => tail(client, &parse_filter(rest)?),
+        "show" => {
+            let (file, rest) = rest.split_once(char::is_whitespace)
+                .map(|(file, rest)| (file, rest.trim()))
+                .unwrap_or((rest, ""));
+            if file.is_empty() || file.starts_with("--") {
+                return Err(RusqlError::Command(USAGE.into()));
+            }
+            show(client, file, &parse_filter(rest)?)
+        }
+        _ => Err(RusqlError::Command(USAGE.into())),
+    }
+}
+
+fn tail(client: &mut MySQLClient, filter: &Filter) -> Result<Option<QueryResult>> {
+    let (file, position) = binlog::current_position(&mut client.conn)?;
+    client.notice(&format!("Following {} from position {}; Ctrl-C to stop", file, position));
+
+    for entry in BinlogReader::open(client.conn_opts.clone(), &file, position, true)? {
+        if interrupt::interrupted() {
+            break;
+        }
+        let entry = entry?;
+        if !filter.matches(&entry) {
+            continue;
+        }
+        let (event, table, detail) = describe(client, &entry.change);
+        println!(
+            "{} {} {} {}",
+            client.paint(&entry.time.with_timezone(&Local).format("%H:%M:%S").to_string(), client.theme.notice),
+            event,
+            table,
+            detail
+        );
+    }
+    Ok(None)
+}
+
+fn show(client: &mut MySQLClient, file: &str, filter: &Filter) -> Result<Option<QueryResult>> {
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT);
+    let mut rows = Vec::new();
+    for entry in BinlogReader::open(client.conn_opts.clone(), file, 4, false)? {
+        let entry = entry?;
+        if filter.until.is_some_and(|until| entry.time > until) || rows.len() == limit {
+            break;
+        }
+        if !filter.matches(&entry) {
+            continue;
+        }
+        let (event, table, detail) = describe(client, &entry.change);
+        rows.push(vec![
+            entry.time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string(),
+            entry.position.to_string(),
+            event.to_string(),
+            table,
+            detail,
+        ]);
+    }
+
+    let mut summary = format!("{} events from {}", rows.len(), file);
+    if rows.len() == limit {
+        summary.push_str(&format!("; stopped at {} (use --limit for more)", limit));
+    }
+    let headers = ["Time", "End position", "Event", "Table", "Change"];
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+/// Event name, table and a one-line summary of a change. Values of
+/// sensitive columns are masked and statements touching them left out.
+fn describe(client: &MySQLClient, change: &Change) -> (&'static str, String, String) {
+    let masked = |db: &str, table: &str, image: &Image| -> Image {
+        let names: Vec<&str> = image.iter().map(|(name, _)| name.as_str()).collect();
+        let mut values = vec![image.iter().map(|(_, value)| value.clone()).collect()];
+        client.sensitive_rules.columns(Some(db), table, &names).apply(&mut values);
+        names.iter().map(|name| name.to_string()).zip(values.remove(0)).collect()
+    };
+    match change {
+        Change::Insert { db, table, after } => {
+            ("INSERT", format!("{}.{}", db, table), format!("({})", assignments(&masked(db, table, after))))
+        }
+        Change::Delete { db, table, before } => {
+            ("DELETE", format!("{}.{}", db, table), format!("({})", assignments(&masked(db, table, before))))
+        }
+        Change::Update { db, table, before, after } => {
+            let (shown_before, shown_after) = (masked(db, table, before), masked(db, table, after));
+            // The first column, usually the primary key, identifies the row
+            let key = shown_before.first().map(|(name, value)| format!("{}={}: ", name, literal(value))).unwrap_or_default();
+            // Compared unmasked, as different values can mask the same
+            let changed: Vec<String> = after.iter().zip(&shown_after)
+                .filter_map(|((name, new), (_, shown_new))| {
+                    let index = before.iter().position(|(column, _)| column == name)?;
+                    (before[index].1 != *new)
+                        .then(|| format!("{} {} -> {}", name, literal(&shown_before[index].1), literal(shown_new)))
+                })
+                .collect();
+            ("UPDATE", format!("{}.{}", db, table), format!("{}{}", key, changed.join(", ")))
+        }
+        Change::Query { db, query } if history::is_sensitive(query, &client.sensitive_columns) => {
+            ("QUERY", db.clone(), "<redacted>".to_string())
+        }
+        Change::Query { db, query } => ("QUERY", db.clone(), query.clone()),
+        Change::Commit => ("COMMIT", String::new(), String::new()),
+        Change::Idle => ("", String::new(), String::new()),
+    }
+}
+
+fn assignments(image: &Image) -> String {
+    image.iter()
+        .map(|(name, value)| format!("{}={}", name, literal(value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A value as it would be written in SQL.
+pub fn literal(value: &Value) -> String {
+    value.as_sql(false)
+}
+
+/// Options after the subcommand, e.g. `--since 2024-05-01 10:00 --table orders`.
+/// A value runs up to the next `--` option, so it may contain spaces.
+pub fn parse_filter(args: &str) -> Result<Filter> {
+    let mut filter = Filter::default();
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let mut i = 0;
+    while i < words.len() {
+        let end = words[i + 1..].iter().position(|w| w.starts_with("--")).map_or(words.len(), |n| i + 1 + n);
+        let value = words[i + 1..end].join(" ");
+        let value = value.trim_matches(|c| c == '\'' || c == '"');
+        if value.is_empty() {
+            return Err(RusqlError::Command(USAGE.into()));
+        }
+        match words[i] {
+            "--since" => filter.since = Some(parse_time(value)?),
+            "--until" => filter.until = Some(parse_time(value)?),
+            "--db" => filter.db = Some(value.replace('`', "")),
+            "--table" => match value.replace('`', "").split_once('.') {
+                Some((db, table)) => {
+                    filter.db = Some(db.to_string());
+                    filter.table = Some(table.to_string());
+                }
+                None => filter.table = Some(value.replace('`', "")),
+            },
+            "--limit" => {
+                filter.limit = Some(value.parse().ok().filter(|&n| n > 0).ok_or_else(|| RusqlError::Command(USAGE.into()))?);
+            }
+            _ => return Err(RusqlError::Command(USAGE.into())),
+        }
+        i = end;
+    }
+    Ok(filter)
+}
+
+/// A local date and time such as `2024-05-01 10:00[:00]`, a date, or a time
+/// of day today such as `10:00`.
+pub fn parse_time(text: &str) -> Result<DateTime<Utc>> {
+    let today = Local::now().date_naive();
+    let local = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"].iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0)))
+        .or_else(|| {
+            ["%H:%M:%S", "%H:%M"].iter()
+                .find_map(|format| NaiveTime::parse_from_str(text, format).ok())
+                .map(|time| today.and_time(time))
+        })
+        .ok_or_else(|| RusqlError::Command(format!("Invalid time '{}', e.g. '2024-05-01 10:00' or '10:00'", text)))?;
+    Local.from_local_datetime(&local)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| RusqlError::Command(format!("'{}' does not exist in the local time zone", text)))
+}
@@ -0,0 +1,224 @@
+use mysql::prelude::*;
+use mysql::{Conn, Params, Row, Value};
+use crate::error::{Result, RusqlError};
+
+/// Upper bound on placeholders in a single prepared statement.
+const MAX_PLACEHOLDERS: usize = 65_535;
+
+/// Bytes reserved for the statement text and protocol framing of a batch.
+const PACKET_OVERHEAD: usize = 1024;
+
+/// Progress reported after every batch written by [`BulkInsert`].
+#[derive(Debug, Clone, Copy)]
+pub struct BulkProgress {
+    pub rows: u64,
+    pub batches: u64,
+}
+
+/// What a [`BulkInsert`] does with a row whose key already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnConflict {
+    /// Fail the batch with a duplicate key error.
+    #[default]
+    Fail,
+    /// Keep the existing row (`INSERT IGNORE`).
+    Skip,
+    /// Delete the existing row and insert the new one (`REPLACE`).
+    Replace,
+    /// Overwrite the existing row's columns (`ON DUPLICATE KEY UPDATE`).
+    Update,
+}
+
+/// Batched multi-row `INSERT` (or `REPLACE`) using prepared statements.
+///
+/// Rows are grouped so that each batch stays below `max_allowed_packet` (read
+/// from the server unless set explicitly) and below the placeholder limit of a
+/// prepared statement.
+///
+/// ```no_run
+/// # fn run(conn: &mut mysql::Conn) -> Result<(), Box<dyn std::error::Error>> {
+/// let rows = vec![(1, "alice"), (2, "bob")];
+/// let inserted = rusql::bulk::BulkInsert::new("users", &["id", "name"])
+///     .on_progress(|p| eprintln!("{} rows", p.rows))
+///     .execute(conn, rows)?;
+/// # Ok(()) }
+/// ```
+pub struct BulkInsert<'a> {
+    table: String,
+    columns: Vec<String>,
+    max_rows: usize,
+    max_packet: Option<usize>,
+    on_conflict: OnConflict,
+    progress: Option<Box<dyn FnMut(BulkProgress) + 'a>>,
+}
+
+impl<'a> BulkInsert<'a> {
+    pub fn new<S: AsRef<str>>(table: &str, columns: &[S]) -> Self {
+        BulkInsert {
+            table: table.to_string(),
+            columns: columns.iter().map(|c| c.as_ref().to_string()).collect(),
+            max_rows: 1000,
+            max_packet: None,
+            on_conflict: OnConflict::Fail,
+            progress: None,
+        }
+    }
+
+    /// Cap the number of rows sent in one statement.
+    pub fn max_rows_per_batch(mut self, rows: usize) -> Self {
+        self.max_rows = rows.max(1);
+        self
+    }
+
+    /// Use this packet size instead of querying `@@max_allowed_packet`.
+    pub fn max_packet_size(mut self, bytes: usize) -> Self {
+        self.max_packet = Some(bytes);
+        self
+    }
+
+    /// Write `REPLACE` instead of `INSERT`, overwriting rows with the same key.
+    pub fn replace(mut self, enabled: bool) -> Self {
+        self.on_conflict = if enabled { OnConflict::Replace } else { OnConflict::Fail };
+        self
+    }
+
+    /// How rows whose key already exists are handled.
+    pub fn on_conflict(mut self, mode: OnConflict) -> Self {
+        self.on_conflict = mode;
+        self
+    }
+
+    /// Called after every batch with the running totals.
+    pub fn on_progress<F: FnMut(BulkProgress) + 'a>(mut self, callback: F) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Insert every row, returning the number of affected rows.
+    pub fn execute<C, I, R>(mut self, conn: &mut C, rows: I) -> Result<u64>
+    where
+        C: Queryable,
+        I: IntoIterator<Item = R>,
+        R: Into<Params>,
+    {
+        if self.columns.is_empty() {
+            return Err(RusqlError::Command("bulk insert needs at least one column".into()));
+        }
+
+        let max_packet = match self.max_packet {
+            Some(bytes) => bytes,
+            None => conn.query_first::<usize, _>("SELECT @@max_allowed_packet")?.unwrap_or(4 << 20),
+        };
+        let budget = max_packet.saturating_sub(PACKET_OVERHEAD).max(1);
+        let max_rows = self.max_rows.min(MAX_PLACEHOLDERS / self.columns.len()).max(1);
+
+        let mut progress = BulkProgress { rows: 0, batches: 0 };
+        let mut affected = 0;
+        let mut batch: Vec<Value> = Vec::new();
+        let mut batch_rows = 0;
+        let mut batch_bytes = 0;
+
+        for row in rows {
+            let values = match row.into() {
+                Params::Positional(values) => values,
+                Params::Empty => Vec::new(),
+                Params::Named(_) => {
+                    return Err(RusqlError::Command("bulk insert rows must use positional values".into()));
+                }
+            };
+            if values.len() != self.columns.len() {
+                return Err(RusqlError::Command(format!(
+                    "row has {} values but {} columns were given",
+                    values.len(),
+                    self.columns.len()
+                )));
+            }
+
+            let row_bytes: usize = values.iter().map(value_size).sum();
+            if batch_rows > 0 && (batch_rows == max_rows || batch_bytes + row_bytes > budget) {
+                affected += self.flush(conn, &mut batch, batch_rows, &mut progress)?;
+                batch_rows = 0;
+                batch_bytes = 0;
+            }
+
+            batch.extend(values);
+            batch_rows += 1;
+            batch_bytes += row_bytes;
+        }
+
+        if batch_rows > 0 {
+            affected += self.flush(conn, &mut batch, batch_rows, &mut progress)?;
+        }
+
+        Ok(affected)
+    }
+
+    fn flush<C: Queryable>(
+        &mut self,
+        conn: &mut C,
+        batch: &mut Vec<Value>,
+        rows: usize,
+        progress: &mut BulkProgress,
+    ) -> Result<u64> {
+        let stmt = conn.prep(self.statement(rows))?;
+        let result = conn.exec_iter(&stmt, std::mem::take(batch))?;
+        let affected = result.affected_rows();
+        drop(result);
+
+        progress.rows += rows as u64;
+        progress.batches += 1;
+        if let Some(callback) = self.progress.as_mut() {
+            callback(*progress);
+        }
+
+        Ok(affected)
+    }
+
+    fn statement(&self, rows: usize) -> String {
+        let columns: Vec<String> = self.columns.iter().map(|c| quote_identifier(c)).collect();
+        let placeholders = format!("({})", vec!["?"; self.columns.len()].join(", "));
+        let verb = match self.on_conflict {
+            OnConflict::Fail | OnConflict::Update => "INSERT",
+            OnConflict::Skip => "INSERT IGNORE",
+            OnConflict::Replace => "REPLACE",
+        };
+        let mut statement = format!(
+            "{} INTO {} ({}) VALUES {}",
+            verb,
+            quote_table(&self.table),
+            columns.join(", "),
+            vec![placeholders; rows].join(", ")
+        );
+        if self.on_conflict == OnConflict::Update {
+            let updates: Vec<String> = columns.iter().map(|c| format!("{c} = VALUES({c})", c = c)).collect();
+            statement.push_str(&format!(" ON DUPLICATE KEY UPDATE {}", updates.join(", ")));
+        }
+        statement
+    }
+}
+
+/// Quote an identifier with backticks, escaping embedded backticks.
+pub fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// Quote a possibly schema-qualified `db.table` name.
+pub fn quote_table(name: &str) -> String {
+    name.split('.').map(quote_identifier).collect::<Vec<_>>().join(".")
+}
+
+/// Primary key columns of `table`, in key order; empty without one.
+pub fn primary_key(conn: &mut Conn, table: &str) -> Result<Vec<String>> {
+    let rows: Vec<Row> = conn.query(format!("SHOW KEYS FROM {} WHERE Key_name = 'PRIMARY'", quote_table(table)))?;
+    Ok(rows.iter().filter_map(|row| row.get::<String, _>("Column_name")).collect())
+}
+
+/// Rough size of a value on the wire, used to keep batches under the packet limit.
+pub fn value_size(value: &Value) -> usize {
+    match value {
+        Value::NULL => 1,
+        Value::Bytes(bytes) => bytes.len() + 9,
+        Value::Date(..) | Value::Time(..) => 13,
+        _ => 9,
+    }
+}
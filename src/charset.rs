@@ -0,0 +1,48 @@
+use rusql::error::RusqlError;
+
+/// Server error for a character set it does not know.
+const UNKNOWN_CHARACTER_SET: u16 = 1115;
+
+/// Server error for a collation it does not know.
+const UNKNOWN_COLLATION: u16 = 1273;
+
+/// Server error for a collation of another character set.
+const COLLATION_CHARSET_MISMATCH: u16 = 1253;
+
+/// Parse a character set or collation name, such as `utf8mb4` or
+/// `utf8mb4_0900_ai_ci`.
+pub fn parse_name(s: &str) -> Result<String, String> {
+    let valid = !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("invalid character set or collation `{}`", s));
+    }
+    Ok(s.to_string())
+}
+
+/// Statement run on every new connection to switch the character set of the
+/// client, connection and results, and optionally the collation.
+pub fn set_names(charset: &str, collation: Option<&str>) -> String {
+    match collation {
+        Some(collation) => format!("SET NAMES '{}' COLLATE '{}'", charset, collation),
+        None => format!("SET NAMES '{}'", charset),
+    }
+}
+
+/// Replace the server's bare errors about the names with what was given.
+pub fn explain(error: RusqlError, charset: &str, collation: Option<&str>) -> RusqlError {
+    match (error, collation) {
+        (RusqlError::Sql { code: UNKNOWN_CHARACTER_SET, .. }, _) => RusqlError::Config(format!(
+            "Unknown character set '{}': SHOW CHARACTER SET lists the server's",
+            charset,
+        )),
+        (RusqlError::Sql { code: UNKNOWN_COLLATION, .. }, Some(collation)) => RusqlError::Config(format!(
+            "Unknown collation '{}': SHOW COLLATION WHERE Charset = '{}' lists the server's",
+            collation, charset,
+        )),
+        (RusqlError::Sql { code: COLLATION_CHARSET_MISMATCH, .. }, Some(collation)) => RusqlError::Config(format!(
+            "Collation '{}' is not one of character set '{}'",
+            collation, charset,
+        )),
+        (other, _) => other,
+    }
+}
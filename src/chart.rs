@@ -0,0 +1,197 @@
+use crate::expand::{last_result, LastResult};
+use crate::{MySQLClient, QueryResult};
+use colored::Color;
+use rusql::error::{Result, RusqlError};
+
+const USAGE: &str = "Usage: \\chart bar|line";
+
+/// Colors of successive series.
+const PALETTE: &[Color] = &[Color::BrightGreen, Color::BrightCyan, Color::BrightYellow, Color::BrightMagenta, Color::BrightBlue, Color::BrightRed];
+
+/// Rows of braille characters in a line chart.
+const LINE_HEIGHT: usize = 12;
+
+/// Width used when stdout is not a terminal.
+const DEFAULT_WIDTH: usize = 80;
+
+/// A numeric column of the last result; NULLs and blanks are gaps.
+struct Series {
+    name: String,
+    values: Vec<Option<f64>>,
+}
+
+/// `\chart bar|line`: plot the numeric columns of the last result, with the
+/// first column as labels or x axis.
+pub fn chart(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let last = last_result(client)?;
+    if last.columns.len() < 2 {
+        return Err(RusqlError::Command("Charts need a label column followed by numeric columns".into()));
+    }
+    let labels: Vec<String> = last.rows.iter()
+        .map(|row| row[0].clone().unwrap_or_else(|| "NULL".to_string()))
+        .collect();
+    let series = numeric_series(last);
+    if series.is_empty() {
+        return Err(RusqlError::Command("The last result has no numeric columns after the first".into()));
+    }
+    let width = term_size::dimensions_stdout().map_or(DEFAULT_WIDTH, |(width, _)| width);
+
+    let lines = match args {
+        "bar" => bar_chart(&labels, &series, width),
+        "line" => line_chart(&labels, &series, width),
+        _ => return Err(RusqlError::Command(USAGE.into())),
+    };
+    for (line, color) in lines {
+        println!("{}", match color {
+            Some(i) => client.paint(&line, Some(PALETTE[i % PALETTE.len()])),
+            None => line,
+        });
+    }
+    if series.len() > 1 {
+        let legend: Vec<String> = series.iter()
+            .enumerate()
+            .map(|(i, s)| client.paint(&format!("■ {}", s.name), Some(PALETTE[i % PALETTE.len()])))
+            .collect();
+        println!("{}", legend.join("  "));
+    }
+    Ok(None)
+}
+
+/// Columns after the first whose values all parse as numbers.
+fn numeric_series(last: &LastResult) -> Vec<Series> {
+    (1..last.columns.len())
+        .filter_map(|i| {
+            let values: Option<Vec<Option<f64>>> = last.rows.iter()
+                .map(|row| match row[i].as_deref() {
+                    None | Some("") => Some(None),
+                    Some(text) => text.parse::<f64>().ok().filter(|v| v.is_finite()).map(Some),
+                })
+                .collect();
+            let values = values?;
+            values.iter().any(Option::is_some).then(|| Series { name: last.columns[i].clone(), values })
+        })
+        .collect()
+}
+
+/// One line per label and series: the label, a bar and the value. Bars start
+/// at zero, so negative values grow to the left of the axis.
+fn bar_chart(labels: &[String], series: &[Series], width: usize) -> Vec<(String, Option<usize>)> {
+    let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0).min(width / 3);
+    let value_texts: Vec<Vec<String>> = series.iter()
+        .map(|s| s.values.iter().map(|v| v.map_or_else(|| "NULL".to_string(), |v| v.to_string())).collect())
+        .collect();
+    let value_width = value_texts.iter().flatten().map(String::len).max().unwrap_or(0);
+    let all = series.iter().flat_map(|s| s.values.iter().flatten());
+    let (min, max) = all.fold((0f64, 0f64), |(lo, hi), v| (lo.min(*v), hi.max(*v)));
+    let bar_width = width.saturating_sub(label_width + value_width + 4).max(10);
+    let span = (max - min).max(f64::MIN_POSITIVE);
+    let zero = ((-min / span) * bar_width as f64).round() as usize;
+
+    let mut lines = Vec::new();
+    for (row, label) in labels.iter().enumerate() {
+        for (i, s) in series.iter().enumerate() {
+            let label: String = if i == 0 { label.chars().take(label_width).collect() } else { String::new() };
+            let bar = match s.values[row] {
+                Some(value) => {
+                    let end = (((value - min) / span) * bar_width as f64).round() as usize;
+                    let (from, to) = if end < zero { (end, zero) } else { (zero, end) };
+                    format!("{}{}{}", " ".repeat(from), "█".repeat(to - from), " ".repeat(bar_width - to))
+                }
+                None => " ".repeat(bar_width),
+            };
+            lines.push((
+                format!("{:>lw$} │{} {:>vw$}", label, bar, value_texts[i][row], lw = label_width, vw = value_width),
+                (series.len() > 1).then_some(i),
+            ));
+        }
+    }
+    lines
+}
+
+/// Braille line chart: every character cell holds 2x4 dots, so the plot has
+/// twice the horizontal and four times the vertical resolution of the text.
+fn line_chart(labels: &[String], series: &[Series], width: usize) -> Vec<(String, Option<usize>)> {
+    let all = series.iter().flat_map(|s| s.values.iter().flatten());
+    let (min, max) = all.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(*v), hi.max(*v)));
+    let (top, bottom) = (axis_label(max), axis_label(min));
+    let axis_width = top.len().max(bottom.len());
+    let columns = width.saturating_sub(axis_width + 2).max(10);
+    let (dots_x, dots_y) = (columns * 2, LINE_HEIGHT * 4);
+
+    // Dots per cell, and the series that drew last in each cell
+    let mut cells = vec![vec![0u8; columns]; LINE_HEIGHT];
+    let mut owners = vec![vec![None; columns]; LINE_HEIGHT];
+    let points = labels.len().max(2) - 1;
+    let span = (max - min).max(f64::MIN_POSITIVE);
+    for (i, s) in series.iter().enumerate() {
+        let mut previous: Option<(usize, usize)> = None;
+        for (index, value) in s.values.iter().enumerate() {
+            let Some(value) = value else {
+                previous = None;
+                continue;
+            };
+            let x = index * (dots_x - 1) / points;
+            let y = (dots_y - 1) - (((value - min) / span) * (dots_y - 1) as f64).round() as usize;
+            for (x, y) in segment(previous.unwrap_or((x, y)), (x, y)) {
+                let (cx, cy) = (x / 2, y / 4);
+                cells[cy][cx] |= braille_bit(x % 2, y % 4);
+                owners[cy][cx] = Some(i);
+            }
+            previous = Some((x, y));
+        }
+    }
+
+    let mut lines = Vec::new();
+    for (row, (dots, owners)) in cells.iter().zip(&owners).enumerate() {
+        let axis = match row {
+            0 => top.clone(),
+            r if r == LINE_HEIGHT - 1 => bottom.clone(),
+            _ => String::new(),
+        };
+        let plot: String = dots.iter().map(|&bits| char::from_u32(0x2800 + u32::from(bits)).unwrap_or(' ')).collect();
+        // A row takes the color of its most frequent series
+        let color = (series.len() > 1).then(|| {
+            (0..series.len()).max_by_key(|i| owners.iter().filter(|o| **o == Some(*i)).count()).unwrap_or(0)
+        });
+        lines.push((format!("{:>w$} ┤{}", axis, plot, w = axis_width), color));
+    }
+    let first = labels.first().cloned().unwrap_or_default();
+    let last = labels.last().cloned().unwrap_or_default();
+    let gap = columns.saturating_sub(first.chars().count() + last.chars().count()).max(1);
+    lines.push((format!("{:w$}  {}{}{}", "", first, " ".repeat(gap), last, w = axis_width), None));
+    lines
+}
+
+/// Axis values are only a guide, so two decimals are plenty.
+fn axis_label(value: f64) -> String {
+    if value.fract() == 0.0 { value.to_string() } else { format!("{:.2}", value) }
+}
+
+/// Bit of the braille dot at column `x` (0-1) and row `y` (0-3) of a cell.
+fn braille_bit(x: usize, y: usize) -> u8 {
+    const BITS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+    BITS[y][x]
+}
+
+/// Dots on the straight line from `from` to `to` (Bresenham).
+fn segment(from: (usize, usize), to: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+    let (x1, y1) = (to.0 as i64, to.1 as i64);
+    let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+    let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+    let mut error = dx + dy;
+    let mut dots = vec![(x as usize, y as usize)];
+    while (x, y) != (x1, y1) {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+        dots.push((x as usize, y as usize));
+    }
+    dots
+}
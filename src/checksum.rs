@@ -0,0 +1,141 @@
+use crate::chunked::chunk_end;
+use crate::introspect::{primary_key, split_table};
+use crate::transfer::connect;
+use crate::{interrupt, MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::{Conn, Params, Value};
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::error::{Result, RusqlError};
+use rusql::format::format_value;
+
+const USAGE: &str = "Usage: \\checksum <[db.]table> [--chunk-size <rows>] [--against <profile>]";
+
+/// Rows and checksum of one chunk on one server.
+type Sum = (u64, u64);
+
+/// `\checksum table [--chunk-size n] [--against profile]`: row count and a
+/// BIT_XOR of per-row CRC32s for every primary-key chunk of a table. With
+/// `--against`, the same chunks are checksummed on the profile's server,
+/// e.g. a replica, and only the chunks that differ are listed.
+///
+/// Writes landing between the two reads show up as differences, so compare
+/// a replica once it has caught up, or repeat to see whether a chunk settles.
+pub fn checksum(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut words = args.split_whitespace();
+    let table = words.next().filter(|word| !word.starts_with("--")).ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+    let mut chunk_size = 1000;
+    let mut against = None;
+    while let Some(flag) = words.next() {
+        match flag {
+            "--chunk-size" => {
+                chunk_size = words.next().and_then(|n| n.parse().ok()).filter(|&n| n > 0)
+                    .ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+            }
+            "--against" => against = Some(words.next().ok_or_else(|| RusqlError::Command(USAGE.into()))?.to_string()),
+            _ => return Err(RusqlError::Command(USAGE.into())),
+        }
+    }
+
+    let (db, table) = split_table(client, table)?;
+    let key = primary_key(client, &db, &table)?;
+    let [key] = key.as_slice() else {
+        return Err(RusqlError::Command(format!("\\checksum needs a single-column primary key on '{}.{}'", db, table)));
+    };
+    let mut other = match &against {
+        Some(profile) => connect(client, profile)?,
+        None => None,
+    };
+
+    let columns: Vec<String> = client.conn.exec(
+        "SELECT COLUMN_NAME FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+        (&db, &table),
+    )?;
+    let full_name = format!("{}.{}", db, table);
+    let key = quote_identifier(key);
+    let query = checksum_query(&full_name, &key, &columns);
+
+    let mut throttle = client.throttle.start();
+    let mut rows = Vec::new();
+    let (mut chunks, mut total_rows, mut differing) = (0u64, 0u64, 0u64);
+    let mut lower: Option<Value> = None;
+    loop {
+        if interrupt::interrupted() {
+            eprintln!();
+            return Err(RusqlError::Cancelled);
+        }
+        // Chunk bounds come from this connection and are applied to both
+        let upper = chunk_end(client, &full_name, &key, lower.as_ref(), chunk_size)?;
+        let params = range_params(lower.as_ref(), upper.as_ref());
+        let here = sum(&mut client.conn, &query, params.clone())?;
+        let there = other.as_mut().map(|conn| sum(conn, &query, params)).transpose()?;
+        chunks += 1;
+        total_rows += here.0;
+
+        let bound = |value: Option<&Value>| value.map_or_else(|| "-".to_string(), format_value);
+        let range = format!("({}, {}]", bound(lower.as_ref()), bound(upper.as_ref()));
+        match there {
+            None => rows.push(vec![chunks.to_string(), range, here.0.to_string(), format!("{:08x}", here.1)]),
+            Some(there) if there != here => {
+                differing += 1;
+                rows.push(vec![
+                    chunks.to_string(),
+                    range,
+                    here.0.to_string(),
+                    format!("{:08x}", here.1),
+                    there.0.to_string(),
+                    client.paint(&format!("{:08x}", there.1), client.theme.error),
+                ]);
+            }
+            Some(_) => {}
+        }
+        eprint!("\rChunk {}: {} rows checksummed", chunks, total_rows);
+
+        let Some(upper) = upper else { break };
+        lower = Some(upper);
+        throttle.pace(total_rows);
+    }
+    eprintln!();
+
+    let result = match &against {
+        None => {
+            let summary = format!("{} chunks, {} rows in {}", chunks, total_rows, full_name);
+            QueryResult { table: client.build_table(&["Chunk", "Key range", "Rows", "Checksum"], rows), summary }
+        }
+        Some(profile) => {
+            let summary = match differing {
+                0 => format!("All {} chunks of {} match on '{}'", chunks, full_name, profile),
+                n => format!("{} of {} chunks of {} differ on '{}'", n, chunks, full_name, profile),
+            };
+            let headers = ["Chunk", "Key range", "Rows", "Checksum", &format!("Rows on {}", profile), &format!("Checksum on {}", profile)];
+            QueryResult { table: client.build_table(&headers, rows), summary }
+        }
+    };
+    Ok(Some(result))
+}
+
+/// Row count and order-independent checksum of the rows with keys in
+/// `(?, ?]`, a NULL bound being open-ended. NULLs are folded in separately
+/// since CONCAT_WS skips them.
+fn checksum_query(table: &str, key: &str, columns: &[String]) -> String {
+    let quoted: Vec<String> = columns.iter().map(|c| quote_identifier(c)).collect();
+    let nulls: Vec<String> = quoted.iter().map(|c| format!("ISNULL({})", c)).collect();
+    format!(
+        "SELECT COUNT(*), COALESCE(BIT_XOR(CRC32(CONCAT_WS('#', {}, CONCAT({})))), 0) FROM {} \
+         WHERE (? IS NULL OR {key} > ?) AND (? IS NULL OR {key} <= ?)",
+        quoted.join(", "),
+        nulls.join(", "),
+        quote_table(table),
+        key = key,
+    )
+}
+
+fn range_params(lower: Option<&Value>, upper: Option<&Value>) -> Params {
+    let lower = lower.cloned().unwrap_or(Value::NULL);
+    let upper = upper.cloned().unwrap_or(Value::NULL);
+    Params::Positional(vec![lower.clone(), lower, upper.clone(), upper])
+}
+
+fn sum(conn: &mut Conn, query: &str, params: Params) -> Result<Sum> {
+    let sum: Option<Sum> = conn.exec_first(query, params)?;
+    Ok(sum.unwrap_or_default())
+}
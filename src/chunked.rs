@@ -0,0 +1,219 @@
+use crate::{MySQLClient, QueryResult};
+use dirs::data_local_dir;
+use mysql::prelude::*;
+use mysql::{Params, Row, Value};
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::error::{Result, RusqlError};
+use rusql::format::format_value;
+use rusql::statements::tokens;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: \\chunked <UPDATE|DELETE ...> --by <key> [--chunk <rows>] [--sleep <200ms|2s>] [--fresh]";
+
+/// Attempts per chunk when it fails with a lock wait timeout or deadlock.
+const MAX_ATTEMPTS: u32 = 5;
+
+struct Options {
+    statement: String,
+    key: String,
+    chunk: usize,
+    sleep: Option<Duration>,
+    fresh: bool,
+}
+
+/// `\chunked <DML> --by id`: run a large UPDATE or DELETE in key-range chunks.
+///
+/// Progress is saved after every chunk, so an aborted run (Ctrl-C, lost
+/// connection) continues where it stopped when the same command is repeated.
+pub fn chunked(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let options = parse(args)?;
+    let table = target_table(&options.statement)?;
+    let server = server_key(&client.host, client.port, client.current_db.as_deref());
+    let state = state_path("chunked", &server, &options.statement);
+
+    let mut lower: Option<Value> = match (&state, options.fresh) {
+        (Some(path), false) => fs::read_to_string(path).ok()
+            .and_then(|contents| contents.lines().nth(1).map(|key| Value::from(key.to_string()))),
+        _ => None,
+    };
+    if let Some(key) = &lower {
+        client.notice(&format!("Resuming after {} = {}", options.key, format_value(key)));
+    }
+
+    let key = quote_identifier(&options.key);
+    let statement = scoped(&options.statement, &key)?;
+    let mut throttle = client.throttle.clone();
+    if let Some(pause) = options.sleep {
+        throttle = throttle.sleep_every(Some(1)).pause(pause);
+    }
+    let mut throttle = throttle.start();
+
+    let (mut chunks, mut affected) = (0u64, 0u64);
+    loop {
+        let upper = chunk_end(client, &table, &key, lower.as_ref(), options.chunk)?;
+        affected += execute_with_retry(client, &statement, lower.as_ref(), upper.as_ref())?;
+        chunks += 1;
+        eprint!("\rChunk {}: {} rows affected", chunks, affected);
+
+        let Some(upper) = upper else { break };
+        if let Some(path) = &state {
+            let _ = fs::write(path, format!("{}\n{}\n", options.statement, format_value(&upper)));
+        }
+        lower = Some(upper);
+        throttle.pace(chunks * options.chunk as u64);
+    }
+
+    eprintln!();
+    if let Some(path) = &state {
+        let _ = fs::remove_file(path);
+    }
+    client.notice(&format!("Query OK, {} rows affected in {} chunks", affected, chunks));
+    Ok(None)
+}
+
+/// The statement restricted to keys in `(?, ?]`, where a NULL bound means
+/// open-ended. Only the statement's own WHERE, ORDER BY and LIMIT count, not
+/// those of subqueries, literals or comments.
+fn scoped(statement: &str, key: &str) -> Result<String> {
+    let tokens = tokens(statement);
+    let clauses: Vec<_> = tokens.iter().filter(|token| token.depth == 0 && !token.quoted).collect();
+    let order_by = clauses.windows(2).any(|pair| pair[0].word == "ORDER" && pair[1].word == "BY");
+    if order_by || clauses.iter().any(|token| token.word == "LIMIT") {
+        return Err(RusqlError::Command("\\chunked statements cannot use ORDER BY or LIMIT".into()));
+    }
+    let range = format!("(? IS NULL OR {key} > ?) AND (? IS NULL OR {key} <= ?)", key = key);
+    Ok(match clauses.iter().find(|token| token.word == "WHERE") {
+        Some(clause) => format!(
+            "{} WHERE ({}) AND {}",
+            statement[..clause.start].trim_end(),
+            statement[clause.end..].trim(),
+            range
+        ),
+        None => format!("{} WHERE {}", statement, range),
+    })
+}
+
+/// Run one chunk, retrying lock wait timeouts and deadlocks with backoff.
+pub fn execute_with_retry(client: &mut MySQLClient, statement: &str, lower: Option<&Value>, upper: Option<&Value>) -> Result<u64> {
+    let lower = lower.cloned().unwrap_or(Value::NULL);
+    let upper = upper.cloned().unwrap_or(Value::NULL);
+    let params = Params::Positional(vec![lower.clone(), lower, upper.clone(), upper]);
+
+    let mut attempt = 1;
+    loop {
+        match client.conn.exec_drop(statement, params.clone()) {
+            Ok(()) => return Ok(client.conn.affected_rows()),
+            Err(e) => {
+                let e = RusqlError::from(e);
+                if !matches!(e, RusqlError::Sql { .. }) || !e.is_transient() || attempt == MAX_ATTEMPTS {
+                    return Err(e);
+                }
+                eprintln!("\n{}; retrying ({}/{})", e, attempt, MAX_ATTEMPTS - 1);
+                thread::sleep(Duration::from_millis(200 * 2u64.pow(attempt)));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Key of the last row in the chunk after `lower`, or `None` for the final chunk.
+pub fn chunk_end(client: &mut MySQLClient, table: &str, key: &str, lower: Option<&Value>, chunk: usize) -> Result<Option<Value>> {
+    let query = format!(
+        "SELECT {key} FROM {table} WHERE ? IS NULL OR {key} > ? ORDER BY {key} LIMIT 1 OFFSET {offset}",
+        key = key,
+        table = quote_table(table),
+        offset = chunk - 1,
+    );
+    let lower = lower.cloned().unwrap_or(Value::NULL);
+    let row: Option<Row> = client.conn.exec_first(query, (lower.clone(), lower))?;
+    Ok(row.and_then(|row| row.as_ref(0).cloned()))
+}
+
+/// Table changed by `UPDATE [LOW_PRIORITY] [IGNORE] t ...` or `DELETE ... FROM t ...`.
+fn target_table(statement: &str) -> Result<String> {
+    let words: Vec<&str> = statement.split_whitespace().collect();
+    let first = words.first().map(|w| w.to_uppercase()).unwrap_or_default();
+    let table = match first.as_str() {
+        "UPDATE" => words.iter()
+            .skip(1)
+            .find(|w| !matches!(w.to_uppercase().as_str(), "LOW_PRIORITY" | "IGNORE")),
+        "DELETE" => words.iter()
+            .position(|w| w.eq_ignore_ascii_case("FROM"))
+            .and_then(|pos| words.get(pos + 1)),
+        _ => None,
+    };
+    table.map(|t| t.replace('`', ""))
+        .ok_or_else(|| RusqlError::Command("\\chunked needs a single-table UPDATE or DELETE".into()))
+}
+
+/// The server and database a job runs against, part of its progress key so
+/// the same statement on another server starts over.
+pub fn server_key(host: &str, port: u16, database: Option<&str>) -> String {
+    format!("{}:{}/{}", host, port, database.unwrap_or_default())
+}
+
+/// Progress file of a resumable job on `server`, e.g.
+/// `~/.local/share/rusql/chunked/1f3a....state` for a `\chunked` statement.
+/// The name is a SHA-256 of both, stable across builds and Rust versions.
+pub fn state_path(kind: &str, server: &str, job: &str) -> Option<PathBuf> {
+    let digest = Sha256::new()
+        .chain_update(server)
+        .chain_update([0])
+        .chain_update(job)
+        .finalize();
+    let mut path = data_local_dir()?;
+    path.push("rusql");
+    path.push(kind);
+    fs::create_dir_all(&path).ok()?;
+    let name: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    path.push(format!("{}.state", name));
+    Some(path)
+}
+
+fn parse(args: &str) -> Result<Options> {
+    let (statement, flags) = match args.find(" --") {
+        Some(pos) => (&args[..pos], &args[pos..]),
+        None => (args, ""),
+    };
+    let mut options = Options {
+        statement: statement.trim().trim_end_matches(';').trim_end().to_string(),
+        key: String::new(),
+        chunk: 1000,
+        sleep: None,
+        fresh: false,
+    };
+
+    let mut words = flags.split_whitespace();
+    while let Some(flag) = words.next() {
+        match flag {
+            "--by" => options.key = words.next().unwrap_or_default().to_string(),
+            "--chunk" => {
+                options.chunk = words.next().and_then(|n| n.parse().ok()).filter(|&n| n > 0)
+                    .ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+            }
+            "--sleep" => options.sleep = Some(parse_duration(words.next().unwrap_or_default())?),
+            "--fresh" => options.fresh = true,
+            _ => return Err(RusqlError::Command(USAGE.into())),
+        }
+    }
+    if options.statement.is_empty() || options.key.is_empty() {
+        return Err(RusqlError::Command(USAGE.into()));
+    }
+    Ok(options)
+}
+
+/// `200ms`, `2s`, `1.5s`, or a bare number of milliseconds.
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let invalid = || RusqlError::Command(format!("Invalid duration '{}', e.g. 200ms or 2s", text));
+    if let Some(ms) = text.strip_suffix("ms") {
+        return ms.parse().map(Duration::from_millis).map_err(|_| invalid());
+    }
+    if let Some(secs) = text.strip_suffix('s') {
+        return secs.parse().map(Duration::from_secs_f64).map_err(|_| invalid());
+    }
+    text.parse().map(Duration::from_millis).map_err(|_| invalid())
+}
@@ -0,0 +1,509 @@
+use crate::{charset, codegen, slowlog, timezone, variables};
+use crate::config::init_statements;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{ArgAction, Parser, Subcommand};
+use clap_complete::Shell;
+use mysql::{consts, OptsBuilder};
+use rusql::format::{NumberFormat, Scientific};
+use rusql::sink::{Borders, OutputFormat, RelativeTime};
+#[cfg(feature = "bulk")]
+use rusql::throttle::Throttle;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A value parser taking one of `variants`, which `--help` and the completion
+/// scripts list, parsed with `FromStr`.
+pub fn choice<T>(variants: &'static [&'static str]) -> impl TypedValueParser<Value = T>
+where
+    T: FromStr<Err = String> + Clone + Send + Sync + 'static,
+{
+    PossibleValuesParser::new(variants).try_map(|value| value.parse::<T>())
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "rusql", about = "Cross-platform MySQL client", version, disable_help_flag = true)]
+pub struct Opts {
+    /// Print help
+    #[arg(long, action = ArgAction::Help, global = true)]
+    pub help: Option<bool>,
+
+    /// Host to connect to
+    #[arg(short, long, default_value = "localhost")]
+    pub host: String,
+
+    /// Port number to connect to
+    #[arg(short = 'P', long, default_value = "3306")]
+    pub port: u16,
+
+    /// Username for login
+    #[arg(short = 'u', long)]
+    pub user: Option<String>,
+
+    /// Password for login
+    #[arg(short = 'p', long)]
+    pub password: Option<String>,
+
+    /// Database to use
+    #[arg(short = 'D', long)]
+    pub database: Option<String>,
+
+    /// Execute command and quit; repeat to run several in order
+    #[arg(short = 'e', long)]
+    pub execute: Vec<String>,
+
+    /// SQL scripts to run in order after any -e statements, then quit
+    pub scripts: Vec<PathBuf>,
+
+    /// Disable colors in output
+    #[arg(long)]
+    pub no_colors: bool,
+
+    /// Do not show the spinner and row counter while a statement runs
+    #[arg(long)]
+    pub no_progress: bool,
+
+    /// Print rows vertically (one column per line) when a table is wider than the terminal
+    #[arg(long)]
+    pub auto_vertical_output: bool,
+
+    /// Cut table cells longer than this many characters (see \expand)
+    #[arg(long)]
+    pub max_column_width: Option<usize>,
+
+    /// Rows appended as LIMIT to interactive SELECTs without one (0 disables; default 1000)
+    #[arg(long)]
+    pub auto_limit: Option<u64>,
+
+    /// Ping the server every this many seconds while the prompt waits, so
+    /// firewalls and wait_timeout don't drop an idle session (off by default)
+    #[arg(long)]
+    pub keepalive: Option<u64>,
+
+    /// Kill statements running longer than this many milliseconds, through
+    /// the server's own limit where it has one (see \timeout)
+    #[arg(long)]
+    pub max_execution_time: Option<u64>,
+
+    /// Retry -e and \source statements failing with a deadlock, lock wait
+    /// timeout or dropped connection up to this many times
+    #[arg(long)]
+    pub retry: Option<u32>,
+
+    /// Milliseconds before the first retry, doubled for each one after
+    #[arg(long, default_value = "500")]
+    pub retry_backoff: u64,
+
+    /// Run -e and \source scripts in one transaction, rolled back at the
+    /// first failing statement
+    #[arg(long)]
+    pub single_transaction: bool,
+
+    /// Append a tamper-evident JSON line per statement to this file
+    /// (checked with `rusql audit verify`)
+    #[arg(long)]
+    pub audit_log: Option<PathBuf>,
+
+    /// Record the SHA-256 of statements in the audit log instead of their text
+    #[arg(long)]
+    pub audit_hash_statements: bool,
+
+    /// Export OTLP trace spans of the connection, statements and fetches
+    /// to this collector, e.g. http://localhost:4318
+    #[arg(long)]
+    pub otel_endpoint: Option<String>,
+
+    /// Print long interactive results all at once instead of a page at a time
+    #[arg(long)]
+    pub no_paging: bool,
+
+    /// Prompt template, e.g. '\u@\h [\d]> ' (overrides MYSQL_PS1 and the config file)
+    #[arg(long)]
+    pub prompt: Option<String>,
+
+    /// Output format for result sets
+    #[arg(long, default_value = "table", value_parser = choice::<OutputFormat>(OutputFormat::VARIANTS))]
+    pub format: OutputFormat,
+
+    /// Write results to this file instead of standard output, as after `\o file`
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// Table borders: unicode, ascii (mysql-style `+---+`), none or compact;
+    /// overrides the theme's border
+    #[arg(long, value_parser = choice::<Borders>(Borders::VARIANTS))]
+    pub table_style: Option<Borders>,
+
+    /// Numbers in tables as in this example: "1,234.56" groups thousands and
+    /// rounds to two places, "1.234,5" uses a decimal comma
+    #[arg(long)]
+    pub number_format: Option<NumberFormat>,
+
+    /// Exponent notation for FLOAT and DOUBLE values in tables
+    #[arg(long, default_value = "auto", value_parser = choice::<Scientific>(Scientific::VARIANTS))]
+    pub scientific: Scientific,
+
+    /// Session time zone, e.g. `UTC`, `Europe/Berlin` or `+02:00`; TIMESTAMP
+    /// values are shown in this zone
+    #[arg(long, value_parser = timezone::parse_zone)]
+    pub timezone: Option<String>,
+
+    /// Character set of the client, connection and results (`SET NAMES`),
+    /// e.g. utf8mb4; kept across reconnects
+    #[arg(long, value_parser = charset::parse_name)]
+    pub default_character_set: Option<String>,
+
+    /// Collation of --default-character-set, e.g. utf8mb4_0900_ai_ci
+    #[arg(long, requires = "default_character_set", value_parser = charset::parse_name)]
+    pub default_collation: Option<String>,
+
+    /// Statements run on every connection and reconnect, e.g. "SET NAMES utf8mb4";
+    /// repeat to add more
+    #[arg(long)]
+    pub init_command: Vec<String>,
+
+    /// Follow TIMESTAMP values in tables with the zone given to --timezone
+    #[arg(long, requires = "timezone")]
+    pub annotate_timestamps: bool,
+
+    /// Show DATETIME and TIMESTAMP values in tables as how long ago they
+    /// were, beside or instead of the value
+    #[arg(long, default_value = "off", value_parser = choice::<RelativeTime>(RelativeTime::VARIANTS))]
+    pub relative_time: RelativeTime,
+
+    /// Neutralize CSV cells that spreadsheets would treat as formulas
+    #[arg(long)]
+    pub spreadsheet_safe: bool,
+
+    /// Table named in the INSERT statements of `--format sql` (defaults to
+    /// the table the columns were read from)
+    #[arg(long)]
+    pub sql_table: Option<String>,
+
+    /// Mask the columns listed under [masking] in config.toml in query results
+    #[arg(long)]
+    pub mask: bool,
+
+    /// Keep executing the remaining statements after an error
+    #[arg(short = 'f', long)]
+    pub force: bool,
+
+    /// Cap rows per second processed by bulk commands (sync, copy, \transfer)
+    #[cfg(feature = "bulk")]
+    #[arg(long)]
+    pub throttle_rows_per_sec: Option<u64>,
+
+    /// Pause bulk commands after every N chunks
+    #[cfg(feature = "bulk")]
+    #[arg(long)]
+    pub sleep_every_n_chunks: Option<u64>,
+
+    /// Length of the pause taken by --sleep-every-n-chunks, in milliseconds
+    #[cfg(feature = "bulk")]
+    #[arg(long, default_value = "1000")]
+    pub chunk_sleep_ms: u64,
+
+    /// Do not read ~/.rusqlrc on startup
+    #[arg(long)]
+    pub no_rc: bool,
+
+    /// Set a client-side variable (name=value), usable as :name or ${name}
+    #[arg(long = "var", value_parser = variables::parse_var)]
+    pub vars: Vec<(String, String)>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Execute statements written to a FIFO and reply with JSON lines
+    Listen {
+        /// FIFO to read statements from
+        fifo: PathBuf,
+
+        /// FIFO to write responses to (defaults to <fifo>.response)
+        #[arg(long)]
+        response: Option<PathBuf>,
+    },
+    /// Copy the chunks of a table that differ between two profiles
+    #[cfg(feature = "bulk")]
+    Sync {
+        /// Profile to read from
+        #[arg(long)]
+        source: String,
+
+        /// Profile to write to
+        #[arg(long)]
+        target: String,
+
+        /// Table to sync, as db.table
+        table: String,
+
+        /// Primary key column used to chunk the table
+        #[arg(long)]
+        key: String,
+
+        /// Rows per compared chunk
+        #[arg(long, default_value = "1000")]
+        chunk_size: usize,
+
+        /// Only report differing chunks
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Stream the rows of a table to another server, resuming an interrupted copy
+    #[cfg(feature = "bulk")]
+    Copy {
+        /// Table to read, as profile/db.table (`.` for the connection on the command line)
+        #[arg(long)]
+        from: String,
+
+        /// Table to write, as profile/db.table
+        #[arg(long)]
+        to: String,
+
+        /// Only copy rows matching this condition
+        #[arg(long = "where")]
+        condition: Option<String>,
+
+        /// What to do about rows already in the target table
+        #[arg(long, default_value = "error", value_parser = ["error", "truncate", "upsert", "skip"])]
+        on_conflict: String,
+
+        /// Rows per batch
+        #[arg(long, default_value = "1000")]
+        batch: usize,
+
+        /// Start over instead of resuming an interrupted copy
+        #[arg(long)]
+        fresh: bool,
+
+        /// Mask the columns listed under [masking] in config.toml
+        #[arg(long)]
+        mask: bool,
+    },
+    /// Inspect the configuration files
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Work with --audit-log files
+    #[command(subcommand)]
+    Audit(AuditCommand),
+    /// Serve the results of configured queries and server status counters as Prometheus metrics
+    Exporter {
+        /// Address to serve /metrics on, e.g. :9104 or 127.0.0.1:9104
+        #[arg(long, default_value = ":9104")]
+        listen: String,
+
+        /// Queries file with a [[metrics]] table per metric
+        #[arg(long)]
+        queries: PathBuf,
+    },
+    /// Print a completion script for flags, subcommands and profile names
+    Completions {
+        /// Shell to complete in
+        #[arg(ignore_case = true, required_unless_present = "profiles")]
+        shell: Option<Shell>,
+
+        /// List the profile names of config.toml, for the completion scripts
+        #[arg(long, hide = true)]
+        profiles: bool,
+    },
+    /// Run the queries of a jobs file on cron schedules until stopped
+    #[cfg(feature = "jobs")]
+    Schedule {
+        /// Jobs file with a [[jobs]] table per scheduled query
+        #[arg(long)]
+        config: PathBuf,
+    },
+    /// Summarize the slow query log
+    #[command(subcommand)]
+    Slowlog(SlowlogCommand),
+    /// Apply or revert versioned SQL migrations
+    #[cfg(feature = "migrate")]
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// Fill a table with fake rows fitting its columns, keys and foreign keys
+    Seed {
+        /// Table to fill, as db.table or a table of --database
+        table: String,
+
+        /// Rows to insert
+        #[arg(long, default_value = "1000")]
+        rows: usize,
+
+        /// Rows per INSERT
+        #[arg(long, default_value = "1000")]
+        batch: usize,
+
+        /// Rows generated for referenced tables that are still empty
+        #[arg(long, default_value = "100")]
+        parent_rows: usize,
+    },
+    /// Write an ER diagram of a database as Graphviz DOT or Mermaid
+    Diagram {
+        /// Database to draw (defaults to --database)
+        #[arg(long)]
+        db: Option<String>,
+
+        /// Output file: .dot or .gv for Graphviz, .mmd for Mermaid
+        #[arg(long)]
+        out: PathBuf,
+
+        /// Only draw tables matching these patterns (`*` wildcards, comma-separated or repeated)
+        #[arg(long, value_delimiter = ',')]
+        include: Vec<String>,
+
+        /// Leave out tables matching these patterns
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+    },
+    /// Export a large table as numbered chunk files with a manifest, resumable after a failure
+    Export {
+        /// Table to export, as db.table or a table of --database
+        table: String,
+
+        /// Directory receiving the chunk files and manifest.json
+        #[arg(long)]
+        dir: PathBuf,
+
+        /// File format of the chunks
+        #[arg(long, default_value = "csv", value_parser = choice::<OutputFormat>(&OutputFormat::VARIANTS[1..]))]
+        format: OutputFormat,
+
+        /// Compress the chunks
+        #[arg(long, value_parser = ["gz", "zst"])]
+        compress: Option<String>,
+
+        /// Rows per chunk file
+        #[arg(long, default_value = "1000000")]
+        chunk_rows: usize,
+
+        /// Only export rows matching this condition
+        #[arg(long = "where")]
+        condition: Option<String>,
+
+        /// Continue the export recorded in the directory's manifest
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Write Rust structs for tables, typed after their columns
+    Codegen {
+        /// Database of the tables (defaults to --database)
+        #[arg(long)]
+        db: Option<String>,
+
+        /// Tables to generate structs for (comma-separated or repeated; all tables when left out)
+        #[arg(long, value_delimiter = ',')]
+        table: Vec<String>,
+
+        /// Library the structs are written for
+        #[arg(long, default_value = "serde", value_parser = choice::<codegen::Style>(codegen::Style::VARIANTS))]
+        style: codegen::Style,
+
+        /// File to write instead of standard output
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SlowlogCommand {
+    /// Group statements by fingerprint and rank them by total time, calls or rows examined
+    Analyze {
+        /// Slow log file written with log_output=FILE
+        #[arg(required_unless_present = "from_table")]
+        file: Option<PathBuf>,
+
+        /// Read mysql.slow_log (log_output=TABLE) on the server instead of a file
+        #[arg(long, conflicts_with = "file")]
+        from_table: bool,
+
+        /// What to rank fingerprints by
+        #[arg(long, default_value = "total", value_parser = choice::<slowlog::OrderBy>(slowlog::OrderBy::VARIANTS))]
+        order_by: slowlog::OrderBy,
+
+        /// Fingerprints shown
+        #[arg(long, default_value = "10")]
+        limit: usize,
+    },
+}
+
+#[cfg(feature = "migrate")]
+#[derive(Subcommand, Debug)]
+pub enum MigrateCommand {
+    /// Apply pending migrations in version order
+    Up {
+        /// Directory of <version>_<name>.up.sql / .down.sql files
+        #[arg(long, default_value = "./migrations")]
+        dir: PathBuf,
+
+        /// Stop after this version
+        #[arg(long)]
+        to: Option<u64>,
+    },
+    /// Revert the latest applied migrations with their .down.sql files
+    Down {
+        /// Directory of <version>_<name>.up.sql / .down.sql files
+        #[arg(long, default_value = "./migrations")]
+        dir: PathBuf,
+
+        /// Migrations to revert
+        #[arg(long, default_value = "1")]
+        steps: usize,
+    },
+    /// List migrations with when they were applied
+    Status {
+        /// Directory of <version>_<name>.up.sql / .down.sql files
+        #[arg(long, default_value = "./migrations")]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AuditCommand {
+    /// Check that no line of an audit log was edited, removed or reordered
+    Verify {
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Report unknown keys, wrong types and conflicting settings with line numbers
+    Check {
+        /// Also try to reach the host of every profile
+        #[arg(long)]
+        probe: bool,
+    },
+}
+
+/// Bulk job pacing from the command line.
+#[cfg(feature = "bulk")]
+pub fn throttle(opts: &Opts) -> Throttle {
+    Throttle::default()
+        .rows_per_sec(opts.throttle_rows_per_sec)
+        .sleep_every(opts.sleep_every_n_chunks)
+        .pause(std::time::Duration::from_millis(opts.chunk_sleep_ms))
+}
+
+/// Whether statements or scripts were given to run instead of a prompt.
+pub fn is_batch(opts: &Opts) -> bool {
+    !opts.execute.is_empty() || !opts.scripts.is_empty()
+}
+
+/// Connection options from the command line.
+pub fn connection_opts(opts: &Opts) -> OptsBuilder {
+    OptsBuilder::new()
+        .user(opts.user.as_deref())
+        .pass(opts.password.as_deref())
+        .ip_or_hostname(Some(opts.host.as_str()))
+        .tcp_port(opts.port)
+        .db_name(opts.database.as_deref())
+        // Lets the server report transaction state changes in OK packets
+        .additional_capabilities(consts::CapabilityFlags::CLIENT_SESSION_TRACK)
+        .init(opts.timezone.iter()
+            .map(|zone| timezone::set_statement(zone))
+            .chain(opts.default_character_set.iter().map(|charset| charset::set_names(charset, opts.default_collation.as_deref())))
+            .chain(init_statements(&opts.init_command))
+            .collect())
+}
@@ -0,0 +1,702 @@
+use crate::{autolimit, charset, commands, format_duration, history, notify, otel, paging, relative, replication, timeout, timezone, transaction, usage, variables};
+use crate::cli::{connection_opts, Opts};
+#[cfg(feature = "bulk")]
+use crate::cli::throttle;
+use crate::config::{NotifySettings, Profile, Settings};
+use crate::counters::Snapshot;
+use crate::expand::LastResult;
+use crate::interrupt::Interrupt;
+#[cfg(feature = "jobs")]
+use crate::jobs::Jobs;
+use crate::paging::Paged;
+use crate::progress::Spinner;
+use crate::recall::ResultCache;
+use crate::redirect::Redirect;
+use crate::retry::Retry;
+use crate::theme::Theme;
+use crate::timeout::{ServerLimit, Watchdog};
+use crate::variables::Variables;
+use chrono::{DateTime, Local, Utc};
+use colored::*;
+use mysql::*;
+use mysql::prelude::*;
+use prettytable::{Table, Row as PrettyRow, Cell, format};
+use rusql::audit::{AuditEntry, AuditLog};
+use rusql::error::{Result, RusqlError};
+use rusql::format::NumberFormat;
+use rusql::mask::MaskRules;
+#[cfg(feature = "jobs")]
+use rusql::shard::ShardMap;
+use rusql::sink::{paint, table_format, RelativeTime, write_rows, OutputFormat, SinkOptions, TableSink};
+use rusql::statements::Splitter;
+#[cfg(feature = "bulk")]
+use rusql::throttle::Throttle;
+use std::collections::{BTreeMap, HashMap};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+pub struct MySQLClient {
+    pub conn: Conn,
+    pub current_db: Option<String>,
+    pub use_colors: bool,
+    pub host: String,
+    pub port: u16,
+    pub variables: Variables,
+    pub format: OutputFormat,
+    pub user: String,
+    pub prompt: Option<String>,
+    pub server_version: String,
+    pub statements: u64,
+    pub aliases: BTreeMap<String, String>,
+    pub pager: Option<String>,
+    pub session_start: DateTime<Local>,
+    pub sink_options: SinkOptions,
+    pub low_priority_columns: Vec<String>,
+    pub profiles: BTreeMap<String, Profile>,
+    /// Sharding keys routed by `\shard`
+    #[cfg(feature = "jobs")]
+    pub shards: BTreeMap<String, ShardMap>,
+    pub in_transaction: bool,
+    /// How `in_transaction` is kept current
+    pub tracking: transaction::Tracking,
+    /// Ctrl-C handling of the interactive session, pointed at a new
+    /// connection whenever one replaces `conn`
+    pub interrupt: Option<Interrupt>,
+    /// Destination of the next statement's result (`; | cmd`, `\pipe`, `\g file`)
+    pub redirect: Option<Redirect>,
+    /// File receiving every result after `\o file`
+    pub output: Option<PathBuf>,
+    pub counters: Option<Snapshot>,
+    /// Keep executing after an error in multi-statement input
+    pub force: bool,
+    pub splitter: Splitter,
+    /// Pacing applied to bulk commands
+    #[cfg(feature = "bulk")]
+    pub throttle: Throttle,
+    /// Options used to open extra connections, e.g. for background jobs
+    pub conn_opts: mysql::Opts,
+    #[cfg(feature = "jobs")]
+    pub jobs: Jobs,
+    pub progress: bool,
+    pub paging: bool,
+    pub auto_limit: Option<u64>,
+    pub max_column_width: Option<usize>,
+    /// Time limit on each statement (`--max-execution-time`, `\timeout`)
+    pub max_execution_time: Option<Duration>,
+    pub server_limit: ServerLimit,
+    /// Retries of script statements failing transiently
+    pub retry: Option<Retry>,
+    /// Run scripts as one transaction (`--single-transaction`)
+    pub single_transaction: bool,
+    /// Record of every statement (`--audit-log`)
+    pub audit: Option<AuditLog>,
+    /// `[notify]` settings and the run time past which `\notify` reports a statement
+    pub notify: NotifySettings,
+    pub notify_after: Option<Duration>,
+    pub results: ResultCache,
+    /// Opened by the first `\copy-last` or `\copy-cell`
+    #[cfg(feature = "clipboard")]
+    pub clipboard: Option<arboard::Clipboard>,
+    /// Opened by the first `\local`
+    #[cfg(feature = "duckdb")]
+    pub local: Option<crate::local::LocalDb>,
+    pub auto_vertical: bool,
+    pub theme: Theme,
+    pub number_format: NumberFormat,
+    pub relative_time: RelativeTime,
+    /// Zone shown after TIMESTAMP values, with --annotate-timestamps
+    pub timestamp_label: Option<String>,
+    /// Seconds the session time zone is ahead of UTC, for relative times
+    pub session_offset: i64,
+    pub totals: bool,
+    /// Rules from `[masking]`, applied to results while `masking` is on
+    pub mask_rules: MaskRules,
+    pub masking: bool,
+    /// `sensitive_columns` patterns, shown partially masked and kept out of history
+    pub sensitive_columns: Vec<String>,
+    pub sensitive_rules: MaskRules,
+    /// Show the next result's sensitive columns as they are (`\unmask`)
+    pub unmask_next: bool,
+    /// Whether the statements being run were typed at the prompt rather than
+    /// read from a script; only those go to the usage log read by `\stats`
+    /// and get `auto_limit`
+    pub at_prompt: bool,
+    /// Connected to a replica, where writes get a warning first
+    pub is_replica: bool,
+}
+
+impl MySQLClient {
+    pub fn new(opts: &Opts, settings: &Settings) -> Result<Self> {
+        let conn_opts: mysql::Opts = connection_opts(opts).into();
+        let connecting = SystemTime::now();
+        let conn = Conn::new(conn_opts.clone());
+        let target = otel::Target {
+            host: &opts.host,
+            port: opts.port,
+            user: opts.user.as_deref().unwrap_or_default(),
+            database: opts.database.as_deref(),
+        };
+        otel::connect(&target, connecting, conn.as_ref().err().map(ToString::to_string));
+        let mut conn = conn
+            .map_err(|e| match (&opts.timezone, &opts.default_character_set) {
+                (Some(zone), _) => timezone::explain(e.into(), zone),
+                (None, Some(charset)) => charset::explain(e.into(), charset, opts.default_collation.as_deref()),
+                (None, None) => e.into(),
+            })?;
+        let current_db = opts.database.clone();
+        if let Some(path) = &opts.output {
+            // Like `\o`, start with an empty file
+            std::fs::File::create(path)?;
+        }
+        let use_colors = !opts.no_colors;
+        let mut theme = settings.theme()?;
+        if let Some(borders) = opts.table_style {
+            theme.borders = borders;
+        }
+        let host = opts.host.clone();
+        let port = opts.port;
+        let variables = opts.vars.iter().cloned().collect();
+        let format = opts.format;
+        let user = opts.user.clone().unwrap_or_default();
+        let prompt = opts.prompt.clone()
+            .or_else(|| std::env::var("MYSQL_PS1").ok())
+            .or_else(|| settings.prompt.clone());
+        let server_version: String = conn.query_first("SELECT VERSION()")?.unwrap_or_default();
+        let session_offset = match opts.relative_time {
+            RelativeTime::Off => 0,
+            _ => relative::session_offset(&mut conn)?,
+        };
+        let is_replica = replication::is_replica(&mut conn);
+        let server_limit = ServerLimit::of(&server_version);
+        let tracking = transaction::track(&mut conn);
+
+        Ok(MySQLClient {
+            conn,
+            current_db,
+            use_colors,
+            host,
+            port,
+            variables,
+            format,
+            user,
+            prompt,
+            server_version,
+            statements: 0,
+            aliases: BTreeMap::new(),
+            pager: None,
+            session_start: Local::now(),
+            sink_options: SinkOptions { spreadsheet_safe: opts.spreadsheet_safe, sql_table: opts.sql_table.clone() },
+            low_priority_columns: settings.low_priority_columns.clone(),
+            profiles: settings.profiles.clone(),
+            #[cfg(feature = "jobs")]
+            shards: settings.shard_maps()?,
+            in_transaction: false,
+            tracking,
+            interrupt: None,
+            redirect: None,
+            output: opts.output.clone(),
+            counters: None,
+            force: opts.force,
+            splitter: Splitter::default(),
+            #[cfg(feature = "bulk")]
+            throttle: throttle(opts),
+            conn_opts,
+            #[cfg(feature = "jobs")]
+            jobs: Jobs::default(),
+            progress: !opts.no_progress,
+            paging: false,
+            auto_limit: None,
+            max_column_width: opts.max_column_width,
+            max_execution_time: None,
+            server_limit,
+            retry: opts.retry
+                .filter(|&attempts| attempts > 0)
+                .map(|attempts| Retry { attempts, backoff: Duration::from_millis(opts.retry_backoff) }),
+            single_transaction: opts.single_transaction,
+            audit: opts.audit_log.as_deref()
+                .map(|path| AuditLog::open(path, opts.audit_hash_statements))
+                .transpose()?,
+            notify: settings.notify.clone(),
+            notify_after: None,
+            results: ResultCache::default(),
+            #[cfg(feature = "clipboard")]
+            clipboard: None,
+            #[cfg(feature = "duckdb")]
+            local: None,
+            auto_vertical: opts.auto_vertical_output,
+            theme,
+            number_format: opts.number_format.unwrap_or_default().scientific(opts.scientific),
+            relative_time: opts.relative_time,
+            timestamp_label: opts.timezone.clone().filter(|_| opts.annotate_timestamps),
+            session_offset,
+            totals: false,
+            mask_rules: settings.mask_rules()?,
+            masking: opts.mask,
+            sensitive_columns: settings.sensitive_columns.clone(),
+            sensitive_rules: settings.sensitive_rules(),
+            unmask_next: false,
+            at_prompt: false,
+            is_replica,
+        })
+    }
+
+    pub fn format_cell(&self, value: String, is_null: bool) -> String {
+        if !self.use_colors {
+            return if is_null { "NULL".to_string() } else { value };
+        }
+
+        if is_null {
+            paint("NULL", self.theme.cells.null)
+        } else {
+            paint(&value, self.theme.cells.text)
+        }
+    }
+
+    /// `text` in a theme color, or unchanged when colors are off.
+    pub fn paint(&self, text: &str, color: Option<Color>) -> String {
+        if self.use_colors { paint(text, color) } else { text.to_string() }
+    }
+
+    /// Theme color for errors, `None` when colors are off.
+    pub fn error_color(&self) -> Option<Color> {
+        self.theme.error.filter(|_| self.use_colors)
+    }
+
+    pub fn notice(&self, msg: &str) {
+        println!("{}", self.paint(msg, self.theme.notice));
+    }
+
+    /// Build a table in the result style from already formatted cells.
+    pub fn build_table(&self, headers: &[&str], rows: Vec<Vec<String>>) -> Table {
+        let mut table = Table::new();
+        table.set_format(table_format(self.theme.borders));
+
+        let headers: Vec<Cell> = headers.iter()
+            .map(|h| {
+                let header = self.paint(h, self.theme.cells.header);
+                Cell::new(&header).style_spec("b")
+            })
+            .collect();
+        table.add_row(PrettyRow::new(headers));
+
+        for row in rows {
+            let cells: Vec<Cell> = row.into_iter()
+                .map(|value| Cell::new(&self.format_cell(value, false)))
+                .collect();
+            table.add_row(PrettyRow::new(cells));
+        }
+        table
+    }
+
+    /// Where results currently go instead of the terminal, if anywhere.
+    pub fn redirect_target(&self) -> Option<Redirect> {
+        self.redirect.clone()
+            .or_else(|| self.output.clone().map(|path| Redirect::File { path, append: true }))
+    }
+
+    /// Write a result to a redirect target, keeping file summaries on screen.
+    pub fn write_redirected(&self, redirect: &Redirect, result: &QueryResult) -> Result<()> {
+        redirect.write_table(&result.table, &result.summary)?;
+        if redirect.summary_on_screen() && !result.summary.is_empty() {
+            self.notice(&result.summary);
+        }
+        Ok(())
+    }
+
+    /// Table renderer narrowed to the terminal width when printing straight to one.
+    /// Redirected tables keep every column and carry no color codes.
+    pub fn table_sink(&self) -> TableSink {
+        if self.redirect_target().is_some() {
+            return TableSink::new(false)
+                .borders(self.theme.borders)
+                .number_format(self.number_format)
+                .timestamp_label(self.timestamp_label.clone());
+        }
+        let width = match self.pager {
+            Some(_) => None,
+            None => term_size::dimensions_stdout().map(|(width, _)| width),
+        };
+        TableSink::new(self.use_colors)
+            .fit_to(width, &self.low_priority_columns)
+            .max_column_width(self.max_column_width)
+            .auto_vertical(self.auto_vertical)
+            .cell_colors(self.theme.cells)
+            .borders(self.theme.borders)
+            .number_format(self.number_format)
+            .relative_time(self.relative_time, Utc::now().naive_utc() + chrono::Duration::seconds(self.session_offset))
+            .timestamp_label(self.timestamp_label.clone())
+            .totals(self.totals)
+    }
+
+    /// Table template and rows per page when the next result should be paged:
+    /// interactive table output going straight to a terminal.
+    pub fn paging_template(&self) -> Option<(TableSink, usize)> {
+        // Masks are applied to the whole result, so shuffled columns mix every row
+        if !self.paging || self.masking || self.format != OutputFormat::Table || self.pager.is_some() || self.redirect_target().is_some() {
+            return None;
+        }
+        // Totals of a single page would pass for totals of the whole result
+        Some((self.table_sink().totals(false), paging::page_size()?))
+    }
+
+    /// Run a parameterized query issued by a meta-command and render it as a table.
+    pub fn query_result(&mut self, query: &str, params: Params) -> Result<QueryResult> {
+        let start_time = std::time::Instant::now();
+        let result = self.conn.exec_iter(query, params)?;
+        let column_info = result.columns().as_ref().to_vec();
+        let rows: Vec<mysql::Row> = result.collect::<std::result::Result<Vec<_>, _>>()?;
+        let summary = format!(
+            "{} {} in set ({:.2} sec)",
+            rows.len(),
+            if rows.len() == 1 { "row" } else { "rows" },
+            start_time.elapsed().as_secs_f64()
+        );
+
+        let mut sink = self.table_sink();
+        write_rows(&mut sink, &column_info, rows, &summary)?;
+        let (table, summary) = sink.finish();
+        Ok(QueryResult { table, summary })
+    }
+
+    pub fn execute_query(&mut self, query: &str) -> Result<Option<QueryResult>> {
+        if commands::is_meta_command(query) {
+            let start = Instant::now();
+            let outcome = commands::execute(self, query);
+            self.audit(query, start.elapsed(), None, &outcome);
+            notify::finished(self, query, start.elapsed(), &outcome, None);
+            return outcome;
+        }
+        let (query, pipe) = commands::split_pipe(query);
+        if let Some(command) = pipe {
+            self.redirect = Some(Redirect::Pipe(command.to_string()));
+        }
+        let (query, file) = commands::split_go(query);
+        if let Some(path) = file.filter(|path| !path.is_empty()) {
+            self.redirect = Some(Redirect::File { path: PathBuf::from(path), append: false });
+        }
+        let query = &variables::interpolate(query, &self.variables);
+
+        let start = Instant::now();
+        let started = SystemTime::now();
+        let outcome = self.execute_sql(query);
+        transaction::refresh(self, query, &outcome);
+        let rows_affected = outcome.is_ok().then(|| self.conn.affected_rows());
+        self.audit(query, start.elapsed(), rows_affected, &outcome);
+        notify::finished(self, query, start.elapsed(), &outcome, rows_affected);
+        otel::statement(&self.trace_target(), query, started, rows_affected, outcome.as_ref().err().map(ToString::to_string));
+        if self.at_prompt {
+            let connection = history::connection_key(&self.host, self.current_db.as_deref());
+            usage::record(&connection, query, outcome.is_ok(), &self.sensitive_columns);
+        }
+
+        // One-shot redirected tables are written here; streaming formats were
+        // written directly, and `\o` output is handled by print_result
+        match (self.redirect.take(), outcome) {
+            (Some(redirect), Ok(Some(result))) => self.write_redirected(&redirect, &result).map(|_| None),
+            (_, outcome) => outcome,
+        }
+    }
+
+    /// Where statements run, for their trace spans.
+    pub fn trace_target(&self) -> otel::Target<'_> {
+        otel::Target { host: &self.host, port: self.port, user: &self.user, database: self.current_db.as_deref() }
+    }
+
+    /// Append a statement to the `--audit-log`, if one is kept.
+    pub fn audit(&mut self, statement: &str, duration: Duration, rows_affected: Option<u64>, outcome: &Result<Option<QueryResult>>) {
+        let Some(log) = self.audit.as_mut() else { return };
+        let entry = AuditEntry {
+            user: &self.user,
+            host: &self.host,
+            database: self.current_db.as_deref(),
+            statement,
+            duration,
+            rows_affected,
+            error: outcome.as_ref().err().map(ToString::to_string),
+        };
+        if let Err(e) = log.record(&entry) {
+            eprintln!("{}", self.paint(&format!("Could not write the audit log: {}", e), self.error_color()));
+        }
+    }
+
+    pub fn execute_sql(&mut self, query: &str) -> Result<Option<QueryResult>> {
+        // Handle special commands
+        match query.trim().to_lowercase().as_str() {
+            "status" => return self.show_status(),
+            "clear" => {
+                print!("\x1B[2J\x1B[1;1H");  // Clear screen
+                return Ok(None);
+            }
+            _ => {}
+        }
+    
+        let start_time = std::time::Instant::now();
+        let use_colors = self.use_colors;
+        let notice_color = self.theme.notice.filter(|_| use_colors);
+        self.statements += 1;
+
+        // `\unmask` only reveals a result shown on screen, never one written to a file or command
+        let on_screen = self.redirect_target().is_none() && self.output.is_none();
+        let unmask = std::mem::take(&mut self.unmask_next);
+        if unmask && !on_screen {
+            self.notice("Sensitive columns stay masked in redirected output");
+        }
+        let unmask = unmask && on_screen;
+    
+        // Handle USE command
+        if query.trim().to_lowercase().starts_with("use ") {
+            let db = query.trim()[4..].trim().trim_matches(';');
+            self.conn.select_db(db)?;
+            self.current_db = Some(db.to_string());
+            
+            self.notice(&format!("Database changed to '{}'", db));
+
+            return Ok(None);
+        }
+    
+        if self.is_replica && replication::is_write(query) {
+            println!("{}", self.paint(replication::WRITE_WARNING, self.theme.warning));
+        }
+
+        // Cap bare SELECTs typed at the prompt
+        let limited = self.auto_limit
+            .filter(|_| self.at_prompt && self.redirect_target().is_none())
+            .and_then(|limit| autolimit::apply(query, limit).map(|query| (query, limit)));
+        let limit = limited.as_ref().map(|(_, limit)| *limit);
+        let query = limited.as_ref().map_or(query, |(query, _)| query.as_str());
+        let limit_reached = |count: usize| limit == Some(count as u64);
+
+        // Page interactive tables; decided up front as the result borrows the connection
+        let paging = self.paging_template();
+        let kill_opts = self.conn_opts.clone();
+        let connection_id = self.conn.connection_id();
+
+        // Execute the query
+        let mut spinner = Spinner::start(self.progress, use_colors);
+        let affected_rows = self.conn.affected_rows();
+        let watchdog = self.max_execution_time
+            .filter(|_| !self.server_limit.covers(query))
+            .map(|limit| (Watchdog::start(kill_opts.clone(), connection_id, limit), limit));
+        let result = self.conn.query_iter(query);
+        otel::executed();
+        if let Some((watchdog, limit)) = watchdog
+            && watchdog.finish() {
+            return Err(timeout::exceeded(limit));
+        }
+        let result = result?;
+        let column_info = result.columns().as_ref().to_vec();
+        let sensitive = match unmask {
+            true => MaskRules::default(),
+            false => self.sensitive_rules.clone(),
+        }.result_columns(&column_info);
+    
+        if column_info.is_empty() {
+            spinner.finish();
+            // Handle non-SELECT queries
+            let elapsed = start_time.elapsed();
+            
+            if affected_rows > 0 {
+                let msg = format!(
+                    "Query OK, {} {} affected ({:.2} sec)",
+                    affected_rows,
+                    if affected_rows == 1 { "row" } else { "rows" },
+                    elapsed.as_secs_f64()
+                );
+                println!("{}", paint(&msg, notice_color));
+            }
+            return Ok(None);
+        }
+    
+        if let Some((template, page_size)) = paging {
+            spinner.finish();
+            self.results.forget_latest();
+            let mut rows = result
+                .map(|row| row.map(|mut row| {
+                    sensitive.apply_rows(std::slice::from_mut(&mut row));
+                    row
+                }))
+                .peekable();
+            let paged = paging::page(&template, &column_info, &mut rows, page_size);
+            if let Ok(Paged::Quit(_)) = paged {
+                // Stop the server from sending the rest instead of reading it all
+                let _ = crate::interrupt::kill_query(kill_opts, connection_id);
+                rows.by_ref().for_each(drop);
+            }
+            drop(rows);
+            let paged = paged?;
+            let (Paged::Complete(fetched) | Paged::Quit(fetched)) = paged;
+            otel::fetched(fetched);
+            let summary = match paged {
+                Paged::Complete(count) => format!(
+                    "{} {} in set ({:.2} sec)",
+                    count,
+                    if count == 1 { "row" } else { "rows" },
+                    start_time.elapsed().as_secs_f64()
+                ),
+                Paged::Quit(count) => format!("Stopped after {} rows ({:.2} sec)", count, start_time.elapsed().as_secs_f64()),
+            };
+            self.notice(&summary);
+            if let Paged::Complete(count) = paged && limit_reached(count) {
+                self.notice(&autolimit::note(count));
+            }
+            return Ok(None);
+        }
+
+        let mut rows = Vec::new();
+        for row in result {
+            let row = row?;
+            spinner.row(&row);
+            rows.push(row);
+        }
+        spinner.finish();
+        if self.masking {
+            self.mask_rules.result_columns(&column_info).apply_rows(&mut rows);
+        }
+        sensitive.apply_rows(&mut rows);
+        let row_count = rows.len();
+        otel::fetched(row_count);
+        let elapsed = start_time.elapsed();
+        let mut summary = format!(
+            "{} {} in set ({:.2} sec)",
+            row_count,
+            if row_count == 1 { "row" } else { "rows" },
+            elapsed.as_secs_f64()
+        );
+        if limit_reached(row_count) {
+            summary = format!("{}\n{}", summary, autolimit::note(row_count));
+        }
+
+        if let Some(redirect) = self.redirect_target().filter(|_| self.format != OutputFormat::Table) {
+            // A binary file holds one result, so `\o` replaces it rather than appending
+            let redirect = match redirect {
+                Redirect::File { path, .. } if self.format.is_binary() => Redirect::File { path, append: false },
+                other => other,
+            };
+            let mut output = redirect.open()?;
+            if let Some(mut sink) = self.format.sink(output.writer(), &self.sink_options) {
+                let written = write_rows(sink.as_mut(), &column_info, rows, &summary);
+                // A pipe command may exit early (e.g. `head`), so only file errors count
+                if redirect.summary_on_screen() {
+                    written?;
+                }
+            }
+            output.close()?;
+            if redirect.summary_on_screen() {
+                self.notice(&summary);
+            }
+            return Ok(None);
+        }
+
+        if self.format.is_binary() && std::io::stdout().is_terminal() {
+            return Err(RusqlError::Command(
+                "Binary output is not shown on a terminal: write it to a file with --output, \\o file or \\g file".into(),
+            ));
+        }
+        if let Some(mut sink) = self.format.sink(std::io::stdout(), &self.sink_options) {
+            write_rows(sink.as_mut(), &column_info, rows, &summary)?;
+            return Ok(None);
+        }
+
+        let mut sink = self.table_sink();
+        write_rows(&mut sink, &column_info, rows, &summary)?;
+        self.results.push(query, LastResult::from_sink(&sink));
+        let (table, summary) = sink.finish();
+
+        Ok(Some(QueryResult { table, summary }))
+    }
+
+    pub fn show_status(&mut self) -> Result<Option<QueryResult>> {
+        let mut table = Table::new();
+        let format = format::FormatBuilder::new()
+            .column_separator(' ')
+            .borders(' ')
+            .padding(1, 1)
+            .build();
+        table.set_format(format);
+
+        let mut add = |label: &str, value: &str| {
+            table.add_row(PrettyRow::new(vec![
+                Cell::new(label).style_spec("Fb"),
+                Cell::new(value),
+            ]));
+        };
+
+        // Server info
+        let server_version: String = self.conn.query_first("SELECT VERSION()")?.unwrap_or_default();
+        add("Server version:", &server_version);
+
+        // Connection info
+        add("Server:", &format!("{}:{}", self.host, self.port));
+        add("Connection id:", &self.conn.connection_id().to_string());
+
+        // Database info
+        add("Current database:", self.current_db.as_deref().unwrap_or("None"));
+
+        // Character set info
+        let names: Vec<String> = self.conn
+            .query_first::<Row, _>(
+                "SELECT @@character_set_client, @@character_set_connection, @@character_set_results, \
+                 @@collation_connection, @@character_set_server, @@character_set_database",
+            )?
+            .map(|row| (0..row.len())
+                .map(|i| row.get::<Option<String>, _>(i).flatten().unwrap_or_else(|| "NULL".to_string()))
+                .collect())
+            .unwrap_or_default();
+        let name = |i: usize| names.get(i).map_or("NULL", String::as_str);
+        let charset = match name(0) == name(1) && name(1) == name(2) {
+            true => name(0).to_string(),
+            false => format!("{} (client), {} (connection), {} (results)", name(0), name(1), name(2)),
+        };
+        add("Character set:", &charset);
+        add("Collation:", name(3));
+        add("Server character set:", name(4));
+        add("Db character set:", name(5));
+
+        // SSL and transaction state
+        let cipher: Option<(String, String)> = self.conn.query_first("SHOW SESSION STATUS LIKE 'Ssl_cipher'")?;
+        let ssl = match cipher.map(|(_, cipher)| cipher).filter(|cipher| !cipher.is_empty()) {
+            Some(cipher) => format!("Cipher in use is {}", cipher),
+            None => "Not in use".to_string(),
+        };
+        add("SSL:", &ssl);
+
+        let isolation: Option<String> = self.conn.query_first("SELECT @@SESSION.transaction_isolation")
+            .or_else(|_| self.conn.query_first("SELECT @@SESSION.tx_isolation"))?;
+        add("Isolation level:", &isolation.unwrap_or_default());
+
+        // Server health counters
+        let counters: HashMap<String, u64> = self.conn
+            .query::<(String, String), _>(
+                "SHOW GLOBAL STATUS WHERE Variable_name IN ('Uptime', 'Threads_connected', \
+                 'Threads_running', 'Questions', 'Slow_queries', \
+                 'Innodb_buffer_pool_read_requests', 'Innodb_buffer_pool_reads')",
+            )?
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value.parse().ok()?)))
+            .collect();
+        let counter = |name: &str| counters.get(name).copied().unwrap_or(0);
+
+        let uptime = counter("Uptime");
+        add("Uptime:", &format_duration(uptime));
+        add("Threads:", &format!("{} connected, {} running", counter("Threads_connected"), counter("Threads_running")));
+        add("Queries per second:", &format!("{:.3}", counter("Questions") as f64 / uptime.max(1) as f64));
+        add("Slow queries:", &counter("Slow_queries").to_string());
+
+        let requests = counter("Innodb_buffer_pool_read_requests");
+        if requests > 0 {
+            let hit_rate = 100.0 * (1.0 - counter("Innodb_buffer_pool_reads") as f64 / requests as f64);
+            add("Buffer pool hit rate:", &format!("{:.2}%", hit_rate));
+        }
+
+        Ok(Some(QueryResult { 
+            table,
+            summary: String::new()
+        }))
+    }
+}
+
+pub struct QueryResult {
+    pub table: Table,
+    pub summary: String,
+}
@@ -0,0 +1,84 @@
+use crate::expand::{last_result, LastResult};
+use crate::{MySQLClient, QueryResult};
+use rusql::error::{Result, RusqlError};
+use rusql::sink::CsvSink;
+
+const USAGE: &str = "Usage: \\copy-last [tsv|csv|md]";
+const CELL_USAGE: &str = "Usage: \\copy-cell <row> <column number or name>";
+
+/// `\copy-last [tsv|csv|md]`: put the last result on the clipboard, as
+/// tab-separated values (the default, which spreadsheets paste into cells),
+/// CSV or a Markdown table.
+pub fn copy_last(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let last = last_result(client)?;
+    let text = match args.to_lowercase().as_str() {
+        "" | "tsv" => tsv(last),
+        "csv" => csv(last)?,
+        "md" | "markdown" => markdown(last),
+        _ => return Err(RusqlError::Command(USAGE.into())),
+    };
+    let rows = last.rows.len();
+    set_clipboard(client, text)?;
+    client.notice(&format!("{} {} copied to the clipboard", rows, if rows == 1 { "row" } else { "rows" }));
+    Ok(None)
+}
+
+/// `\copy-cell <row> <col>`: put one value of the last result on the
+/// clipboard, untruncated. Rows and column numbers count from 1.
+pub fn copy_cell(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let last = last_result(client)?;
+    let mut words = args.split_whitespace();
+    let (Some(row), Some(column), None) = (words.next(), words.next(), words.next()) else {
+        return Err(RusqlError::Command(CELL_USAGE.into()));
+    };
+    let row: usize = row.parse().map_err(|_| RusqlError::Command(CELL_USAGE.into()))?;
+    let index = last.column(column)?;
+    let value = last.row(row)?[index].clone().unwrap_or_else(|| "NULL".to_string());
+    let name = last.columns[index].clone();
+    set_clipboard(client, value)?;
+    client.notice(&format!("Row {}, {} copied to the clipboard", row, name));
+    Ok(None)
+}
+
+/// The clipboard is kept open for the session: on X11 copied text is only
+/// available while its owner is alive.
+fn set_clipboard(client: &mut MySQLClient, text: String) -> Result<()> {
+    let unavailable = |e: arboard::Error| RusqlError::Command(format!("The clipboard is not available: {}", e));
+    if client.clipboard.is_none() {
+        client.clipboard = Some(arboard::Clipboard::new().map_err(unavailable)?);
+    }
+    match client.clipboard.as_mut() {
+        Some(clipboard) => clipboard.set_text(text).map_err(unavailable),
+        None => Ok(()),
+    }
+}
+
+fn tsv(last: &LastResult) -> String {
+    // Tabs and line breaks inside values would start new cells or rows
+    let clean = |text: &str| text.replace(['\t', '\n', '\r'], " ");
+    let mut lines = vec![last.columns.iter().map(|name| clean(name)).collect::<Vec<_>>().join("\t")];
+    for row in &last.rows {
+        lines.push(row.iter().map(|value| clean(value.as_deref().unwrap_or("NULL"))).collect::<Vec<_>>().join("\t"));
+    }
+    lines.join("\n") + "\n"
+}
+
+fn csv(last: &LastResult) -> Result<String> {
+    let mut out = Vec::new();
+    let mut sink = CsvSink::new(&mut out);
+    last.replay(&mut sink, "")?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+fn markdown(last: &LastResult) -> String {
+    let cell = |text: &str| text.replace('|', "\\|").replace(['\n', '\r'], " ");
+    let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+    let mut lines = vec![
+        line(last.columns.iter().map(|name| cell(name)).collect()),
+        line(last.columns.iter().map(|_| "---".to_string()).collect()),
+    ];
+    for row in &last.rows {
+        lines.push(line(row.iter().map(|value| cell(value.as_deref().unwrap_or("NULL"))).collect()));
+    }
+    lines.join("\n") + "\n"
+}
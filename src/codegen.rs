@@ -0,0 +1,259 @@
+use mysql::prelude::*;
+use mysql::Conn;
+use rusql::error::{Result, RusqlError};
+use std::str::FromStr;
+
+/// Words that need a raw identifier (`r#type`) as field names.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "type", "unsafe", "use", "where", "while", "yield",
+];
+
+/// Which library the generated structs are written for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Style {
+    /// Plain structs deriving `Serialize` and `Deserialize`.
+    #[default]
+    Serde,
+    /// `sqlx::FromRow` structs, also serializable.
+    Sqlx,
+    /// A `diesel::table!` schema with `Queryable`, `Selectable` and `Insertable` structs.
+    Diesel,
+}
+
+impl Style {
+    pub const VARIANTS: &'static [&'static str] = &["serde", "sqlx", "diesel"];
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "serde" => Ok(Style::Serde),
+            "sqlx" => Ok(Style::Sqlx),
+            "diesel" => Ok(Style::Diesel),
+            other => Err(format!("unknown style '{}'", other)),
+        }
+    }
+}
+
+/// A column as needed for code generation.
+struct Column {
+    name: String,
+    data_type: String,
+    column_type: String,
+    nullable: bool,
+    primary: bool,
+    comment: String,
+}
+
+/// Rust source for `tables` of `db` (all base tables when empty): one
+/// struct per table, field types following the column types with `Option`
+/// for nullable columns. Returns the source and the number of structs.
+pub fn render(conn: &mut Conn, db: &str, tables: &[String], style: Style) -> Result<(String, usize)> {
+    let tables: Vec<String> = match tables.is_empty() {
+        true => conn.exec(
+            "SELECT TABLE_NAME FROM information_schema.TABLES \
+             WHERE TABLE_SCHEMA = ? AND TABLE_TYPE = 'BASE TABLE' ORDER BY TABLE_NAME",
+            (db,),
+        )?,
+        false => tables.to_vec(),
+    };
+    if tables.is_empty() {
+        return Err(RusqlError::Command(format!("No tables in '{}'", db)));
+    }
+
+    let mut source = String::from("// Generated by `rusql codegen` from the live schema; regenerate rather than edit.\n\n");
+    source.push_str(match style {
+        Style::Serde => "use serde::{Deserialize, Serialize};\n",
+        Style::Sqlx => "use serde::{Deserialize, Serialize};\nuse sqlx::FromRow;\n",
+        Style::Diesel => "use diesel::prelude::*;\n",
+    });
+
+    for table in &tables {
+        let rows: Vec<(String, String, String, String, String, String)> = conn.exec(
+            "SELECT COLUMN_NAME, DATA_TYPE, COLUMN_TYPE, IS_NULLABLE, COLUMN_KEY, COLUMN_COMMENT \
+             FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+            (db, table),
+        )?;
+        if rows.is_empty() {
+            return Err(RusqlError::Command(format!("Table '{}.{}' doesn't exist", db, table)));
+        }
+        let columns: Vec<Column> = rows.into_iter()
+            .map(|(name, data_type, column_type, nullable, key, comment)| Column {
+                name,
+                data_type: data_type.to_lowercase(),
+                column_type: column_type.to_lowercase(),
+                nullable: nullable == "YES",
+                primary: key == "PRI",
+                comment,
+            })
+            .collect();
+
+        source.push('\n');
+        if style == Style::Diesel {
+            source.push_str(&diesel_table(table, &columns)?);
+            source.push('\n');
+        }
+        source.push_str(&structure(table, &columns, style));
+    }
+    Ok((source, tables.len()))
+}
+
+fn structure(table: &str, columns: &[Column], style: Style) -> String {
+    let mut code = match style {
+        Style::Serde => "#[derive(Debug, Clone, Serialize, Deserialize)]\n".to_string(),
+        Style::Sqlx => "#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]\n".to_string(),
+        Style::Diesel => format!(
+            "#[derive(Debug, Clone, Queryable, Selectable, Insertable)]\n#[diesel(table_name = {})]\n\
+             #[diesel(check_for_backend(diesel::mysql::Mysql))]\n",
+            field_name(table)
+        ),
+    };
+    code.push_str(&format!("pub struct {} {{\n", struct_name(table)));
+    for column in columns {
+        if !column.comment.is_empty() {
+            code.push_str(&format!("    /// {}\n", column.comment.replace('\n', " ")));
+        }
+        let field = field_name(&column.name);
+        // Diesel renames in `table!` instead, where fields meet columns
+        if renamed(&field, &column.name) && style != Style::Diesel {
+            code.push_str(&format!("    #[serde(rename = \"{}\")]\n", column.name));
+            if style == Style::Sqlx {
+                code.push_str(&format!("    #[sqlx(rename = \"{}\")]\n", column.name));
+            }
+        }
+        let rust = rust_type(column, style);
+        let rust = if column.nullable { format!("Option<{}>", rust) } else { rust.to_string() };
+        code.push_str(&format!("    pub {}: {},\n", field, rust));
+    }
+    code.push_str("}\n");
+    code
+}
+
+/// The Rust type a column reads into.
+fn rust_type(column: &Column, style: Style) -> &'static str {
+    let unsigned = column.column_type.contains("unsigned");
+    match column.data_type.as_str() {
+        "tinyint" if column.column_type.starts_with("tinyint(1)") => "bool",
+        "bit" if column.column_type == "bit(1)" => "bool",
+        "tinyint" if unsigned => "u8",
+        "tinyint" => "i8",
+        "smallint" if unsigned => "u16",
+        "smallint" => "i16",
+        "mediumint" | "int" | "integer" if unsigned => "u32",
+        "mediumint" | "int" | "integer" => "i32",
+        "bigint" if unsigned => "u64",
+        "bigint" => "i64",
+        "bit" => "u64",
+        "year" => "u16",
+        "float" => "f32",
+        "double" | "real" => "f64",
+        "decimal" | "numeric" if style == Style::Diesel => "bigdecimal::BigDecimal",
+        "decimal" | "numeric" => "rust_decimal::Decimal",
+        "date" => "chrono::NaiveDate",
+        "time" => "chrono::NaiveTime",
+        "datetime" => "chrono::NaiveDateTime",
+        "timestamp" if style == Style::Diesel => "chrono::NaiveDateTime",
+        "timestamp" => "chrono::DateTime<chrono::Utc>",
+        "json" => "serde_json::Value",
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => "Vec<u8>",
+        _ => "String",
+    }
+}
+
+/// A `diesel::table!` block; diesel needs a primary key to build one.
+fn diesel_table(table: &str, columns: &[Column]) -> Result<String> {
+    let key: Vec<String> = columns.iter().filter(|c| c.primary).map(|c| field_name(&c.name)).collect();
+    if key.is_empty() {
+        return Err(RusqlError::Command(format!("Table '{}' has no primary key, which diesel requires", table)));
+    }
+    let mut code = String::from("diesel::table! {\n");
+    if renamed(&field_name(table), table) {
+        code.push_str(&format!("    #[sql_name = \"{}\"]\n", table));
+    }
+    code.push_str(&format!("    {} ({}) {{\n", field_name(table), key.join(", ")));
+    for column in columns {
+        let unsigned = column.column_type.contains("unsigned");
+        let sql = match column.data_type.as_str() {
+            "tinyint" if column.column_type.starts_with("tinyint(1)") => "Bool",
+            "bit" if column.column_type == "bit(1)" => "Bool",
+            "tinyint" => "Tinyint",
+            "smallint" | "year" => "Smallint",
+            "mediumint" | "int" | "integer" => "Integer",
+            "bigint" | "bit" => "Bigint",
+            "float" => "Float",
+            "double" | "real" => "Double",
+            "decimal" | "numeric" => "Decimal",
+            "date" => "Date",
+            "time" => "Time",
+            "datetime" => "Datetime",
+            "timestamp" => "Timestamp",
+            "json" => "Json",
+            "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => "Blob",
+            "char" | "varchar" => "Varchar",
+            _ => "Text",
+        };
+        let sql = match unsigned && matches!(sql, "Tinyint" | "Smallint" | "Integer" | "Bigint") {
+            true => format!("Unsigned<{}>", sql),
+            false => sql.to_string(),
+        };
+        let sql = if column.nullable { format!("Nullable<{}>", sql) } else { sql };
+        let name = field_name(&column.name);
+        if renamed(&name, &column.name) {
+            code.push_str(&format!("        #[sql_name = \"{}\"]\n", column.name));
+        }
+        code.push_str(&format!("        {} -> {},\n", name, sql));
+    }
+    code.push_str("    }\n}\n");
+    Ok(code)
+}
+
+/// `order_items` -> `OrderItem`: PascalCase, with a plural ending dropped.
+fn struct_name(table: &str) -> String {
+    let singular = if let Some(stem) = table.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if table.ends_with("ss") || table.ends_with("us") || !table.ends_with('s') {
+        table.to_string()
+    } else {
+        table[..table.len() - 1].to_string()
+    };
+    singular.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+        })
+        .collect()
+}
+
+/// A snake_case field name, as a raw identifier when it is a keyword.
+fn field_name(column: &str) -> String {
+    let mut name = String::new();
+    let mut previous_lower = false;
+    for c in column.chars() {
+        if c.is_uppercase() && previous_lower {
+            name.push('_');
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        if c.is_alphanumeric() {
+            name.extend(c.to_lowercase());
+        } else {
+            name.push('_');
+        }
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    match KEYWORDS.contains(&name.as_str()) {
+        true => format!("r#{}", name),
+        false => name,
+    }
+}
+
+
+fn renamed(field: &str, column: &str) -> bool {
+    field.trim_start_matches("r#") != column
+}
@@ -0,0 +1,547 @@
+use crate::favorites::{self, Favorites};
+use crate::{autolimit, binlogs, chart, counters, ddl, deadlocks, expand, execute_statements, filter, fk_graph, flashback, histogram, index_report, infer_schema, introspect, notify, partitions, print_result, profile, recall, relative, replication, reshape, row_edit, server_variables, sizes, sqlmode, timeout, usage, users, variables};
+#[cfg(feature = "bulk")]
+use crate::{checksum, chunked, osc, transfer};
+#[cfg(feature = "clipboard")]
+use crate::clipboard;
+#[cfg(feature = "duckdb")]
+use crate::local;
+#[cfg(feature = "jobs")]
+use crate::{fanout, jobs, shards};
+use crate::expand::last_result;
+use mysql::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process::Command as Process;
+use crate::redirect::Redirect;
+use crate::{MySQLClient, QueryResult};
+use rusql::compress;
+use rusql::error::{Result, RusqlError};
+use rusql::sink::OutputFormat;
+use rusql::statements;
+
+/// Whether the input is a backslash meta-command rather than SQL.
+pub fn is_meta_command(input: &str) -> bool {
+    input.trim_start().starts_with('\\')
+}
+
+/// Whether the buffered input is ready to be executed.
+///
+/// Meta-commands and `DELIMITER` lines run at the end of their line, except
+/// `\save` whose query may span several lines and is terminated by `;` like
+/// any statement. SQL runs once the buffer ends with the active delimiter.
+pub fn is_complete(buffer: &str, delimiter: &str) -> bool {
+    let trimmed = buffer.trim();
+    if is_meta_command(trimmed) && !trimmed.starts_with("\\save") {
+        return true;
+    }
+    if statements::delimiter_command(trimmed).is_some() {
+        return true;
+    }
+    trimmed.ends_with(delimiter) || split_pipe(trimmed).1.is_some() || split_go(trimmed).1.is_some()
+}
+
+/// Split `SELECT ... \g [file]` into the statement and the file its result
+/// is written to. A bare `\g` yields an empty file name and just executes.
+pub fn split_go(input: &str) -> (&str, Option<&str>) {
+    let trimmed = input.trim_end();
+    let Some(pos) = trimmed.rfind("\\g") else {
+        return (input, None);
+    };
+    let rest = &trimmed[pos + 2..];
+    if !(rest.is_empty() || rest.starts_with(char::is_whitespace)) || trimmed[..pos].trim().is_empty() {
+        return (input, None);
+    }
+    (&trimmed[..pos], Some(rest.trim()))
+}
+
+/// Split `SELECT ...; | command` into the statement and the command its
+/// output is piped to. A `|` only starts a pipe after the final `;`.
+pub fn split_pipe(input: &str) -> (&str, Option<&str>) {
+    let Some(end) = input.rfind(';') else {
+        return (input, None);
+    };
+    match input[end + 1..].trim().strip_prefix('|') {
+        Some(command) if !command.trim().is_empty() => (&input[..=end], Some(command.trim())),
+        _ => (input, None),
+    }
+}
+
+/// Run a backslash meta-command, expanding user-defined aliases.
+pub fn execute(client: &mut MySQLClient, input: &str) -> Result<Option<QueryResult>> {
+    let (command, args) = split_command(input);
+
+    if let Some(target) = client.aliases.get(&command[1..]).cloned() {
+        let expanded = format!("{} {}", target, args);
+        return if is_meta_command(&target) {
+            dispatch(client, &expanded)
+        } else {
+            client.execute_query(&expanded)
+        };
+    }
+
+    dispatch(client, input)
+}
+
+fn split_command(input: &str) -> (&str, &str) {
+    let input = input.trim().trim_end_matches(';').trim_end();
+    input.split_once(char::is_whitespace)
+        .map(|(command, args)| (command, args.trim()))
+        .unwrap_or((input, ""))
+}
+
+fn dispatch(client: &mut MySQLClient, input: &str) -> Result<Option<QueryResult>> {
+    let (command, args) = split_command(input);
+
+    match command {
+        "\\!" => shell(client, args),
+        "\\source" | "\\." => source(client, args),
+        "\\c" => {
+            print!("\x1B[2J\x1B[1;1H");  // Clear screen
+            Ok(None)
+        }
+        "\\save" => save_favorite(client, args),
+        "\\run" => run_favorite(client, args),
+        "\\favorites" => list_favorites(client),
+        "\\delete-favorite" => delete_favorite(client, args),
+        "\\set" => set_variable(client, args),
+        "\\unset" => unset_variable(client, args),
+        "\\alias" => set_alias(client, args),
+        "\\format" => set_format(client, args),
+        "\\pager" => set_pager(client, args),
+        "\\pipe" => set_pipe(client, args),
+        "\\o" => set_output(client, args),
+        "\\export" => export(client, args),
+        "\\nopager" => set_pager(client, ""),
+        "\\paging" => set_paging(client, args),
+        "\\totals" => set_totals(client, args),
+        "\\mask" => set_masking(client, args),
+        "\\unmask" => unmask(client),
+        "\\autolimit" => autolimit::set(client, args),
+        "\\relative" => relative::set(client, args),
+        "\\expand" => expand::expand(client, args),
+        "\\recall" => recall::recall(client, args),
+        #[cfg(feature = "duckdb")]
+        "\\local" => local::local(client, args),
+        #[cfg(feature = "clipboard")]
+        "\\copy-last" => clipboard::copy_last(client, args),
+        #[cfg(feature = "clipboard")]
+        "\\copy-cell" => clipboard::copy_cell(client, args),
+        "\\transpose" => reshape::transpose(client, args),
+        "\\pivot" => reshape::pivot(client, args),
+        "\\chart" => chart::chart(client, args),
+        "\\histogram" => histogram::histogram(client, args),
+        "\\profile-table" => profile::profile_table(client, args),
+        "\\sizes" => sizes::sizes(client, args),
+        "\\index-report" => index_report::index_report(client, args),
+        "\\deadlocks" => deadlocks::deadlocks(client, args),
+        "\\replication" => replication::replication(client, args),
+        "\\binlog" => binlogs::binlog(client, args),
+        "\\flashback" => flashback::flashback(client, args),
+        "\\variables" => server_variables::variables(client, args),
+        "\\set-global" => server_variables::set_global(client, args),
+        "\\users" => users::users(client, args),
+        "\\grants" => users::grants(client, args),
+        "\\create-user" => users::create_user(client, args),
+        "\\grep" => filter::grep(client, args),
+        "\\filter" => filter::filter(client, args),
+        "\\safe-updates" => set_safe_updates(client, args),
+        "\\sqlmode" => sqlmode::sqlmode(client, args),
+        "\\timeout" => timeout::timeout(client, args),
+        "\\notify" => notify::set(client, args),
+        "\\begin" => client.execute_query("START TRANSACTION"),
+        "\\commit" => client.execute_query("COMMIT"),
+        "\\rollback" => client.execute_query("ROLLBACK"),
+        "\\autocommit" => set_autocommit(client, args),
+        "\\stats" => show_stats(client, args),
+        "\\counters" => counters::counters(client, args),
+        #[cfg(feature = "jobs")]
+        "\\bg" => jobs::background(client, args),
+        #[cfg(feature = "jobs")]
+        "\\jobs" => jobs::list(client),
+        #[cfg(feature = "jobs")]
+        "\\fg" => jobs::foreground(client, args),
+        #[cfg(feature = "jobs")]
+        "\\fanout" => fanout::fanout(client, args),
+        #[cfg(feature = "jobs")]
+        "\\shard" => shards::shard(client, args),
+        "\\l" => introspect::list_databases(client),
+        "\\dt" => introspect::list_tables(client, args),
+        "\\d" if args.is_empty() => introspect::list_tables(client, ""),
+        "\\d" => introspect::describe_table(client, args),
+        "\\df" => introspect::list_routines(client, args),
+        "\\events" => introspect::list_events(client, args),
+        "\\triggers" => introspect::list_triggers(client, args),
+        "\\fk-graph" => fk_graph::fk_graph(client, args),
+        "\\infer-schema" => infer_schema::infer_schema(client, args),
+        "\\ddl-diff" => ddl::ddl_diff(client, args),
+        #[cfg(feature = "bulk")]
+        "\\transfer" => transfer::transfer(client, args),
+        #[cfg(feature = "bulk")]
+        "\\chunked" => chunked::chunked(client, args),
+        #[cfg(feature = "bulk")]
+        "\\osc" => osc::osc(client, args),
+        #[cfg(feature = "bulk")]
+        "\\checksum" => checksum::checksum(client, args),
+        "\\partitions" => partitions::partitions(client, args),
+        "\\edit-row" => row_edit::edit_row(client, args),
+        _ => Err(RusqlError::Command(format!("Unknown command '{}'", command))),
+    }
+}
+
+/// `\! command` runs a shell command; `\!` alone starts an interactive shell.
+fn shell(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut command = if args.is_empty() {
+        let shell = std::env::var(if cfg!(windows) { "COMSPEC" } else { "SHELL" })
+            .unwrap_or_else(|_| if cfg!(windows) { "cmd".into() } else { "sh".into() });
+        Process::new(shell)
+    } else {
+        let mut command = Process::new(if cfg!(windows) { "cmd" } else { "sh" });
+        command.arg(if cfg!(windows) { "/C" } else { "-c" }).arg(args);
+        command
+    };
+
+    let status = command.status()?;
+    if !status.success() {
+        client.notice(&format!("Shell exited with {}", status));
+    }
+    Ok(None)
+}
+
+/// `\source [--single-transaction] file`: run a script, which may change the
+/// delimiter for its own statements only. `--single-transaction` runs it as
+/// one transaction, as the command-line flag of that name does for all scripts.
+fn source(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let (single_transaction, path) = match args.strip_prefix("--single-transaction") {
+        Some(path) => (true, path.trim_start()),
+        None => (client.single_transaction, args),
+    };
+    if path.is_empty() {
+        return Err(RusqlError::Command("Usage: \\source [--single-transaction] <file>".into()));
+    }
+    let single_transaction = std::mem::replace(&mut client.single_transaction, single_transaction);
+    let outcome = source_file(client, Path::new(path));
+    client.single_transaction = single_transaction;
+    outcome.map(|_| None)
+}
+
+/// Run the statements of a script file, which may change the delimiter for
+/// its own statements only.
+pub fn source_file(client: &mut MySQLClient, path: &Path) -> Result<()> {
+    let script = compress::read_to_string(path)?;
+    let splitter = std::mem::take(&mut client.splitter);
+    let outcome = execute_statements(client, &script, true);
+    client.splitter = splitter;
+    outcome
+}
+
+fn save_favorite(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let (name, query) = args.split_once(char::is_whitespace)
+        .map(|(name, query)| (name, query.trim()))
+        .filter(|(_, query)| !query.is_empty())
+        .ok_or_else(|| RusqlError::Command("Usage: \\save <name> <query>;".into()))?;
+
+    Favorites::load().insert(name, query)?;
+    client.notice(&format!("Saved favorite '{}'", name));
+    Ok(None)
+}
+
+fn run_favorite(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut args = args.split_whitespace();
+    let name = args.next().ok_or_else(|| RusqlError::Command("Usage: \\run <name> [args...]".into()))?;
+    let params: Vec<&str> = args.collect();
+
+    let favorites = Favorites::load();
+    let query = favorites.get(name).ok_or_else(|| RusqlError::Command(format!("No favorite named '{}'", name)))?;
+    let query = favorites::substitute(query, &params)?;
+
+    println!("> {}", query);
+    client.execute_query(&query)
+}
+
+fn list_favorites(client: &mut MySQLClient) -> Result<Option<QueryResult>> {
+    let favorites = Favorites::load();
+    let rows: Vec<Vec<String>> = favorites.iter()
+        .map(|(name, query)| vec![name.clone(), query.clone()])
+        .collect();
+    let summary = format!("{} {}", rows.len(), if rows.len() == 1 { "favorite" } else { "favorites" });
+
+    Ok(Some(QueryResult {
+        table: client.build_table(&["Name", "Query"], rows),
+        summary,
+    }))
+}
+
+fn delete_favorite(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        return Err(RusqlError::Command("Usage: \\delete-favorite <name>".into()));
+    }
+
+    if Favorites::load().remove(args)? {
+        client.notice(&format!("Deleted favorite '{}'", args));
+        Ok(None)
+    } else {
+        Err(RusqlError::Command(format!("No favorite named '{}'", args)))
+    }
+}
+
+fn set_variable(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        let rows: Vec<Vec<String>> = client.variables.iter()
+            .map(|(name, value)| vec![name.clone(), value.clone()])
+            .collect();
+        let summary = format!("{} {}", rows.len(), if rows.len() == 1 { "variable" } else { "variables" });
+        return Ok(Some(QueryResult {
+            table: client.build_table(&["Name", "Value"], rows),
+            summary,
+        }));
+    }
+
+    let (name, value) = args.split_once(char::is_whitespace)
+        .map(|(name, value)| (name, value.trim()))
+        .unwrap_or((args, ""));
+    if !variables::is_valid_name(name) {
+        return Err(RusqlError::Command(format!("Invalid variable name '{}'", name)));
+    }
+
+    client.variables.insert(name.to_string(), value.to_string());
+    Ok(None)
+}
+
+fn unset_variable(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if client.variables.remove(args).is_none() {
+        return Err(RusqlError::Command(format!("No variable named '{}'", args)));
+    }
+    Ok(None)
+}
+
+fn set_alias(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        let rows: Vec<Vec<String>> = client.aliases.iter()
+            .map(|(name, target)| vec![format!("\\{}", name), target.clone()])
+            .collect();
+        let summary = format!("{} {}", rows.len(), if rows.len() == 1 { "alias" } else { "aliases" });
+        return Ok(Some(QueryResult {
+            table: client.build_table(&["Alias", "Expands to"], rows),
+            summary,
+        }));
+    }
+
+    let (name, target) = args.split_once(char::is_whitespace)
+        .map(|(name, target)| (name.trim_start_matches('\\'), target.trim()))
+        .filter(|(name, target)| !name.is_empty() && !target.is_empty())
+        .ok_or_else(|| RusqlError::Command("Usage: \\alias <name> <command>".into()))?;
+
+    client.aliases.insert(name.to_string(), target.to_string());
+    Ok(None)
+}
+
+fn set_format(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    client.format = args.parse().map_err(RusqlError::Command)?;
+    client.notice(&format!("Output format set to {}", args.to_lowercase()));
+    Ok(None)
+}
+
+fn set_pager(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        client.pager = None;
+        client.notice("PAGER set to stdout");
+    } else {
+        client.pager = Some(args.to_string());
+        client.notice(&format!("PAGER set to '{}'", args));
+    }
+    Ok(None)
+}
+
+fn set_paging(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    client.paging = match args.to_lowercase().as_str() {
+        "on" | "1" | "true" => true,
+        "off" | "0" | "false" => false,
+        _ => return Err(RusqlError::Command("Usage: \\paging on|off".into())),
+    };
+    client.notice(&format!("Paging {}", if client.paging { "enabled" } else { "disabled" }));
+    Ok(None)
+}
+
+fn set_totals(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    client.totals = match args.to_lowercase().as_str() {
+        "" => !client.totals,
+        "on" | "1" | "true" => true,
+        "off" | "0" | "false" => false,
+        _ => return Err(RusqlError::Command("Usage: \\totals [on|off]".into())),
+    };
+    client.notice(&format!("Totals {}", if client.totals { "enabled" } else { "disabled" }));
+    Ok(None)
+}
+
+fn set_masking(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let masking = match args.to_lowercase().as_str() {
+        "" => !client.masking,
+        "on" | "1" | "true" => true,
+        "off" | "0" | "false" => false,
+        _ => return Err(RusqlError::Command("Usage: \\mask [on|off]".into())),
+    };
+    if masking && client.mask_rules.is_empty() {
+        return Err(RusqlError::Command("No [masking] rules in config.toml".into()));
+    }
+    client.masking = masking;
+    client.notice(&format!("Masking {}", if client.masking { "enabled" } else { "disabled" }));
+    Ok(None)
+}
+
+fn unmask(client: &mut MySQLClient) -> Result<Option<QueryResult>> {
+    if client.sensitive_columns.is_empty() {
+        return Err(RusqlError::Command("No sensitive_columns in config.toml".into()));
+    }
+    client.unmask_next = true;
+    client.notice("The next result shows sensitive columns unmasked");
+    Ok(None)
+}
+
+fn set_pipe(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        return Err(RusqlError::Command("Usage: \\pipe <command>".into()));
+    }
+    client.redirect = Some(Redirect::Pipe(args.to_string()));
+    client.notice(&format!("The next result goes to '{}'", args));
+    Ok(None)
+}
+
+fn set_output(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        client.output = None;
+        client.notice("Results go to stdout");
+        return Ok(None);
+    }
+    // Start with an empty file; results are appended one after another
+    std::fs::File::create(args)?;
+    client.output = Some(PathBuf::from(args));
+    client.notice(&format!("Results go to '{}'", args));
+    Ok(None)
+}
+
+/// `\export file [format] query`: write one result to a file in the format
+/// named by its extension (`.csv`, `.json`, `.xlsx`, ...) or given after it.
+/// `\export last file [format]` writes the last result shown instead.
+fn export(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let usage = || RusqlError::Command(format!(
+        "Usage: \\export <file> [{}] <query> or \\export last <file> [format]",
+        OutputFormat::VARIANTS[1..].join("|")
+    ));
+    let (file, rest) = args.split_once(char::is_whitespace).ok_or_else(usage)?;
+    if file == "last" {
+        let mut words = rest.split_whitespace();
+        let (Some(file), named, None) = (words.next(), words.next(), words.next()) else {
+            return Err(usage());
+        };
+        return export_last(client, file, export_format(file, named)?);
+    }
+    let (named, query) = match rest.trim().split_once(char::is_whitespace) {
+        Some((word, query)) if OutputFormat::VARIANTS[1..].contains(&word.to_lowercase().as_str()) => (Some(word), query),
+        _ => (None, rest),
+    };
+    let format = export_format(file, named)?;
+
+    let previous = std::mem::replace(&mut client.format, format);
+    client.redirect = Some(Redirect::File { path: PathBuf::from(file), append: false });
+    let outcome = client.execute_query(query.trim());
+    client.format = previous;
+    client.redirect = None;
+    outcome
+}
+
+/// The format given by name, or else by the file's extension; never `table`.
+fn export_format(file: &str, named: Option<&str>) -> Result<OutputFormat> {
+    let extension = compress::inner_path(Path::new(file)).extension().map(|ext| ext.to_string_lossy().to_lowercase());
+    named.map(str::to_lowercase).or(extension)
+        .filter(|format| OutputFormat::VARIANTS[1..].contains(&format.as_str()))
+        .ok_or_else(|| RusqlError::Command(format!(
+            "Name the format of '{}': {}",
+            file,
+            OutputFormat::VARIANTS[1..].join(", ")
+        )))?
+        .parse()
+        .map_err(RusqlError::Command)
+}
+
+fn export_last(client: &mut MySQLClient, file: &str, format: OutputFormat) -> Result<Option<QueryResult>> {
+    let last = last_result(client)?;
+    let mut out = compress::create(Path::new(file), false)?;
+    let Some(mut sink) = format.sink(&mut out, &client.sink_options) else {
+        return Err(RusqlError::Command("Tables can't be exported; pick a file format".into()));
+    };
+    last.replay(sink.as_mut(), "")?;
+    client.notice(&format!("{} rows written to '{}'", last.rows.len(), file));
+    Ok(None)
+}
+
+fn set_safe_updates(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let enabled = match args.to_lowercase().as_str() {
+        "on" | "1" | "true" => true,
+        "off" | "0" | "false" => false,
+        _ => return Err(RusqlError::Command("Usage: \\safe-updates on|off".into())),
+    };
+
+    client.conn.query_drop(format!("SET SESSION sql_safe_updates = {}", enabled as u8))?;
+    client.notice(&format!("Safe updates {}", if enabled { "enabled" } else { "disabled" }));
+    Ok(None)
+}
+
+fn set_autocommit(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let enabled = match args.to_lowercase().as_str() {
+        "" => {
+            let enabled: u8 = client.conn.query_first("SELECT @@autocommit")?.unwrap_or(1);
+            client.notice(&format!("Autocommit is {}", if enabled != 0 { "on" } else { "off" }));
+            return Ok(None);
+        }
+        "on" | "1" | "true" => true,
+        "off" | "0" | "false" => false,
+        _ => return Err(RusqlError::Command("Usage: \\autocommit [on|off]".into())),
+    };
+
+    client.execute_query(&format!("SET autocommit = {}", enabled as u8))?;
+    client.notice(&format!("Autocommit {}", if enabled { "enabled" } else { "disabled" }));
+    Ok(None)
+}
+
+fn show_stats(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let since = match args {
+        "" | "session" => Some(client.session_start),
+        "lifetime" => None,
+        _ => return Err(RusqlError::Command("Usage: \\stats [session|lifetime]".into())),
+    };
+
+    let stats = usage::aggregate(&usage::load(since), 5);
+    let error_rate = if stats.total == 0 { 0.0 } else { stats.errors as f64 * 100.0 / stats.total as f64 };
+
+    let rows = stats.per_connection.iter()
+        .map(|(name, total, errors)| vec![name.clone(), total.to_string(), errors.to_string()])
+        .collect();
+    print_result(client, QueryResult {
+        table: client.build_table(&["Connection", "Statements", "Errors"], rows),
+        summary: format!("{} statements, {:.1}% failed", stats.total, error_rate),
+    });
+
+    let rows = stats.top_statements.iter()
+        .map(|(statement, count)| vec![count.to_string(), statement.clone()])
+        .collect();
+    print_result(client, QueryResult {
+        table: client.build_table(&["Runs", "Most used statements"], rows),
+        summary: String::new(),
+    });
+
+    let busiest = stats.per_hour.iter().copied().max().unwrap_or(0).max(1);
+    let rows = stats.per_hour.iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(hour, count)| vec![
+            format!("{:02}:00", hour),
+            count.to_string(),
+            "#".repeat((count * 40).div_ceil(busiest)),
+        ])
+        .collect();
+
+    Ok(Some(QueryResult {
+        table: client.build_table(&["Hour", "Statements", ""], rows),
+        summary: "Statistics are computed from the local usage log and never leave this machine".into(),
+    }))
+}
@@ -0,0 +1,157 @@
+use crate::config::Settings;
+use rusql::error::Result;
+use std::io::Write;
+use clap::Command;
+use clap_complete::Shell;
+
+/// Options taking a profile name, by subcommand, and what follows the name.
+/// `copy` tables are written `profile/db.table`, so their names end in `/`.
+const PROFILE_OPTIONS: &[(&str, &str, &str)] = &[
+    ("sync", "source", ""),
+    ("sync", "target", ""),
+    ("copy", "from", "/"),
+    ("copy", "to", "/"),
+];
+
+/// `rusql completions <shell>`: print a script completing flags and
+/// subcommands, and the profile names of the options taking one. The names
+/// are read when completing, from `rusql completions --profiles`, so the
+/// script stays current as profiles are added.
+pub fn print(mut command: Command, shell: Shell) -> Result<()> {
+    let mut script = Vec::new();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut script);
+    let script = String::from_utf8_lossy(&script);
+    let script = match shell {
+        Shell::Bash => format!("{}\n{}", script, bash()),
+        Shell::Zsh => zsh(&script),
+        Shell::Fish => format!("{}{}", script, fish()),
+        Shell::PowerShell => powershell(&script),
+        _ => script.into_owned(),
+    };
+    let mut stdout = std::io::stdout();
+    stdout.write_all(script.as_bytes())?;
+    Ok(())
+}
+
+/// `rusql completions --profiles`: the profile names, one per line.
+pub fn print_profiles(settings: &Settings) -> Result<()> {
+    for name in settings.profiles.keys() {
+        println!("{}", name);
+    }
+    Ok(())
+}
+
+/// A completion function taking over from the generated one after the
+/// options taking a profile.
+fn bash() -> String {
+    let cases: String = PROFILE_OPTIONS.iter()
+        .map(|(command, flag, suffix)| {
+            let nospace = if suffix.is_empty() { "" } else { "compopt -o nospace; " };
+            format!(
+                "        {}:--{})\n            {}COMPREPLY=($(compgen -S \"{}\" -W \"$(rusql completions --profiles 2>/dev/null)\" -- \"${{cur}}\"))\n            return 0\n            ;;\n",
+                command, flag, nospace, suffix
+            )
+        })
+        .collect();
+    format!(
+        r#"_rusql_profiles() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" word command
+    for word in "${{COMP_WORDS[@]:1:COMP_CWORD-1}}"; do
+        case "${{word}}" in
+            sync|copy) command="${{word}}"; break ;;
+        esac
+    done
+    case "${{command}}:${{COMP_WORDS[COMP_CWORD-1]}}" in
+{}    esac
+    _rusql "$@"
+}}
+
+complete -F _rusql_profiles -o bashdefault -o default rusql
+"#,
+        cases
+    )
+}
+
+/// The generated script with the profile options completing names.
+fn zsh(script: &str) -> String {
+    let mut command = "";
+    let mut lines = Vec::new();
+    for line in script.lines() {
+        if let Some(name) = line.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+            command = name;
+        }
+        let profile = PROFILE_OPTIONS.iter()
+            .find(|(name, flag, _)| *name == command && line.starts_with(&format!("'--{}=[", flag)));
+        // `'--source=[help]:SOURCE:_default' \` names the completer after the help
+        match (profile, line.rfind("]:")) {
+            (Some((_, _, suffix)), Some(end)) => {
+                let completer = if suffix.is_empty() { "_rusql_profiles" } else { "_rusql_profile_tables" };
+                lines.push(format!("{}]:profile:{}' \\", &line[..end], completer));
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    let mut script = lines.join("\n");
+    // Defined before the script either runs or registers the completion
+    let call = script.rfind("if [ \"$funcstack[1]\" = \"_rusql\" ]").or_else(|| script.rfind("_rusql \"$@\""));
+    if let Some(call) = call {
+        script.insert_str(call, r#"(( $+functions[_rusql_profiles] )) ||
+_rusql_profiles() {
+    local -a profiles
+    profiles=(${(f)"$(rusql completions --profiles 2>/dev/null)"})
+    compadd -a profiles
+}
+(( $+functions[_rusql_profile_tables] )) ||
+_rusql_profile_tables() {
+    local -a profiles
+    profiles=(${(f)"$(rusql completions --profiles 2>/dev/null)"})
+    compadd -S / -a profiles
+}
+
+"#);
+    }
+    script.push('\n');
+    script
+}
+
+fn fish() -> String {
+    PROFILE_OPTIONS.iter()
+        .map(|(command, flag, suffix)| {
+            let names = match *suffix {
+                "" => "rusql completions --profiles".to_string(),
+                suffix => format!("rusql completions --profiles | string replace -r '$' '{}'", suffix),
+            };
+            format!("complete -c rusql -n \"__fish_seen_subcommand_from {}\" -l {} -x -a \"({})\"\n", command, flag, names)
+        })
+        .collect()
+}
+
+/// The generated script, which only completes flags, also completing the
+/// names after the profile options.
+fn powershell(script: &str) -> String {
+    let cases: String = PROFILE_OPTIONS.iter()
+        .map(|(command, flag, suffix)| format!(
+            "        'rusql;{};--{}' {{ $suffix = '{}'; $profiles = $true }}\n",
+            command, flag, suffix
+        ))
+        .collect();
+    let profiles = format!(
+        r#"    $previous = $commandElements[$commandElements.Count - $(if ($wordToComplete) {{ 2 }} else {{ 1 }})].ToString()
+    $profiles = $false
+    switch ("$command;$previous") {{
+{}    }}
+    if ($profiles) {{
+        $completions = @(rusql completions --profiles | ForEach-Object {{
+            [CompletionResult]::new("$_$suffix", $_, [CompletionResultType]::ParameterValue, $_)
+        }})
+    }}
+
+"#,
+        cases
+    );
+    match script.rfind("    $completions.Where") {
+        Some(at) => format!("{}{}{}", &script[..at], profiles, &script[at..]),
+        None => script.to_string(),
+    }
+}
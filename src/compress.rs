@@ -0,0 +1,86 @@
+//! Transparent gzip and zstd compression of files, chosen by extension, so
+//! exports and dumps can be written and read compressed without a temporary
+//! uncompressed copy.
+
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Compression of a file, from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+            Some("gz" | "gzip") => Compression::Gzip,
+            Some("zst" | "zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// The path without a compression extension, for telling the format of
+/// the data inside: `orders.csv.gz` -> `orders.csv`.
+///
+/// ```
+/// use std::path::Path;
+/// use rusql::compress::inner_path;
+/// assert_eq!(inner_path(Path::new("dump/orders.csv.zst")), Path::new("dump/orders.csv"));
+/// assert_eq!(inner_path(Path::new("orders.json")), Path::new("orders.json"));
+/// ```
+pub fn inner_path(path: &Path) -> PathBuf {
+    match Compression::of(path) {
+        Compression::None => path.to_path_buf(),
+        _ => path.with_extension(""),
+    }
+}
+
+/// Open a file for writing, compressing when its extension says so.
+/// Appending to a compressed file adds a new gzip member or zstd frame,
+/// which readers take as one stream. Compression is completed when the
+/// writer is dropped.
+///
+/// ```
+/// use std::io::Write;
+/// use rusql::compress;
+/// let path = std::env::temp_dir().join("rusql-doctest.sql.zst");
+/// for (statement, append) in [("SELECT 1;\n", false), ("SELECT 2;\n", true)] {
+///     compress::create(&path, append)?.write_all(statement.as_bytes())?;
+/// }
+/// assert_eq!(compress::read_to_string(&path)?, "SELECT 1;\nSELECT 2;\n");
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn create(path: &Path, append: bool) -> io::Result<Box<dyn Write + Send>> {
+    let file = OpenOptions::new().create(true).write(true).append(append).truncate(!append).open(path)?;
+    let file = BufWriter::new(file);
+    Ok(match Compression::of(path) {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(GzEncoder::new(file, flate2::Compression::default())),
+        Compression::Zstd => Box::new(zstd::Encoder::new(file, 0)?.auto_finish()),
+    })
+}
+
+/// Open a file for reading, decompressing when its extension says so.
+pub fn open(path: &Path) -> io::Result<Box<dyn Read + Send>> {
+    let file = BufReader::new(File::open(path)?);
+    Ok(match Compression::of(path) {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(MultiGzDecoder::new(file)),
+        Compression::Zstd => Box::new(zstd::Decoder::with_buffer(file)?),
+    })
+}
+
+/// The whole text of a file, decompressed when its extension says so.
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    let mut text = String::new();
+    open(path)?.read_to_string(&mut text)?;
+    Ok(text)
+}
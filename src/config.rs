@@ -0,0 +1,592 @@
+use crate::charset;
+use crate::favorites::FAVORITES_FILE;
+use crate::theme::{Theme, THEMES};
+use colored::Color;
+use rusql::sink::Borders;
+use dirs::config_dir;
+use mysql::{Conn, OptsBuilder};
+use rusql::error::{Result, RusqlError};
+use rusql::mask::{Mask, MaskRules};
+use rusql::shard::ShardHash;
+#[cfg(feature = "jobs")]
+use rusql::shard::ShardMap;
+use rusql::statements;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::time::Duration;
+use toml::de::{DeString, DeTable, DeValue};
+use toml::Spanned;
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// Path of a file inside the Rusql configuration directory
+/// (`~/.config/rusql` on Linux), creating the directory if needed.
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    let mut path = config_dir()?;
+    path.push("rusql");
+    std::fs::create_dir_all(&path).ok()?;
+    path.push(name);
+    Some(path)
+}
+
+/// Options read from `config.toml`. Command line flags take precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Prompt template using MYSQL_PS1 escapes such as `\u@\h [\d]> `
+    pub prompt: Option<String>,
+    /// Column name patterns hidden first when a table is too wide for the terminal
+    pub low_priority_columns: Vec<String>,
+    /// Rows appended as `LIMIT` to interactive SELECTs without one; 0 disables
+    pub auto_limit: Option<u64>,
+    /// Seconds between pings of an idle interactive session; 0 disables
+    pub keepalive: Option<u64>,
+    /// Named connections, e.g. `[profiles.prod]`
+    pub profiles: BTreeMap<String, Profile>,
+    /// Built-in theme and its overrides, e.g. `name = "solarized"`
+    pub theme: ThemeSettings,
+    /// Cell colors by kind of value, e.g. `number = "bright blue"`
+    pub colors: Colors,
+    /// Columns shown masked, as `[db.]table.column` patterns such as `*.password`
+    pub sensitive_columns: Vec<String>,
+    /// Masks by table pattern and column, e.g. `[masking."shop.customers"]` with `email = "email"`
+    pub masking: BTreeMap<String, BTreeMap<String, String>>,
+    /// Key of the `[masking]` hashes and fakes, so they can't be recomputed from guessed values
+    pub mask_secret: Option<String>,
+    /// Where each sharding key's values live, e.g. `[shards.user_id]`
+    #[cfg(feature = "jobs")]
+    pub shards: BTreeMap<String, ShardSettings>,
+    /// Long-statement notifications, see `\notify`
+    pub notify: NotifySettings,
+}
+
+/// The `[notify]` section: how `\notify` reports long statements finishing.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NotifySettings {
+    /// Seconds a statement must run to be reported (default 60)
+    pub after: Option<u64>,
+    /// URL receiving a Slack-style `{"text": ...}` POST
+    pub webhook: Option<String>,
+}
+
+/// The `[theme]` section: a built-in theme with individual colors replaced.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub name: Option<String>,
+    pub prompt: Option<String>,
+    pub header: Option<String>,
+    pub null: Option<String>,
+    pub error: Option<String>,
+    pub warning: Option<String>,
+    pub notice: Option<String>,
+    pub border: Option<String>,
+}
+
+/// The `[colors]` section: color names (`red`, `bright blue`), `#rrggbb`,
+/// or `default` for the terminal's own color.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Colors {
+    pub number: Option<String>,
+    pub date: Option<String>,
+    pub boolean: Option<String>,
+    pub text: Option<String>,
+    pub null: Option<String>,
+}
+
+impl Settings {
+    /// The configured theme; `[colors]` entries win over the theme's cell colors.
+    pub fn theme(&self) -> Result<Theme> {
+        let settings = &self.theme;
+        let name = settings.name.as_deref().unwrap_or("default");
+        let mut theme = Theme::builtin(name).ok_or_else(|| RusqlError::Config(
+            format!("unknown theme '{}', expected one of: {}", name, THEMES.join(", "))))?;
+
+        // `[colors]` predates themes, so its null color wins over `theme.null`
+        let colors = &self.colors;
+        let null = colors.null.as_ref().map(|c| ("colors.null", c))
+            .or(settings.null.as_ref().map(|c| ("theme.null", c)));
+        for (name, setting, color) in [
+            ("theme.prompt", settings.prompt.as_ref(), &mut theme.prompt),
+            ("theme.header", settings.header.as_ref(), &mut theme.cells.header),
+            ("theme.error", settings.error.as_ref(), &mut theme.error),
+            ("theme.warning", settings.warning.as_ref(), &mut theme.warning),
+            ("theme.notice", settings.notice.as_ref(), &mut theme.notice),
+            ("colors.number", colors.number.as_ref(), &mut theme.cells.number),
+            ("colors.date", colors.date.as_ref(), &mut theme.cells.date),
+            ("colors.boolean", colors.boolean.as_ref(), &mut theme.cells.boolean),
+            ("colors.text", colors.text.as_ref(), &mut theme.cells.text),
+            (null.map_or("", |(name, _)| name), null.map(|(_, c)| c), &mut theme.cells.null),
+        ] {
+            if let Some(setting) = setting {
+                *color = parse_color(setting)
+                    .ok_or_else(|| RusqlError::Config(format!("unknown color '{}' for {}", setting, name)))?;
+            }
+        }
+        if let Some(border) = &settings.border {
+            theme.borders = border.parse().map_err(|e| RusqlError::Config(format!("{} for theme.border", e)))?;
+        }
+        Ok(theme)
+    }
+
+    /// Rules showing `sensitive_columns` partially masked.
+    pub fn sensitive_rules(&self) -> MaskRules {
+        self.sensitive_columns.iter().fold(MaskRules::default(), |rules, pattern| {
+            let (table, column) = pattern.rsplit_once('.').unwrap_or(("*", pattern));
+            rules.rule(table, column, Mask::Partial)
+        })
+    }
+
+    /// The `[masking]` rules, in table then column order.
+    pub fn mask_rules(&self) -> Result<MaskRules> {
+        let mut rules = MaskRules::default().secret(self.mask_secret.as_deref().unwrap_or_default());
+        for (table, columns) in &self.masking {
+            for (column, mask) in columns {
+                let mask: Mask = mask.parse()
+                    .map_err(|e| RusqlError::Config(format!("{} for masking.{}.{}", e, table, column)))?;
+                rules = rules.rule(table, column, mask);
+            }
+        }
+        Ok(rules)
+    }
+
+    /// The `[shards]` maps by key name.
+    #[cfg(feature = "jobs")]
+    pub fn shard_maps(&self) -> Result<BTreeMap<String, ShardMap>> {
+        let mut maps = BTreeMap::new();
+        for (key, shard) in &self.shards {
+            let map = match (&shard.hash, shard.ranges.is_empty()) {
+                (None, false) => ShardMap::Ranges(shard.ranges.iter().map(|range| (range.to, range.profile.clone())).collect()),
+                (Some(hash), true) if !shard.profiles.is_empty() => {
+                    let hash: ShardHash = hash.parse()
+                        .map_err(|e| RusqlError::Config(format!("{} for shards.{}.hash", e, key)))?;
+                    ShardMap::Hash(hash, shard.profiles.clone())
+                }
+                _ => return Err(RusqlError::Config(format!(
+                    "shards.{} needs either 'ranges' or 'hash' with 'profiles'",
+                    key
+                ))),
+            };
+            maps.insert(key.clone(), map);
+        }
+        Ok(maps)
+    }
+}
+
+/// A color name (`red`, `bright blue`), `#rrggbb`, or `default` for the
+/// terminal's own foreground color.
+fn parse_color(name: &str) -> Option<Option<Color>> {
+    if name.eq_ignore_ascii_case("default") {
+        return Some(None);
+    }
+    name.parse().ok().map(Some)
+}
+
+/// Connection settings for a named profile.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub database: Option<String>,
+    /// `SET NAMES` on every connection, like `--default-character-set`
+    pub default_character_set: Option<String>,
+    pub default_collation: Option<String>,
+    /// Statements run on every connection, like `--init-command`
+    pub init_command: Vec<String>,
+}
+
+/// A `[shards.key]` section: either `ranges` or `hash` with `profiles`.
+#[cfg(feature = "jobs")]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ShardSettings {
+    /// Profiles by ascending inclusive upper bound, e.g. `{ to = 999999, profile = "shard01" }`
+    pub ranges: Vec<ShardRange>,
+    /// `crc32` or `modulo`, spreading keys over `profiles`
+    pub hash: Option<String>,
+    pub profiles: Vec<String>,
+}
+
+#[cfg(feature = "jobs")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShardRange {
+    pub to: Option<i64>,
+    pub profile: String,
+}
+
+impl Profile {
+    pub fn opts(&self) -> OptsBuilder {
+        OptsBuilder::new()
+            .user(self.user.as_deref())
+            .pass(self.password.as_deref())
+            .ip_or_hostname(Some(self.host.as_deref().unwrap_or("localhost")))
+            .tcp_port(self.port.unwrap_or(3306))
+            .db_name(self.database.as_deref())
+            .init(self.default_character_set.iter()
+                .map(|charset| charset::set_names(charset, self.default_collation.as_deref()))
+                .chain(init_statements(&self.init_command))
+                .collect())
+    }
+
+    pub fn connect(&self) -> Result<Conn> {
+        Ok(Conn::new(self.opts())?)
+    }
+}
+
+/// The statements of `--init-command` values, each of which may hold several.
+pub fn init_statements(commands: &[String]) -> Vec<String> {
+    commands.iter().flat_map(|command| statements::split(command)).collect()
+}
+
+impl Settings {
+    /// Load the configuration file, falling back to defaults when it is missing.
+    pub fn load() -> Result<Settings> {
+        let Some(path) = config_file(CONFIG_FILE) else {
+            return Ok(Settings::default());
+        };
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let contents = std::fs::read_to_string(&path)?;
+        toml::from_str(&contents)
+            .map_err(|e| RusqlError::Config(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Expected type of a configuration value, for `config check`.
+#[derive(Clone, Copy)]
+enum Kind {
+    Text,
+    TextList,
+    Count,
+    Port,
+    Color,
+    Choice(&'static [&'static str]),
+    Profiles,
+    Masking,
+    Shards,
+    /// `[{ to = 999, profile = "name" }, ...]`
+    ShardRanges,
+    Section(&'static [(&'static str, Kind)]),
+}
+
+/// Keys accepted at the top level of `config.toml`; keep in sync with [`Settings`].
+const SETTINGS_KEYS: &[(&str, Kind)] = &[
+    ("prompt", Kind::Text),
+    ("low_priority_columns", Kind::TextList),
+    ("auto_limit", Kind::Count),
+    ("keepalive", Kind::Count),
+    ("profiles", Kind::Profiles),
+    ("theme", Kind::Section(THEME_KEYS)),
+    ("colors", Kind::Section(COLOR_KEYS)),
+    ("sensitive_columns", Kind::TextList),
+    ("masking", Kind::Masking),
+    ("mask_secret", Kind::Text),
+    ("shards", Kind::Shards),
+    ("notify", Kind::Section(NOTIFY_KEYS)),
+];
+
+/// Keys accepted in the `[theme]` table; keep in sync with [`ThemeSettings`].
+const THEME_KEYS: &[(&str, Kind)] = &[
+    ("name", Kind::Choice(THEMES)),
+    ("prompt", Kind::Color),
+    ("header", Kind::Color),
+    ("null", Kind::Color),
+    ("error", Kind::Color),
+    ("warning", Kind::Color),
+    ("notice", Kind::Color),
+    ("border", Kind::Choice(Borders::VARIANTS)),
+];
+
+/// Keys accepted in the `[colors]` table; keep in sync with [`Colors`].
+const COLOR_KEYS: &[(&str, Kind)] = &[
+    ("number", Kind::Color),
+    ("date", Kind::Color),
+    ("boolean", Kind::Color),
+    ("text", Kind::Color),
+    ("null", Kind::Color),
+];
+
+/// Keys accepted in a `[profiles.name]` table; keep in sync with [`Profile`].
+const PROFILE_KEYS: &[(&str, Kind)] = &[
+    ("host", Kind::Text),
+    ("port", Kind::Port),
+    ("user", Kind::Text),
+    ("password", Kind::Text),
+    ("database", Kind::Text),
+    ("default_character_set", Kind::Text),
+    ("default_collation", Kind::Text),
+    ("init_command", Kind::TextList),
+];
+
+/// Keys accepted in a `[shards.key]` table; keep in sync with [`ShardSettings`].
+const SHARD_KEYS: &[(&str, Kind)] = &[
+    ("ranges", Kind::ShardRanges),
+    ("hash", Kind::Choice(ShardHash::VARIANTS)),
+    ("profiles", Kind::TextList),
+];
+
+/// Keys accepted in the `[notify]` table; keep in sync with [`NotifySettings`].
+const NOTIFY_KEYS: &[(&str, Kind)] = &[
+    ("after", Kind::Count),
+    ("webhook", Kind::Text),
+];
+
+/// Seconds to wait for a profile's host when probing.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A problem found by `config check`, located by line.
+pub struct Diagnostic {
+    pub file: String,
+    pub line: usize,
+    pub error: bool,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = if self.error { "error" } else { "warning" };
+        write!(f, "{}:{}: {}: {}", self.file, self.line, severity, self.message)
+    }
+}
+
+/// Validate the configuration files: unknown keys, wrong types, settings that
+/// override each other and, with `probe`, profile hosts that cannot be reached.
+pub fn check(probe: bool) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if let Some(path) = config_file(CONFIG_FILE).filter(|path| path.exists()) {
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                check_settings(&contents, probe, &mut diagnostics);
+                // Tables iterate in key order, so put findings back in file order
+                diagnostics.sort_by_key(|d| d.line);
+            }
+            Err(e) => diagnostics.push(Diagnostic {
+                file: CONFIG_FILE.to_string(),
+                line: 0,
+                error: true,
+                message: e.to_string(),
+            }),
+        }
+    }
+    if let Some(contents) = config_file(FAVORITES_FILE).and_then(|path| std::fs::read_to_string(path).ok()) {
+        check_favorites(&contents, &mut diagnostics);
+    }
+    diagnostics
+}
+
+fn check_settings(contents: &str, probe: bool, diagnostics: &mut Vec<Diagnostic>) {
+    let mut report = |offset: usize, error: bool, message: String| diagnostics.push(Diagnostic {
+        file: CONFIG_FILE.to_string(),
+        line: contents[..offset.min(contents.len())].matches('\n').count() + 1,
+        error,
+        message,
+    });
+
+    let document = match DeTable::parse(contents) {
+        Ok(document) => document,
+        Err(e) => {
+            report(e.span().map_or(0, |span| span.start), true, e.message().to_string());
+            return;
+        }
+    };
+
+    for (key, value) in document.get_ref().iter() {
+        let Some(kind) = expected(SETTINGS_KEYS, key, &mut report) else { continue };
+        match kind {
+            Kind::Profiles => check_profiles(value, probe, &mut report),
+            Kind::Masking => check_masking(value, &mut report),
+            Kind::Shards => check_shards(value, &mut report),
+            Kind::Section(keys) => check_section(key.get_ref(), value, keys, &mut report),
+            kind => check_value(key.get_ref(), value, kind, &mut report),
+        }
+    }
+
+    if document.get_ref().iter().any(|(key, _)| key.get_ref() == "prompt") && std::env::var_os("MYSQL_PS1").is_some() {
+        let offset = document.get_ref().iter()
+            .find(|(key, _)| key.get_ref() == "prompt")
+            .map_or(0, |(key, _)| key.span().start);
+        report(offset, false, "'prompt' is overridden by the MYSQL_PS1 environment variable".to_string());
+    }
+
+    let section_key = |section: &str, name: &str| document.get_ref().get(section)
+        .and_then(|value| value.get_ref().as_table())
+        .and_then(|table| table.iter().find(|(key, _)| key.get_ref() == name).map(|(key, _)| key.span().start));
+    if let (Some(_), Some(offset)) = (section_key("theme", "null"), section_key("colors", "null")) {
+        report(offset, false, "'colors.null' overrides 'theme.null'".to_string());
+    }
+}
+
+fn check_profiles(value: &Spanned<DeValue>, probe: bool, report: &mut impl FnMut(usize, bool, String)) {
+    let Some(profiles) = value.get_ref().as_table() else {
+        report(value.span().start, true, "'profiles' must be a table of [profiles.name] sections".to_string());
+        return;
+    };
+
+    for (name, profile) in profiles.iter() {
+        let Some(fields) = profile.get_ref().as_table() else {
+            report(profile.span().start, true, format!("profile '{}' must be a table", name.get_ref()));
+            continue;
+        };
+        for (key, value) in fields.iter() {
+            if let Some(kind) = expected(PROFILE_KEYS, key, report) {
+                check_value(&format!("profiles.{}.{}", name.get_ref(), key.get_ref()), value, kind, report);
+            }
+        }
+
+        if probe {
+            let text = |key: &str| fields.get(key).and_then(|v| v.get_ref().as_str().map(str::to_string));
+            let host = text("host").unwrap_or_else(|| "localhost".to_string());
+            let port = fields.get("port")
+                .and_then(|v| v.get_ref().as_integer())
+                .and_then(|n| u16::from_str_radix(n.as_str(), n.radix()).ok())
+                .unwrap_or(3306);
+            if let Err(e) = reachable(&host, port) {
+                report(name.span().start, false, format!("profile '{}': cannot reach {}:{}: {}", name.get_ref(), host, port, e));
+            }
+        }
+    }
+}
+
+fn check_masking(value: &Spanned<DeValue>, report: &mut impl FnMut(usize, bool, String)) {
+    let Some(tables) = value.get_ref().as_table() else {
+        report(value.span().start, true, "'masking' must be a table of [masking.\"db.table\"] sections".to_string());
+        return;
+    };
+    for (table, columns) in tables.iter() {
+        let Some(columns) = columns.get_ref().as_table() else {
+            report(columns.span().start, true, format!("masking for '{}' must be a table of column = mask", table.get_ref()));
+            continue;
+        };
+        for (column, mask) in columns.iter() {
+            check_value(&format!("masking.{}.{}", table.get_ref(), column.get_ref()), mask, Kind::Choice(Mask::VARIANTS), report);
+        }
+    }
+}
+
+fn check_shards(value: &Spanned<DeValue>, report: &mut impl FnMut(usize, bool, String)) {
+    let Some(keys) = value.get_ref().as_table() else {
+        report(value.span().start, true, "'shards' must be a table of [shards.key] sections".to_string());
+        return;
+    };
+    for (key, shard) in keys.iter() {
+        check_section(&format!("shards.{}", key.get_ref()), shard, SHARD_KEYS, report);
+        let Some(fields) = shard.get_ref().as_table() else { continue };
+        if fields.get("ranges").is_some() == fields.get("hash").is_some() {
+            report(key.span().start, true, format!("shards.{} needs either 'ranges' or 'hash' with 'profiles'", key.get_ref()));
+        } else if fields.get("hash").is_some() && fields.get("profiles").is_none() {
+            report(key.span().start, true, format!("shards.{} hashes keys but lists no 'profiles'", key.get_ref()));
+        }
+    }
+}
+
+fn check_section(name: &str, value: &Spanned<DeValue>, schema: &[(&str, Kind)], report: &mut impl FnMut(usize, bool, String)) {
+    let Some(fields) = value.get_ref().as_table() else {
+        report(value.span().start, true, format!("'{}' must be a [{}] table", name, name));
+        return;
+    };
+    for (key, value) in fields.iter() {
+        if let Some(kind) = expected(schema, key, report) {
+            check_value(&format!("{}.{}", name, key.get_ref()), value, kind, report);
+        }
+    }
+}
+
+/// The expected kind of `key`, reporting it when it is not part of `schema`.
+fn expected(schema: &[(&str, Kind)], key: &Spanned<DeString>, report: &mut impl FnMut(usize, bool, String)) -> Option<Kind> {
+    let name = key.get_ref();
+    if let Some((_, kind)) = schema.iter().find(|(known, _)| known == name) {
+        return Some(*kind);
+    }
+    let suggestion = schema.iter()
+        .map(|(known, _)| (*known, edit_distance(known, name)))
+        .filter(|(_, distance)| *distance <= 2)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(known, _)| format!(" (did you mean '{}'?)", known))
+        .unwrap_or_default();
+    report(key.span().start, true, format!("unknown key '{}'{}", name, suggestion));
+    None
+}
+
+fn check_value(name: &str, value: &Spanned<DeValue>, kind: Kind, report: &mut impl FnMut(usize, bool, String)) {
+    let value_ref = value.get_ref();
+    let integer = value_ref.as_integer().and_then(|n| i64::from_str_radix(n.as_str(), n.radix()).ok());
+    let problem = match kind {
+        Kind::Text if !value_ref.is_str() => Some("a string".to_string()),
+        Kind::TextList => match value_ref.as_array() {
+            Some(items) if items.iter().all(|item| item.get_ref().is_str()) => None,
+            _ => Some("an array of strings".to_string()),
+        },
+        Kind::Count if integer.is_none_or(|n| n < 0) => Some("a non-negative integer".to_string()),
+        Kind::Port if integer.is_none_or(|n| !(1..=65535).contains(&n)) => Some("a port number (1-65535)".to_string()),
+        Kind::Color if value_ref.as_str().is_none_or(|name| parse_color(name).is_none()) => {
+            Some("a color name such as \"bright blue\" or \"#rrggbb\"".to_string())
+        }
+        Kind::ShardRanges => match value_ref.as_array() {
+            Some(ranges) if ranges.iter().all(|range| range.get_ref().as_table().is_some_and(|fields| {
+                fields.get("profile").is_some_and(|profile| profile.get_ref().is_str())
+                    && fields.get("to").is_none_or(|to| to.get_ref().as_integer().is_some())
+                    && fields.iter().all(|(key, _)| ["to", "profile"].contains(&key.get_ref().as_ref()))
+            })) => None,
+            _ => Some("an array of { to = <integer>, profile = \"name\" } ranges".to_string()),
+        },
+        Kind::Choice(choices) if value_ref.as_str().is_none_or(|text| !choices.iter().any(|c| c.eq_ignore_ascii_case(text))) => {
+            Some(format!("one of {}", choices.join(", ")))
+        }
+        _ => None,
+    };
+    if let Some(wanted) = problem {
+        let found = match (integer, value_ref.as_str()) {
+            (Some(n), _) => n.to_string(),
+            (_, Some(text)) => format!("'{}'", text),
+            _ => value_ref.type_str().to_string(),
+        };
+        report(value.span().start, true, format!("'{}' must be {}, found {}", name, wanted, found));
+    }
+}
+
+fn check_favorites(contents: &str, diagnostics: &mut Vec<Diagnostic>) {
+    for (index, line) in contents.lines().enumerate() {
+        if !line.is_empty() && !line.contains('\t') {
+            diagnostics.push(Diagnostic {
+                file: FAVORITES_FILE.to_string(),
+                line: index + 1,
+                error: true,
+                message: "expected a name and a query separated by a TAB".to_string(),
+            });
+        }
+    }
+}
+
+fn reachable(host: &str, port: u16) -> std::io::Result<()> {
+    let address = (host, port).to_socket_addrs()?
+        .next()
+        .ok_or_else(|| std::io::Error::other("no address found"))?;
+    TcpStream::connect_timeout(&address, PROBE_TIMEOUT)?;
+    Ok(())
+}
+
+/// Levenshtein distance, for suggesting the intended key.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
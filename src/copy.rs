@@ -0,0 +1,233 @@
+//! Streaming copy of a table's rows from one server to another.
+//!
+//! With a single-column primary key the source is read in key order, one
+//! batch at a time, and the last key written is reported after every batch so
+//! an interrupted copy can resume after it. Other tables are copied in one
+//! streaming pass that cannot be resumed.
+
+use crate::bulk::{primary_key, quote_identifier, quote_table, BulkInsert, OnConflict};
+use crate::error::{Result, RusqlError};
+use crate::mask::{ColumnMasks, MaskRules};
+use crate::throttle::Throttle;
+use mysql::prelude::*;
+use mysql::{Conn, Params, Row, Value};
+use std::cell::RefCell;
+
+/// Progress reported after every batch written by [`TableCopy`].
+#[derive(Debug, Clone, Default)]
+pub struct CopyProgress {
+    pub rows: u64,
+    pub batches: u64,
+    /// Key of the last row written, when the copy walks the primary key.
+    pub last_key: Option<Value>,
+}
+
+/// Callback receiving [`CopyProgress`] after every batch.
+type OnProgress<'a> = Box<dyn FnMut(&CopyProgress) + 'a>;
+
+/// Batched copy of the rows of one table to a table on another connection.
+///
+/// ```no_run
+/// # fn run(source: &mut mysql::Conn, target: &mut mysql::Conn) -> rusql::error::Result<()> {
+/// use rusql::bulk::OnConflict;
+/// let report = rusql::copy::TableCopy::new("shop.orders", "shop.orders")
+///     .condition(Some("created_at >= '2024-01-01'"))
+///     .on_conflict(OnConflict::Update)
+///     .execute(source, target)?;
+/// println!("{} rows copied", report.rows);
+/// # Ok(()) }
+/// ```
+pub struct TableCopy<'a> {
+    source: String,
+    target: String,
+    condition: Option<String>,
+    batch_size: usize,
+    on_conflict: OnConflict,
+    resume_after: Option<Value>,
+    mask: MaskRules,
+    throttle: Throttle,
+    progress: Option<OnProgress<'a>>,
+}
+
+impl<'a> TableCopy<'a> {
+    pub fn new(source: &str, target: &str) -> Self {
+        TableCopy {
+            source: source.to_string(),
+            target: target.to_string(),
+            condition: None,
+            batch_size: 1000,
+            on_conflict: OnConflict::Fail,
+            resume_after: None,
+            mask: MaskRules::default(),
+            throttle: Throttle::default(),
+            progress: None,
+        }
+    }
+
+    /// Only copy source rows matching this `WHERE` condition.
+    pub fn condition(mut self, condition: Option<&str>) -> Self {
+        self.condition = condition.map(str::to_string);
+        self
+    }
+
+    /// Rows read and written per batch.
+    pub fn batch_size(mut self, rows: usize) -> Self {
+        self.batch_size = rows.max(1);
+        self
+    }
+
+    /// What to do with rows whose key already exists on the target.
+    pub fn on_conflict(mut self, mode: OnConflict) -> Self {
+        self.on_conflict = mode;
+        self
+    }
+
+    /// Skip source rows with a primary key up to and including `key`, as
+    /// reported in [`CopyProgress::last_key`] by an earlier run.
+    pub fn resume_after(mut self, key: Option<Value>) -> Self {
+        self.resume_after = key;
+        self
+    }
+
+    /// Mask source columns matching these rules before writing them;
+    /// shuffled columns are shuffled within each batch.
+    pub fn mask(mut self, rules: MaskRules) -> Self {
+        self.mask = rules;
+        self
+    }
+
+    /// Pace batches to spare the servers.
+    pub fn throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Called after every batch with the running totals.
+    pub fn on_progress<F: FnMut(&CopyProgress) + 'a>(mut self, callback: F) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    pub fn execute(mut self, source: &mut Conn, target: &mut Conn) -> Result<CopyProgress> {
+        let key = primary_key(source, &self.source)?;
+        let columns = self.columns(source)?;
+        let masks = self.masks(source, &columns)?;
+        match key.as_slice() {
+            [key] => self.by_key(source, target, &columns, key, &masks),
+            _ if self.resume_after.is_some() => Err(RusqlError::Command(format!(
+                "{} has no single-column primary key, so a copy of it cannot resume",
+                self.source
+            ))),
+            _ => self.in_one_pass(source, target, &columns, &masks),
+        }
+    }
+
+    fn masks(&self, source: &mut Conn, columns: &[String]) -> Result<ColumnMasks> {
+        let (db, table) = match self.source.split_once('.') {
+            Some((db, table)) => (Some(db.to_string()), table),
+            None if self.mask.is_empty() => (None, self.source.as_str()),
+            None => (source.query_first("SELECT DATABASE()")?.flatten(), self.source.as_str()),
+        };
+        Ok(self.mask.columns(db.as_deref(), table, columns))
+    }
+
+    fn columns(&self, source: &mut Conn) -> Result<Vec<String>> {
+        let result = source.query_iter(format!("SELECT * FROM {} LIMIT 0", quote_table(&self.source)))?;
+        Ok(result.columns().as_ref().iter().map(|c| c.name_str().into_owned()).collect())
+    }
+
+    /// Read batches in key order, each starting after the last key written.
+    fn by_key(
+        &mut self,
+        source: &mut Conn,
+        target: &mut Conn,
+        columns: &[String],
+        key: &str,
+        masks: &ColumnMasks,
+    ) -> Result<CopyProgress> {
+        let position = columns.iter().position(|c| c == key)
+            .ok_or_else(|| RusqlError::Command(format!("column '{}' not found in {}", key, self.source)))?;
+        let quoted: Vec<String> = columns.iter().map(|c| quote_identifier(c)).collect();
+        let query = format!(
+            "SELECT {columns} FROM {table} WHERE ({condition}) AND (? IS NULL OR {key} > ?) ORDER BY {key} LIMIT {limit}",
+            columns = quoted.join(", "),
+            table = quote_table(&self.source),
+            condition = self.condition.as_deref().unwrap_or("1 = 1"),
+            key = quote_identifier(key),
+            limit = self.batch_size,
+        );
+
+        let mut progress = CopyProgress { last_key: self.resume_after.take(), ..CopyProgress::default() };
+        let mut throttle = self.throttle.start();
+        loop {
+            let lower = progress.last_key.clone().unwrap_or(Value::NULL);
+            let rows: Vec<Row> = source.exec(&query, Params::Positional(vec![lower.clone(), lower]))?;
+            let Some(last) = rows.last() else {
+                return Ok(progress);
+            };
+            let last_key = last.as_ref(position).cloned();
+            let count = rows.len();
+            let mut rows: Vec<Vec<Value>> = rows.into_iter().map(Row::unwrap).collect();
+            masks.apply(&mut rows);
+
+            BulkInsert::new(&self.target, columns)
+                .max_rows_per_batch(self.batch_size)
+                .on_conflict(self.on_conflict)
+                .execute(target, rows.into_iter().map(Params::Positional))?;
+            progress.rows += count as u64;
+            progress.batches += 1;
+            progress.last_key = last_key;
+            if let Some(callback) = self.progress.as_mut() {
+                callback(&progress);
+            }
+            if count < self.batch_size {
+                return Ok(progress);
+            }
+            throttle.pace(progress.rows);
+        }
+    }
+
+    /// Stream every matching row through one bulk insert.
+    fn in_one_pass(&mut self, source: &mut Conn, target: &mut Conn, columns: &[String], masks: &ColumnMasks) -> Result<CopyProgress> {
+        let quoted: Vec<String> = columns.iter().map(|c| quote_identifier(c)).collect();
+        let query = format!(
+            "SELECT {} FROM {}{}",
+            quoted.join(", "),
+            quote_table(&self.source),
+            self.condition.as_ref().map(|c| format!(" WHERE {}", c)).unwrap_or_default()
+        );
+        let result = source.query_iter(query)?;
+
+        // Stop at the first read error and report it once the batches written so far are in
+        let read_error = RefCell::new(None);
+        let rows = result.map_while(|row| match row {
+            Ok(row) => Some(row.unwrap()),
+            Err(e) => {
+                *read_error.borrow_mut() = Some(e);
+                None
+            }
+        });
+        let rows = masks.stream(rows, self.batch_size).map(Params::Positional);
+
+        let mut throttle = self.throttle.start();
+        let mut progress = CopyProgress::default();
+        let callback = &mut self.progress;
+        BulkInsert::new(&self.target, columns)
+            .max_rows_per_batch(self.batch_size)
+            .on_conflict(self.on_conflict)
+            .on_progress(|batch| {
+                progress.rows = batch.rows;
+                progress.batches = batch.batches;
+                if let Some(callback) = callback.as_mut() {
+                    callback(&progress);
+                }
+                throttle.pace(batch.rows);
+            })
+            .execute(target, rows)?;
+
+        match read_error.into_inner() {
+            Some(e) => Err(e.into()),
+            None => Ok(progress),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{format_duration, MySQLClient, QueryResult};
+use mysql::prelude::*;
+use rusql::error::{Result, RusqlError};
+use rusql::narrow::glob_match;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// Counters always reported by `\counters stop`, in display order.
+const CURATED: &[&str] = &[
+    "Questions",
+    "Com_select",
+    "Com_insert",
+    "Com_update",
+    "Com_delete",
+    "Slow_queries",
+    "Select_scan",
+    "Select_full_join",
+    "Sort_merge_passes",
+    "Created_tmp_tables",
+    "Created_tmp_disk_tables",
+    "Handler_read_key",
+    "Handler_read_rnd_next",
+    "Innodb_rows_read",
+    "Innodb_rows_inserted",
+    "Innodb_rows_updated",
+    "Innodb_rows_deleted",
+    "Innodb_buffer_pool_read_requests",
+    "Innodb_buffer_pool_reads",
+    "Innodb_data_written",
+    "Innodb_log_writes",
+    "Bytes_received",
+    "Bytes_sent",
+    "Threads_created",
+];
+
+/// Global status counters captured by `\counters start`.
+pub struct Snapshot {
+    taken: Instant,
+    values: BTreeMap<String, u64>,
+}
+
+/// `\counters start|stop [pattern]`: snapshot `SHOW GLOBAL STATUS` and report
+/// how the curated counters, and any matching `pattern`, changed since.
+pub fn counters(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let (action, pattern) = args.split_once(char::is_whitespace)
+        .map(|(action, pattern)| (action, pattern.trim()))
+        .unwrap_or((args, ""));
+
+    match action {
+        "start" => {
+            client.counters = Some(snapshot(client)?);
+            client.notice("Counter window started; run \\counters stop to see the deltas");
+            Ok(None)
+        }
+        "stop" => {
+            let start = client.counters.take()
+                .ok_or_else(|| RusqlError::Command("No counter window open; run \\counters start first".into()))?;
+            let end = snapshot(client)?;
+            Ok(Some(report(client, &start, &end, pattern)))
+        }
+        "" => {
+            match &client.counters {
+                Some(start) => client.notice(&format!(
+                    "Counter window open for {}",
+                    format_duration(start.taken.elapsed().as_secs())
+                )),
+                None => client.notice("No counter window open"),
+            }
+            Ok(None)
+        }
+        _ => Err(RusqlError::Command("Usage: \\counters start | stop [pattern]".into())),
+    }
+}
+
+fn snapshot(client: &mut MySQLClient) -> Result<Snapshot> {
+    let rows: Vec<(String, String)> = client.conn.query("SHOW GLOBAL STATUS")?;
+    let values = rows.into_iter()
+        .filter_map(|(name, value)| value.parse().ok().map(|value| (name, value)))
+        .collect();
+    Ok(Snapshot { taken: Instant::now(), values })
+}
+
+fn report(client: &MySQLClient, start: &Snapshot, end: &Snapshot, pattern: &str) -> QueryResult {
+    let seconds = end.taken.duration_since(start.taken).as_secs_f64().max(0.001);
+
+    let mut names: Vec<&str> = CURATED.to_vec();
+    if !pattern.is_empty() {
+        names.extend(end.values.keys()
+            .map(String::as_str)
+            .filter(|name| glob_match(pattern, name) && !CURATED.contains(name)));
+    }
+
+    let rows = names.into_iter()
+        .filter_map(|name| {
+            let before = *start.values.get(name)?;
+            let after = *end.values.get(name)?;
+            // Counters can go backwards after FLUSH STATUS
+            let delta = after as i128 - before as i128;
+            Some(vec![
+                name.to_string(),
+                before.to_string(),
+                after.to_string(),
+                delta.to_string(),
+                format!("{:.1}", delta as f64 / seconds),
+            ])
+        })
+        .collect();
+
+    QueryResult {
+        table: client.build_table(&["Counter", "Start", "End", "Delta", "Per sec"], rows),
+        summary: format!("Counter deltas over {}", format_duration(seconds as u64)),
+    }
+}
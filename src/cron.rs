@@ -0,0 +1,154 @@
+//! Cron expressions telling when a scheduled query runs.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use std::str::FromStr;
+
+const MONTHS: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// How far ahead a run is looked for, beyond which a schedule never fires
+/// (`0 0 30 2 *`).
+const HORIZON_DAYS: i64 = 5 * 366;
+
+/// A five-field cron expression (`minute hour day-of-month month
+/// day-of-week`) with `*`, lists, ranges, `/` steps and month and weekday
+/// names, or one of `@hourly`, `@daily`, `@weekly`, `@monthly`, `@yearly`.
+///
+/// As in cron, when both the day of month and the day of week are
+/// restricted, a day matching either one runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Schedule {
+    /// The first time after `after` that the schedule fires, to the minute.
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use rusql::cron::Schedule;
+    /// let at = |d, h, m| NaiveDate::from_ymd_opt(2024, 3, d).unwrap().and_hms_opt(h, m, 0).unwrap();
+    ///
+    /// let quarter_hours: Schedule = "*/15 9-17 * * mon-fri".parse()?;
+    /// assert_eq!(quarter_hours.next_after(at(1, 9, 7)), Some(at(1, 9, 15)));
+    /// // Friday 17:45 is the last run of the week
+    /// assert_eq!(quarter_hours.next_after(at(1, 17, 45)), Some(at(4, 9, 0)));
+    ///
+    /// let nightly: Schedule = "@daily".parse()?;
+    /// assert_eq!(nightly.next_after(at(1, 0, 0)), Some(at(2, 0, 0)));
+    /// assert!("0 0 30 2 *".parse::<Schedule>()?.next_after(at(1, 0, 0)).is_none());
+    /// assert!("61 * * * *".parse::<Schedule>().is_err());
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let horizon = time + Duration::days(HORIZON_DAYS);
+        while time < horizon {
+            if !has(self.months, time.month()) {
+                let (year, month) = if time.month() == 12 { (time.year() + 1, 1) } else { (time.year(), time.month() + 1) };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(time.date()) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !has(self.hours, time.hour()) {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if !has(self.minutes, time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression = match s.trim().to_lowercase().as_str() {
+            "@hourly" => "0 * * * *".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            other => other.to_string(),
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields.as_slice() else {
+            return Err(format!("'{}' is not a cron expression of five fields", s.trim()));
+        };
+        let weekday_bits = field(weekdays, 0, 7, WEEKDAYS)?;
+        Ok(Schedule {
+            minutes: field(minutes, 0, 59, &[])?,
+            hours: field(hours, 0, 23, &[])?,
+            days: field(days, 1, 31, &[])?,
+            months: field(months, 1, 12, MONTHS)?,
+            // Sunday is both 0 and 7
+            weekdays: (weekday_bits | weekday_bits >> 7) & 0x7f,
+            any_day: days.starts_with('*'),
+            any_weekday: weekdays.starts_with('*'),
+        })
+    }
+}
+
+fn has(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+/// The values a field allows, as bits. `names` spell the values from `min`
+/// on (months from 1, weekdays from 0).
+fn field(text: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |part: &str| -> Result<u32, String> {
+        let value = match names.iter().position(|name| *name == part) {
+            Some(position) => position as u32 + min,
+            None => part.parse().map_err(|_| format!("'{}' is not a number in cron field '{}'", part, text))?,
+        };
+        match (min..=max).contains(&value) {
+            true => Ok(value),
+            false => Err(format!("{} is outside {}-{} in cron field '{}'", value, min, max, text)),
+        }
+    };
+
+    let mut bits = 0;
+    for item in text.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().ok().filter(|&step| step > 0)
+                    .ok_or_else(|| format!("'{}' is not a valid step in cron field '{}'", step, text))?;
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // `5/10` runs from 5 to the end in steps of 10
+            None if step > 1 => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if start > end {
+            return Err(format!("{}-{} is an empty range in cron field '{}'", start, end, text));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
@@ -0,0 +1,91 @@
+use crate::{introspect, MySQLClient, QueryResult};
+use colored::*;
+use mysql::prelude::*;
+use mysql::Row;
+use rusql::bulk::quote_table;
+use rusql::diff::{unified_diff, DiffLine};
+use rusql::error::{Result, RusqlError};
+
+/// Lines of context shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+/// Definitions that MySQL lists in no particular order once a table has been altered.
+const KEY_PREFIXES: &[&str] = &[
+    "PRIMARY KEY", "UNIQUE KEY", "KEY ", "INDEX ", "FULLTEXT KEY", "SPATIAL KEY", "CONSTRAINT ",
+];
+
+/// `\ddl-diff profileA profileB db.table`: compare a table's definition on two
+/// connections. `.` stands for the current connection.
+pub fn ddl_diff(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let parts: Vec<&str> = args.split_whitespace().collect();
+    let [left, right, name] = parts[..] else {
+        return Err(RusqlError::Command("Usage: \\ddl-diff <profile> <profile> <db.table>".into()));
+    };
+    let (db, table) = introspect::split_table(client, name)?;
+    let qualified = format!("{}.{}", db, table);
+
+    let old = normalize(&show_create(client, left, &qualified)?);
+    let new = normalize(&show_create(client, right, &qualified)?);
+    let lines = unified_diff(&old, &new, CONTEXT_LINES);
+    if lines.is_empty() {
+        client.notice(&format!("{} is identical on '{}' and '{}'", qualified, left, right));
+        return Ok(None);
+    }
+
+    let colors = client.use_colors;
+    let paint = |text: String, color: Color| if colors { text.color(color).to_string() } else { text };
+    println!("{}", paint(format!("--- {} {}", left, qualified), Color::BrightRed));
+    println!("{}", paint(format!("+++ {} {}", right, qualified), Color::BrightGreen));
+    for line in lines {
+        match line {
+            DiffLine::Hunk(text) => println!("{}", paint(text, Color::BrightCyan)),
+            DiffLine::Context(text) => println!(" {}", text),
+            DiffLine::Removed(text) => println!("{}", paint(format!("-{}", text), Color::BrightRed)),
+            DiffLine::Added(text) => println!("{}", paint(format!("+{}", text), Color::BrightGreen)),
+        }
+    }
+    Ok(None)
+}
+
+/// `SHOW CREATE TABLE` on the connection named by `profile`.
+fn show_create(client: &mut MySQLClient, profile: &str, table: &str) -> Result<String> {
+    let query = format!("SHOW CREATE TABLE {}", quote_table(table));
+    let row: Option<Row> = if profile == "." {
+        client.conn.query_first(query)?
+    } else {
+        let profile = client.profiles.get(profile)
+            .ok_or_else(|| RusqlError::Command(format!("Unknown profile '{}'", profile)))?;
+        profile.connect()?.query_first(query)?
+    };
+
+    row.and_then(|row| row.get::<String, _>(1))
+        .ok_or_else(|| RusqlError::Command(format!("Table '{}' doesn't exist", table)))
+}
+
+/// Strip noise from a CREATE statement: the `AUTO_INCREMENT` counter,
+/// trailing commas, and the order of index and constraint definitions.
+fn normalize(create: &str) -> Vec<String> {
+    let lines: Vec<&str> = create.lines().collect();
+    let Some((first, rest)) = lines.split_first() else {
+        return Vec::new();
+    };
+    let Some((last, body)) = rest.split_last() else {
+        return vec![first.to_string()];
+    };
+
+    let (mut keys, columns): (Vec<String>, Vec<String>) = body.iter()
+        .map(|line| line.trim_end().trim_end_matches(',').to_string())
+        .partition(|line| KEY_PREFIXES.iter().any(|prefix| line.trim_start().starts_with(prefix)));
+    keys.sort();
+
+    let options = last.split(' ')
+        .filter(|option| !option.starts_with("AUTO_INCREMENT="))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    std::iter::once(first.to_string())
+        .chain(columns)
+        .chain(keys)
+        .chain(std::iter::once(options))
+        .collect()
+}
@@ -0,0 +1,230 @@
+use crate::{print_result, MySQLClient, QueryResult};
+use mysql::prelude::*;
+use rusql::error::{Result, RusqlError};
+use std::collections::HashSet;
+
+/// One transaction of the latest deadlock in `SHOW ENGINE INNODB STATUS`.
+#[derive(Default)]
+struct DeadlockTrx {
+    /// Number InnoDB gives the transaction within the report, e.g. `1`.
+    number: String,
+    thread: String,
+    statement: String,
+    holds: Vec<String>,
+    waits_for: Vec<String>,
+}
+
+/// The LATEST DETECTED DEADLOCK section, parsed.
+struct Deadlock {
+    detected_at: String,
+    transactions: Vec<DeadlockTrx>,
+    rolled_back: Option<String>,
+}
+
+/// A row of performance_schema.data_lock_waits joined with both transactions.
+struct LockWait {
+    waiting_thread: u64,
+    waiting_statement: Option<String>,
+    blocking_thread: u64,
+    blocking_statement: Option<String>,
+    lock: Option<String>,
+    waited_secs: Option<i64>,
+}
+
+/// `\deadlocks`: the latest deadlock from `SHOW ENGINE INNODB STATUS` and the
+/// current lock waits as a tree of blocking threads and the threads they block.
+pub fn deadlocks(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if !args.is_empty() {
+        return Err(RusqlError::Command("Usage: \\deadlocks".into()));
+    }
+
+    let status: Option<(String, String, String)> = client.conn.query_first("SHOW ENGINE INNODB STATUS")?;
+    let deadlock = status.and_then(|(_, _, status)| parse_deadlock(&status));
+    match &deadlock {
+        Some(deadlock) => print_result(client, deadlock_result(client, deadlock)),
+        None => client.notice("No deadlock since the server started"),
+    }
+
+    // data_lock_waits is MySQL 8.0+; older servers and MariaDB only get the deadlock
+    let waits: Option<Vec<LockWait>> = client.conn.query_map(
+        "SELECT rt.trx_mysql_thread_id, rt.trx_query, bt.trx_mysql_thread_id, bt.trx_query, \
+         CONCAT(l.LOCK_MODE, ' on ', l.OBJECT_SCHEMA, '.', l.OBJECT_NAME, COALESCE(CONCAT(' (', l.INDEX_NAME, ')'), '')), \
+         TIMESTAMPDIFF(SECOND, rt.trx_wait_started, NOW()) \
+         FROM performance_schema.data_lock_waits w \
+         JOIN information_schema.INNODB_TRX rt ON rt.trx_id = w.REQUESTING_ENGINE_TRANSACTION_ID \
+         JOIN information_schema.INNODB_TRX bt ON bt.trx_id = w.BLOCKING_ENGINE_TRANSACTION_ID \
+         JOIN performance_schema.data_locks l ON l.ENGINE_LOCK_ID = w.REQUESTING_ENGINE_LOCK_ID \
+         ORDER BY rt.trx_wait_started",
+        |(waiting_thread, waiting_statement, blocking_thread, blocking_statement, lock, waited_secs)| LockWait {
+            waiting_thread,
+            waiting_statement,
+            blocking_thread,
+            blocking_statement,
+            lock,
+            waited_secs,
+        },
+    ).ok();
+
+    let Some(waits) = waits else {
+        client.notice("Lock waits not checked (performance_schema.data_lock_waits not available)");
+        return Ok(None);
+    };
+    if waits.is_empty() {
+        client.notice("No transaction is waiting for a lock");
+        return Ok(None);
+    }
+
+    let mut rows = Vec::new();
+    for root in roots(&waits) {
+        let statement = waits.iter()
+            .find(|wait| wait.blocking_thread == root)
+            .and_then(|wait| wait.blocking_statement.clone());
+        // A blocker running nothing holds its locks in an open transaction
+        let statement = statement.unwrap_or_else(|| "(idle in transaction)".to_string());
+        rows.push(vec![root.to_string(), String::new(), String::new(), statement]);
+        let mut visited = HashSet::from([root]);
+        blocked_by(&waits, root, 1, &mut visited, &mut rows);
+    }
+    let waiting: HashSet<u64> = waits.iter().map(|wait| wait.waiting_thread).collect();
+    let summary = format!(
+        "{} {} waiting for a lock; KILL the thread at the top of a chain to release it",
+        waiting.len(),
+        if waiting.len() == 1 { "thread" } else { "threads" }
+    );
+    let headers = ["Thread", "Waiting", "Lock", "Statement"];
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+fn deadlock_result(client: &MySQLClient, deadlock: &Deadlock) -> QueryResult {
+    let rows = deadlock.transactions.iter()
+        .map(|trx| vec![
+            format!("({})", trx.number),
+            trx.thread.clone(),
+            trx.statement.clone(),
+            trx.holds.join("\n"),
+            trx.waits_for.join("\n"),
+        ])
+        .collect();
+    let mut summary = format!("Latest deadlock detected at {}", deadlock.detected_at);
+    if let Some(number) = &deadlock.rolled_back {
+        let thread = deadlock.transactions.iter()
+            .find(|trx| &trx.number == number)
+            .map_or_else(String::new, |trx| format!(" (thread {})", trx.thread));
+        summary.push_str(&format!("; transaction ({}){} was rolled back", number, thread));
+    }
+    let headers = ["Trx", "Thread", "Statement", "Holds", "Waits for"];
+    QueryResult { table: client.build_table(&headers, rows), summary }
+}
+
+/// Threads blocking others without waiting themselves: the tops of the chains.
+/// Should every blocker also be waiting, the first blocker stands in.
+fn roots(waits: &[LockWait]) -> Vec<u64> {
+    let waiting: HashSet<u64> = waits.iter().map(|wait| wait.waiting_thread).collect();
+    let mut roots: Vec<u64> = Vec::new();
+    for wait in waits {
+        if !waiting.contains(&wait.blocking_thread) && !roots.contains(&wait.blocking_thread) {
+            roots.push(wait.blocking_thread);
+        }
+    }
+    if roots.is_empty() {
+        roots.extend(waits.first().map(|wait| wait.blocking_thread));
+    }
+    roots
+}
+
+/// Append the threads waiting on `blocker`, indented by `depth`, and theirs in turn.
+fn blocked_by(waits: &[LockWait], blocker: u64, depth: usize, visited: &mut HashSet<u64>, rows: &mut Vec<Vec<String>>) {
+    for wait in waits.iter().filter(|wait| wait.blocking_thread == blocker) {
+        let indent = format!("{}└─ ", "   ".repeat(depth - 1));
+        let waited = wait.waited_secs.map_or_else(String::new, |secs| format!("{}s", secs));
+        rows.push(vec![
+            format!("{}{}", indent, wait.waiting_thread),
+            waited,
+            wait.lock.clone().unwrap_or_default(),
+            wait.waiting_statement.clone().unwrap_or_default(),
+        ]);
+        if visited.insert(wait.waiting_thread) {
+            blocked_by(waits, wait.waiting_thread, depth + 1, visited, rows);
+        }
+    }
+}
+
+/// Parse the LATEST DETECTED DEADLOCK section of `SHOW ENGINE INNODB STATUS`.
+fn parse_deadlock(status: &str) -> Option<Deadlock> {
+    let mut lines = status.lines()
+        .skip_while(|line| line.trim() != "LATEST DETECTED DEADLOCK")
+        .skip(2)
+        // Sections are separated by lines of dashes
+        .take_while(|line| !is_rule(line));
+
+    let detected_at = lines.next()?.split_whitespace().take(2).collect::<Vec<_>>().join(" ");
+    let mut deadlock = Deadlock { detected_at, transactions: Vec::new(), rolled_back: None };
+
+    enum Part { Header, Statement, Holds, WaitsFor }
+    let mut part = Part::Header;
+    for line in lines {
+        if let Some(marker) = line.strip_prefix("*** ") {
+            let number = marker.strip_prefix('(')
+                .and_then(|rest| rest.split_once(')'))
+                .map(|(number, rest)| (number.to_string(), rest.trim()));
+            match number {
+                Some((number, "TRANSACTION:")) => {
+                    deadlock.transactions.push(DeadlockTrx { number, ..Default::default() });
+                    part = Part::Header;
+                }
+                Some((_, rest)) if rest.starts_with("HOLDS") => part = Part::Holds,
+                Some((_, rest)) if rest.starts_with("WAITING") => part = Part::WaitsFor,
+                _ => {
+                    if let Some(number) = marker.strip_prefix("WE ROLL BACK TRANSACTION (") {
+                        deadlock.rolled_back = Some(number.trim_end_matches(')').to_string());
+                    }
+                }
+            }
+            continue;
+        }
+        let Some(trx) = deadlock.transactions.last_mut() else {
+            continue;
+        };
+        match part {
+            Part::Header => {
+                if let Some(rest) = line.strip_prefix("MySQL thread id ") {
+                    trx.thread = rest.split(',').next().unwrap_or_default().to_string();
+                    part = Part::Statement;
+                }
+            }
+            Part::Statement if !line.trim().is_empty() => {
+                if !trx.statement.is_empty() {
+                    trx.statement.push(' ');
+                }
+                trx.statement.push_str(line.trim());
+            }
+            Part::Statement => {}
+            Part::Holds => trx.holds.extend(describe_lock(line)),
+            Part::WaitsFor => trx.waits_for.extend(describe_lock(line)),
+        }
+    }
+    (!deadlock.transactions.is_empty()).then_some(deadlock)
+}
+
+fn is_rule(line: &str) -> bool {
+    line.len() > 3 && line.trim_end().chars().all(|c| c == '-')
+}
+
+/// Shorten a lock line, dropping page and transaction ids:
+/// `RECORD LOCKS space id 2 page no 4 n bits 72 index PRIMARY of table `db`.`t` trx id 1234 lock_mode X waiting`
+/// becomes `lock_mode X on index PRIMARY of table `db`.`t``. The record dumps
+/// following it yield nothing.
+fn describe_lock(line: &str) -> Option<String> {
+    let (target, rest) = if line.starts_with("RECORD LOCKS ") {
+        let (_, index) = line.split_once(" index ")?;
+        let (target, rest) = index.split_once(" trx id ")?;
+        (format!("index {}", target), rest)
+    } else {
+        let table = line.strip_prefix("TABLE LOCK ")?;
+        let (target, rest) = table.split_once(" trx id ")?;
+        (target.to_string(), rest)
+    };
+    let mode = rest.split_once(' ').map_or("", |(_, mode)| mode);
+    let mode = mode.trim().trim_end_matches("waiting").trim_end();
+    Some(format!("{} on {}", mode, target))
+}
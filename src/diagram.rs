@@ -0,0 +1,161 @@
+use crate::fk_graph::{self, ForeignKey};
+use mysql::prelude::*;
+use mysql::Conn;
+use rusql::error::{Result, RusqlError};
+use rusql::narrow::glob_match;
+use std::path::Path;
+
+/// Diagram languages, chosen by the output file's extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Graphviz,
+    Mermaid,
+}
+
+impl Format {
+    /// `.dot` and `.gv` for Graphviz, `.mmd` for Mermaid.
+    pub fn of(path: &Path) -> Result<Format> {
+        match path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+            Some("dot" | "gv") => Ok(Format::Graphviz),
+            Some("mmd") => Ok(Format::Mermaid),
+            _ => Err(RusqlError::Command(format!(
+                "Cannot tell the diagram format of '{}'; use a .dot, .gv or .mmd file",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// A column as drawn: name, type and whether it is part of a key.
+struct Column {
+    name: String,
+    column_type: String,
+    data_type: String,
+    primary: bool,
+    foreign: bool,
+}
+
+/// An ER diagram of `db`: every base table whose name matches one of
+/// `include` (all when empty) and none of `exclude`, with its columns and the
+/// foreign keys between the tables drawn. Returns the diagram and the number
+/// of tables and relationships in it.
+pub fn render(conn: &mut Conn, db: &str, include: &[String], exclude: &[String], format: Format) -> Result<(String, usize, usize)> {
+    let (tables, keys) = fk_graph::load(conn, db)?;
+    let tables: Vec<String> = tables.into_iter()
+        .filter(|table| include.is_empty() || include.iter().any(|pattern| glob_match(pattern, table)))
+        .filter(|table| !exclude.iter().any(|pattern| glob_match(pattern, table)))
+        .collect();
+    if tables.is_empty() {
+        return Err(RusqlError::Command(format!("No tables of '{}' to draw", db)));
+    }
+    // Keys to tables left out, or in other databases, have nothing to point at
+    let keys: Vec<ForeignKey> = keys.into_iter()
+        .filter(|key| tables.contains(&key.table) && tables.contains(&key.parent))
+        .collect();
+
+    let rows: Vec<(String, String, String, String, String)> = conn.exec(
+        "SELECT TABLE_NAME, COLUMN_NAME, COLUMN_TYPE, DATA_TYPE, COLUMN_KEY FROM information_schema.COLUMNS \
+         WHERE TABLE_SCHEMA = ? ORDER BY TABLE_NAME, ORDINAL_POSITION",
+        (db,),
+    )?;
+    let columns = |table: &str| -> Vec<Column> {
+        rows.iter()
+            .filter(|row| row.0 == table)
+            .map(|(_, name, column_type, data_type, key)| Column {
+                name: name.clone(),
+                column_type: column_type.clone(),
+                data_type: data_type.clone(),
+                primary: key == "PRI",
+                foreign: keys.iter().any(|fk| fk.table == table && fk.columns.contains(name)),
+            })
+            .collect()
+    };
+
+    let diagram = match format {
+        Format::Graphviz => graphviz(db, &tables, &keys, columns),
+        Format::Mermaid => mermaid(&tables, &keys, columns),
+    };
+    Ok((diagram, tables.len(), keys.len()))
+}
+
+/// One HTML-like table node per table, with a port per column so edges run
+/// from the foreign key column to the referenced one.
+fn graphviz(db: &str, tables: &[String], keys: &[ForeignKey], columns: impl Fn(&str) -> Vec<Column>) -> String {
+    let mut dot = format!("digraph \"{}\" {{\n    rankdir=LR;\n    node [shape=plaintext];\n\n", quote(db));
+    for table in tables {
+        dot.push_str(&format!(
+            "    \"{}\" [label=<<table border=\"0\" cellborder=\"1\" cellspacing=\"0\">\n        <tr><td bgcolor=\"lightgrey\"><b>{}</b></td></tr>\n",
+            quote(table),
+            escape(table)
+        ));
+        for column in columns(table) {
+            let marks = match (column.primary, column.foreign) {
+                (true, true) => " PK FK",
+                (true, false) => " PK",
+                (false, true) => " FK",
+                (false, false) => "",
+            };
+            let name = if column.primary { format!("<u>{}</u>", escape(&column.name)) } else { escape(&column.name) };
+            dot.push_str(&format!(
+                "        <tr><td align=\"left\" port=\"{}\">{} {}{}</td></tr>\n",
+                escape(&column.name),
+                name,
+                escape(&column.column_type),
+                marks
+            ));
+        }
+        dot.push_str("    </table>>];\n");
+    }
+    dot.push('\n');
+    for key in keys {
+        let (column, parent_column) = (&key.columns[0], &key.parent_columns[0]);
+        dot.push_str(&format!(
+            "    \"{}\":\"{}\" -> \"{}\":\"{}\" [label=\"{}\"];\n",
+            quote(&key.table),
+            quote(column),
+            quote(&key.parent),
+            quote(parent_column),
+            quote(&key.name)
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// A Mermaid `erDiagram`. Mermaid types are single words, so columns show
+/// their data type without length or options.
+fn mermaid(tables: &[String], keys: &[ForeignKey], columns: impl Fn(&str) -> Vec<Column>) -> String {
+    let mut diagram = String::from("erDiagram\n");
+    for table in tables {
+        diagram.push_str(&format!("    {} {{\n", entity(table)));
+        for column in columns(table) {
+            let marks: Vec<&str> = [(column.primary, "PK"), (column.foreign, "FK")]
+                .into_iter()
+                .filter_map(|(set, mark)| set.then_some(mark))
+                .collect();
+            let line = format!("        {} {} {}", column.data_type, entity(&column.name), marks.join(", "));
+            diagram.push_str(line.trim_end());
+            diagram.push('\n');
+        }
+        diagram.push_str("    }\n");
+    }
+    for key in keys {
+        diagram.push_str(&format!("    {} ||--o{{ {} : \"{}\"\n", entity(&key.parent), entity(&key.table), key.name.replace('"', "'")));
+    }
+    diagram
+}
+
+/// A name Mermaid accepts unquoted: letters, digits, `_` and `-`.
+fn entity(name: &str) -> String {
+    name.chars().map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '_' }).collect()
+}
+
+/// Text escaped for a DOT quoted string.
+fn quote(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Text escaped for an HTML-like DOT label.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
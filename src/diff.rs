@@ -0,0 +1,90 @@
+//! Line-based unified diffs.
+
+/// One line of a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Hunk(String),
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Unified diff of two line sequences with `context` lines around changes.
+/// Returns an empty vector when the inputs are equal.
+pub fn unified_diff(old: &[String], new: &[String], context: usize) -> Vec<DiffLine> {
+    // Longest common subsequence table, filled from the end
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            ops.push((Op::Keep, i, j));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            ops.push((Op::Add, i, j));
+            j += 1;
+        } else {
+            ops.push((Op::Remove, i, j));
+            i += 1;
+        }
+    }
+
+    let changed: Vec<usize> = ops.iter()
+        .enumerate()
+        .filter(|(_, (op, _, _))| *op != Op::Keep)
+        .map(|(k, _)| k)
+        .collect();
+    if changed.is_empty() {
+        return Vec::new();
+    }
+
+    // Group changes whose context windows overlap into hunks
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &k in &changed {
+        let start = k.saturating_sub(context);
+        let end = (k + context + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut lines = Vec::new();
+    for (start, end) in hunks {
+        let slice = &ops[start..end];
+        let old_count = slice.iter().filter(|(op, _, _)| *op != Op::Add).count();
+        let new_count = slice.iter().filter(|(op, _, _)| *op != Op::Remove).count();
+        let (_, old_start, new_start) = slice[0];
+        lines.push(DiffLine::Hunk(format!(
+            "@@ -{},{} +{},{} @@",
+            old_start + 1, old_count, new_start + 1, new_count
+        )));
+        for &(op, i, j) in slice {
+            lines.push(match op {
+                Op::Keep => DiffLine::Context(old[i].clone()),
+                Op::Remove => DiffLine::Removed(old[i].clone()),
+                Op::Add => DiffLine::Added(new[j].clone()),
+            });
+        }
+    }
+    lines
+}
@@ -0,0 +1,131 @@
+//! Single-row edits guarded against concurrent changes.
+//!
+//! An interactive fix usually reads a row, lets the user change it and writes
+//! it back. [`RowUpdate`] repeats the values that were read (or a version
+//! column) in the `WHERE` clause, so the write only lands if nobody changed
+//! the row in between.
+
+use crate::bulk::{quote_identifier, quote_table};
+use crate::error::{Result, RusqlError};
+use mysql::prelude::*;
+use mysql::{Conn, Params, Value};
+
+/// An `UPDATE` of one row identified by its key, with optimistic concurrency checks.
+///
+/// ```no_run
+/// # fn run(conn: &mut mysql::Conn) -> rusql::error::Result<()> {
+/// rusql::edit::RowUpdate::new("shop.orders")
+///     .key("id", 42)
+///     .expect("status", "pending")
+///     .set("status", "shipped")
+///     .execute(conn)?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RowUpdate {
+    table: String,
+    key: Vec<(String, Value)>,
+    expected: Vec<(String, Value)>,
+    changes: Vec<(String, Value)>,
+    version: Option<(String, Value)>,
+}
+
+impl RowUpdate {
+    pub fn new(table: &str) -> Self {
+        RowUpdate {
+            table: table.to_string(),
+            key: Vec::new(),
+            expected: Vec::new(),
+            changes: Vec::new(),
+            version: None,
+        }
+    }
+
+    /// A key column identifying the row; call once per column of a composite key.
+    pub fn key<V: Into<Value>>(mut self, column: &str, value: V) -> Self {
+        self.key.push((column.to_string(), value.into()));
+        self
+    }
+
+    /// Only update if `column` still holds the value that was read (`NULL`-safe).
+    pub fn expect<V: Into<Value>>(mut self, column: &str, value: V) -> Self {
+        self.expected.push((column.to_string(), value.into()));
+        self
+    }
+
+    /// Only update if the version column still holds `value`, and increment it.
+    pub fn version<V: Into<Value>>(mut self, column: &str, value: V) -> Self {
+        self.version = Some((column.to_string(), value.into()));
+        self
+    }
+
+    /// Assign a new value to `column`.
+    pub fn set<V: Into<Value>>(mut self, column: &str, value: V) -> Self {
+        self.changes.push((column.to_string(), value.into()));
+        self
+    }
+
+    /// The statement and its parameters.
+    pub fn sql(&self) -> (String, Params) {
+        let mut params = Vec::new();
+        let mut assignments = Vec::new();
+        for (column, value) in &self.changes {
+            assignments.push(format!("{} = ?", quote_identifier(column)));
+            params.push(value.clone());
+        }
+        if let Some((column, _)) = &self.version {
+            let column = quote_identifier(column);
+            assignments.push(format!("{} = {} + 1", column, column));
+        }
+
+        let mut conditions = Vec::new();
+        for (column, value) in self.key.iter().chain(&self.version) {
+            conditions.push(format!("{} = ?", quote_identifier(column)));
+            params.push(value.clone());
+        }
+        for (column, value) in &self.expected {
+            conditions.push(format!("{} <=> ?", quote_identifier(column)));
+            params.push(value.clone());
+        }
+
+        let sql = format!(
+            "UPDATE {} SET {} WHERE {}",
+            quote_table(&self.table),
+            assignments.join(", "),
+            conditions.join(" AND ")
+        );
+        (sql, Params::Positional(params))
+    }
+
+    /// Run the update. Fails without changing anything when the row was
+    /// changed or deleted since it was read.
+    pub fn execute(&self, conn: &mut Conn) -> Result<()> {
+        if self.key.is_empty() {
+            return Err(RusqlError::Command(format!("no key given for the update of {}", self.table)));
+        }
+        if self.changes.is_empty() && self.version.is_none() {
+            return Ok(());
+        }
+
+        let (sql, params) = self.sql();
+        conn.exec_drop(sql, params)?;
+        // Affected rows only counts rows that changed; the info string also
+        // counts rows that matched but already held the new values.
+        if conn.affected_rows() == 0 && matched_rows(&conn.info_str()) == Some(0) {
+            return Err(RusqlError::Command(format!(
+                "Row in {} was changed or deleted since it was read; nothing was updated",
+                self.table
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// `N` from an UPDATE info string like `Rows matched: N  Changed: 0  Warnings: 0`.
+fn matched_rows(info: &str) -> Option<u64> {
+    info.strip_prefix("Rows matched: ")?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
@@ -0,0 +1,165 @@
+use serde_json::json;
+use std::fmt;
+use std::io;
+
+/// Server error codes that mean the credentials were rejected.
+const AUTH_ERROR_CODES: &[u16] = &[
+    1044, // ER_DBACCESS_DENIED_ERROR
+    1045, // ER_ACCESS_DENIED_ERROR
+    1251, // ER_NOT_SUPPORTED_AUTH_MODE
+    1698, // ER_ACCESS_DENIED_NO_PASSWORD_ERROR
+    1862, // ER_MUST_CHANGE_PASSWORD_LOGIN
+];
+
+pub type Result<T> = std::result::Result<T, RusqlError>;
+
+/// Every failure Rusql can report, grouped by what the caller can do about it.
+#[derive(Debug)]
+pub enum RusqlError {
+    /// The server could not be reached or the connection broke.
+    Connection(String),
+    /// The server rejected the credentials.
+    Auth { code: u16, message: String },
+    /// The server rejected a statement.
+    Sql { code: u16, state: String, message: String },
+    Io(io::Error),
+    /// Invalid configuration file or option.
+    Config(String),
+    /// Invalid meta-command usage or client-side input.
+    Command(String),
+    /// A value could not be converted to or from the requested type.
+    Data(String),
+    /// The user interrupted the operation.
+    Cancelled,
+}
+
+impl RusqlError {
+    /// Short machine-readable name of the error category.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RusqlError::Connection(_) => "connection",
+            RusqlError::Auth { .. } => "auth",
+            RusqlError::Sql { .. } => "sql",
+            RusqlError::Io(_) => "io",
+            RusqlError::Config(_) => "config",
+            RusqlError::Command(_) => "command",
+            RusqlError::Data(_) => "data",
+            RusqlError::Cancelled => "cancelled",
+        }
+    }
+
+    /// Process exit code used when this error ends the program.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RusqlError::Sql { .. } | RusqlError::Command(_) | RusqlError::Data(_) => 1,
+            RusqlError::Connection(_) => 2,
+            RusqlError::Auth { .. } => 3,
+            RusqlError::Io(_) => 4,
+            RusqlError::Config(_) => 5,
+            RusqlError::Cancelled => 130,
+        }
+    }
+
+    /// Whether running the same operation again may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            // Deadlock and lock wait timeout
+            RusqlError::Sql { code, .. } => matches!(code, 1205 | 1213),
+            RusqlError::Connection(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            RusqlError::Sql { code, state, message } => json!({
+                "kind": self.kind(),
+                "code": code,
+                "state": state,
+                "message": message,
+            }),
+            RusqlError::Auth { code, message } => json!({
+                "kind": self.kind(),
+                "code": code,
+                "message": message,
+            }),
+            other => json!({ "kind": other.kind(), "message": other.to_string() }),
+        }
+    }
+}
+
+impl fmt::Display for RusqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RusqlError::Connection(message) => write!(f, "Connection failed: {}", message),
+            RusqlError::Auth { code, message } => write!(f, "ERROR {}: {}", code, message),
+            RusqlError::Sql { code, state, message } => write!(f, "ERROR {} ({}): {}", code, state, message),
+            RusqlError::Io(e) => write!(f, "I/O error: {}", e),
+            RusqlError::Config(message) => write!(f, "Configuration error: {}", message),
+            RusqlError::Command(message) => f.write_str(message),
+            RusqlError::Data(message) => write!(f, "Conversion error: {}", message),
+            RusqlError::Cancelled => f.write_str("Query aborted by Ctrl+C"),
+        }
+    }
+}
+
+impl std::error::Error for RusqlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RusqlError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<mysql::Error> for RusqlError {
+    fn from(e: mysql::Error) -> Self {
+        match e {
+            mysql::Error::MySqlError(e) if AUTH_ERROR_CODES.contains(&e.code) => {
+                RusqlError::Auth { code: e.code, message: e.message }
+            }
+            mysql::Error::MySqlError(e) => RusqlError::Sql { code: e.code, state: e.state, message: e.message },
+            mysql::Error::IoError(e) => RusqlError::Connection(e.to_string()),
+            mysql::Error::UrlError(e) => RusqlError::Config(e.to_string()),
+            mysql::Error::FromValueError(_) | mysql::Error::FromRowError(_) => RusqlError::Data(e.to_string()),
+            other => RusqlError::Connection(other.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "jobs")]
+impl From<mysql_async::Error> for RusqlError {
+    fn from(e: mysql_async::Error) -> Self {
+        match e {
+            mysql_async::Error::Server(e) if AUTH_ERROR_CODES.contains(&e.code) => {
+                RusqlError::Auth { code: e.code, message: e.message }
+            }
+            mysql_async::Error::Server(e) => RusqlError::Sql { code: e.code, state: e.state, message: e.message },
+            mysql_async::Error::Io(mysql_async::IoError::Io(e)) => RusqlError::Connection(e.to_string()),
+            mysql_async::Error::Url(e) => RusqlError::Config(e.to_string()),
+            other => RusqlError::Connection(other.to_string()),
+        }
+    }
+}
+
+impl From<io::Error> for RusqlError {
+    fn from(e: io::Error) -> Self {
+        RusqlError::Io(e)
+    }
+}
+
+impl From<rustyline::error::ReadlineError> for RusqlError {
+    fn from(e: rustyline::error::ReadlineError) -> Self {
+        match e {
+            rustyline::error::ReadlineError::Io(e) => RusqlError::Io(e),
+            rustyline::error::ReadlineError::Interrupted => RusqlError::Cancelled,
+            other => RusqlError::Io(io::Error::other(other.to_string())),
+        }
+    }
+}
+
+impl From<serde_json::Error> for RusqlError {
+    fn from(e: serde_json::Error) -> Self {
+        RusqlError::Data(e.to_string())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{MySQLClient, QueryResult};
+use mysql::{Column, Value};
+use rusql::error::{Result, RusqlError};
+use rusql::sink::{OutputSink, TableSink};
+use std::io;
+
+const USAGE: &str = "Usage: \\expand <row> <column number or name>";
+
+/// Full values of the last table printed, for showing cells the table cut short.
+pub struct LastResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+    /// Column types, for exporting the result again.
+    pub column_info: Vec<Column>,
+}
+
+impl LastResult {
+    pub fn from_sink(sink: &TableSink) -> Self {
+        LastResult { columns: sink.column_names(), rows: sink.rows().to_vec(), column_info: sink.columns().to_vec() }
+    }
+
+    /// Rough memory held by the values, for bounding the result cache.
+    pub fn size(&self) -> usize {
+        let cell = std::mem::size_of::<Option<String>>();
+        self.rows.iter()
+            .flatten()
+            .map(|value| cell + value.as_ref().map_or(0, String::len))
+            .sum()
+    }
+
+    /// Feed the result through a sink again, as if it came from the server.
+    pub fn replay(&self, sink: &mut dyn OutputSink, summary: &str) -> io::Result<()> {
+        sink.begin(&self.column_info)?;
+        for row in &self.rows {
+            sink.row_began()?;
+            for (index, cell) in row.iter().enumerate() {
+                sink.cell(index, &cell.as_deref().map_or(Value::NULL, Value::from))?;
+            }
+            sink.row_ended()?;
+        }
+        sink.summary(summary)
+    }
+
+    /// Values of a row, counting from 1.
+    pub fn row(&self, row: usize) -> Result<&[Option<String>]> {
+        row.checked_sub(1).and_then(|i| self.rows.get(i)).map(Vec::as_slice)
+            .ok_or_else(|| RusqlError::Command(format!("Row {} is out of range (1-{})", row, self.rows.len())))
+    }
+
+    /// Index of a column given by number, counting from 1, or by name.
+    pub fn column(&self, column: &str) -> Result<usize> {
+        match column.parse::<usize>() {
+            Ok(n) => n.checked_sub(1).filter(|&i| i < self.columns.len()),
+            Err(_) => self.columns.iter().position(|name| name.eq_ignore_ascii_case(column)),
+        }
+        .ok_or_else(|| RusqlError::Command(format!("No column '{}' in the last result", column)))
+    }
+}
+
+/// The cached last result, or an error saying there is none yet.
+pub fn last_result(client: &MySQLClient) -> Result<&LastResult> {
+    client.results.latest()
+        .ok_or_else(|| RusqlError::Command("No result yet; run a query first".into()))
+}
+
+/// `\expand <row> <col>`: print one cell of the last result in full.
+/// Rows and column numbers count from 1; columns may also be given by name.
+pub fn expand(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let last = last_result(client)?;
+    let mut words = args.split_whitespace();
+    let (Some(row), Some(column), None) = (words.next(), words.next(), words.next()) else {
+        return Err(RusqlError::Command(USAGE.into()));
+    };
+
+    let row: usize = row.parse().map_err(|_| RusqlError::Command(USAGE.into()))?;
+    let values = last.row(row)?;
+    let index = last.column(column)?;
+
+    let header = format!("Row {}, {}:", row, last.columns[index]);
+    let value = values[index].as_deref().unwrap_or("NULL").to_string();
+    client.notice(&header);
+    println!("{}", value);
+    Ok(None)
+}
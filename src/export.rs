@@ -0,0 +1,305 @@
+//! Chunked export of a large table to a directory of files.
+//!
+//! The table is read in primary key order, one range of keys per chunk file,
+//! and a `manifest.json` listing the finished chunks is rewritten after each
+//! one. An export that stops part way resumes after the last chunk in the
+//! manifest instead of starting over.
+
+use crate::bulk::{primary_key, quote_identifier, quote_table};
+use crate::compress;
+use crate::error::{Result, RusqlError};
+use crate::format::format_value;
+use crate::sink::{write_rows, OutputFormat, SinkOptions};
+use mysql::prelude::*;
+use mysql::{Conn, Row, Value};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+/// Name of the manifest file in the export directory.
+pub const MANIFEST: &str = "manifest.json";
+
+/// What has been exported so far, as stored in the manifest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub table: String,
+    pub key: String,
+    pub format: String,
+    /// Extension of the chunk files, e.g. `csv.gz`.
+    pub extension: String,
+    pub condition: Option<String>,
+    pub chunk_rows: usize,
+    pub chunks: Vec<Chunk>,
+    /// Set once the last chunk is written.
+    pub complete: bool,
+}
+
+impl Manifest {
+    pub fn rows(&self) -> u64 {
+        self.chunks.iter().map(|chunk| chunk.rows).sum()
+    }
+}
+
+/// One exported file and the key range it holds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chunk {
+    pub file: String,
+    pub rows: u64,
+    pub first_key: String,
+    pub last_key: String,
+}
+
+/// Callback receiving the manifest after every chunk.
+type OnChunk<'a> = Box<dyn FnMut(&Manifest) + 'a>;
+
+/// Export of one table as numbered chunk files and a manifest.
+///
+/// ```no_run
+/// # fn run(conn: &mut mysql::Conn) -> rusql::error::Result<()> {
+/// use rusql::export::TableExport;
+/// use rusql::sink::OutputFormat;
+/// let manifest = TableExport::new("shop.orders", "exports/orders")
+///     .format(OutputFormat::Csv)
+///     .compression(Some("zst"))
+///     .chunk_rows(500_000)
+///     .resume(true)
+///     .execute(conn)?;
+/// println!("{} rows in {} files", manifest.rows(), manifest.chunks.len());
+/// # Ok(()) }
+/// ```
+pub struct TableExport<'a> {
+    table: String,
+    dir: PathBuf,
+    format: OutputFormat,
+    compression: Option<String>,
+    condition: Option<String>,
+    chunk_rows: usize,
+    resume: bool,
+    options: SinkOptions,
+    progress: Option<OnChunk<'a>>,
+}
+
+impl<'a> TableExport<'a> {
+    pub fn new(table: &str, dir: impl Into<PathBuf>) -> Self {
+        TableExport {
+            table: table.to_string(),
+            dir: dir.into(),
+            format: OutputFormat::Csv,
+            compression: None,
+            condition: None,
+            chunk_rows: 1_000_000,
+            resume: false,
+            options: SinkOptions::default(),
+            progress: None,
+        }
+    }
+
+    /// File format of the chunks; tables are not a file format.
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Compress chunks with `gz` or `zst`.
+    pub fn compression(mut self, extension: Option<&str>) -> Self {
+        self.compression = extension.map(str::to_string);
+        self
+    }
+
+    /// Only export rows matching this `WHERE` condition.
+    pub fn condition(mut self, condition: Option<&str>) -> Self {
+        self.condition = condition.map(str::to_string);
+        self
+    }
+
+    /// Rows per chunk file.
+    pub fn chunk_rows(mut self, rows: usize) -> Self {
+        self.chunk_rows = rows.max(1);
+        self
+    }
+
+    /// Continue the export described by the directory's manifest instead
+    /// of refusing to touch a directory that has one.
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub fn sink_options(mut self, options: SinkOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Called after every chunk with the updated manifest.
+    pub fn on_chunk<F: FnMut(&Manifest) + 'a>(mut self, callback: F) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    pub fn execute(mut self, conn: &mut Conn) -> Result<Manifest> {
+        if self.format == OutputFormat::Table {
+            return Err(RusqlError::Command("Tables are not a file format; pick csv, json or another".into()));
+        }
+        let key = match primary_key(conn, &self.table)?.as_slice() {
+            [key] => key.clone(),
+            _ => return Err(RusqlError::Command(format!(
+                "{} has no single-column primary key to split it by",
+                self.table
+            ))),
+        };
+        let mut manifest = self.manifest(&key)?;
+        if manifest.complete {
+            return Ok(manifest);
+        }
+
+        let no_backslash_escape = conn.no_backslash_escape();
+        loop {
+            // Keys are inlined so rows come as text, exported as the server shows them
+            let after = manifest.chunks.last()
+                .map(|chunk| format!(
+                    " AND {} > {}",
+                    quote_identifier(&key),
+                    Value::from(chunk.last_key.as_str()).as_sql(no_backslash_escape)
+                ))
+                .unwrap_or_default();
+            let query = format!(
+                "SELECT * FROM {table} WHERE ({condition}){after} ORDER BY {key} LIMIT {limit}",
+                table = quote_table(&self.table),
+                condition = self.condition.as_deref().unwrap_or("1 = 1"),
+                key = quote_identifier(&key),
+                limit = self.chunk_rows,
+            );
+            let Some(chunk) = self.write_chunk(conn, &query, &key, manifest.chunks.len() + 1)? else {
+                break;
+            };
+            let full = chunk.rows as usize == self.chunk_rows;
+            manifest.chunks.push(chunk);
+            manifest.complete = !full;
+            self.save(&manifest)?;
+            if let Some(callback) = self.progress.as_mut() {
+                callback(&manifest);
+            }
+            if !full {
+                return Ok(manifest);
+            }
+        }
+        manifest.complete = true;
+        self.save(&manifest)?;
+        Ok(manifest)
+    }
+
+    /// The manifest to continue from, or a new one.
+    fn manifest(&self, key: &str) -> Result<Manifest> {
+        let path = self.dir.join(MANIFEST);
+        if path.exists() {
+            if !self.resume {
+                return Err(RusqlError::Command(format!(
+                    "'{}' already holds an export; resume it or pick another directory",
+                    self.dir.display()
+                )));
+            }
+            let manifest: Manifest = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            let same = manifest.table == self.table
+                && manifest.condition == self.condition
+                && manifest.key == key
+                && manifest.extension == self.extension();
+            if !same {
+                return Err(RusqlError::Command(format!(
+                    "'{}' holds an export of {} with other settings",
+                    self.dir.display(),
+                    manifest.table
+                )));
+            }
+            return Ok(manifest);
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        Ok(Manifest {
+            table: self.table.clone(),
+            key: key.to_string(),
+            format: self.format.name().to_string(),
+            extension: self.extension(),
+            condition: self.condition.clone(),
+            chunk_rows: self.chunk_rows,
+            chunks: Vec::new(),
+            complete: false,
+        })
+    }
+
+    fn extension(&self) -> String {
+        match &self.compression {
+            Some(compression) => format!("{}.{}", self.format.name(), compression),
+            None => self.format.name().to_string(),
+        }
+    }
+
+    /// Stream one key range into its file; `None` once no rows are left.
+    fn write_chunk(&self, conn: &mut Conn, query: &str, key: &str, number: usize) -> Result<Option<Chunk>> {
+        let table = self.table.rsplit('.').next().unwrap_or(&self.table);
+        let file = format!("{}.{:05}.{}", table, number, self.extension());
+        let path = self.dir.join(&file);
+
+        let result = conn.query_iter(query)?;
+        let columns = result.columns().as_ref().to_vec();
+        let position = columns.iter().position(|c| c.name_str() == key)
+            .ok_or_else(|| RusqlError::Command(format!("column '{}' not found in {}", key, self.table)))?;
+
+        let read_error = RefCell::new(None);
+        let keys = RefCell::new((None::<Value>, None::<Value>));
+        let mut rows = result
+            .map_while(|row| match row {
+                Ok(row) => Some(row),
+                Err(e) => {
+                    *read_error.borrow_mut() = Some(e);
+                    None
+                }
+            })
+            .inspect(|row: &Row| {
+                let value = row.as_ref(position).cloned();
+                let mut keys = keys.borrow_mut();
+                if keys.0.is_none() {
+                    keys.0 = value.clone();
+                }
+                keys.1 = value;
+            })
+            .peekable();
+        if rows.peek().is_none() {
+            drop(rows);
+            return match read_error.into_inner() {
+                Some(e) => Err(e.into()),
+                None => Ok(None),
+            };
+        }
+
+        let mut counted = 0u64;
+        let rows = rows.inspect(|_| counted += 1);
+        {
+            let mut out = compress::create(&path, false)?;
+            let Some(mut sink) = self.format.sink(&mut out, &self.options) else {
+                return Err(RusqlError::Command("Tables are not a file format".into()));
+            };
+            write_rows(sink.as_mut(), &columns, rows, "")?;
+        }
+        if let Some(e) = read_error.into_inner() {
+            // The chunk is incomplete; it is written again on resume
+            let _ = std::fs::remove_file(&path);
+            return Err(e.into());
+        }
+        let (first, last) = keys.into_inner();
+        Ok(Some(Chunk {
+            file,
+            rows: counted,
+            first_key: first.as_ref().map(format_value).unwrap_or_default(),
+            last_key: last.as_ref().map(format_value).unwrap_or_default(),
+        }))
+    }
+
+    /// Replace the manifest atomically, so a crash never leaves half of one.
+    fn save(&self, manifest: &Manifest) -> Result<()> {
+        let path = self.dir.join(MANIFEST);
+        let partial = path.with_extension("json.partial");
+        std::fs::write(&partial, serde_json::to_string_pretty(manifest)?)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+}
+
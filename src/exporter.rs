@@ -0,0 +1,215 @@
+use crate::config::Settings;
+use chrono::Local;
+use mysql::prelude::*;
+use mysql::{Conn, Opts, OptsBuilder};
+use rusql::compress;
+use rusql::error::{Result, RusqlError};
+use rusql::format::format_value;
+use rusql::prometheus::{Exposition, MetricKind, Sample};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Seconds between refreshes when the queries file sets no `interval`.
+const DEFAULT_INTERVAL: u64 = 30;
+
+/// `SHOW GLOBAL STATUS` variables exported as `mysql_global_status_*`.
+const STATUS_VARIABLES: &[(&str, MetricKind)] = &[
+    ("Uptime", MetricKind::Counter),
+    ("Connections", MetricKind::Counter),
+    ("Aborted_connects", MetricKind::Counter),
+    ("Threads_connected", MetricKind::Gauge),
+    ("Threads_running", MetricKind::Gauge),
+    ("Questions", MetricKind::Counter),
+    ("Slow_queries", MetricKind::Counter),
+    ("Bytes_received", MetricKind::Counter),
+    ("Bytes_sent", MetricKind::Counter),
+    ("Innodb_row_lock_waits", MetricKind::Counter),
+    ("Innodb_buffer_pool_reads", MetricKind::Counter),
+    ("Innodb_buffer_pool_read_requests", MetricKind::Counter),
+];
+
+/// The queries file of `rusql exporter`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QueriesFile {
+    /// Seconds between runs of the queries
+    interval: Option<u64>,
+    /// Profile to connect with; the command-line connection if unset
+    profile: Option<String>,
+    #[serde(default)]
+    metrics: Vec<MetricSettings>,
+}
+
+/// A `[[metrics]]` entry: a query whose last column is the value and whose
+/// other columns, if any, are labels.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MetricSettings {
+    name: String,
+    help: Option<String>,
+    query: String,
+    /// `gauge` (default) or `counter`
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+struct Metric {
+    name: String,
+    help: String,
+    query: String,
+    kind: MetricKind,
+}
+
+/// `rusql exporter --listen :9104 --queries metrics.toml`: serve the
+/// results of the queries in a file, refreshed every `interval` seconds,
+/// with a few server status counters as Prometheus metrics on `/metrics`.
+pub fn run(listen: &str, path: &Path, settings: &Settings, connection: OptsBuilder) -> Result<()> {
+    let contents = compress::read_to_string(path)?;
+    let file: QueriesFile = toml::from_str(&contents)
+        .map_err(|e| RusqlError::Config(format!("{}: {}", path.display(), e)))?;
+    let opts: Opts = match &file.profile {
+        Some(name) => settings.profiles.get(name)
+            .ok_or_else(|| RusqlError::Config(format!("{}: unknown profile '{}'", path.display(), name)))?
+            .opts()
+            .into(),
+        None => connection.into(),
+    };
+    let metrics = file.metrics.into_iter()
+        .map(|metric| {
+            let kind = match &metric.kind {
+                Some(kind) => kind.parse().map_err(|e| RusqlError::Config(format!("{}: metric '{}': {}", path.display(), metric.name, e)))?,
+                None => MetricKind::default(),
+            };
+            let help = metric.help.unwrap_or_else(|| format!("Result of: {}", metric.query.split_whitespace().collect::<Vec<_>>().join(" ")));
+            Ok(Metric { name: metric.name, help, query: metric.query, kind })
+        })
+        .collect::<Result<Vec<Metric>>>()?;
+    let interval = Duration::from_secs(file.interval.unwrap_or(DEFAULT_INTERVAL).max(1));
+
+    let address = match listen.strip_prefix(':') {
+        Some(port) => format!("0.0.0.0:{}", port),
+        None => listen.to_string(),
+    };
+    let listener = TcpListener::bind(&address)?;
+    log(&format!("Serving {} metrics on http://{}/metrics", metrics.len(), address));
+
+    let page = Arc::new(Mutex::new(collect(&opts, &metrics)));
+    let latest = page.clone();
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        let text = collect(&opts, &metrics);
+        if let Ok(mut page) = latest.lock() {
+            *page = text;
+        }
+    });
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let text = page.lock().map(|page| page.clone()).unwrap_or_default();
+                if let Err(e) = respond(stream, &text) {
+                    log(&format!("Could not answer a scrape: {}", e));
+                }
+            }
+            Err(e) => log(&format!("Could not accept a scrape: {}", e)),
+        }
+    }
+    Ok(())
+}
+
+/// Run every query and render the page served until the next refresh.
+fn collect(opts: &Opts, metrics: &[Metric]) -> String {
+    let mut page = Exposition::default();
+    let mut conn = match Conn::new(opts.clone()) {
+        Ok(conn) => conn,
+        Err(e) => {
+            log(&format!("Could not connect: {}", e));
+            page.metric("mysql_up", "Whether the server could be reached at the last refresh", MetricKind::Gauge, &[(vec![], 0.0)]);
+            return page.finish();
+        }
+    };
+    page.metric("mysql_up", "Whether the server could be reached at the last refresh", MetricKind::Gauge, &[(vec![], 1.0)]);
+
+    match conn.query::<(String, String), _>("SHOW GLOBAL STATUS") {
+        Ok(status) => {
+            for (name, kind) in STATUS_VARIABLES {
+                let value = status.iter()
+                    .find(|(variable, _)| variable.eq_ignore_ascii_case(name))
+                    .and_then(|(_, value)| value.parse::<f64>().ok());
+                if let Some(value) = value {
+                    let metric = format!("mysql_global_status_{}", name.to_lowercase());
+                    page.metric(&metric, &format!("SHOW GLOBAL STATUS variable {}", name), *kind, &[(vec![], value)]);
+                }
+            }
+        }
+        Err(e) => log(&format!("Could not read the server status: {}", e)),
+    }
+
+    let mut outcomes = Vec::with_capacity(metrics.len());
+    for metric in metrics {
+        match samples(&mut conn, &metric.query) {
+            Ok(samples) => {
+                page.metric(&metric.name, &metric.help, metric.kind, &samples);
+                outcomes.push((vec![("metric".to_string(), metric.name.clone())], 1.0));
+            }
+            Err(e) => {
+                log(&format!("{}: {}", metric.name, e));
+                outcomes.push((vec![("metric".to_string(), metric.name.clone())], 0.0));
+            }
+        }
+    }
+    page.metric("rusql_exporter_query_ok", "Whether the query of a metric succeeded at the last refresh", MetricKind::Gauge, &outcomes);
+    page.finish()
+}
+
+/// A sample per row: the last column is the value and the others label it.
+/// Rows whose value is NULL or not a number are left out.
+fn samples(conn: &mut Conn, query: &str) -> Result<Vec<Sample>> {
+    let result = conn.query_iter(query)?;
+    let columns: Vec<String> = result.columns().as_ref().iter().map(|column| column.name_str().into_owned()).collect();
+    let mut samples = Vec::new();
+    for row in result {
+        let values = row?.unwrap();
+        let Some((value, labels)) = values.split_last() else { continue };
+        let Ok(value) = format_value(value).parse::<f64>() else { continue };
+        let labels = columns.iter().zip(labels).map(|(column, value)| (column.clone(), format_value(value))).collect();
+        samples.push((labels, value));
+    }
+    Ok(samples)
+}
+
+/// Answer one HTTP request: the metrics on `/metrics`, 404 elsewhere.
+fn respond(stream: TcpStream, text: &str) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // Skip the headers; the request has no body worth reading
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let (status, body) = match path.split('?').next() {
+        Some("/metrics") => ("200 OK", text),
+        _ => ("404 Not Found", "Metrics are on /metrics\n"),
+    };
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn log(message: &str) {
+    eprintln!("{} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+}
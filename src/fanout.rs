@@ -0,0 +1,201 @@
+use crate::expand::LastResult;
+use crate::recall::show_latest;
+use crate::{interrupt, variables, MySQLClient, QueryResult};
+use mysql::consts::ColumnType;
+use mysql::{Column, Opts, OptsBuilder, Value};
+use rusql::error::{Result, RusqlError};
+use rusql::job::{Job, Outcome};
+use rusql::sink::{OutputSink, TableSink};
+use std::time::Instant;
+
+const USAGE: &str = "Usage: \\fanout <profile,profile,...|prefix*> [--side-by-side] <statement>";
+
+/// `\fanout shard1,shard2 <statement>`: run a statement on several profiles
+/// at once, each on its own connection, and show the rows of all of them
+/// with a leading `source` column. `--side-by-side` instead matches rows on
+/// their first column and gives every source its own columns, which suits
+/// status and variable lookups. `shard*` names every profile starting with
+/// `shard` and `.` the current connection. Sources that fail are reported
+/// and left out.
+pub fn fanout(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let (names, rest) = args.trim().split_once(char::is_whitespace).ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+    let rest = rest.trim_start();
+    let (side_by_side, statement) = match rest.strip_prefix("--side-by-side") {
+        Some(statement) => (true, statement),
+        None => (false, rest),
+    };
+    let statement = statement.trim().trim_end_matches(';').trim_end();
+    if statement.is_empty() {
+        return Err(RusqlError::Command(USAGE.into()));
+    }
+    let statement = variables::interpolate(statement, &client.variables);
+    let sources = sources(client, names)?;
+
+    let start = Instant::now();
+    let (labels, opts): (Vec<String>, Vec<Opts>) = sources.into_iter().unzip();
+    let jobs: Vec<(String, Result<Job>)> = labels.into_iter().zip(Job::spawn_all(opts, &statement)?).collect();
+    let total = jobs.len();
+    let mut outcomes: Vec<(String, Outcome)> = Vec::new();
+    for (name, job) in jobs {
+        match job.and_then(|job| job.wait_unless(interrupt::interrupted)) {
+            Ok(outcome) => outcomes.push((name, outcome)),
+            Err(e) => report(client, &name, &e.to_string()),
+        }
+    }
+    let elapsed = start.elapsed();
+
+    if outcomes.is_empty() {
+        return Err(RusqlError::Command(format!("The statement failed on all {} sources", total)));
+    }
+    if outcomes.iter().all(|(_, outcome)| outcome.columns.is_empty()) {
+        return Ok(Some(affected(client, &outcomes, total, elapsed.as_secs_f64())));
+    }
+    let Some(columns) = outcomes.iter().map(|(_, outcome)| &outcome.columns).find(|columns| !columns.is_empty()).cloned() else {
+        return Ok(None);
+    };
+    let names: Vec<String> = columns.iter().map(|c| c.name_str().into_owned()).collect();
+    outcomes.retain(|(name, outcome)| {
+        let same = outcome.columns.iter().map(|c| c.name_str().into_owned()).eq(names.iter().cloned());
+        if !same {
+            report(client, name, "returned other columns than the first source; left out");
+        }
+        same
+    });
+    for (_, outcome) in &mut outcomes {
+        client.sensitive_rules.result_columns(&outcome.columns).apply_rows(&mut outcome.rows);
+    }
+
+    let mut sink = TableSink::new(false);
+    if side_by_side {
+        side_by_side_rows(&mut sink, &columns, &outcomes)?;
+    } else {
+        merged_rows(&mut sink, &columns, &outcomes)?;
+    }
+    let result = LastResult::from_sink(&sink);
+    let rows = result.rows.len();
+    client.results.push(&format!("\\fanout {}", args.trim()), result);
+    let summary = format!(
+        "{} {} from {} of {} sources ({:.2} sec)",
+        rows,
+        if rows == 1 { "row" } else { "rows" },
+        outcomes.len(),
+        total,
+        elapsed.as_secs_f64()
+    );
+    show_latest(client, client.format, &summary)
+}
+
+/// Connection options for each named source, in the order given.
+fn sources(client: &MySQLClient, names: &str) -> Result<Vec<(String, Opts)>> {
+    let mut sources: Vec<(String, Opts)> = Vec::new();
+    for name in names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let matched: Vec<(String, Opts)> = match name {
+            "." => {
+                let opts = OptsBuilder::from_opts(client.conn_opts.clone()).db_name(client.current_db.clone());
+                vec![(".".to_string(), opts.into())]
+            }
+            _ => match name.strip_suffix('*') {
+                Some(prefix) => client.profiles.iter()
+                    .filter(|(profile, _)| profile.starts_with(prefix))
+                    .map(|(profile, settings)| (profile.clone(), settings.opts().into()))
+                    .collect(),
+                None => {
+                    let profile = client.profiles.get(name)
+                        .ok_or_else(|| RusqlError::Command(format!("Unknown profile '{}'", name)))?;
+                    vec![(name.to_string(), profile.opts().into())]
+                }
+            },
+        };
+        if matched.is_empty() {
+            return Err(RusqlError::Command(format!("No profile matches '{}'", name)));
+        }
+        for source in matched {
+            if !sources.iter().any(|(taken, _)| *taken == source.0) {
+                sources.push(source);
+            }
+        }
+    }
+    if sources.is_empty() {
+        return Err(RusqlError::Command(USAGE.into()));
+    }
+    Ok(sources)
+}
+
+fn report(client: &MySQLClient, source: &str, message: &str) {
+    eprintln!("{}", client.paint(&format!("{}: {}", source, message), client.error_color()));
+}
+
+/// Rows affected on each source, for statements that return no rows.
+fn affected(client: &MySQLClient, outcomes: &[(String, Outcome)], total: usize, seconds: f64) -> QueryResult {
+    let rows: Vec<Vec<String>> = outcomes.iter()
+        .map(|(name, outcome)| vec![name.clone(), outcome.affected_rows.to_string()])
+        .collect();
+    let summary = format!("Query OK on {} of {} sources ({:.2} sec)", outcomes.len(), total, seconds);
+    QueryResult { table: client.build_table(&["source", "Rows affected"], rows), summary }
+}
+
+/// Every source's rows one after another, after a `source` column.
+fn merged_rows(sink: &mut TableSink, columns: &[Column], outcomes: &[(String, Outcome)]) -> Result<()> {
+    let mut all = vec![Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"source")];
+    all.extend(columns.iter().cloned());
+    sink.begin(&all)?;
+    for (name, outcome) in outcomes {
+        for row in &outcome.rows {
+            sink.row_began()?;
+            sink.cell(0, &Value::from(name.as_str()))?;
+            for index in 0..columns.len() {
+                sink.cell(index + 1, row.as_ref(index).unwrap_or(&Value::NULL))?;
+            }
+            sink.row_ended()?;
+        }
+    }
+    Ok(())
+}
+
+/// One row per distinct value of the first column, in order of first
+/// appearance, with the other columns repeated for every source.
+fn side_by_side_rows(sink: &mut TableSink, columns: &[Column], outcomes: &[(String, Outcome)]) -> Result<()> {
+    let Some((key, values)) = columns.split_first() else {
+        return Ok(());
+    };
+    let mut all = vec![key.clone()];
+    for (name, _) in outcomes {
+        for column in values {
+            let header = match values.len() {
+                1 => name.clone(),
+                _ => format!("{}.{}", name, column.name_str()),
+            };
+            all.push(column.clone().with_name(header.as_bytes()));
+        }
+    }
+
+    let mut keys: Vec<Value> = Vec::new();
+    let mut cells: Vec<Vec<Value>> = Vec::new();
+    for (source, (_, outcome)) in outcomes.iter().enumerate() {
+        for row in &outcome.rows {
+            let key = row.as_ref(0).cloned().unwrap_or(Value::NULL);
+            let line = match keys.iter().position(|taken| *taken == key) {
+                Some(line) => line,
+                None => {
+                    keys.push(key);
+                    cells.push(vec![Value::NULL; all.len() - 1]);
+                    keys.len() - 1
+                }
+            };
+            for index in 0..values.len() {
+                cells[line][source * values.len() + index] = row.as_ref(index + 1).cloned().unwrap_or(Value::NULL);
+            }
+        }
+    }
+
+    sink.begin(&all)?;
+    for (key, line) in keys.iter().zip(&cells) {
+        sink.row_began()?;
+        sink.cell(0, key)?;
+        for (index, value) in line.iter().enumerate() {
+            sink.cell(index + 1, value)?;
+        }
+        sink.row_ended()?;
+    }
+    Ok(())
+}
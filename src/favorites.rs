@@ -0,0 +1,115 @@
+use crate::config::config_file;
+use std::collections::BTreeMap;
+use rusql::error::{Result, RusqlError};
+use std::fs;
+
+pub const FAVORITES_FILE: &str = "favorites";
+
+/// Named queries saved with `\save`, stored one per line as `name<TAB>query`
+/// with the query's line breaks and backslashes escaped.
+pub struct Favorites {
+    queries: BTreeMap<String, String>,
+}
+
+impl Favorites {
+    pub fn load() -> Self {
+        let queries = config_file(FAVORITES_FILE)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|contents| {
+                contents.lines()
+                    .filter_map(|line| line.split_once('\t'))
+                    .map(|(name, query)| (name.to_string(), unescape(query)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Favorites { queries }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.queries.get(name).map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.queries.iter()
+    }
+
+    /// Save `query` as it was written, string literals and all.
+    pub fn insert(&mut self, name: &str, query: &str) -> Result<()> {
+        self.queries.insert(name.to_string(), query.to_string());
+        self.save()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<bool> {
+        let removed = self.queries.remove(name).is_some();
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = config_file(FAVORITES_FILE)
+            .ok_or_else(|| RusqlError::Config("no configuration directory available".into()))?;
+        let contents: String = self.queries.iter()
+            .map(|(name, query)| format!("{}\t{}\n", name, escape(query)))
+            .collect();
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+/// One line of the favorites file holding `query`.
+fn escape(query: &str) -> String {
+    query.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape(line: &str) -> String {
+    let mut query = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            query.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => query.push('\n'),
+            Some('r') => query.push('\r'),
+            Some('\\') => query.push('\\'),
+            // Files written before escaping kept backslashes as they were
+            Some(other) => {
+                query.push('\\');
+                query.push(other);
+            }
+            None => query.push('\\'),
+        }
+    }
+    query
+}
+
+/// Replace `$1`, `$2`, ... in a saved query with positional arguments.
+pub fn substitute(query: &str, args: &[&str]) -> Result<String> {
+    let mut result = String::with_capacity(query.len());
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' || !chars.peek().is_some_and(|n| n.is_ascii_digit()) {
+            result.push(c);
+            continue;
+        }
+
+        let mut index = String::new();
+        while let Some(digit) = chars.peek().filter(|n| n.is_ascii_digit()) {
+            index.push(*digit);
+            chars.next();
+        }
+        let position: usize = index.parse()
+            .map_err(|_| RusqlError::Command(format!("invalid parameter ${}", index)))?;
+        match position.checked_sub(1).and_then(|i| args.get(i)) {
+            Some(arg) => result.push_str(arg),
+            None => return Err(RusqlError::Command(format!("missing value for parameter ${}", position))),
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,68 @@
+use crate::expand::{last_result, LastResult};
+use crate::{MySQLClient, QueryResult};
+use regex::RegexBuilder;
+use rusql::error::{Result, RusqlError};
+
+/// `\grep <regex>`: show the rows of the last result with any cell matching,
+/// case-insensitively. The cached result is kept, so searches can be refined.
+pub fn grep(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        return Err(RusqlError::Command("Usage: \\grep <regex>".into()));
+    }
+    let pattern = RegexBuilder::new(args)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| RusqlError::Command(format!("Invalid regex: {}", e)))?;
+    let last = last_result(client)?;
+    let rows = matching_rows(last, |row| row.iter().flatten().any(|value| pattern.is_match(value)));
+    Ok(Some(show(client, last, rows)))
+}
+
+/// `\filter <col>=<value>` or `<col>!=<value>`: show the rows of the last
+/// result whose column equals (or differs from) the value; `NULL` matches
+/// NULL.
+pub fn filter(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let usage = || RusqlError::Command("Usage: \\filter <column>=<value> or <column>!=<value>".into());
+    let (column, value) = args.split_once('=').ok_or_else(usage)?;
+    let (column, equal) = match column.strip_suffix('!') {
+        Some(column) => (column.trim(), false),
+        None => (column.trim(), true),
+    };
+    if column.is_empty() {
+        return Err(usage());
+    }
+    let value = value.trim();
+
+    let last = last_result(client)?;
+    let index = last.column(column)?;
+    let rows = matching_rows(last, |row| {
+        let matches = match &row[index] {
+            Some(cell) => cell == value,
+            None => value.eq_ignore_ascii_case("NULL"),
+        };
+        matches == equal
+    });
+    Ok(Some(show(client, last, rows)))
+}
+
+/// Rows of `last` passing `keep`, with their row numbers counted from 1.
+fn matching_rows(last: &LastResult, keep: impl Fn(&[Option<String>]) -> bool) -> Vec<Vec<String>> {
+    last.rows.iter()
+        .enumerate()
+        .filter(|(_, row)| keep(row))
+        .map(|(i, row)| {
+            let mut cells = vec![(i + 1).to_string()];
+            cells.extend(row.iter().map(|value| value.as_deref().unwrap_or("NULL").to_string()));
+            cells
+        })
+        .collect()
+}
+
+/// The matching rows as a table; the `#` column gives the row numbers
+/// `\expand` expects.
+fn show(client: &MySQLClient, last: &LastResult, rows: Vec<Vec<String>>) -> QueryResult {
+    let mut headers = vec!["#"];
+    headers.extend(last.columns.iter().map(String::as_str));
+    let summary = format!("{} of {} rows match", rows.len(), last.rows.len());
+    QueryResult { table: client.build_table(&headers, rows), summary }
+}
@@ -0,0 +1,216 @@
+use crate::introspect::split_table;
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::Conn;
+use rusql::error::{Result, RusqlError};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+const USAGE: &str = "Usage: \\fk-graph [[db.]table] [--dot [file]]";
+
+/// One foreign key, possibly over several columns.
+pub struct ForeignKey {
+    pub table: String,
+    pub name: String,
+    pub columns: Vec<String>,
+    /// The referenced table, as `db.table` when it is in another database.
+    pub parent: String,
+    pub parent_columns: Vec<String>,
+}
+
+/// Base tables of `db` and the foreign keys between them.
+pub fn load(conn: &mut Conn, db: &str) -> Result<(Vec<String>, Vec<ForeignKey>)> {
+    let tables: Vec<String> = conn.exec(
+        "SELECT TABLE_NAME FROM information_schema.TABLES \
+         WHERE TABLE_SCHEMA = ? AND TABLE_TYPE = 'BASE TABLE' ORDER BY TABLE_NAME",
+        (db,),
+    )?;
+    let columns: Vec<(String, String, String, String, String, String)> = conn.exec(
+        "SELECT TABLE_NAME, CONSTRAINT_NAME, COLUMN_NAME, \
+         REFERENCED_TABLE_SCHEMA, REFERENCED_TABLE_NAME, REFERENCED_COLUMN_NAME \
+         FROM information_schema.KEY_COLUMN_USAGE \
+         WHERE TABLE_SCHEMA = ? AND REFERENCED_TABLE_NAME IS NOT NULL \
+         ORDER BY TABLE_NAME, CONSTRAINT_NAME, ORDINAL_POSITION",
+        (db,),
+    )?;
+
+    let mut keys: Vec<ForeignKey> = Vec::new();
+    for (table, name, column, parent_db, parent, parent_column) in columns {
+        match keys.last_mut() {
+            Some(key) if key.table == table && key.name == name => {
+                key.columns.push(column);
+                key.parent_columns.push(parent_column);
+            }
+            _ => keys.push(ForeignKey {
+                table,
+                name,
+                columns: vec![column],
+                parent: if parent_db == db { parent } else { format!("{}.{}", parent_db, parent) },
+                parent_columns: vec![parent_column],
+            }),
+        }
+    }
+    Ok((tables, keys))
+}
+
+/// Load steps: a table's step is one more than the latest step of the tables
+/// it references, so loading step by step never violates a key. Tables in a
+/// reference cycle get no step.
+pub fn load_steps(tables: &[String], keys: &[ForeignKey]) -> BTreeMap<String, usize> {
+    let mut steps = BTreeMap::new();
+    let mut remaining: Vec<&String> = tables.iter().collect();
+    let mut step = 1;
+    while !remaining.is_empty() {
+        // Ready when every table it references is loaded, lives elsewhere, or is itself
+        let (ready, waiting): (Vec<&String>, Vec<&String>) = remaining.into_iter().partition(|table| {
+            keys.iter()
+                .filter(|key| &key.table == *table && &key.parent != *table && tables.contains(&key.parent))
+                .all(|key| steps.contains_key(&key.parent))
+        });
+        if ready.is_empty() {
+            break;
+        }
+        for table in ready {
+            steps.insert(table.clone(), step);
+        }
+        remaining = waiting;
+        step += 1;
+    }
+    steps
+}
+
+/// `\fk-graph [table] [--dot [file]]`: the tables of the current database as
+/// a tree of referenced tables and the tables referencing them, with the step
+/// in which each can be loaded (drop in reverse). Given a table, only it and
+/// its dependents are shown. `--dot` writes Graphviz DOT instead.
+pub fn fk_graph(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let (args, dot) = match args.split_once("--dot") {
+        Some((args, file)) => (args.trim(), Some(file.trim())),
+        None => (args, None),
+    };
+    if args.starts_with("--") || args.contains(char::is_whitespace) {
+        return Err(RusqlError::Command(USAGE.into()));
+    }
+    let (db, start) = match args {
+        "" => (client.current_db.clone().ok_or_else(|| RusqlError::Command("No database selected".into()))?, None),
+        table => {
+            let (db, table) = split_table(client, table)?;
+            (db, Some(table))
+        }
+    };
+
+    let (tables, keys) = load(&mut client.conn, &db)?;
+    if let Some(table) = &start
+        && !tables.contains(table)
+    {
+        return Err(RusqlError::Command(format!("Table '{}.{}' doesn't exist", db, table)));
+    }
+
+    if let Some(file) = dot {
+        let shown = match &start {
+            Some(table) => dependents(table, &keys),
+            None => tables.iter().cloned().collect(),
+        };
+        let graph = to_dot(&db, &shown, &keys);
+        if file.is_empty() {
+            print!("{}", graph);
+        } else {
+            std::fs::write(file, graph)?;
+            client.notice(&format!("Graph of {} tables written to '{}'", shown.len(), file));
+        }
+        return Ok(None);
+    }
+
+    let steps = load_steps(&tables, &keys);
+    let roots: Vec<String> = match &start {
+        Some(table) => vec![table.clone()],
+        // Tables referencing nothing else in the database; cycles have none,
+        // so their members are listed as roots too
+        None => tables.iter()
+            .filter(|table| {
+                !steps.contains_key(*table)
+                    || keys.iter().all(|key| &key.table != *table || &key.parent == *table || !tables.contains(&key.parent))
+            })
+            .cloned()
+            .collect(),
+    };
+
+    let mut rows = Vec::new();
+    for root in &roots {
+        let mut path = HashSet::new();
+        tree(root, None, 0, &keys, &steps, &mut path, &mut rows);
+    }
+
+    let cycles = tables.iter().filter(|table| !steps.contains_key(*table)).count();
+    let mut summary = format!("{} tables, {} foreign keys; load in step order, drop in reverse", tables.len(), keys.len());
+    if cycles > 0 {
+        summary.push_str(&format!("; {} tables are in a reference cycle and need FOREIGN_KEY_CHECKS=0", cycles));
+    }
+    if let Some(table) = &start {
+        let needs: Vec<&str> = keys.iter()
+            .filter(|key| &key.table == table && &key.parent != table)
+            .map(|key| key.parent.as_str())
+            .collect();
+        if !needs.is_empty() {
+            summary.push_str(&format!("; {} references {}", table, needs.join(", ")));
+        }
+    }
+    let headers = ["Step", "Table", "Foreign key"];
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+/// Append `table` at `depth`, then the tables referencing it. `path` holds
+/// the tables above, so a cycle is shown once and not followed.
+fn tree(
+    table: &str,
+    via: Option<&ForeignKey>,
+    depth: usize,
+    keys: &[ForeignKey],
+    steps: &BTreeMap<String, usize>,
+    path: &mut HashSet<String>,
+    rows: &mut Vec<Vec<String>>,
+) {
+    let indent = if depth == 0 { String::new() } else { format!("{}└─ ", "   ".repeat(depth - 1)) };
+    let cycle = path.contains(table);
+    let key = via.map_or_else(String::new, |key| {
+        format!("{} ({}) -> {} ({})", key.name, key.columns.join(", "), key.parent, key.parent_columns.join(", "))
+    });
+    let name = if cycle { format!("{}{} (cycle)", indent, table) } else { format!("{}{}", indent, table) };
+    let step = steps.get(table).map_or_else(|| "-".to_string(), |step| step.to_string());
+    rows.push(vec![step, name, key]);
+    if cycle {
+        return;
+    }
+
+    path.insert(table.to_string());
+    for key in keys.iter().filter(|key| key.parent == table && key.table != table) {
+        tree(&key.table, Some(key), depth + 1, keys, steps, path, rows);
+    }
+    path.remove(table);
+}
+
+/// `table` and every table referencing it, directly or not.
+fn dependents(table: &str, keys: &[ForeignKey]) -> BTreeSet<String> {
+    let mut found = BTreeSet::from([table.to_string()]);
+    let mut queue = vec![table.to_string()];
+    while let Some(parent) = queue.pop() {
+        for key in keys.iter().filter(|key| key.parent == parent) {
+            if found.insert(key.table.clone()) {
+                queue.push(key.table.clone());
+            }
+        }
+    }
+    found
+}
+
+/// Graphviz DOT with an edge from each table to the tables it references.
+fn to_dot(db: &str, tables: &BTreeSet<String>, keys: &[ForeignKey]) -> String {
+    let mut dot = format!("digraph \"{}\" {{\n    rankdir=LR;\n    node [shape=box];\n", db);
+    for table in tables {
+        dot.push_str(&format!("    \"{}\";\n", table));
+    }
+    for key in keys.iter().filter(|key| tables.contains(&key.table)) {
+        dot.push_str(&format!("    \"{}\" -> \"{}\" [label=\"{}\"];\n", key.table, key.parent, key.columns.join(", ")));
+    }
+    dot.push_str("}\n");
+    dot
+}
@@ -0,0 +1,173 @@
+use crate::binlogs::parse_filter;
+use crate::introspect::{primary_key, split_table};
+use crate::{interrupt, MySQLClient, QueryResult};
+use chrono::{Local, Utc};
+use mysql::prelude::*;
+use mysql::Value;
+use rusql::binlog::{self, BinlogReader, Change, Image};
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::compress;
+use rusql::error::{Result, RusqlError};
+use rusql::statements::keywords;
+use std::io::Write;
+use std::path::Path;
+
+const USAGE: &str = "Usage: \\flashback --table <[db.]table> --since <datetime> [--until <datetime>] [--out <file>]";
+
+/// `\flashback --table t --since 10:00 [--until 10:05] [--out file]`: write
+/// the statements undoing the table's row changes in a time window, newest
+/// first, to a file for review. Nothing is executed.
+pub fn flashback(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let (args, out) = split_out(args);
+    let mut filter = parse_filter(&args)?;
+    let (Some(table), Some(since)) = (filter.table.clone(), filter.since) else {
+        return Err(RusqlError::Command(USAGE.into()));
+    };
+    let (db, table) = match &filter.db {
+        Some(db) => (db.clone(), table),
+        None => split_table(client, &table)?,
+    };
+    let until = filter.until.unwrap_or_else(Utc::now);
+    filter.db = Some(db.clone());
+
+    let columns: Vec<String> = client.conn.exec(
+        "SELECT COLUMN_NAME FROM information_schema.COLUMNS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+        (&db, &table),
+    )?;
+    if columns.is_empty() {
+        return Err(RusqlError::Command(format!("Table '{}.{}' doesn't exist", db, table)));
+    }
+    let key = primary_key(client, &db, &table)?;
+
+    // Start at the last log begun before the window; earlier ones cannot hold it
+    let files: Vec<String> = client.conn.query_map("SHOW BINARY LOGS", |row: mysql::Row| {
+        row.get::<String, _>(0).unwrap_or_default()
+    })?;
+    let mut start = 0;
+    for (i, file) in files.iter().enumerate().rev() {
+        if binlog::started_at(client.conn_opts.clone(), file)?.is_some_and(|time| time <= since) {
+            start = i;
+            break;
+        }
+    }
+
+    let full_name = quote_table(&format!("{}.{}", db, table));
+    let no_backslash_escape = client.conn.no_backslash_escape();
+    let mut statements = Vec::new();
+    let mut unreversible = 0;
+    'files: for file in &files[start..] {
+        for entry in BinlogReader::open(client.conn_opts.clone(), file, 4, false)? {
+            if interrupt::interrupted() {
+                return Err(RusqlError::Cancelled);
+            }
+            let entry = entry?;
+            if entry.time > until {
+                break 'files;
+            }
+            if !filter.matches(&entry) {
+                // Statements changing the table cannot be undone from the log
+                if let Change::Query { db: query_db, query } = &entry.change
+                    && entry.time >= since
+                    && *query_db == db
+                    && keywords(query).contains(&table.to_uppercase())
+                {
+                    unreversible += 1;
+                }
+                continue;
+            }
+            let literal = |value: &Value| value.as_sql(no_backslash_escape);
+            let statement = match &entry.change {
+                Change::Insert { after, .. } => {
+                    let after = named(after, &columns);
+                    format!("DELETE FROM {} WHERE {} LIMIT 1;", full_name, condition(&after, &key, literal))
+                }
+                Change::Delete { before, .. } => {
+                    let before = named(before, &columns);
+                    let names: Vec<String> = before.iter().map(|(name, _)| quote_identifier(name)).collect();
+                    let values: Vec<String> = before.iter().map(|(_, value)| literal(value)).collect();
+                    format!("INSERT INTO {} ({}) VALUES ({});", full_name, names.join(", "), values.join(", "))
+                }
+                Change::Update { before, after, .. } => {
+                    let (before, after) = (named(before, &columns), named(after, &columns));
+                    let changed: Vec<String> = before.iter()
+                        .filter(|(name, old)| after.iter().find(|(column, _)| column == name).is_none_or(|(_, new)| new != old))
+                        .map(|(name, old)| format!("{} = {}", quote_identifier(name), literal(old)))
+                        .collect();
+                    if changed.is_empty() {
+                        continue;
+                    }
+                    format!("UPDATE {} SET {} WHERE {} LIMIT 1;", full_name, changed.join(", "), condition(&after, &key, literal))
+                }
+                _ => continue,
+            };
+            statements.push(format!("-- {} at {}:{}\n{}", local(entry.time), entry.file, entry.position, statement));
+        }
+    }
+
+    if statements.is_empty() {
+        client.notice(&format!("No row changes to {}.{} in the window", db, table));
+    } else {
+        let path = out.unwrap_or_else(|| format!("flashback-{}-{}.sql", table, Local::now().format("%Y%m%d-%H%M%S")));
+        let mut file = compress::create(Path::new(&path), false)?;
+        writeln!(file, "-- Undoes the changes to {} between {} and {}, newest first.", full_name, local(since), local(until))?;
+        writeln!(file, "-- Review before running; rows changed again since then may be overwritten.")?;
+        writeln!(file, "START TRANSACTION;\n")?;
+        for statement in statements.iter().rev() {
+            writeln!(file, "{}\n", statement)?;
+        }
+        writeln!(file, "COMMIT;")?;
+        client.notice(&format!("{} statements written to '{}'; nothing was executed", statements.len(), path));
+    }
+    if unreversible > 0 {
+        let warning = format!(
+            "Warning: {} statements logged as text (DDL or statement-based DML) touched the table and cannot be undone",
+            unreversible
+        );
+        println!("{}", client.paint(&warning, client.theme.warning));
+    }
+    Ok(None)
+}
+
+/// Split `--out <file>` off the other options.
+fn split_out(args: &str) -> (String, Option<String>) {
+    let Some(pos) = args.find("--out ") else {
+        return (args.to_string(), None);
+    };
+    let rest = args[pos + "--out ".len()..].trim_start();
+    let (path, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let path = path.trim_matches(|c| c == '\'' || c == '"').to_string();
+    (format!("{} {}", &args[..pos], after), Some(path).filter(|path| !path.is_empty()))
+}
+
+/// Image columns by name, mapping the `@n` positions used when the server
+/// does not log column names.
+fn named(image: &Image, columns: &[String]) -> Image {
+    image.iter()
+        .map(|(name, value)| {
+            let name = name.strip_prefix('@')
+                .and_then(|n| n.parse::<usize>().ok())
+                .and_then(|n| columns.get(n).cloned())
+                .unwrap_or_else(|| name.clone());
+            (name, value.clone())
+        })
+        .collect()
+}
+
+/// WHERE condition finding a row by its primary key, or by every logged
+/// column when the key is missing from the image.
+fn condition(image: &Image, key: &[String], literal: impl Fn(&Value) -> String) -> String {
+    let by_key: Vec<&(String, Value)> = image.iter().filter(|(name, _)| key.contains(name)).collect();
+    let columns = if !key.is_empty() && by_key.len() == key.len() { by_key } else { image.iter().collect() };
+    columns.into_iter()
+        .map(|(name, value)| match value {
+            Value::NULL => format!("{} IS NULL", quote_identifier(name)),
+            value => format!("{} = {}", quote_identifier(name), literal(value)),
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+fn local(time: chrono::DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()
+}
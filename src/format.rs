@@ -0,0 +1,290 @@
+use chrono::NaiveDateTime;
+use mysql::consts::ColumnType;
+use mysql::Value;
+use serde_json::Value as Json;
+use std::str::FromStr;
+
+/// Render a single MySQL value the way the table output displays it.
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::NULL => "NULL".to_string(),
+        Value::Bytes(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+        Value::Int(n) => n.to_string(),
+        Value::UInt(n) => n.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Double(d) => d.to_string(),
+        Value::Date(y, m, d, h, i, s, _) =>
+            format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", y, m, d, h, i, s),
+        Value::Time(neg, d, h, i, s, _) => {
+            let sign = if *neg { "-" } else { "" };
+            format!("{}{}.{:02}:{:02}:{:02}", sign, d, h, i, s)
+        }
+    }
+}
+
+/// Convert a value to JSON, using the column type to recover numbers from the
+/// text protocol. Decimals stay strings so no precision is lost.
+pub fn value_to_json(value: &Value, column_type: ColumnType) -> Json {
+    match value {
+        Value::NULL => Json::Null,
+        Value::Int(n) => Json::from(*n),
+        Value::UInt(n) => Json::from(*n),
+        Value::Float(f) => Json::from(*f),
+        Value::Double(d) => Json::from(*d),
+        Value::Bytes(bytes) => {
+            let text = String::from_utf8_lossy(bytes);
+            let number = match column_type {
+                ColumnType::MYSQL_TYPE_TINY
+                | ColumnType::MYSQL_TYPE_SHORT
+                | ColumnType::MYSQL_TYPE_INT24
+                | ColumnType::MYSQL_TYPE_LONG
+                | ColumnType::MYSQL_TYPE_LONGLONG
+                | ColumnType::MYSQL_TYPE_YEAR => text.parse::<i64>().map(Json::from).ok()
+                    .or_else(|| text.parse::<u64>().map(Json::from).ok()),
+                ColumnType::MYSQL_TYPE_FLOAT | ColumnType::MYSQL_TYPE_DOUBLE =>
+                    text.parse::<f64>().ok().map(Json::from),
+                _ => None,
+            };
+            number.unwrap_or_else(|| Json::String(text.into_owned()))
+        }
+        other => Json::String(format_value(other)),
+    }
+}
+
+/// How floating point values are written with respect to exponent notation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scientific {
+    /// As the server sends them: plain for ordinary values, `1e20` for huge ones.
+    #[default]
+    Auto,
+    /// Always written out in full.
+    Never,
+    /// Always `1.5e3`.
+    Always,
+}
+
+impl Scientific {
+    pub const VARIANTS: &'static [&'static str] = &["auto", "never", "always"];
+}
+
+impl FromStr for Scientific {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Scientific::Auto),
+            "never" => Ok(Scientific::Never),
+            "always" => Ok(Scientific::Always),
+            other => Err(format!("unknown scientific notation setting '{}'", other)),
+        }
+    }
+}
+
+/// Display of numeric columns in tables: digit grouping, the decimal mark,
+/// fixed decimal places and exponent notation.
+///
+/// Parsed from an example number, where the last separator is the decimal
+/// mark and the digits after it the number of places. When every separator
+/// is the same and followed by exactly three digits, as in `1,234`, it
+/// separates thousands instead.
+///
+/// ```
+/// use mysql::consts::ColumnType;
+/// use rusql::format::NumberFormat;
+///
+/// let format: NumberFormat = "1.234,56".parse().unwrap();
+/// assert_eq!(format.apply("-1234567.891", ColumnType::MYSQL_TYPE_NEWDECIMAL), "-1.234.567,89");
+/// assert_eq!(format.apply("1234567", ColumnType::MYSQL_TYPE_LONGLONG), "1.234.567");
+/// assert_eq!(format.apply("2024", ColumnType::MYSQL_TYPE_YEAR), "2024");
+///
+/// let format: NumberFormat = "1,234".parse().unwrap();
+/// assert_eq!(format.apply("999999.995", ColumnType::MYSQL_TYPE_DOUBLE), "999,999.995");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub grouping: Option<char>,
+    pub decimal_mark: char,
+    /// Places for FLOAT, DOUBLE and DECIMAL values, rounded half away from zero.
+    pub decimals: Option<usize>,
+    pub scientific: Scientific,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat { grouping: None, decimal_mark: '.', decimals: None, scientific: Scientific::Auto }
+    }
+}
+
+impl NumberFormat {
+    pub fn scientific(mut self, scientific: Scientific) -> Self {
+        self.scientific = scientific;
+        self
+    }
+
+    /// Whether values are shown exactly as the server sent them.
+    pub fn is_plain(&self) -> bool {
+        *self == NumberFormat::default()
+    }
+
+    /// `text`, a value of a `column_type` column, in this format. Values of
+    /// other types, and anything that does not parse as a number, are
+    /// returned unchanged.
+    pub fn apply(&self, text: &str, column_type: ColumnType) -> String {
+        let floating = matches!(column_type, ColumnType::MYSQL_TYPE_FLOAT | ColumnType::MYSQL_TYPE_DOUBLE);
+        let fractional = floating
+            || matches!(column_type, ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL);
+        let integer = matches!(
+            column_type,
+            ColumnType::MYSQL_TYPE_TINY
+                | ColumnType::MYSQL_TYPE_SHORT
+                | ColumnType::MYSQL_TYPE_INT24
+                | ColumnType::MYSQL_TYPE_LONG
+                | ColumnType::MYSQL_TYPE_LONGLONG
+        );
+        if !fractional && !integer {
+            return text.to_string();
+        }
+
+        let mut expanded = None;
+        if floating && let Ok(value) = text.parse::<f64>() && value.is_finite() {
+            match self.scientific {
+                Scientific::Always => {
+                    let formatted = match self.decimals {
+                        Some(places) => format!("{:.*e}", places, value),
+                        None => format!("{:e}", value),
+                    };
+                    return formatted.replacen('.', &self.decimal_mark.to_string(), 1);
+                }
+                // Display for f64 never uses an exponent
+                Scientific::Never if text.contains(['e', 'E']) => expanded = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        let text = expanded.as_deref().unwrap_or(text);
+
+        let (sign, digits) = text.strip_prefix('-').map_or(("", text), |rest| ("-", rest));
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+            return text.to_string();
+        }
+
+        let (whole, fraction) = match self.decimals {
+            Some(places) if fractional => round(whole, fraction, places),
+            _ => (whole.to_string(), fraction.to_string()),
+        };
+        // Rounding can leave nothing but zeros, which should not read as negative
+        let zero = whole.bytes().chain(fraction.bytes()).all(|b| b == b'0');
+        let mut formatted = String::from(if zero { "" } else { sign });
+        formatted.push_str(&group(&whole, self.grouping));
+        if !fraction.is_empty() {
+            formatted.push(self.decimal_mark);
+            formatted.push_str(&fraction);
+        }
+        formatted
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("plain") {
+            return Ok(NumberFormat::default());
+        }
+        let invalid = || format!("invalid number format '{}', expected an example such as \"1,234.56\"", s);
+        if !s.starts_with(|c: char| c.is_ascii_digit()) || !s.ends_with(|c: char| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        // Digit runs and the separators between them
+        let separators: Vec<char> = s.chars().filter(|c| !c.is_ascii_digit()).collect();
+        let runs: Vec<usize> = s.split(|c: char| !c.is_ascii_digit()).map(str::len).collect();
+        if runs.contains(&0) {
+            return Err(invalid());
+        }
+
+        let mut format = NumberFormat::default();
+        match separators.as_slice() {
+            [] => {}
+            [first, ..] if separators.iter().all(|c| c == first) && runs[0] <= 3 && runs[1..].iter().all(|&len| len == 3) => {
+                format.grouping = Some(*first);
+            }
+            [mark] => {
+                format.decimal_mark = *mark;
+                format.decimals = Some(runs[1]);
+            }
+            [group @ .., mark] => {
+                let grouping = group[0];
+                let grouped = &runs[1..runs.len() - 1];
+                if group.iter().any(|c| *c != grouping) || *mark == grouping
+                    || runs[0] > 3 || grouped.iter().any(|&len| len != 3) {
+                    return Err(invalid());
+                }
+                format.grouping = Some(grouping);
+                format.decimal_mark = *mark;
+                format.decimals = Some(runs[runs.len() - 1]);
+            }
+        }
+        Ok(format)
+    }
+}
+
+/// `whole.fraction` rounded half away from zero to `places` decimals.
+fn round(whole: &str, fraction: &str, places: usize) -> (String, String) {
+    let padded = fraction.bytes().chain(std::iter::repeat(b'0')).take(places);
+    let mut digits: Vec<u8> = whole.bytes().chain(padded).collect();
+    if fraction.as_bytes().get(places).is_some_and(|&digit| digit >= b'5') {
+        let mut carry = true;
+        for digit in digits.iter_mut().rev() {
+            if *digit == b'9' {
+                *digit = b'0';
+            } else {
+                *digit += 1;
+                carry = false;
+                break;
+            }
+        }
+        if carry {
+            digits.insert(0, b'1');
+        }
+    }
+    let (whole, fraction) = digits.split_at(digits.len() - places);
+    (String::from_utf8_lossy(whole).into_owned(), String::from_utf8_lossy(fraction).into_owned())
+}
+
+/// Integer digits with `separator` between groups of three.
+fn group(digits: &str, separator: Option<char>) -> String {
+    let Some(separator) = separator else {
+        return digits.to_string();
+    };
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// How far `then` is from `now`, in the largest whole unit: `3h ago`, `in 2d`.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use rusql::format::relative_duration;
+///
+/// let now = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(12, 0, 0).unwrap();
+/// assert_eq!(relative_duration(now - chrono::Duration::minutes(190), now), "3h ago");
+/// assert_eq!(relative_duration(now + chrono::Duration::days(2), now), "in 2d");
+/// assert_eq!(relative_duration(now, now), "now");
+/// ```
+pub fn relative_duration(then: NaiveDateTime, now: NaiveDateTime) -> String {
+    const UNITS: &[(i64, &str)] = &[(365 * 86400, "y"), (30 * 86400, "mo"), (86400, "d"), (3600, "h"), (60, "m"), (1, "s")];
+    let seconds = (then - now).num_seconds();
+    let Some((size, unit)) = UNITS.iter().find(|(size, _)| seconds.abs() >= *size) else {
+        return "now".to_string();
+    };
+    let amount = seconds.abs() / size;
+    if seconds < 0 { format!("{}{} ago", amount, unit) } else { format!("in {}{}", amount, unit) }
+}
@@ -0,0 +1,86 @@
+use chrono::{Local, NaiveDate};
+use mysql::prelude::*;
+use mysql::{Conn, Row, Value};
+
+/// Replication lag above this many seconds is reported.
+pub const LAG_WARNING_SECS: u64 = 30;
+
+/// End of support per release series: (server, series, last day of support).
+const END_OF_LIFE: &[(&str, &str, &str)] = &[
+    ("MySQL", "5.5", "2018-12-31"),
+    ("MySQL", "5.6", "2021-02-28"),
+    ("MySQL", "5.7", "2023-10-31"),
+    ("MySQL", "8.0", "2026-04-30"),
+    ("MySQL", "8.4", "2032-04-30"),
+    ("MariaDB", "10.3", "2023-05-25"),
+    ("MariaDB", "10.4", "2024-06-18"),
+    ("MariaDB", "10.5", "2025-06-24"),
+    ("MariaDB", "10.6", "2026-07-06"),
+    ("MariaDB", "10.11", "2028-02-16"),
+    ("MariaDB", "11.4", "2029-05-29"),
+];
+
+/// Quick checks run on connect; each returns a one-line warning for the banner.
+/// Probes the account lacks privileges for are skipped silently.
+pub fn probes(conn: &mut Conn, version: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    warnings.extend(read_only(conn));
+    warnings.extend(replica_lag(conn));
+    warnings.extend(disk_full(conn));
+    warnings.extend(end_of_life(version, Local::now().date_naive()));
+    warnings
+}
+
+fn read_only(conn: &mut Conn) -> Option<String> {
+    let (read_only, super_read_only): (u8, u8) = conn
+        .query_first("SELECT @@global.read_only, @@global.super_read_only")
+        .ok()
+        .flatten()
+        .or_else(|| {
+            // MariaDB has no super_read_only
+            let read_only: Option<u8> = conn.query_first("SELECT @@global.read_only").ok().flatten();
+            read_only.map(|flag| (flag, 0))
+        })?;
+    match (read_only, super_read_only) {
+        (_, 1) => Some("Server is read-only (super_read_only is ON)".to_string()),
+        (1, _) => Some("Server is read-only (read_only is ON)".to_string()),
+        _ => None,
+    }
+}
+
+fn replica_lag(conn: &mut Conn) -> Option<String> {
+    let status: Row = conn.query_first("SHOW REPLICA STATUS").ok()
+        .or_else(|| conn.query_first("SHOW SLAVE STATUS").ok())
+        .flatten()?;
+
+    let lag = ["Seconds_Behind_Source", "Seconds_Behind_Master"].iter()
+        .find_map(|column| status.get_opt::<Value, _>(*column).and_then(|value| value.ok()))?;
+    match lag {
+        Value::NULL => Some("This is a replica and replication is not running".to_string()),
+        lag => {
+            let secs: u64 = mysql::from_value_opt(lag).ok()?;
+            (secs > LAG_WARNING_SECS).then(|| format!("Replica is {} seconds behind its source", secs))
+        }
+    }
+}
+
+fn disk_full(conn: &mut Conn) -> Option<String> {
+    // The server parks writers in this state until space is freed
+    let waiting: u64 = conn.query_first(
+        "SELECT COUNT(*) FROM information_schema.PROCESSLIST WHERE STATE LIKE 'Waiting for someone to free space%'",
+    ).ok().flatten()?;
+    (waiting > 0).then(|| format!("Disk full: {} connections are waiting for free space", waiting))
+}
+
+fn end_of_life(version: &str, today: NaiveDate) -> Option<String> {
+    let server = if version.contains("MariaDB") { "MariaDB" } else { "MySQL" };
+    let (_, series, date) = END_OF_LIFE.iter()
+        .find(|(name, series, _)| *name == server && is_series(version, series))?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    (date < today).then(|| format!("{} {} reached end of life on {}", server, series, date))
+}
+
+/// Whether `version` (e.g. `8.0.36-log`) belongs to the release series `8.0`.
+fn is_series(version: &str, series: &str) -> bool {
+    version.strip_prefix(series).is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '-']))
+}
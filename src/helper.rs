@@ -0,0 +1,59 @@
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::{Hinter, HistoryHinter};
+use rustyline::validate::Validator;
+use rustyline::{Cmd, ConditionalEventHandler, Context, Event, EventContext, Helper, RepeatCount};
+use std::borrow::Cow;
+
+/// Line editor helper providing fish-style inline suggestions from history.
+///
+/// The suggestion is the remainder of the most recent history entry that
+/// starts with the current input. History holds whole statements, so a
+/// suggestion can complete a multi-line query. It is rendered dimmed after
+/// the cursor and accepted with the right arrow or End key.
+pub struct RusqlHelper {
+    hinter: HistoryHinter,
+    use_colors: bool,
+}
+
+impl RusqlHelper {
+    pub fn new(use_colors: bool) -> Self {
+        RusqlHelper { hinter: HistoryHinter::new(), use_colors }
+    }
+}
+
+impl Hinter for RusqlHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<String> {
+        self.hinter.hint(line, pos, ctx)
+    }
+}
+
+impl Highlighter for RusqlHelper {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        if self.use_colors {
+            Cow::Owned(format!("\x1B[2m{}\x1B[0m", hint))
+        } else {
+            Cow::Borrowed(hint)
+        }
+    }
+}
+
+impl Completer for RusqlHelper {
+    type Candidate = String;
+}
+
+impl Validator for RusqlHelper {}
+
+impl Helper for RusqlHelper {}
+
+/// Binding for End that accepts the suggestion when the cursor is already at
+/// the end of the input, and otherwise moves there as usual.
+pub struct AcceptHint;
+
+impl ConditionalEventHandler for AcceptHint {
+    fn handle(&self, _: &Event, _: RepeatCount, _: bool, ctx: &EventContext) -> Option<Cmd> {
+        (ctx.has_hint() && ctx.pos() == ctx.line().len()).then_some(Cmd::CompleteHint)
+    }
+}
@@ -0,0 +1,157 @@
+use crate::introspect::split_table;
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::Value;
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::error::{Result, RusqlError};
+use rusql::format::format_value;
+use rusql::mask::ColumnMasks;
+
+const USAGE: &str = "Usage: \\histogram [db.]table.column [buckets]";
+
+const DEFAULT_BUCKETS: usize = 10;
+
+/// Width of the longest bar.
+const BAR_WIDTH: usize = 40;
+
+/// How a column's values are grouped into buckets.
+enum Shape {
+    /// Equal-width ranges over this SQL expression of the column.
+    Ranges(String),
+    /// The most frequent values, the rest counted together.
+    Values,
+}
+
+/// `\histogram table.column [buckets]`: distribution of a column's values,
+/// as equal-width ranges for numbers and dates or the most frequent values
+/// for anything else, with counts and percentages of all rows. Values of a
+/// sensitive column are shown masked.
+pub fn histogram(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut words = args.split_whitespace();
+    let (Some(target), buckets, None) = (words.next(), words.next(), words.next()) else {
+        return Err(RusqlError::Command(USAGE.into()));
+    };
+    let buckets = match buckets {
+        Some(n) => n.parse().ok().filter(|&n: &usize| n > 0).ok_or_else(|| RusqlError::Command(USAGE.into()))?,
+        None => DEFAULT_BUCKETS,
+    };
+    let (table, column) = target.rsplit_once('.').ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+    let (db, table) = split_table(client, table)?;
+    let column = column.replace('`', "");
+    let start_time = std::time::Instant::now();
+
+    let data_type: String = client.conn.exec_first(
+        "SELECT DATA_TYPE FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND COLUMN_NAME = ?",
+        (&db, &table, &column),
+    )?
+    .ok_or_else(|| RusqlError::Command(format!("No column '{}' in table '{}.{}'", column, db, table)))?;
+
+    let masks = client.sensitive_rules.columns(Some(&db), &table, &[&column]);
+    let quoted = quote_identifier(&column);
+    let from = quote_table(&format!("{}.{}", db, table));
+    let shape = match data_type.to_lowercase().as_str() {
+        "tinyint" | "smallint" | "mediumint" | "int" | "bigint" | "decimal" | "float" | "double" | "year" => {
+            Shape::Ranges(quoted.clone())
+        }
+        // Seconds since year 0, so dates before 1970 bucket correctly too
+        "date" | "datetime" | "timestamp" => Shape::Ranges(format!("TO_SECONDS({})", quoted)),
+        _ => Shape::Values,
+    };
+
+    let (total, nulls): (u64, u64) = client.conn
+        .query_first(format!("SELECT COUNT(*), COUNT(*) - COUNT({}) FROM {}", quoted, from))?
+        .unwrap_or((0, 0));
+    let mut rows = match shape {
+        Shape::Ranges(expression) => ranges(client, &quoted, &expression, &from, buckets, &masks)?,
+        Shape::Values => top_values(client, &quoted, &from, buckets, total - nulls, &masks)?,
+    };
+    if nulls > 0 {
+        rows.push(("NULL".to_string(), nulls));
+    }
+
+    let largest = rows.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    let table_rows: Vec<Vec<String>> = rows.iter()
+        .map(|(label, count)| vec![
+            label.clone(),
+            count.to_string(),
+            format!("{:.1}%", percent(*count, total)),
+            "#".repeat((*count as usize * BAR_WIDTH).div_ceil(largest.max(1) as usize)),
+        ])
+        .collect();
+    let summary = format!("{} rows of {}.{} ({:.2} sec)", total, db, table, start_time.elapsed().as_secs_f64());
+    Ok(Some(QueryResult { table: client.build_table(&[&column, "Count", "%", ""], table_rows), summary }))
+}
+
+/// Counts per equal-width range of `expression`, labelled with the smallest
+/// and largest value that fell into each range. Empty ranges are kept so gaps
+/// in the data show.
+fn ranges(
+    client: &mut MySQLClient,
+    column: &str,
+    expression: &str,
+    from: &str,
+    buckets: usize,
+    masks: &ColumnMasks,
+) -> Result<Vec<(String, u64)>> {
+    let bounds: Option<(Option<f64>, Option<f64>)> = client.conn
+        .query_first(format!("SELECT MIN({0}), MAX({0}) FROM {1}", expression, from))?;
+    let (Some(min), Some(max)) = bounds.unwrap_or((None, None)) else {
+        return Ok(Vec::new());
+    };
+    let width = ((max - min) / buckets as f64).max(f64::MIN_POSITIVE);
+
+    let found: Vec<(i64, Value, Value, u64)> = client.conn.exec(
+        format!(
+            "SELECT LEAST(FLOOR(({1} - ?) / ?), ?) AS bucket, MIN({0}), MAX({0}), COUNT(*) \
+             FROM {2} WHERE {0} IS NOT NULL GROUP BY bucket ORDER BY bucket",
+            column, expression, from,
+        ),
+        (min, width, buckets as i64 - 1),
+    )?;
+    let mut rows: Vec<(String, u64)> = (0..buckets).map(|_| ("(empty)".to_string(), 0)).collect();
+    for (bucket, low, high, count) in found {
+        let mut bounds = vec![vec![low], vec![high]];
+        masks.apply(&mut bounds);
+        let (low, high) = (format_value(&bounds[0][0]), format_value(&bounds[1][0]));
+        let label = if low == high { low } else { format!("{} – {}", low, high) };
+        if let Some(row) = usize::try_from(bucket).ok().and_then(|b| rows.get_mut(b)) {
+            *row = (label, count);
+        }
+    }
+    // A single value needs only one bucket
+    if min == max {
+        rows.truncate(1);
+    }
+    Ok(rows)
+}
+
+/// The `limit` most frequent values, with everything else counted as one row.
+fn top_values(
+    client: &mut MySQLClient,
+    column: &str,
+    from: &str,
+    limit: usize,
+    non_null: u64,
+    masks: &ColumnMasks,
+) -> Result<Vec<(String, u64)>> {
+    let found: Vec<(Value, u64)> = client.conn.exec(
+        format!(
+            "SELECT {0}, COUNT(*) AS n FROM {1} WHERE {0} IS NOT NULL GROUP BY {0} ORDER BY n DESC, {0} LIMIT ?",
+            column, from,
+        ),
+        (limit as u64,),
+    )?;
+    let (values, counts): (Vec<Value>, Vec<u64>) = found.into_iter().unzip();
+    let mut values: Vec<Vec<Value>> = values.into_iter().map(|value| vec![value]).collect();
+    masks.apply(&mut values);
+    let mut rows: Vec<(String, u64)> = values.iter().map(|value| format_value(&value[0])).zip(counts).collect();
+    let shown: u64 = rows.iter().map(|(_, count)| count).sum();
+    if non_null > shown {
+        rows.push(("(other)".to_string(), non_null - shown));
+    }
+    Ok(rows)
+}
+
+fn percent(count: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { count as f64 * 100.0 / total as f64 }
+}
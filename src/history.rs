@@ -0,0 +1,70 @@
+use dirs::{data_local_dir, home_dir};
+use rusql::narrow::glob_match;
+use std::path::PathBuf;
+
+/// Maximum number of entries kept in a history file.
+pub const MAX_HISTORY_SIZE: usize = 1000;
+
+/// Statement fragments that must never be written to the history file.
+const SENSITIVE_PATTERNS: &[&str] = &[
+    "identified by",
+    "identified with",
+    "set password",
+    "password(",
+    "master_password",
+    "source_password",
+];
+
+/// Location of the history file for a host/database pair, e.g.
+/// `~/.local/share/rusql/history/prod_orders`.
+///
+/// Keeping one file per connection stops production queries from showing up
+/// as suggestions in scratch environments and vice versa.
+pub fn history_path(host: &str, database: Option<&str>) -> PathBuf {
+    let name = connection_key(host, database);
+
+    match data_local_dir() {
+        Some(mut path) => {
+            path.push("rusql");
+            path.push("history");
+            let _ = std::fs::create_dir_all(&path);
+            path.push(name);
+            path
+        }
+        None => home_dir()
+            .map(|mut path| {
+                path.push(".mysql_history");
+                path
+            })
+            .unwrap_or_else(|| PathBuf::from(".mysql_history")),
+    }
+}
+
+/// File-name safe key identifying a host/database pair, e.g. `prod_orders`.
+pub fn connection_key(host: &str, database: Option<&str>) -> String {
+    match database {
+        Some(db) => format!("{}_{}", sanitize(host), sanitize(db)),
+        None => sanitize(host),
+    }
+}
+
+/// Whether a statement looks like it carries credentials, or names one of
+/// the `sensitive_columns` (`[db.]table.column` patterns) and so may hold
+/// one of their values.
+pub fn is_sensitive(statement: &str, sensitive_columns: &[String]) -> bool {
+    let lower = statement.to_lowercase();
+    if SENSITIVE_PATTERNS.iter().any(|pattern| lower.contains(pattern)) {
+        return true;
+    }
+    let columns: Vec<&str> = sensitive_columns.iter()
+        .filter_map(|pattern| pattern.rsplit('.').next())
+        .collect();
+    lower.split(|c: char| !c.is_alphanumeric() && c != '_' && c != '$')
+        .any(|word| columns.iter().any(|column| glob_match(column, word)))
+}
+
+fn sanitize(part: &str) -> String {
+    part.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,150 @@
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::error::{Result, RusqlError};
+use std::collections::{BTreeMap, HashSet};
+
+/// Below this share of distinct values an index rarely narrows a search.
+const LOW_SELECTIVITY: f64 = 0.01;
+
+/// Tables smaller than this are not flagged for low selectivity.
+const MIN_ROWS_FOR_SELECTIVITY: u64 = 1000;
+
+/// One index as listed in information_schema.STATISTICS.
+struct Index {
+    name: String,
+    unique: bool,
+    kind: String,
+    /// Columns in index order, with prefix lengths such as `name(10)`.
+    columns: Vec<String>,
+    cardinality: Option<u64>,
+    table_rows: Option<u64>,
+}
+
+/// `\index-report [db]`: unused indexes (from the sys schema), duplicate and
+/// left-prefix redundant indexes, and indexes with low cardinality, with the
+/// statements that would drop the first two kinds.
+pub fn index_report(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let db = match args {
+        "" => client.current_db.clone()
+            .ok_or_else(|| RusqlError::Command("No database selected; use \\index-report <db>".into()))?,
+        db => db.replace('`', ""),
+    };
+
+    let tables = indexes(client, &db)?;
+    if tables.is_empty() {
+        return Err(RusqlError::Command(format!("No indexes in database '{}'", db)));
+    }
+    // Usage counters live in performance_schema, which the sys schema reads;
+    // servers without either simply get no unused-index findings
+    let unused: Option<HashSet<(String, String)>> = client.conn.exec(
+        "SELECT object_name, index_name FROM sys.schema_unused_indexes WHERE object_schema = ?",
+        (&db,),
+    )
+    .ok()
+    .map(|rows: Vec<(String, String)>| rows.into_iter().collect());
+
+    let mut rows = Vec::new();
+    for (table, indexes) in &tables {
+        for index in indexes {
+            let mut findings = Vec::new();
+            let mut drop = false;
+            if let Some(other) = covering_index(index, indexes) {
+                findings.push(if index.columns == other.columns {
+                    format!("duplicate of {}", other.name)
+                } else {
+                    format!("redundant with {}", other.name)
+                });
+                drop = true;
+            }
+            if index.name != "PRIMARY" && unused.as_ref().is_some_and(|u| u.contains(&(table.clone(), index.name.clone()))) {
+                findings.push("unused since server start".to_string());
+                drop = true;
+            }
+            if let (Some(cardinality), Some(rows)) = (index.cardinality, index.table_rows)
+                && rows >= MIN_ROWS_FOR_SELECTIVITY
+                && (cardinality as f64) < rows as f64 * LOW_SELECTIVITY
+            {
+                findings.push(format!("low selectivity ({} distinct of ~{} rows)", cardinality, rows));
+            }
+            if findings.is_empty() {
+                continue;
+            }
+
+            let statement = if drop {
+                format!("ALTER TABLE {} DROP INDEX {};", quote_table(&format!("{}.{}", db, table)), quote_identifier(&index.name))
+            } else {
+                String::new()
+            };
+            rows.push(vec![
+                table.clone(),
+                index.name.clone(),
+                index.columns.join(", "),
+                index.cardinality.map_or_else(String::new, |n| n.to_string()),
+                findings.join("; "),
+                statement,
+            ]);
+        }
+    }
+
+    let mut summary = format!("{} findings in {}", rows.len(), db);
+    if unused.is_none() {
+        summary.push_str("; unused indexes not checked (sys schema not available)");
+    }
+    let headers = ["Table", "Index", "Columns", "Cardinality", "Finding", "Suggestion"];
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+/// Indexes of every table in `db`, by table name.
+fn indexes(client: &mut MySQLClient, db: &str) -> Result<BTreeMap<String, Vec<Index>>> {
+    type Part = (String, String, bool, String, String, Option<u64>, Option<u64>, Option<u64>);
+    let parts: Vec<Part> = client.conn.exec(
+        "SELECT s.TABLE_NAME, s.INDEX_NAME, s.NON_UNIQUE = 0, s.INDEX_TYPE, s.COLUMN_NAME, s.SUB_PART, \
+         s.CARDINALITY, t.TABLE_ROWS \
+         FROM information_schema.STATISTICS s \
+         JOIN information_schema.TABLES t ON t.TABLE_SCHEMA = s.TABLE_SCHEMA AND t.TABLE_NAME = s.TABLE_NAME \
+         WHERE s.TABLE_SCHEMA = ? \
+         ORDER BY s.TABLE_NAME, s.INDEX_NAME, s.SEQ_IN_INDEX",
+        (db,),
+    )?;
+
+    let mut tables: BTreeMap<String, Vec<Index>> = BTreeMap::new();
+    for (table, name, unique, kind, column, sub_part, cardinality, table_rows) in parts {
+        let column = match sub_part {
+            Some(length) => format!("{}({})", column, length),
+            None => column,
+        };
+        let indexes = tables.entry(table).or_default();
+        match indexes.last_mut().filter(|index| index.name == name) {
+            Some(index) => {
+                index.columns.push(column);
+                // The last column's cardinality counts distinct prefixes of the whole index
+                index.cardinality = cardinality;
+            }
+            None => indexes.push(Index { name, unique, kind, columns: vec![column], cardinality, table_rows }),
+        }
+    }
+    Ok(tables)
+}
+
+/// Another index of the same table that makes `index` unnecessary: one with
+/// the same columns, or one starting with all of its columns. Unique and
+/// primary keys are only covered by an identical unique key, since dropping
+/// them would lose the constraint. Of two identical indexes only one is
+/// reported.
+fn covering_index<'a>(index: &Index, indexes: &'a [Index]) -> Option<&'a Index> {
+    if index.name == "PRIMARY" {
+        return None;
+    }
+    indexes.iter().find(|other| {
+        if other.name == index.name || other.kind != index.kind {
+            return false;
+        }
+        if other.columns == index.columns {
+            let keep_other = other.name == "PRIMARY" || (other.unique && !index.unique);
+            let same_rank = other.unique == index.unique && other.name < index.name;
+            return keep_other || same_rank;
+        }
+        !index.unique && other.columns.starts_with(&index.columns)
+    })
+}
@@ -0,0 +1,309 @@
+//! Column types inferred from sample data, for creating a table to load a
+//! CSV or JSON file into.
+
+use crate::bulk::{quote_identifier, quote_table};
+use crate::compress;
+use crate::error::{Result, RusqlError};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde_json::Value as Json;
+use std::fmt;
+use std::path::Path;
+
+/// VARCHAR lengths a column is rounded up to, leaving room for values
+/// longer than the ones sampled.
+const VARCHAR_STEPS: &[usize] = &[16, 32, 64, 128, 255];
+
+/// A column type inferred from its values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlType {
+    Int,
+    BigInt,
+    /// Precision and scale.
+    Decimal(u32, u32),
+    Double,
+    Date,
+    /// Fractional second digits.
+    DateTime(u32),
+    Varchar(usize),
+    Text,
+    MediumText,
+    Json,
+}
+
+impl fmt::Display for SqlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlType::Int => write!(f, "INT"),
+            SqlType::BigInt => write!(f, "BIGINT"),
+            SqlType::Decimal(precision, scale) => write!(f, "DECIMAL({},{})", precision, scale),
+            SqlType::Double => write!(f, "DOUBLE"),
+            SqlType::Date => write!(f, "DATE"),
+            SqlType::DateTime(0) => write!(f, "DATETIME"),
+            SqlType::DateTime(digits) => write!(f, "DATETIME({})", digits),
+            SqlType::Varchar(length) => write!(f, "VARCHAR({})", length),
+            SqlType::Text => write!(f, "TEXT"),
+            SqlType::MediumText => write!(f, "MEDIUMTEXT"),
+            SqlType::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+/// A column of the inferred table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnGuess {
+    pub name: String,
+    pub sql_type: SqlType,
+    pub nullable: bool,
+}
+
+/// Rows read from a data file: column names, then values with `None` for
+/// empty CSV cells and JSON nulls.
+pub struct Records {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+    /// Columns holding JSON objects or arrays.
+    pub json_columns: Vec<bool>,
+}
+
+/// Read a `.csv` file (first line is the header) or a `.json` file holding
+/// an array of objects or one object per line, either of them optionally
+/// compressed (`.csv.gz`, `.json.zst`).
+pub fn read_file(path: &Path) -> Result<Records> {
+    let text = compress::read_to_string(path)
+        .map_err(|e| RusqlError::Command(format!("Cannot read '{}': {}", path.display(), e)))?;
+    match compress::inner_path(path).extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+        Some("json" | "jsonl" | "ndjson") => read_json(&text),
+        _ => read_csv(&text),
+    }
+}
+
+fn read_csv(text: &str) -> Result<Records> {
+    let mut lines = parse_csv(text).into_iter();
+    let headers = lines.next().ok_or_else(|| RusqlError::Command("The file is empty".into()))?;
+    let rows: Vec<Vec<Option<String>>> = lines
+        .map(|line| {
+            let mut row: Vec<Option<String>> = line.into_iter().map(|cell| Some(cell).filter(|c| !c.is_empty())).collect();
+            row.resize(headers.len(), None);
+            row
+        })
+        .collect();
+    let json_columns = vec![false; headers.len()];
+    Ok(Records { headers: column_names(headers), rows, json_columns })
+}
+
+fn read_json(text: &str) -> Result<Records> {
+    let objects: Vec<Json> = match serde_json::from_str::<Json>(text) {
+        Ok(Json::Array(items)) => items,
+        Ok(object @ Json::Object(_)) => vec![object],
+        // One object per line
+        _ => text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| RusqlError::Command(format!("Not a JSON array or JSON lines file: {}", e)))?,
+    };
+
+    let mut headers: Vec<String> = Vec::new();
+    for object in &objects {
+        let Json::Object(fields) = object else {
+            return Err(RusqlError::Command("Expected JSON objects, one per row".into()));
+        };
+        for key in fields.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let mut json_columns = vec![false; headers.len()];
+    let rows = objects.iter()
+        .map(|object| {
+            headers.iter().enumerate()
+                .map(|(i, key)| match object.get(key) {
+                    None | Some(Json::Null) => None,
+                    Some(Json::String(text)) => Some(text.clone()),
+                    Some(value @ (Json::Object(_) | Json::Array(_))) => {
+                        json_columns[i] = true;
+                        Some(value.to_string())
+                    }
+                    Some(value) => Some(value.to_string()),
+                })
+                .collect()
+        })
+        .collect();
+    Ok(Records { headers: column_names(headers), rows, json_columns })
+}
+
+/// Split CSV text into records: fields separated by commas, optionally
+/// quoted with `"` (doubled inside quotes), records ending at unquoted
+/// line breaks.
+///
+/// ```
+/// let records = rusql::infer::parse_csv("id,note\n1,\"a, \"\"b\"\"\"\r\n2,\n");
+/// assert_eq!(records, vec![vec!["id", "note"], vec!["1", "a, \"b\""], vec!["2", ""]]);
+/// ```
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// Header names made usable as columns: blanks named `column_N`, repeats
+/// numbered.
+fn column_names(headers: Vec<String>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (i, header) in headers.into_iter().enumerate() {
+        let base = match header.trim() {
+            "" => format!("column_{}", i + 1),
+            name => name.to_string(),
+        };
+        let mut name = base.clone();
+        let mut n = 2;
+        while names.iter().any(|taken| taken.eq_ignore_ascii_case(&name)) {
+            name = format!("{}_{}", base, n);
+            n += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// The narrowest type holding every sampled value of each column.
+///
+/// ```
+/// use rusql::infer::{infer, SqlType};
+/// let headers = vec!["id".to_string(), "price".to_string(), "sold".to_string(), "zip".to_string()];
+/// let rows = vec![
+///     vec![Some("1".into()), Some("9.5".into()), Some("2024-05-01 10:00:00".into()), Some("02134".into())],
+///     vec![Some("2".into()), Some("120.25".into()), None, Some("94107".into())],
+/// ];
+/// let columns = infer(&headers, &rows, &[false; 4]);
+/// assert_eq!(columns[0].sql_type, SqlType::Int);
+/// assert_eq!(columns[1].sql_type, SqlType::Decimal(5, 2));
+/// assert_eq!(columns[2].sql_type, SqlType::DateTime(0));
+/// assert!(columns[2].nullable);
+/// assert_eq!(columns[3].sql_type, SqlType::Varchar(16));
+/// ```
+pub fn infer(headers: &[String], rows: &[Vec<Option<String>>], json_columns: &[bool]) -> Vec<ColumnGuess> {
+    headers.iter().enumerate()
+        .map(|(i, name)| {
+            let values: Vec<&str> = rows.iter().filter_map(|row| row.get(i).and_then(|v| v.as_deref())).collect();
+            let nullable = values.len() < rows.len() || rows.is_empty();
+            let sql_type = match json_columns.get(i) {
+                Some(true) => SqlType::Json,
+                _ => column_type(&values),
+            };
+            ColumnGuess { name: name.clone(), sql_type, nullable }
+        })
+        .collect()
+}
+
+fn column_type(values: &[&str]) -> SqlType {
+    if values.is_empty() {
+        return SqlType::Varchar(255);
+    }
+    if values.iter().all(|v| is_integer(v)) {
+        let fits = values.iter().all(|v| v.parse::<i32>().is_ok());
+        return if fits { SqlType::Int } else { SqlType::BigInt };
+    }
+    if values.iter().all(|v| is_integer(v) || is_decimal(v)) {
+        let (mut whole, mut scale) = (1, 0);
+        for value in values {
+            let digits = value.trim_start_matches('-');
+            let (int_part, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+            whole = whole.max(int_part.len() as u32);
+            scale = scale.max(fraction.len() as u32);
+        }
+        return match whole + scale {
+            precision if precision <= 65 && scale <= 30 => SqlType::Decimal(precision, scale),
+            _ => SqlType::Double,
+        };
+    }
+    if values.iter().all(|v| v.parse::<f64>().is_ok_and(f64::is_finite) && !leading_zero(v)) {
+        return SqlType::Double;
+    }
+    if values.iter().all(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").is_ok()) {
+        return SqlType::Date;
+    }
+    if values.iter().all(|v| parse_datetime(v)) {
+        let digits = values.iter()
+            .map(|v| v.rsplit_once('.').map_or(0, |(_, fraction)| fraction.len() as u32))
+            .max()
+            .unwrap_or(0);
+        return SqlType::DateTime(digits.min(6));
+    }
+
+    let longest = values.iter().map(|v| v.chars().count()).max().unwrap_or(0);
+    match VARCHAR_STEPS.iter().find(|&&step| step >= longest) {
+        Some(&length) => SqlType::Varchar(length),
+        None if longest <= 65_535 / 4 => SqlType::Text,
+        None => SqlType::MediumText,
+    }
+}
+
+/// An optionally signed number without leading zeros, which would be lost
+/// (as in zip codes).
+fn is_integer(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) && !leading_zero(value)
+}
+
+fn leading_zero(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value).as_bytes();
+    digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()
+}
+
+fn is_decimal(value: &str) -> bool {
+    match value.split_once('.') {
+        Some((whole, fraction)) => {
+            (whole.is_empty() || whole == "-" || is_integer(whole))
+                && !fraction.is_empty()
+                && fraction.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+fn parse_datetime(value: &str) -> bool {
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .any(|format| NaiveDateTime::parse_from_str(value, format).is_ok())
+}
+
+/// `CREATE TABLE` for the inferred columns.
+pub fn create_table(table: &str, columns: &[ColumnGuess]) -> String {
+    let definitions: Vec<String> = columns.iter()
+        .map(|column| {
+            format!(
+                "  {} {}{}",
+                quote_identifier(&column.name),
+                column.sql_type,
+                if column.nullable { "" } else { " NOT NULL" }
+            )
+        })
+        .collect();
+    format!("CREATE TABLE {} (\n{}\n)", quote_table(table), definitions.join(",\n"))
+}
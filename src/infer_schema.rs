@@ -0,0 +1,71 @@
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::{Params, Value};
+use rusql::bulk::BulkInsert;
+use rusql::compress;
+use rusql::error::{Result, RusqlError};
+use rusql::infer::{create_table, infer, read_file};
+use std::path::Path;
+
+const USAGE: &str = "Usage: \\infer-schema <file.csv|file.json> [table] [--sample <rows>] [--execute] [--import]";
+
+/// `\infer-schema data.csv [table] [--sample n] [--execute] [--import]`:
+/// infer column types from the first rows of a CSV or JSON file and print
+/// a CREATE TABLE for it. `--execute` creates the table; `--import` also
+/// loads every row of the file into it.
+pub fn infer_schema(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut words = args.split_whitespace();
+    let file = words.next().filter(|word| !word.starts_with("--")).ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+    let mut table = None;
+    let mut sample = 1000;
+    let (mut execute, mut import) = (false, false);
+    while let Some(word) = words.next() {
+        match word {
+            "--sample" => {
+                sample = words.next().and_then(|n| n.parse().ok()).filter(|&n| n > 0)
+                    .ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+            }
+            "--execute" => execute = true,
+            "--import" => import = true,
+            name if !name.starts_with("--") && table.is_none() => table = Some(name.to_string()),
+            _ => return Err(RusqlError::Command(USAGE.into())),
+        }
+    }
+
+    let path = Path::new(file);
+    let table = match table {
+        Some(table) => table,
+        // The file name, without dots that would read as a database
+        None => compress::inner_path(path).file_stem().map(|stem| stem.to_string_lossy().replace('.', "_"))
+            .ok_or_else(|| RusqlError::Command(USAGE.into()))?,
+    };
+    let records = read_file(path)?;
+    let sampled = &records.rows[..records.rows.len().min(sample)];
+    let columns = infer(&records.headers, sampled, &records.json_columns);
+    let statement = create_table(&table, &columns);
+
+    if !execute && !import {
+        println!("{};", statement);
+        client.notice(&format!(
+            "Types inferred from {} of {} rows; add --execute to create the table or --import to also load it",
+            sampled.len(),
+            records.rows.len()
+        ));
+        return Ok(None);
+    }
+
+    client.conn.query_drop(&statement)?;
+    client.notice(&format!("Table {} created with {} columns", table, columns.len()));
+    if !import {
+        return Ok(None);
+    }
+
+    let rows = records.rows.into_iter()
+        .map(|row| Params::Positional(row.into_iter().map(|value| value.map_or(Value::NULL, Value::from)).collect()));
+    let imported = BulkInsert::new(&table, &records.headers)
+        .on_progress(|progress| eprint!("\r{} rows imported", progress.rows))
+        .execute(&mut client.conn, rows)?;
+    eprintln!();
+    client.notice(&format!("{} rows imported into {}", imported, table));
+    Ok(None)
+}
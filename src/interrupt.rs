@@ -0,0 +1,74 @@
+use mysql::prelude::Queryable;
+use mysql::{Conn, Opts, OptsBuilder};
+use rusql::error::{Result, RusqlError};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Two Ctrl-C presses at an empty prompt within this window exit the client.
+pub const EXIT_INTERRUPT_WINDOW: Duration = Duration::from_secs(2);
+
+/// Set by Ctrl-C outside the line editor, for loops that wait between
+/// queries, whether or not a query was running at the time.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether Ctrl-C was pressed since the current statement started, e.g. to
+/// stop a `\replication watch` between refreshes.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Ctrl-C handling outside the line editor.
+///
+/// While reading input, rustyline puts the terminal in raw mode and reports
+/// Ctrl-C itself. While a statement runs, SIGINT reaches this handler, which
+/// kills the running query from a second connection so the session survives.
+/// Clones share the handler's state.
+#[derive(Clone)]
+pub struct Interrupt {
+    executing: Arc<AtomicBool>,
+    connection_id: Arc<AtomicU32>,
+}
+
+impl Interrupt {
+    pub fn install(opts: OptsBuilder, connection_id: u32) -> Result<Interrupt> {
+        let executing = Arc::new(AtomicBool::new(false));
+        let flag = executing.clone();
+        let connection_id = Arc::new(AtomicU32::new(connection_id));
+        let target = connection_id.clone();
+
+        ctrlc::set_handler(move || {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+            if !flag.load(Ordering::SeqCst) {
+                return;
+            }
+            if let Err(e) = kill_query(opts.clone(), target.load(Ordering::SeqCst)) {
+                eprintln!("\nCould not cancel the query: {}", e);
+            }
+        })
+        .map_err(|e| RusqlError::Io(std::io::Error::other(e)))?;
+
+        Ok(Interrupt { executing, connection_id })
+    }
+
+    /// Point Ctrl-C at a new connection after reconnecting.
+    pub fn set_connection_id(&self, connection_id: u32) {
+        self.connection_id.store(connection_id, Ordering::SeqCst);
+    }
+
+    /// Run `f` with Ctrl-C armed to kill the current query.
+    pub fn while_executing<T>(&self, f: impl FnOnce() -> T) -> T {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        self.executing.store(true, Ordering::SeqCst);
+        let result = f();
+        self.executing.store(false, Ordering::SeqCst);
+        result
+    }
+}
+
+/// Abort the statement running on `connection_id` from a separate connection.
+pub fn kill_query<O: Into<Opts>>(opts: O, connection_id: u32) -> Result<()> {
+    let mut conn = Conn::new(opts)?;
+    conn.query_drop(format!("KILL QUERY {}", connection_id))?;
+    Ok(())
+}
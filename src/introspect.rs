@@ -0,0 +1,217 @@
+use crate::{print_result, MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::Params;
+use rusql::bulk::quote_table;
+use rusql::error::{Result, RusqlError};
+
+/// Translate a psql-style wildcard pattern (`*`, `?`) into a LIKE pattern.
+pub fn like_pattern(pattern: &str) -> String {
+    if pattern.is_empty() {
+        return "%".to_string();
+    }
+    pattern.chars()
+        .map(|c| match c {
+            '*' => "%".to_string(),
+            '?' => "_".to_string(),
+            '%' | '_' | '\\' => format!("\\{}", c),
+            other => other.to_string(),
+        })
+        .collect()
+}
+
+/// Split `db.table` into its parts, defaulting to the current database.
+pub fn split_table(client: &MySQLClient, name: &str) -> Result<(String, String)> {
+    let name = name.replace('`', "");
+    match name.split_once('.') {
+        Some((db, table)) => Ok((db.to_string(), table.to_string())),
+        None => client.current_db.clone()
+            .map(|db| (db, name.clone()))
+            .ok_or_else(|| RusqlError::Command("No database selected; use db.table".into())),
+    }
+}
+
+/// Columns of a table's primary key, in key order; empty without one.
+pub fn primary_key(client: &mut MySQLClient, db: &str, table: &str) -> Result<Vec<String>> {
+    Ok(client.conn.exec(
+        "SELECT COLUMN_NAME FROM information_schema.KEY_COLUMN_USAGE \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND CONSTRAINT_NAME = 'PRIMARY' ORDER BY ORDINAL_POSITION",
+        (db, table),
+    )?)
+}
+
+/// `\l`: databases with their default character set and collation.
+pub fn list_databases(client: &mut MySQLClient) -> Result<Option<QueryResult>> {
+    client.query_result(
+        "SELECT SCHEMA_NAME AS `Database`, DEFAULT_CHARACTER_SET_NAME AS `Charset`, \
+         DEFAULT_COLLATION_NAME AS `Collation` \
+         FROM information_schema.SCHEMATA ORDER BY SCHEMA_NAME",
+        Params::Empty,
+    ).map(Some)
+}
+
+/// `\dt [pattern]`: tables of the current database matching a wildcard pattern.
+pub fn list_tables(client: &mut MySQLClient, pattern: &str) -> Result<Option<QueryResult>> {
+    let db = client.current_db.clone()
+        .ok_or_else(|| RusqlError::Command("No database selected".into()))?;
+
+    client.query_result(
+        "SELECT TABLE_NAME AS `Table`, TABLE_TYPE AS `Type`, ENGINE AS `Engine`, \
+         TABLE_ROWS AS `Rows (est.)`, TABLE_COMMENT AS `Comment` \
+         FROM information_schema.TABLES \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME LIKE ? ORDER BY TABLE_NAME",
+        Params::Positional(vec![db.into(), like_pattern(pattern).into()]),
+    ).map(Some)
+}
+
+/// `\d table`: columns (with generation expressions), indexes, CHECK
+/// constraints, and foreign keys in one view.
+pub fn describe_table(client: &mut MySQLClient, name: &str) -> Result<Option<QueryResult>> {
+    let (db, table) = split_table(client, name)?;
+    let params = || Params::Positional(vec![db.clone().into(), table.clone().into()]);
+
+    let mut columns = client.query_result(
+        "SELECT COLUMN_NAME AS `Column`, COLUMN_TYPE AS `Type`, IS_NULLABLE AS `Null`, \
+         COLUMN_KEY AS `Key`, COLUMN_DEFAULT AS `Default`, \
+         CONCAT_WS(' ', NULLIF(EXTRA, ''), \
+         IF(GENERATION_EXPRESSION <> '', CONCAT('AS (', GENERATION_EXPRESSION, ')'), NULL)) AS `Extra` \
+         FROM information_schema.COLUMNS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+        params(),
+    )?;
+    if columns.table.len() <= 1 {
+        return Err(RusqlError::Command(format!("Table '{}.{}' doesn't exist", db, table)));
+    }
+    columns.summary = format!("Table {}.{}", db, table);
+    print_result(client, columns);
+
+    let mut indexes = client.query_result(
+        "SELECT INDEX_NAME AS `Index`, IF(NON_UNIQUE = 0, 'YES', 'NO') AS `Unique`, \
+         GROUP_CONCAT(COLUMN_NAME ORDER BY SEQ_IN_INDEX SEPARATOR ', ') AS `Columns`, \
+         INDEX_TYPE AS `Type` \
+         FROM information_schema.STATISTICS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? \
+         GROUP BY INDEX_NAME, NON_UNIQUE, INDEX_TYPE ORDER BY INDEX_NAME = 'PRIMARY' DESC, INDEX_NAME",
+        params(),
+    )?;
+    indexes.summary = String::new();
+    print_result(client, indexes);
+
+    // CHECK constraints exist from MySQL 8.0.16 and MariaDB 10.2; older servers lack the table
+    if let Ok(mut checks) = client.query_result(
+        "SELECT tc.CONSTRAINT_NAME AS `Check`, cc.CHECK_CLAUSE AS `Condition` \
+         FROM information_schema.TABLE_CONSTRAINTS tc \
+         JOIN information_schema.CHECK_CONSTRAINTS cc \
+         ON cc.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA AND cc.CONSTRAINT_NAME = tc.CONSTRAINT_NAME \
+         WHERE tc.TABLE_SCHEMA = ? AND tc.TABLE_NAME = ? AND tc.CONSTRAINT_TYPE = 'CHECK' \
+         ORDER BY tc.CONSTRAINT_NAME",
+        params(),
+    ) && checks.table.len() > 1
+    {
+        checks.summary = String::new();
+        print_result(client, checks);
+    }
+
+    let mut foreign_keys = client.query_result(
+        "SELECT k.CONSTRAINT_NAME AS `Foreign key`, \
+         GROUP_CONCAT(k.COLUMN_NAME ORDER BY k.ORDINAL_POSITION SEPARATOR ', ') AS `Columns`, \
+         CONCAT(k.REFERENCED_TABLE_SCHEMA, '.', k.REFERENCED_TABLE_NAME, '(', \
+         GROUP_CONCAT(k.REFERENCED_COLUMN_NAME ORDER BY k.ORDINAL_POSITION SEPARATOR ', '), ')') AS `References`, \
+         r.UPDATE_RULE AS `On update`, r.DELETE_RULE AS `On delete` \
+         FROM information_schema.KEY_COLUMN_USAGE k \
+         JOIN information_schema.REFERENTIAL_CONSTRAINTS r \
+         ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME \
+         WHERE k.TABLE_SCHEMA = ? AND k.TABLE_NAME = ? AND k.REFERENCED_TABLE_NAME IS NOT NULL \
+         GROUP BY k.CONSTRAINT_NAME, k.REFERENCED_TABLE_SCHEMA, k.REFERENCED_TABLE_NAME, r.UPDATE_RULE, r.DELETE_RULE \
+         ORDER BY k.CONSTRAINT_NAME",
+        params(),
+    )?;
+    foreign_keys.summary = String::new();
+    Ok(Some(foreign_keys))
+}
+
+/// `\df [pattern]`: stored procedures and functions of the current database.
+pub fn list_routines(client: &mut MySQLClient, pattern: &str) -> Result<Option<QueryResult>> {
+    let db = client.current_db.clone()
+        .ok_or_else(|| RusqlError::Command("No database selected".into()))?;
+
+    client.query_result(
+        "SELECT ROUTINE_NAME AS `Routine`, ROUTINE_TYPE AS `Type`, \
+         DTD_IDENTIFIER AS `Returns`, SECURITY_TYPE AS `Security`, CREATED AS `Created` \
+         FROM information_schema.ROUTINES \
+         WHERE ROUTINE_SCHEMA = ? AND ROUTINE_NAME LIKE ? ORDER BY ROUTINE_NAME",
+        Params::Positional(vec![db.into(), like_pattern(pattern).into()]),
+    ).map(Some)
+}
+
+/// Next run of a recurring event estimated from its last run (or start) and
+/// interval. Compound intervals such as `DAY_HOUR` are left empty.
+const NEXT_EVENT_RUN: &str = "CASE WHEN EVENT_TYPE = 'ONE TIME' THEN EXECUTE_AT ELSE \
+     CASE INTERVAL_FIELD \
+     WHEN 'SECOND' THEN COALESCE(LAST_EXECUTED, STARTS) + INTERVAL INTERVAL_VALUE SECOND \
+     WHEN 'MINUTE' THEN COALESCE(LAST_EXECUTED, STARTS) + INTERVAL INTERVAL_VALUE MINUTE \
+     WHEN 'HOUR' THEN COALESCE(LAST_EXECUTED, STARTS) + INTERVAL INTERVAL_VALUE HOUR \
+     WHEN 'DAY' THEN COALESCE(LAST_EXECUTED, STARTS) + INTERVAL INTERVAL_VALUE DAY \
+     WHEN 'WEEK' THEN COALESCE(LAST_EXECUTED, STARTS) + INTERVAL INTERVAL_VALUE WEEK \
+     WHEN 'MONTH' THEN COALESCE(LAST_EXECUTED, STARTS) + INTERVAL INTERVAL_VALUE MONTH \
+     WHEN 'QUARTER' THEN COALESCE(LAST_EXECUTED, STARTS) + INTERVAL INTERVAL_VALUE QUARTER \
+     WHEN 'YEAR' THEN COALESCE(LAST_EXECUTED, STARTS) + INTERVAL INTERVAL_VALUE YEAR \
+     END END";
+
+/// `\events [enable|disable name | scheduler on|off]`: scheduler status and
+/// the events of the current database, or toggle one of them.
+pub fn list_events(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    match words[..] {
+        [] => {}
+        ["scheduler", state @ ("on" | "off")] => {
+            client.conn.query_drop(format!("SET GLOBAL event_scheduler = {}", state.to_uppercase()))?;
+            client.notice(&format!("Event scheduler turned {}", state));
+            return Ok(None);
+        }
+        [action @ ("enable" | "disable"), name] => {
+            let (db, event) = split_table(client, name)?;
+            client.conn.query_drop(format!(
+                "ALTER EVENT {} {}",
+                quote_table(&format!("{}.{}", db, event)),
+                action.to_uppercase()
+            ))?;
+            client.notice(&format!("Event {}.{} {}d", db, event, action));
+            return Ok(None);
+        }
+        _ => return Err(RusqlError::Command(
+            "Usage: \\events [enable|disable <event> | scheduler on|off]".into(),
+        )),
+    }
+
+    let db = client.current_db.clone()
+        .ok_or_else(|| RusqlError::Command("No database selected".into()))?;
+    let scheduler: String = client.conn.query_first("SELECT @@event_scheduler")?.unwrap_or_default();
+    client.notice(&format!("Event scheduler: {}", scheduler));
+
+    let query = format!(
+        "SELECT EVENT_NAME AS `Event`, STATUS AS `Status`, \
+         IF(EVENT_TYPE = 'ONE TIME', CONCAT('AT ', EXECUTE_AT), \
+         CONCAT('EVERY ', INTERVAL_VALUE, ' ', INTERVAL_FIELD)) AS `Schedule`, \
+         LAST_EXECUTED AS `Last run`, {} AS `Next run (est.)`, \
+         EVENT_DEFINITION AS `Definition` \
+         FROM information_schema.EVENTS WHERE EVENT_SCHEMA = ? ORDER BY EVENT_NAME",
+        NEXT_EVENT_RUN
+    );
+    client.query_result(&query, Params::Positional(vec![db.into()])).map(Some)
+}
+
+/// `\triggers [table]`: triggers of the current database with their bodies.
+pub fn list_triggers(client: &mut MySQLClient, table: &str) -> Result<Option<QueryResult>> {
+    let db = client.current_db.clone()
+        .ok_or_else(|| RusqlError::Command("No database selected".into()))?;
+
+    client.query_result(
+        "SELECT TRIGGER_NAME AS `Trigger`, EVENT_OBJECT_TABLE AS `Table`, \
+         CONCAT(ACTION_TIMING, ' ', EVENT_MANIPULATION) AS `Fires`, \
+         ACTION_STATEMENT AS `Body` \
+         FROM information_schema.TRIGGERS \
+         WHERE TRIGGER_SCHEMA = ? AND EVENT_OBJECT_TABLE LIKE ? \
+         ORDER BY EVENT_OBJECT_TABLE, ACTION_TIMING, EVENT_MANIPULATION, ACTION_ORDER",
+        Params::Positional(vec![db.into(), like_pattern(table).into()]),
+    ).map(Some)
+}
@@ -0,0 +1,213 @@
+//! Queries running on their own connection, on `mysql_async` and tokio.
+//!
+//! Every [`Job`] owns a `mysql_async` connection and runs one statement as a
+//! task on a shared tokio runtime, so any number of them run concurrently
+//! without a thread each. While one runs, the caller can poll it, wait with a
+//! deadline, or cancel it with `KILL QUERY` sent from a short-lived second
+//! connection. The blocking callers of the client wait on a job from outside
+//! the runtime.
+//!
+//! Statements typed at the prompt or read from scripts don't run here: they
+//! use the session's blocking `mysql::Conn`, whose database, variables and
+//! transaction the meta-commands read directly. Ctrl-C and `\timeout` cancel
+//! those with `KILL QUERY` from a second connection.
+
+use crate::error::{Result, RusqlError};
+use mysql::{Column, Opts, Row};
+use mysql_async::prelude::*;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+/// How often [`Job::wait_unless`] asks whether to stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// The runtime every job runs on, started by the first one.
+fn runtime() -> Result<&'static Runtime> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .thread_name("rusql-job")
+        .enable_all()
+        .build()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// The async driver's options for the same server, user and session setup.
+fn async_opts(opts: &Opts) -> mysql_async::Opts {
+    mysql_async::OptsBuilder::default()
+        .ip_or_hostname(opts.get_ip_or_hostname())
+        .tcp_port(opts.get_tcp_port())
+        .socket(opts.get_socket())
+        .prefer_socket(opts.get_prefer_socket())
+        .user(opts.get_user())
+        .pass(opts.get_pass())
+        .db_name(opts.get_db_name())
+        .init(opts.get_init())
+        .into()
+}
+
+/// Everything a finished statement produced.
+#[derive(Debug)]
+pub struct Outcome {
+    pub columns: Vec<Column>,
+    pub rows: Vec<Row>,
+    pub affected_rows: u64,
+    pub elapsed: Duration,
+}
+
+/// A statement executing in the background.
+///
+/// ```no_run
+/// # fn run(opts: mysql::Opts) -> rusql::error::Result<()> {
+/// use std::time::Duration;
+/// let job = rusql::job::Job::spawn(opts, "SELECT SLEEP(10)")?;
+/// match job.wait_timeout(Duration::from_secs(2)) {
+///     Ok(outcome) => println!("{} rows", outcome.rows.len()),
+///     Err(e) => eprintln!("{}", e),
+/// }
+/// # Ok(()) }
+/// ```
+pub struct Job {
+    query: String,
+    opts: Opts,
+    connection_id: u32,
+    started: Instant,
+    handle: JoinHandle<Result<Outcome>>,
+}
+
+impl Job {
+    /// Connect with `opts` and start running `query`.
+    /// Connection errors are returned here; statement errors from [`Job::wait`].
+    pub fn spawn<O: Into<Opts>>(opts: O, query: &str) -> Result<Job> {
+        let opts = opts.into();
+        let runtime = runtime()?;
+        let conn = runtime.block_on(mysql_async::Conn::new(async_opts(&opts)))?;
+        Ok(Job::start(runtime, conn, opts, query))
+    }
+
+    /// [`Job::spawn`] on every server in `sources` at once, connecting to all
+    /// of them concurrently. Results are in the order of `sources`.
+    ///
+    /// ```no_run
+    /// # fn run(shard1: mysql::Opts, shard2: mysql::Opts) -> rusql::error::Result<()> {
+    /// let jobs = rusql::job::Job::spawn_all(vec![shard1, shard2], "SELECT COUNT(*) FROM orders")?;
+    /// for job in jobs {
+    ///     println!("{} rows", job?.wait()?.rows.len());
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn spawn_all<O: Into<Opts>>(sources: Vec<O>, query: &str) -> Result<Vec<Result<Job>>> {
+        let runtime = runtime()?;
+        let connecting: Vec<(Opts, JoinHandle<mysql_async::Result<mysql_async::Conn>>)> = sources.into_iter()
+            .map(|opts| {
+                let opts = opts.into();
+                let connect = runtime.spawn(mysql_async::Conn::new(async_opts(&opts)));
+                (opts, connect)
+            })
+            .collect();
+        Ok(connecting.into_iter()
+            .map(|(opts, connect)| {
+                let conn = runtime.block_on(connect).map_err(|e| RusqlError::Connection(e.to_string()))??;
+                Ok(Job::start(runtime, conn, opts, query))
+            })
+            .collect())
+    }
+
+    fn start(runtime: &Runtime, mut conn: mysql_async::Conn, opts: Opts, query: &str) -> Job {
+        let connection_id = conn.id();
+        let statement = query.to_string();
+        let started = Instant::now();
+
+        let handle = runtime.spawn(async move {
+            let mut result = conn.query_iter(statement).await?;
+            let columns = result.columns().map(|columns| columns.to_vec()).unwrap_or_default();
+            let affected_rows = result.affected_rows();
+            let rows = result.collect::<Row>().await?;
+            result.drop_result().await?;
+            conn.disconnect().await?;
+            Ok(Outcome { columns, rows, affected_rows, elapsed: started.elapsed() })
+        });
+
+        Job { query: query.to_string(), opts, connection_id, started, handle }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Server-side id of the job's connection.
+    pub fn connection_id(&self) -> u32 {
+        self.connection_id
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Ask the server to abort the statement. The job then finishes with
+    /// `ERROR 1317: Query execution was interrupted`.
+    pub fn cancel(&self) -> Result<()> {
+        runtime()?.block_on(kill(async_opts(&self.opts), self.connection_id))
+    }
+
+    /// Block until the statement finishes.
+    pub fn wait(self) -> Result<Outcome> {
+        runtime()?.block_on(self.handle)
+            .map_err(|_| RusqlError::Connection("query task panicked".into()))?
+    }
+
+    /// Block until the statement finishes or `timeout` passes, in which case
+    /// it is cancelled and [`RusqlError::Cancelled`] is returned.
+    pub fn wait_timeout(self, timeout: Duration) -> Result<Outcome> {
+        let Job { opts, connection_id, mut handle, .. } = self;
+        runtime()?.block_on(async move {
+            match tokio::time::timeout(timeout, &mut handle).await {
+                Ok(joined) => joined.map_err(|_| RusqlError::Connection("query task panicked".into()))?,
+                Err(_) => cancelled(&opts, connection_id, handle).await,
+            }
+        })
+    }
+
+    /// Block until the statement finishes, cancelling it as soon as `stop`
+    /// returns true, e.g. on Ctrl-C.
+    pub fn wait_unless(self, stop: impl Fn() -> bool) -> Result<Outcome> {
+        let Job { opts, connection_id, mut handle, .. } = self;
+        runtime()?.block_on(async move {
+            loop {
+                if let Ok(joined) = tokio::time::timeout(POLL_INTERVAL, &mut handle).await {
+                    return joined.map_err(|_| RusqlError::Connection("query task panicked".into()))?;
+                }
+                if stop() {
+                    return cancelled(&opts, connection_id, handle).await;
+                }
+            }
+        })
+    }
+}
+
+/// Kill a job's statement and wait for it to stop. One that finished anyway
+/// still returns its outcome.
+async fn cancelled(opts: &Opts, connection_id: u32, handle: JoinHandle<Result<Outcome>>) -> Result<Outcome> {
+    kill(async_opts(opts), connection_id).await?;
+    match handle.await {
+        Ok(Ok(outcome)) => Ok(outcome),
+        _ => Err(RusqlError::Cancelled),
+    }
+}
+
+/// Abort the statement running on `connection_id` from a separate connection.
+async fn kill(opts: mysql_async::Opts, connection_id: u32) -> Result<()> {
+    let mut conn = mysql_async::Conn::new(opts).await?;
+    conn.query_drop(format!("KILL QUERY {}", connection_id)).await?;
+    conn.disconnect().await?;
+    Ok(())
+}
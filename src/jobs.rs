@@ -0,0 +1,100 @@
+use crate::{interrupt, variables, MySQLClient, QueryResult};
+use mysql::OptsBuilder;
+use rusql::error::{Result, RusqlError};
+use rusql::job::{Job, Outcome};
+use rusql::sink::write_rows;
+use std::collections::BTreeMap;
+
+/// Statements started with `\bg`, numbered from 1.
+#[derive(Default)]
+pub struct Jobs {
+    next: u32,
+    jobs: BTreeMap<u32, (Job, bool)>,
+}
+
+impl Jobs {
+    /// Jobs that finished since the last call, for announcing at the prompt.
+    pub fn newly_finished(&mut self) -> Vec<u32> {
+        self.jobs.iter_mut()
+            .filter(|(_, (job, announced))| !*announced && job.is_finished())
+            .map(|(id, (_, announced))| {
+                *announced = true;
+                *id
+            })
+            .collect()
+    }
+}
+
+/// `\bg statement`: run a statement on its own connection in the background.
+pub fn background(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let statement = args.trim().trim_end_matches(';').trim_end();
+    if statement.is_empty() {
+        return Err(RusqlError::Command("Usage: \\bg <statement>".into()));
+    }
+    let statement = variables::interpolate(statement, &client.variables);
+
+    let opts = OptsBuilder::from_opts(client.conn_opts.clone()).db_name(client.current_db.clone());
+    let job = Job::spawn(opts, &statement)?;
+    client.jobs.next += 1;
+    let id = client.jobs.next;
+    client.jobs.jobs.insert(id, (job, false));
+    client.notice(&format!("[{}] started; \\jobs lists jobs, \\fg {} shows the result", id, id));
+    Ok(None)
+}
+
+/// `\jobs`: background statements with their state and elapsed time.
+pub fn list(client: &mut MySQLClient) -> Result<Option<QueryResult>> {
+    let rows = client.jobs.jobs.iter()
+        .map(|(id, (job, _))| vec![
+            id.to_string(),
+            if job.is_finished() { "done" } else { "running" }.to_string(),
+            format!("{:.1} sec", job.elapsed().as_secs_f64()),
+            job.query().to_string(),
+        ])
+        .collect();
+    Ok(Some(QueryResult {
+        table: client.build_table(&["Job", "State", "Elapsed", "Statement"], rows),
+        summary: format!("{} background jobs", client.jobs.jobs.len()),
+    }))
+}
+
+/// `\fg [n]`: wait for a job (the latest by default) and show its result.
+pub fn foreground(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let id = match args {
+        "" => client.jobs.jobs.keys().next_back().copied()
+            .ok_or_else(|| RusqlError::Command("No background jobs".into()))?,
+        id => id.trim_start_matches('%').parse()
+            .map_err(|_| RusqlError::Command("Usage: \\fg [job]".into()))?,
+    };
+    let (job, _) = client.jobs.jobs.remove(&id)
+        .ok_or_else(|| RusqlError::Command(format!("No job [{}]", id)))?;
+
+    if !job.is_finished() {
+        client.notice(&format!("Waiting for [{}]...", id));
+    }
+    let Outcome { columns, mut rows, affected_rows, elapsed } = job.wait_unless(interrupt::interrupted)?;
+
+    if columns.is_empty() {
+        client.notice(&format!(
+            "[{}] Query OK, {} {} affected ({:.2} sec)",
+            id,
+            affected_rows,
+            if affected_rows == 1 { "row" } else { "rows" },
+            elapsed.as_secs_f64()
+        ));
+        return Ok(None);
+    }
+
+    client.sensitive_rules.result_columns(&columns).apply_rows(&mut rows);
+    let summary = format!(
+        "[{}] {} {} in set ({:.2} sec)",
+        id,
+        rows.len(),
+        if rows.len() == 1 { "row" } else { "rows" },
+        elapsed.as_secs_f64()
+    );
+    let mut sink = client.table_sink();
+    write_rows(&mut sink, &columns, rows, &summary)?;
+    let (table, summary) = sink.finish();
+    Ok(Some(QueryResult { table, summary }))
+}
@@ -0,0 +1,50 @@
+use crate::MySQLClient;
+use mysql::{Conn, OptsBuilder};
+use rusql::error::RusqlError;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// What became of the connection while the prompt waited.
+pub enum Idle {
+    Alive,
+    /// A ping failed and a new connection replaced the old one, so session
+    /// state (variables, temporary tables, an open transaction) is gone.
+    Reconnected,
+    /// A ping failed and reconnecting did too; tried again next interval.
+    Lost(RusqlError),
+}
+
+/// Run `wait` (reading a line at the prompt) while a second thread pings
+/// the connection every `interval`, so firewalls and `wait_timeout` don't
+/// drop a session left idle. A connection found dead is replaced.
+pub fn while_idle<T>(client: &mut MySQLClient, interval: Option<Duration>, wait: impl FnOnce() -> T) -> (T, Idle) {
+    let Some(interval) = interval else {
+        return (wait(), Idle::Alive);
+    };
+    let opts = OptsBuilder::from_opts(client.conn_opts.clone()).db_name(client.current_db.clone());
+    let conn = &mut client.conn;
+    let (stop, stopped) = mpsc::channel::<()>();
+
+    thread::scope(|scope| {
+        let pinger = scope.spawn(move || {
+            let mut idle = Idle::Alive;
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                if !matches!(idle, Idle::Lost(_)) && conn.ping().is_ok() {
+                    continue;
+                }
+                idle = match Conn::new(opts.clone()) {
+                    Ok(fresh) => {
+                        *conn = fresh;
+                        Idle::Reconnected
+                    }
+                    Err(e) => Idle::Lost(e.into()),
+                };
+            }
+            idle
+        });
+        let value = wait();
+        let _ = stop.send(());
+        (value, pinger.join().unwrap_or(Idle::Alive))
+    })
+}
@@ -0,0 +1,36 @@
+//! Library side of Rusql: building blocks shared by the command line client
+//! and applications embedding it.
+
+pub mod audit;
+pub mod binlog;
+pub mod bulk;
+pub mod compress;
+#[cfg(feature = "bulk")]
+pub mod copy;
+pub mod cron;
+pub mod diff;
+pub mod edit;
+pub mod error;
+pub mod export;
+pub mod format;
+pub mod infer;
+#[cfg(feature = "jobs")]
+pub mod job;
+pub mod mask;
+#[cfg(feature = "migrate")]
+pub mod migrate;
+pub mod narrow;
+pub mod otlp;
+#[cfg(feature = "export-parquet")]
+pub mod parquet_sink;
+pub mod prometheus;
+pub mod shard;
+pub mod sink;
+pub mod statements;
+#[cfg(feature = "bulk")]
+pub mod sync;
+pub mod throttle;
+pub mod totals;
+pub mod typed;
+#[cfg(feature = "xlsx")]
+pub mod xlsx_sink;
@@ -0,0 +1,89 @@
+use crate::MySQLClient;
+use rusql::error::{Result, RusqlError};
+use rusql::format::value_to_json;
+use mysql::prelude::*;
+use serde_json::{json, Value as Json};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// Keep the connection open and execute statements written to `fifo`.
+///
+/// Statements are accumulated line by line until one ends with `;`, exactly
+/// like the interactive prompt. Every statement produces one JSON object on a
+/// single line of the response FIFO. When the last writer closes the input
+/// FIFO it is reopened, so independent processes can take turns.
+pub fn run(client: &mut MySQLClient, fifo: &Path, response: &Path) -> Result<()> {
+    eprintln!("Listening on {} (responses to {})", fifo.display(), response.display());
+
+    loop {
+        // Opening a FIFO for reading blocks until a writer shows up
+        let reader = BufReader::new(File::open(fifo)?);
+        let mut replies: Option<File> = None;
+        let mut buffer = String::new();
+
+        for line in reader.lines() {
+            let line = line?;
+            buffer.push_str(&line);
+            buffer.push(' ');
+
+            if !line.trim().ends_with(';') {
+                continue;
+            }
+
+            let statement = buffer.trim().to_string();
+            buffer.clear();
+            let reply = execute(client, &statement);
+
+            if replies.is_none() {
+                replies = Some(OpenOptions::new().write(true).open(response)?);
+            }
+            if let Some(out) = replies.as_mut() {
+                writeln!(out, "{}", reply)?;
+                out.flush()?;
+            }
+        }
+    }
+}
+
+fn execute(client: &mut MySQLClient, statement: &str) -> Json {
+    let start = Instant::now();
+    let mut result = match client.conn.query_iter(statement) {
+        Ok(result) => result,
+        Err(e) => return failure(statement, e.into()),
+    };
+
+    let column_info = result.columns().as_ref().to_vec();
+    let columns: Vec<String> = column_info.iter()
+        .map(|c| c.name_str().to_string())
+        .collect();
+
+    let affected_rows = result.affected_rows();
+    let mut rows = Vec::new();
+    if let Some(set) = result.iter() {
+        for row in set {
+            match row {
+                Ok(row) => rows.push(row.unwrap().iter()
+                    .zip(&column_info)
+                    .map(|(value, column)| value_to_json(value, column.column_type()))
+                    .collect::<Vec<_>>()),
+                Err(e) => return failure(statement, e.into()),
+            }
+        }
+    }
+    drop(result);
+
+    json!({
+        "ok": true,
+        "statement": statement,
+        "columns": columns,
+        "rows": rows,
+        "affected_rows": affected_rows,
+        "elapsed": start.elapsed().as_secs_f64(),
+    })
+}
+
+fn failure(statement: &str, error: RusqlError) -> Json {
+    json!({ "ok": false, "statement": statement, "error": error.to_json() })
+}
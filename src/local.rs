@@ -0,0 +1,203 @@
+use crate::expand::LastResult;
+use crate::recall::{show_latest, ResultCache};
+use crate::{MySQLClient, QueryResult};
+use duckdb::{appender_params_from_iter, Connection};
+use mysql::consts::{ColumnFlags, ColumnType};
+use mysql::Column;
+use rusql::error::{Result, RusqlError};
+use std::rc::{Rc, Weak};
+use std::time::Instant;
+
+const USAGE: &str = "Usage: \\local <DuckDB SQL over last, result1..result10 or files>";
+
+/// Statements that return rows; anything else is run for its effect.
+const QUERY_KEYWORDS: &[&str] = &["select", "with", "from", "values", "table", "describe", "show", "summarize", "pivot", "unpivot"];
+
+/// The embedded DuckDB database, opened by the first `\local`.
+pub struct LocalDb {
+    conn: Connection,
+    /// Kept result loaded as each `resultN` table.
+    loaded: Vec<Weak<LastResult>>,
+}
+
+impl LocalDb {
+    fn open() -> Result<Self> {
+        Ok(LocalDb { conn: Connection::open_in_memory().map_err(local_error)?, loaded: Vec::new() })
+    }
+
+    /// Bring the `resultN` tables and the `last` view in line with the
+    /// kept results, loading only the ones that changed place.
+    fn load_results(&mut self, results: &ResultCache) -> Result<()> {
+        let kept: Vec<&Rc<LastResult>> = results.kept().collect();
+        for (i, result) in kept.iter().enumerate() {
+            let current = self.loaded.get(i).is_some_and(|loaded| Weak::ptr_eq(loaded, &Rc::downgrade(result)));
+            if !current {
+                self.load(&format!("result{}", i + 1), result)?;
+            }
+        }
+        for i in kept.len()..self.loaded.len() {
+            self.conn.execute_batch(&format!("DROP TABLE IF EXISTS result{}", i + 1)).map_err(local_error)?;
+        }
+        self.loaded = kept.into_iter().map(Rc::downgrade).collect();
+
+        let last = match results.latest() {
+            Some(_) => "CREATE OR REPLACE VIEW last AS SELECT * FROM result1",
+            None => "DROP VIEW IF EXISTS last",
+        };
+        self.conn.execute_batch(last).map_err(local_error)
+    }
+
+    /// Copy a result into a table with the columns typed after the server's.
+    /// The values arrive as text and are cast once loaded; ones that don't
+    /// cast (zero dates) become NULL.
+    fn load(&self, table: &str, result: &LastResult) -> Result<()> {
+        let names = unique_names(&result.columns);
+        let text_table = format!("{}_text", table);
+        let text_columns: Vec<String> = names.iter().map(|name| format!("{} VARCHAR", quote(name))).collect();
+        self.conn.execute_batch(&format!("CREATE OR REPLACE TABLE {} ({})", text_table, text_columns.join(", ")))
+            .map_err(local_error)?;
+        {
+            let mut appender = self.conn.appender(&text_table).map_err(local_error)?;
+            for row in &result.rows {
+                appender.append_row(appender_params_from_iter(row.iter().map(Option::as_deref))).map_err(local_error)?;
+            }
+        }
+        let typed: Vec<String> = names.iter()
+            .zip(&result.column_info)
+            .map(|(name, column)| format!("TRY_CAST({0} AS {1}) AS {0}", quote(name), duckdb_type(column)))
+            .collect();
+        self.conn.execute_batch(&format!(
+            "CREATE OR REPLACE TABLE {table} AS SELECT {} FROM {text_table}; DROP TABLE {text_table}",
+            typed.join(", ")
+        ))
+        .map_err(local_error)
+    }
+
+    /// Run a query and read its result as text, with the DuckDB type of
+    /// each column.
+    fn query(&self, sql: &str) -> Result<(Vec<(String, String)>, Vec<Vec<Option<String>>>)> {
+        let keyword = sql.split_whitespace().next().unwrap_or_default().to_lowercase();
+        let create = match keyword.as_str() {
+            // Only a plain query can follow AS; these work as subqueries
+            "describe" | "show" | "summarize" => format!("CREATE OR REPLACE TABLE local_result AS FROM ({})", sql),
+            _ => format!("CREATE OR REPLACE TABLE local_result AS {}", sql),
+        };
+        self.conn.execute_batch(&create).map_err(local_error)?;
+        let columns: Vec<(String, String)> = self.conn.prepare("SELECT column_name, column_type FROM (DESCRIBE local_result)")
+            .and_then(|mut statement| statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect())
+            .map_err(local_error)?;
+        let casts: Vec<String> = columns.iter().map(|(name, _)| format!("CAST({} AS VARCHAR)", quote(name))).collect();
+        let rows: Vec<Vec<Option<String>>> = self.conn.prepare(&format!("SELECT {} FROM local_result", casts.join(", ")))
+            .and_then(|mut statement| {
+                statement.query_map([], |row| (0..columns.len()).map(|i| row.get(i)).collect())?.collect()
+            })
+            .map_err(local_error)?;
+        self.conn.execute_batch("DROP TABLE local_result").map_err(local_error)?;
+        Ok((columns, rows))
+    }
+}
+
+/// `\local <sql>`: run DuckDB SQL in an embedded database, without touching
+/// the server. The kept results are tables `result1`.. (numbered as in
+/// `\recall`) and `last` is the latest; files are read with DuckDB's own
+/// `FROM 'orders.csv'` or `read_parquet(..)`. A query's result becomes the
+/// latest kept result in turn.
+pub fn local(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let sql = args.trim().trim_end_matches(';').trim();
+    let Some(keyword) = sql.split_whitespace().next().map(str::to_lowercase) else {
+        return Err(RusqlError::Command(USAGE.into()));
+    };
+    if client.local.is_none() {
+        client.local = Some(LocalDb::open()?);
+    }
+    let Some(db) = client.local.as_mut() else {
+        return Ok(None);
+    };
+    db.load_results(&client.results)?;
+
+    let start = Instant::now();
+    if !QUERY_KEYWORDS.contains(&keyword.as_str()) {
+        db.conn.execute_batch(sql).map_err(local_error)?;
+        client.notice(&format!("OK ({:.2} sec)", start.elapsed().as_secs_f64()));
+        return Ok(None);
+    }
+    let (columns, rows) = db.query(sql)?;
+    let elapsed = start.elapsed();
+
+    let result = LastResult {
+        columns: columns.iter().map(|(name, _)| name.clone()).collect(),
+        column_info: columns.iter().map(|(name, kind)| Column::new(column_type(kind)).with_name(name.as_bytes())).collect(),
+        rows,
+    };
+    let row_count = result.rows.len();
+    client.results.push(&format!("\\local {}", sql), result);
+    let summary = format!(
+        "{} {} in set ({:.2} sec, local)",
+        row_count,
+        if row_count == 1 { "row" } else { "rows" },
+        elapsed.as_secs_f64()
+    );
+    show_latest(client, client.format, &summary)
+}
+
+fn local_error(e: duckdb::Error) -> RusqlError {
+    RusqlError::Command(format!("DuckDB: {}", e))
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Column names made unique, as a join can return two `id` columns.
+fn unique_names(columns: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(columns.len());
+    for column in columns {
+        let mut name = column.clone();
+        let mut n = 1;
+        while names.iter().any(|taken| taken.eq_ignore_ascii_case(&name)) {
+            n += 1;
+            name = format!("{}_{}", column, n);
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// DuckDB type holding a server column's values.
+fn duckdb_type(column: &Column) -> String {
+    let unsigned = column.flags().contains(ColumnFlags::UNSIGNED_FLAG);
+    match column.column_type() {
+        ColumnType::MYSQL_TYPE_TINY
+        | ColumnType::MYSQL_TYPE_SHORT
+        | ColumnType::MYSQL_TYPE_INT24
+        | ColumnType::MYSQL_TYPE_LONG
+        | ColumnType::MYSQL_TYPE_LONGLONG
+        | ColumnType::MYSQL_TYPE_YEAR => if unsigned { "UBIGINT" } else { "BIGINT" }.to_string(),
+        ColumnType::MYSQL_TYPE_FLOAT | ColumnType::MYSQL_TYPE_DOUBLE => "DOUBLE".to_string(),
+        ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL if column.decimals() <= 38 => {
+            format!("DECIMAL(38, {})", column.decimals())
+        }
+        ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE => "DATE".to_string(),
+        ColumnType::MYSQL_TYPE_DATETIME
+        | ColumnType::MYSQL_TYPE_DATETIME2
+        | ColumnType::MYSQL_TYPE_TIMESTAMP
+        | ColumnType::MYSQL_TYPE_TIMESTAMP2 => "TIMESTAMP".to_string(),
+        // TIME spans days, BIT and binary strings have no text form to cast
+        _ => "VARCHAR".to_string(),
+    }
+}
+
+/// Server column type a DuckDB column is shown as, so numbers and dates
+/// are aligned and exported like server results.
+fn column_type(duckdb_type: &str) -> ColumnType {
+    match duckdb_type.split('(').next().unwrap_or(duckdb_type) {
+        "TINYINT" | "SMALLINT" | "INTEGER" | "BIGINT" | "HUGEINT" | "UTINYINT" | "USMALLINT" | "UINTEGER"
+        | "UBIGINT" | "UHUGEINT" => ColumnType::MYSQL_TYPE_LONGLONG,
+        "FLOAT" | "DOUBLE" => ColumnType::MYSQL_TYPE_DOUBLE,
+        "DECIMAL" => ColumnType::MYSQL_TYPE_NEWDECIMAL,
+        "DATE" => ColumnType::MYSQL_TYPE_DATE,
+        "TIMESTAMP" | "TIMESTAMP WITH TIME ZONE" => ColumnType::MYSQL_TYPE_DATETIME,
+        _ => ColumnType::MYSQL_TYPE_VAR_STRING,
+    }
+}
+
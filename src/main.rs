@@ -1,420 +1,350 @@
 use mysql::*;
-use mysql::prelude::*;
-use rustyline::Editor;
-use rustyline::error::ReadlineError;
-use rustyline::history::FileHistory;
-use structopt::StructOpt;
-use prettytable::{Table, Row as PrettyRow, Cell, format};
-use std::error::Error;
+use clap::{CommandFactory, Parser};
+use prettytable::Table;
+use std::io::Write;
 use std::path::PathBuf;
-use dirs::home_dir;
+use std::process::{Child, Command as Process, Stdio};
+use std::time::Duration;
 use colored::*;
 
-#[derive(StructOpt, Debug)]
-#[structopt(name = "mysql", about = "Cross-platform MySQL client")]
-struct Opts {
-    /// Host to connect to
-    #[structopt(short, long, default_value = "localhost")]
-    host: String,
-
-    /// Port number to connect to
-    #[structopt(short = "P", long, default_value = "3306")]
-    port: u16,
-
-    /// Username for login
-    #[structopt(short = "u", long)]
-    user: Option<String>,
-
-    /// Password for login
-    #[structopt(short = "p", long)]
-    password: Option<String>,
-
-    /// Database to use
-    #[structopt(short = "D", long)]
-    database: Option<String>,
-
-    /// Execute command and quit
-    #[structopt(short = "e", long)]
-    execute: Option<String>,
-
-    /// Disable colors in output
-    #[structopt(long)]
-    no_colors: bool,
-}
-
-struct MySQLClient {
-    conn: Conn,
-    current_db: Option<String>,
-    use_colors: bool,
-    host: String,
-    port: u16,
+mod autolimit;
+mod binlogs;
+mod charset;
+mod chart;
+#[cfg(feature = "bulk")]
+mod checksum;
+#[cfg(feature = "bulk")]
+mod chunked;
+mod cli;
+mod client;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod codegen;
+mod commands;
+mod completions;
+mod config;
+mod counters;
+mod deadlocks;
+mod diagram;
+mod ddl;
+mod expand;
+mod exporter;
+#[cfg(feature = "jobs")]
+mod fanout;
+mod favorites;
+mod filter;
+mod flashback;
+mod fk_graph;
+mod health;
+mod helper;
+mod histogram;
+mod history;
+mod index_report;
+mod infer_schema;
+mod interrupt;
+mod introspect;
+#[cfg(feature = "jobs")]
+mod jobs;
+mod keepalive;
+mod listen;
+#[cfg(feature = "duckdb")]
+mod local;
+mod notify;
+#[cfg(feature = "bulk")]
+mod osc;
+mod otel;
+mod paging;
+mod profile;
+mod partitions;
+mod progress;
+mod prompt;
+mod rc;
+#[cfg(feature = "jobs")]
+mod schedule;
+mod seed;
+mod server_variables;
+mod recall;
+mod redirect;
+mod relative;
+mod repl;
+mod replication;
+mod reshape;
+mod retry;
+mod row_edit;
+#[cfg(feature = "jobs")]
+mod shards;
+mod sizes;
+mod slowlog;
+mod subcommands;
+mod sqlmode;
+mod theme;
+mod timeout;
+mod timezone;
+mod transaction;
+#[cfg(feature = "bulk")]
+mod transfer;
+mod usage;
+mod users;
+mod variables;
+
+use cli::{connection_opts, is_batch, AuditCommand, Command, ConfigCommand, Opts};
+use client::{MySQLClient, QueryResult};
+use config::Settings;
+use redirect::Redirect;
+use rusql::audit;
+use rusql::error::{Result, RusqlError};
+use theme::Theme;
+use rusql::sink::{paint, OutputFormat};
+use subcommands::{run_codegen, run_config_check, run_diagram, run_export, run_seed, run_slowlog};
+#[cfg(feature = "bulk")]
+use subcommands::{run_copy, run_sync};
+#[cfg(feature = "migrate")]
+use subcommands::run_migrate;
+
+/// ER_QUERY_INTERRUPTED, returned for a query killed with Ctrl-C.
+const QUERY_INTERRUPTED: u16 = 1317;
+
+/// Render seconds the way the mysql client's status does, e.g. `2 days 3 hours 4 min 5 sec`.
+fn format_duration(seconds: u64) -> String {
+    let parts = [
+        (seconds / 86_400, "day"),
+        (seconds / 3_600 % 24, "hour"),
+        (seconds / 60 % 60, "min"),
+        (seconds % 60, "sec"),
+    ];
+    let text: Vec<String> = parts.iter()
+        .skip_while(|(value, _)| *value == 0)
+        .map(|(value, unit)| match *unit {
+            "day" | "hour" if *value != 1 => format!("{} {}s", value, unit),
+            _ => format!("{} {}", value, unit),
+        })
+        .collect();
+    if text.is_empty() { "0 sec".to_string() } else { text.join(" ") }
 }
 
-impl MySQLClient {
-    fn new(opts: &Opts) -> Result<Self, Box<dyn Error>> {
-        let builder = OptsBuilder::new()
-            .user(opts.user.as_deref())
-            .pass(opts.password.as_deref())
-            .ip_or_hostname(Some(opts.host.as_str()))
-            .tcp_port(opts.port)
-            .db_name(opts.database.as_deref());
-
-        let conn = Conn::new(builder)?;
-        let current_db = opts.database.clone();
-        let use_colors = !opts.no_colors;
-        let host = opts.host.clone();
-        let port = opts.port;
-
-        Ok(MySQLClient { conn, current_db, use_colors, host, port })
+/// Print a result table and its summary, through the pager if one is set.
+fn print_result(client: &MySQLClient, result: QueryResult) {
+    if let Some(path) = &client.output {
+        let redirect = Redirect::File { path: path.clone(), append: true };
+        if let Err(e) = client.write_redirected(&redirect, &result) {
+            print_error(client.error_color(), &e);
+        }
+        return;
     }
 
-    fn format_cell(&self, value: String, is_null: bool) -> String {
-        if !self.use_colors {
-            return if is_null { "NULL".to_string() } else { value };
-        }
+    let summary = client.paint(&result.summary, client.theme.notice);
 
-        if is_null {
-            "NULL".bright_red().to_string()
-        } else {
-            value.bright_white().to_string()
+    if let Some(pager) = &client.pager {
+        if write_to_command(pager, &result.table, &summary) {
+            return;
         }
+        eprintln!("Could not start pager '{}', printing to stdout", pager);
     }
 
-    fn execute_query(&mut self, query: &str) -> Result<Option<QueryResult>, Box<dyn Error>> {
-        // Handle special commands
-        match query.trim().to_lowercase().as_str() {
-            "status" => return self.show_status(),
-            "clear" | "\\c" => {
-                print!("\x1B[2J\x1B[1;1H");  // Clear screen
-                return Ok(None);
-            }
-            _ => {}
-        }
-    
-        let start_time = std::time::Instant::now();
-        let use_colors = self.use_colors;
-    
-        // Handle USE command
-        if query.trim().to_lowercase().starts_with("use ") {
-            let db = query.trim()[4..].trim().trim_matches(';');
-            self.conn.select_db(db)?;
-            self.current_db = Some(db.to_string());
-            
-            let msg = format!("Database changed to '{}'", db);
-            println!("{}", if use_colors { msg.green().to_string() } else { msg });
-            
-            return Ok(None);
-        }
-    
-        // Execute the query
-        let affected_rows = self.conn.affected_rows();
-        let result = self.conn.query_iter(query)?;
-        let column_info = result.columns().as_ref().to_vec();
-    
-        if column_info.is_empty() {
-            // Handle non-SELECT queries
-            let elapsed = start_time.elapsed();
-            
-            if affected_rows > 0 {
-                let msg = format!(
-                    "Query OK, {} {} affected ({:.2} sec)",
-                    affected_rows,
-                    if affected_rows == 1 { "row" } else { "rows" },
-                    elapsed.as_secs_f64()
-                );
-                println!("{}", if use_colors { msg.green().to_string() } else { msg });
-            }
-            return Ok(None);
-        }
-    
-        // Format SELECT query results
-        let mut table = Table::new();
-        let format = format::FormatBuilder::new()
-            .column_separator('│')
-            .borders('│')
-            .separator(format::LinePosition::Top, format::LineSeparator::new('─', '┌', '┐', '┬'))
-            .separator(format::LinePosition::Bottom, format::LineSeparator::new('─', '└', '┘', '┴'))
-            .separator(format::LinePosition::Title, format::LineSeparator::new('─', '├', '┤', '┼'))
-            .padding(1, 1)
-            .build();
-        table.set_format(format);
-    
-        // Add header row
-        let headers: Vec<Cell> = column_info.iter()
-            .map(|c| {
-                let header = if use_colors {
-                    c.name_str().bright_cyan().to_string()
-                } else {
-                    c.name_str().to_string()
-                };
-                Cell::new(&header).style_spec("b")
-            })
-            .collect();
-        table.add_row(PrettyRow::new(headers));
-    
-        // Calculate maximum widths for each column
-        let mut max_widths: Vec<usize> = column_info.iter()
-            .map(|c| c.name_str().len())
-            .collect();
-    
-        // Collect all rows
-        let rows: Vec<mysql::Row> = result.collect::<Result<Vec<_>, _>>()?;
-    
-        // First pass to find maximum widths
-        for row in &rows {
-            for i in 0..column_info.len() {
-                if i < max_widths.len() {
-                    let formatted = match row.get_opt(i) {
-                        Some(val) => {
-                            match val {
-                                Ok(Value::NULL) => "NULL".to_string(),
-                                Ok(Value::Bytes(bytes)) => String::from_utf8_lossy(&bytes).into_owned(),
-                                Ok(Value::Int(n)) => n.to_string(),
-                                Ok(Value::UInt(n)) => n.to_string(),
-                                Ok(Value::Float(f)) => f.to_string(),
-                                Ok(Value::Double(d)) => d.to_string(),
-                                Ok(Value::Date(y, m, d, h, i, s, _)) => 
-                                    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", y, m, d, h, i, s),
-                                Ok(Value::Time(neg, d, h, i, s, _)) => {
-                                    let sign = if neg { "-" } else { "" };
-                                    format!("{}{}.{:02}:{:02}:{:02}", sign, d, h, i, s)
-                                },
-                                Err(_) => "ERROR".to_string()
-                            }
-                        },
-                        _ => "NULL".to_string()
-                    };
-                    max_widths[i] = max_widths[i].max(formatted.len());
-                }
-            }
-        }
-    
-        // Add data rows with proper width alignment
-        for row in rows {
-            let cells: Vec<Cell> = (0..column_info.len())
-                .map(|i| {
-                    let val = row.get_opt(i);
-                    let (value, is_null) = match val {
-                        Some(Ok(val)) => {
-                            let formatted = match val {
-                                Value::NULL => ("NULL".to_string(), true),
-                                Value::Bytes(bytes) => (String::from_utf8_lossy(&bytes).into_owned(), false),
-                                Value::Int(n) => (n.to_string(), false),
-                                Value::UInt(n) => (n.to_string(), false),
-                                Value::Float(f) => (f.to_string(), false),
-                                Value::Double(d) => (d.to_string(), false),
-                                Value::Date(y, m, d, h, i, s, _) => 
-                                    (format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", y, m, d, h, i, s), false),
-                                Value::Time(neg, d, h, i, s, _) => {
-                                    let sign = if neg { "-" } else { "" };
-                                    (format!("{}{}.{:02}:{:02}:{:02}", sign, d, h, i, s), false)
-                                }
-                            };
-                            formatted
-                        },
-                        _ => ("NULL".to_string(), true)
-                    };
-                
-                    let formatted = if use_colors {
-                        if is_null {
-                            "NULL".bright_red().to_string()
-                        } else {
-                            value.bright_white().to_string()
-                        }
-                    } else {
-                        if is_null { "NULL".to_string() } else { value }
-                    };
-                
-                    Cell::new(&formatted)
-                })
-                .collect();
-            table.add_row(PrettyRow::new(cells));
-        }
-    
-        let row_count = table.len() - 1; // Subtract 1 to account for header row
-        let elapsed = start_time.elapsed();
-        let summary = format!(
-            "{} {} in set ({:.2} sec)",
-            row_count,
-            if row_count == 1 { "row" } else { "rows" },
-            elapsed.as_secs_f64()
-        );
-    
-        Ok(Some(QueryResult { table, summary }))
+    result.table.printstd();
+    if !summary.is_empty() {
+        println!("\n{}", summary);
     }
+}
 
-    fn show_status(&mut self) -> Result<Option<QueryResult>, Box<dyn Error>> {
-        let mut table = Table::new();
-        let format = format::FormatBuilder::new()
-            .column_separator(' ')
-            .borders(' ')
-            .padding(1, 1)
-            .build();
-        table.set_format(format);
-
-        // Server info
-        let server_version: String = self.conn.query_first("SELECT VERSION()")?.unwrap_or_default();
-        table.add_row(PrettyRow::new(vec![
-            Cell::new("Server version:").style_spec("Fb"),
-            Cell::new(&server_version),
-        ]));
-
-        // Connection info
-        table.add_row(PrettyRow::new(vec![
-            Cell::new("Server:").style_spec("Fb"),
-            Cell::new(&format!("{}:{}", self.host, self.port)),
-        ]));
-
-        // Database info
-        table.add_row(PrettyRow::new(vec![
-            Cell::new("Current database:").style_spec("Fb"),
-            Cell::new(self.current_db.as_deref().unwrap_or("None")),
-        ]));
-
-        // Character set info
-        let charset: String = self.conn.query_first("SELECT @@character_set_client")?.unwrap_or_default();
-        table.add_row(PrettyRow::new(vec![
-            Cell::new("Character set:").style_spec("Fb"),
-            Cell::new(&charset),
-        ]));
+/// Start a shell command reading from a pipe.
+fn spawn_shell(command: &str) -> std::io::Result<Child> {
+    Process::new(if cfg!(windows) { "cmd" } else { "sh" })
+        .arg(if cfg!(windows) { "/C" } else { "-c" })
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()
+}
 
-        Ok(Some(QueryResult { 
-            table,
-            summary: String::new()
-        }))
+/// Feed a table and its summary to a pager or pipe command, returning false
+/// when the command could not be started.
+fn write_to_command(command: &str, table: &Table, summary: &str) -> bool {
+    let Ok(mut child) = spawn_shell(command) else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The command may exit early (e.g. `q` in less), so write errors are ignored
+        let _ = table.print(&mut stdin);
+        if !summary.is_empty() {
+            let _ = writeln!(stdin, "\n{}", summary);
+        }
     }
+    let _ = child.wait();
+    true
 }
 
-struct QueryResult {
-    table: Table,
-    summary: String,
+fn print_error(color: Option<Color>, e: &RusqlError) {
+    eprintln!("{}", paint(&e.to_string(), color));
 }
 
-fn print_welcome_message(client: &mut MySQLClient) {
-    if let Ok(Some(version)) = client.conn.query_first::<String, _>("SELECT VERSION()") {
-        let banner = format!(r#"
-Welcome to the MySQL monitor.  Commands end with ;
-
-Server version: {}
-Connection Id: {}
-
-Copyright (c) 2000, 2024, Oracle and/or its affiliates.
-Rust MySQL Monitor. A cross-platform MySQL client.
-
-Type 'help;' or '\h' for help. Type '\c' to clear the current input statement.
-"#, version, client.conn.connection_id());
+fn main() {
+    let opts = Opts::parse();
 
-        if client.use_colors {
-            println!("{}", banner.bright_blue());
+    if let Some(endpoint) = &opts.otel_endpoint {
+        otel::init(endpoint);
+    }
+    let outcome = run(&opts);
+    otel::shutdown();
+    if let Err(e) = outcome {
+        // Batch callers asking for JSON get a machine-readable error
+        if is_batch(&opts) && opts.format == OutputFormat::Json {
+            eprintln!("{}", serde_json::json!({ "error": e.to_json() }));
         } else {
-            println!("{}", banner);
+            print_error(Theme::default().error.filter(|_| !opts.no_colors), &e);
         }
+        std::process::exit(e.exit_code());
     }
 }
 
-fn format_prompt(client: &MySQLClient, is_continuation: bool) -> String {
-    if is_continuation {
-        if client.use_colors {
-            "    -> ".bright_green().to_string()
-        } else {
-            "    -> ".to_string()
-        }
+/// Execute every statement in `input` in order, printing each result.
+///
+/// Execution stops at the first error, unless `--force` is set, in which case
+/// errors are printed as they happen and counted. A killed query always stops.
+/// Statements of a `script` (not typed at the prompt) are retried per `--retry`.
+fn execute_statements(client: &mut MySQLClient, input: &str, script: bool) -> Result<()> {
+    // Script statements skip the usage log and auto_limit
+    if script && client.at_prompt {
+        client.at_prompt = false;
+        let outcome = execute_statements(client, input, script);
+        client.at_prompt = true;
+        return outcome;
+    }
+    let statements = if commands::is_meta_command(input) {
+        vec![input.to_string()]
     } else {
-        let db_str = client.current_db
-            .as_ref()
-            .map(|db| format!("({})", db))
-            .unwrap_or_default();
-        
-        if client.use_colors {
-            format!("mysql{} > ", db_str).bright_green().to_string()
-        } else {
-            format!("mysql{} > ", db_str)
+        // `SELECT ...; | cmd` pipes the output of the statement before it
+        let mut statements: Vec<String> = Vec::new();
+        for statement in client.splitter.split(input) {
+            match statements.last_mut() {
+                Some(previous) if statement.starts_with('|') => {
+                    previous.push(' ');
+                    previous.push_str(&statement);
+                }
+                _ => statements.push(statement),
+            }
         }
+        statements
+    };
+    if script && client.single_transaction {
+        return transaction::run_script(client, &statements);
     }
-}
-
-fn main() -> Result<(), Box<dyn Error>> {
-    let opts = Opts::from_args();
-    let mut client = MySQLClient::new(&opts)?;
 
-    // Handle -e execute flag
-    if let Some(query) = opts.execute {
-        if let Some(result) = client.execute_query(&query)? {
-            result.table.printstd();
-            if !result.summary.is_empty() {
-                println!("\n{}", if client.use_colors {
-                    result.summary.green().to_string()
-                } else {
-                    result.summary
-                });
+    let mut failed = 0;
+    for statement in &statements {
+        let outcome = match script {
+            true => retry::execute(client, statement),
+            false => client.execute_query(statement),
+        };
+        match outcome {
+            Ok(Some(result)) => print_result(client, result),
+            Ok(None) => {}
+            Err(e) if client.force && !matches!(e, RusqlError::Sql { code: QUERY_INTERRUPTED, .. }) => {
+                print_error(client.error_color(), &e);
+                failed += 1;
             }
+            Err(e) => return Err(e),
         }
-        return Ok(());
     }
 
-    // Set up interactive mode
-    let history_file = home_dir()
-        .map(|mut path| {
-            path.push(".mysql_history");
-            path
-        })
-        .unwrap_or_else(|| PathBuf::from(".mysql_history"));
-
-    let mut rl = Editor::<(), FileHistory>::new()?;
-    if rl.load_history(&history_file).is_err() {
-        println!("No previous history.");
+    match failed {
+        0 => Ok(()),
+        _ => Err(RusqlError::Command(format!("{} of {} statements failed", failed, statements.len()))),
     }
+}
 
-    print_welcome_message(&mut client);
+/// Run the -e statements, then the script files, and quit. With `--force` a
+/// failing one is reported and the rest still run.
+fn run_batch(client: &mut MySQLClient, opts: &Opts) -> Result<()> {
+    let steps = opts.execute.len() + opts.scripts.len();
+    let mut failed = 0;
+    for step in 0..steps {
+        let outcome = match opts.execute.get(step) {
+            Some(statements) => execute_statements(client, statements, true),
+            None => commands::source_file(client, &opts.scripts[step - opts.execute.len()]),
+        };
+        match outcome {
+            Ok(()) => {}
+            Err(e) if client.force && steps > 1 && !matches!(e, RusqlError::Sql { code: QUERY_INTERRUPTED, .. }) => {
+                print_error(client.error_color(), &e);
+                failed += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(RusqlError::Command(format!("{} of {} -e arguments and scripts failed", failed, steps))),
+    }
+}
 
-    let mut query_buffer = String::new();
-    loop {
-        let prompt = format_prompt(&client, !query_buffer.is_empty());
+fn run(opts: &Opts) -> Result<()> {
+    // Checked before loading, which would stop at the first problem
+    if let Some(Command::Config(ConfigCommand::Check { probe })) = &opts.command {
+        return run_config_check(*probe);
+    }
+    if let Some(Command::Audit(AuditCommand::Verify { file })) = &opts.command {
+        let records = audit::verify(file)?;
+        println!("{}: {} records, chain intact", file.display(), records);
+        return Ok(());
+    }
+    let settings = Settings::load()?;
+    if let Some(Command::Completions { shell, profiles }) = &opts.command {
+        return match shell.filter(|_| !profiles) {
+            Some(shell) => completions::print(Opts::command(), shell),
+            None => completions::print_profiles(&settings),
+        };
+    }
+    if let Some(Command::Slowlog(_)) = &opts.command {
+        return run_slowlog(opts, &settings);
+    }
+    #[cfg(feature = "jobs")]
+    if let Some(Command::Schedule { config }) = &opts.command {
+        return schedule::run(config, &settings, connection_opts(opts));
+    }
+    if let Some(Command::Exporter { listen, queries }) = &opts.command {
+        return exporter::run(listen, queries, &settings, connection_opts(opts));
+    }
+    #[cfg(feature = "migrate")]
+    if let Some(Command::Migrate(_)) = &opts.command {
+        return run_migrate(opts, &settings);
+    }
+    if let Some(Command::Diagram { .. }) = &opts.command {
+        return run_diagram(opts);
+    }
+    if let Some(Command::Export { .. }) = &opts.command {
+        return run_export(opts);
+    }
+    if let Some(Command::Codegen { .. }) = &opts.command {
+        return run_codegen(opts);
+    }
+    if let Some(Command::Seed { .. }) = &opts.command {
+        return run_seed(opts);
+    }
+    #[cfg(feature = "bulk")]
+    if let Some(Command::Sync { .. }) = &opts.command {
+        return run_sync(opts, &settings);
+    }
+    #[cfg(feature = "bulk")]
+    if let Some(Command::Copy { .. }) = &opts.command {
+        return run_copy(opts, &settings);
+    }
+    let mut client = MySQLClient::new(opts, &settings)?;
+    if let Some(millis) = opts.max_execution_time.filter(|&millis| millis > 0) {
+        timeout::apply(&mut client, Some(Duration::from_millis(millis)))?;
+    }
 
-        match rl.readline(&prompt) {
-            Ok(line) => {
-                rl.add_history_entry(line.as_str())?;
-                
-                query_buffer.push_str(&line);
-                query_buffer.push(' ');
+    if let Some(Command::Listen { fifo, response }) = &opts.command {
+        let response = response.clone().unwrap_or_else(|| {
+            let mut path = fifo.clone().into_os_string();
+            path.push(".response");
+            PathBuf::from(path)
+        });
+        return listen::run(&mut client, fifo, &response);
+    }
 
-                if line.trim().ends_with(';') {
-                    match client.execute_query(&query_buffer) {
-                        Ok(Some(result)) => {
-                            result.table.printstd();
-                            if !result.summary.is_empty() {
-                                println!("\n{}", if client.use_colors {
-                                    result.summary.green().to_string()
-                                } else {
-                                    result.summary
-                                });
-                            }
-                        }
-                        Ok(None) => {}
-                        Err(e) => eprintln!("{}", if client.use_colors {
-                            format!("Error: {}", e).bright_red().to_string()
-                        } else {
-                            format!("Error: {}", e)
-                        }),
-                    }
-                    query_buffer.clear();
-                }
-            }
-            Err(ReadlineError::Interrupted) => {
-                println!("^C");
-                query_buffer.clear();
-            }
-            Err(ReadlineError::Eof) => {
-                println!("Bye");
-                break;
-            }
-            Err(err) => {
-                println!("Error: {:?}", err);
-                break;
-            }
-        }
+    if is_batch(opts) {
+        return run_batch(&mut client, opts);
     }
 
-    rl.save_history(&history_file)?;
-    Ok(())
-}
\ No newline at end of file
+    repl::run(client, opts, &settings)
+}
@@ -0,0 +1,287 @@
+//! Column masking for pulling production data into other environments.
+//!
+//! Rules name a table pattern, a column pattern and a [`Mask`]. Fakers and
+//! hashes derive their output from an HMAC-SHA256 of the original value
+//! under the rules' secret, so the same input always gets the same
+//! replacement and joins on masked columns still line up, while values can't
+//! be recovered by hashing guesses without the secret. NULL stays NULL under
+//! every mask.
+
+use crate::format::format_value;
+use crate::narrow::glob_match;
+use mysql::{Column, Row, Value};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FIRST_NAMES: &[&str] = &[
+    "Alex", "Blake", "Casey", "Dana", "Eli", "Frankie", "Gray", "Harper", "Indy", "Jamie", "Kai", "Logan",
+    "Morgan", "Noel", "Oakley", "Parker", "Quinn", "Reese", "Sage", "Taylor", "Val", "Wren", "Avery", "Rowan",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Adams", "Brooks", "Carter", "Diaz", "Evans", "Fischer", "Garcia", "Hughes", "Ito", "Jensen", "Kim", "Lopez",
+    "Moreau", "Novak", "Olsen", "Patel", "Quist", "Rossi", "Silva", "Tanaka", "Udeh", "Varga", "Weber", "Young",
+];
+
+/// How a masked column's values are replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mask {
+    /// Text becomes `x` of the same length, numbers 0, dates and times zero.
+    Redact,
+    /// Always NULL.
+    Null,
+    /// The hex HMAC-SHA256 of the value under the secret.
+    Hash,
+    /// The column's values moved between rows, keeping their distribution.
+    Shuffle,
+    /// A fake full name.
+    Name,
+    FirstName,
+    LastName,
+    /// A fake address at `example.com`.
+    Email,
+    /// A fake `555-` phone number.
+    Phone,
+    /// The first two characters kept as a hint, e.g. `ab***@***`; shorter
+    /// values are hidden entirely.
+    Partial,
+}
+
+impl Mask {
+    pub const VARIANTS: &'static [&'static str] =
+        &["redact", "null", "hash", "shuffle", "name", "first_name", "last_name", "email", "phone", "partial"];
+
+    /// The replacement for one value, keyed by `secret`; [`Mask::Shuffle`]
+    /// needs the whole column and leaves a single value as it is.
+    ///
+    /// ```
+    /// use mysql::Value;
+    /// use rusql::mask::Mask;
+    /// let email = Value::from("jane@corp.com");
+    /// assert_eq!(Mask::Email.apply(&email, b"key"), Mask::Email.apply(&email, b"key"));
+    /// assert_ne!(Mask::Hash.apply(&email, b"key"), Mask::Hash.apply(&email, b"other key"));
+    /// let digest = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+    /// assert_eq!(Mask::Hash.apply(&Value::from("what do ya want for nothing?"), b"Jefe"), Value::from(digest));
+    /// assert_eq!(Mask::Redact.apply(&Value::from("secret"), b"key"), Value::from("xxxxxx"));
+    /// assert_eq!(Mask::Partial.apply(&Value::from("abby@corp.com"), b"key"), Value::from("ab***@***"));
+    /// assert_eq!(Mask::Hash.apply(&Value::NULL, b"key"), Value::NULL);
+    /// ```
+    pub fn apply(self, value: &Value, secret: &[u8]) -> Value {
+        if *value == Value::NULL {
+            return Value::NULL;
+        }
+        let digest = hmac_sha256(secret, format_value(value).as_bytes());
+        let seed = u64::from_be_bytes(digest[..8].try_into().unwrap_or_default());
+        let first = FIRST_NAMES[(seed % FIRST_NAMES.len() as u64) as usize];
+        let last = LAST_NAMES[((seed >> 16) % LAST_NAMES.len() as u64) as usize];
+        match self {
+            Mask::Redact => match value {
+                Value::Bytes(bytes) => Value::from("x".repeat(String::from_utf8_lossy(bytes).chars().count())),
+                Value::Int(_) => Value::Int(0),
+                Value::UInt(_) => Value::UInt(0),
+                Value::Float(_) => Value::Float(0.0),
+                Value::Double(_) => Value::Double(0.0),
+                Value::Date(..) => Value::Date(1970, 1, 1, 0, 0, 0, 0),
+                Value::Time(..) => Value::Time(false, 0, 0, 0, 0, 0),
+                Value::NULL => Value::NULL,
+            },
+            Mask::Null => Value::NULL,
+            Mask::Hash => Value::from(digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>()),
+            Mask::Shuffle => value.clone(),
+            Mask::Name => Value::from(format!("{} {}", first, last)),
+            Mask::FirstName => Value::from(first),
+            Mask::LastName => Value::from(last),
+            // The whole seed keeps distinct values from sharing an address
+            Mask::Email => Value::from(format!("{}.{}.{:016x}@example.com", first, last, seed).to_lowercase()),
+            Mask::Phone => Value::from(format!("555-{:03}-{:04}", (seed >> 24) % 1000, (seed >> 40) % 10000)),
+            Mask::Partial => {
+                let text = format_value(value);
+                let hint: String = text.chars().take(2).filter(|_| text.chars().count() > 4).collect();
+                let domain = if text.contains('@') { "@***" } else { "" };
+                Value::from(format!("{}***{}", hint, domain))
+            }
+        }
+    }
+}
+
+impl FromStr for Mask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "redact" => Ok(Mask::Redact),
+            "null" => Ok(Mask::Null),
+            "hash" => Ok(Mask::Hash),
+            "shuffle" => Ok(Mask::Shuffle),
+            "name" => Ok(Mask::Name),
+            "first_name" => Ok(Mask::FirstName),
+            "last_name" => Ok(Mask::LastName),
+            "email" => Ok(Mask::Email),
+            "phone" => Ok(Mask::Phone),
+            "partial" => Ok(Mask::Partial),
+            other => Err(format!("unknown mask '{}'", other)),
+        }
+    }
+}
+
+/// Masking rules: the first rule whose table and column patterns match a
+/// column decides its mask. Table patterns containing a `.` are matched
+/// against `db.table`, others against the table name; both take `*` wildcards.
+///
+/// ```
+/// use rusql::mask::{Mask, MaskRules};
+/// let rules = MaskRules::default()
+///     .secret("s3cret")
+///     .rule("shop.customers", "email", Mask::Email)
+///     .rule("*", "password*", Mask::Null);
+/// let masks = rules.columns(Some("shop"), "customers", &["id", "email", "password_hash"]);
+/// let mut rows = vec![vec![1.into(), "jane@corp.com".into(), "$2b$12$abc".into()]];
+/// masks.apply(&mut rows);
+/// assert_eq!(rows[0][0], 1.into());
+/// assert_eq!(rows[0][2], mysql::Value::NULL);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MaskRules {
+    rules: Vec<(String, String, Mask)>,
+    secret: Vec<u8>,
+}
+
+impl MaskRules {
+    /// Key the fakes and hashes; without one, anyone can recompute them.
+    pub fn secret(mut self, secret: &str) -> Self {
+        self.secret = secret.as_bytes().to_vec();
+        self
+    }
+
+    pub fn rule(mut self, table: &str, column: &str, mask: Mask) -> Self {
+        self.rules.push((table.to_string(), column.to_string(), mask));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Masks for the columns of `table` in `db`.
+    pub fn columns<S: AsRef<str>>(&self, db: Option<&str>, table: &str, columns: &[S]) -> ColumnMasks {
+        ColumnMasks {
+            masks: columns.iter().map(|column| self.find(db, table, column.as_ref())).collect(),
+            secret: self.secret.clone(),
+        }
+    }
+
+    /// Masks for the columns of a query result, located by the table and
+    /// column each was read from. Computed columns have neither and are left
+    /// as they are.
+    pub fn result_columns(&self, columns: &[Column]) -> ColumnMasks {
+        let masks = columns.iter()
+            .map(|column| {
+                let table = column.org_table_str();
+                let schema = column.schema_str();
+                let db = Some(schema.as_ref()).filter(|db| !db.is_empty());
+                match table.is_empty() {
+                    true => None,
+                    false => self.find(db, &table, &column.org_name_str()),
+                }
+            })
+            .collect();
+        ColumnMasks { masks, secret: self.secret.clone() }
+    }
+
+    fn find(&self, db: Option<&str>, table: &str, column: &str) -> Option<Mask> {
+        self.rules.iter()
+            .find(|(table_pattern, column_pattern, _)| {
+                let table_matches = match (table_pattern.contains('.'), db) {
+                    (true, Some(db)) => glob_match(table_pattern, &format!("{}.{}", db, table)),
+                    (true, None) => false,
+                    (false, _) => glob_match(table_pattern, table),
+                };
+                table_matches && glob_match(column_pattern, column)
+            })
+            .map(|(_, _, mask)| *mask)
+    }
+}
+
+/// The mask of each column of a row set, from [`MaskRules`].
+#[derive(Debug, Clone)]
+pub struct ColumnMasks {
+    masks: Vec<Option<Mask>>,
+    secret: Vec<u8>,
+}
+
+impl ColumnMasks {
+    /// True when no column is masked.
+    pub fn is_empty(&self) -> bool {
+        self.masks.iter().all(Option::is_none)
+    }
+
+    /// Mask rows of values in place; shuffled columns are shuffled among these rows.
+    pub fn apply(&self, rows: &mut [Vec<Value>]) {
+        for (index, mask) in self.masked() {
+            let values = rows.iter_mut()
+                .map(|row| std::mem::replace(&mut row[index], Value::NULL))
+                .collect();
+            for (row, value) in rows.iter_mut().zip(mask_column(mask, values, &self.secret)) {
+                row[index] = value;
+            }
+        }
+    }
+
+    /// Mask result rows in place, like [`ColumnMasks::apply`].
+    pub fn apply_rows(&self, rows: &mut [Row]) {
+        for (index, mask) in self.masked() {
+            let values = rows.iter_mut().map(|row| row.take(index).unwrap_or(Value::NULL)).collect();
+            for (row, value) in rows.iter_mut().zip(mask_column(mask, values, &self.secret)) {
+                row.place(index, value);
+            }
+        }
+    }
+
+    /// Mask a stream of rows `window` rows at a time, shuffling within each window.
+    pub fn stream<'a, I>(&'a self, rows: I, window: usize) -> impl Iterator<Item = Vec<Value>> + 'a
+    where
+        I: Iterator<Item = Vec<Value>> + 'a,
+    {
+        let mut rows = rows.peekable();
+        std::iter::from_fn(move || {
+            rows.peek()?;
+            let mut chunk: Vec<Vec<Value>> = rows.by_ref().take(window.max(1)).collect();
+            self.apply(&mut chunk);
+            Some(chunk)
+        })
+        .flatten()
+    }
+
+    fn masked(&self) -> impl Iterator<Item = (usize, Mask)> + '_ {
+        self.masks.iter().enumerate().filter_map(|(index, mask)| mask.map(|mask| (index, mask)))
+    }
+}
+
+fn mask_column(mask: Mask, mut values: Vec<Value>, secret: &[u8]) -> Vec<Value> {
+    if mask != Mask::Shuffle {
+        return values.iter().map(|value| mask.apply(value, secret)).collect();
+    }
+    // Fisher-Yates with an xorshift generator seeded from the clock
+    let mut state = SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |t| t.as_nanos() as u64) | 1;
+    for i in (1..values.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        values.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+    values
+}
+
+/// HMAC-SHA256 (RFC 2104), stable across runs and platforms.
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    match key.len() > BLOCK {
+        true => block[..32].copy_from_slice(&Sha256::digest(key)),
+        false => block[..key.len()].copy_from_slice(key),
+    }
+    let pad = |byte: u8| block.map(|key| key ^ byte);
+    let inner = Sha256::new().chain_update(pad(0x36)).chain_update(message).finalize();
+    Sha256::new().chain_update(pad(0x5c)).chain_update(inner).finalize().into()
+}
@@ -0,0 +1,225 @@
+//! Versioned SQL migrations applied in order.
+//!
+//! A migrations directory holds files named `<version>_<name>.up.sql`, with an
+//! optional `<version>_<name>.down.sql` undoing it; a plain
+//! `<version>_<name>.sql` is an up migration without a down. Versions are
+//! numbers, such as `001` or a `20240501120000` timestamp, and are applied in
+//! numeric order. Applied versions are recorded in a `schema_migrations`
+//! table in the connection's database.
+
+use crate::bulk::quote_table;
+use crate::error::{Result, RusqlError};
+use crate::statements::{keywords, Splitter};
+use mysql::prelude::*;
+use mysql::{Conn, Params, TxOpts};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Statements that commit implicitly, so a migration holding one cannot be
+/// rolled back as a whole.
+const IMPLICIT_COMMIT: &[&str] = &["CREATE", "ALTER", "DROP", "RENAME", "TRUNCATE", "GRANT", "REVOKE"];
+
+/// One migration found in the directory.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: u64,
+    pub name: String,
+    pub up: PathBuf,
+    pub down: Option<PathBuf>,
+}
+
+/// A migration and, once applied, when.
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub migration: Migration,
+    pub applied_at: Option<String>,
+}
+
+/// What happened to a migration run by [`Migrator::up`] or [`Migrator::down`].
+#[derive(Debug, Clone)]
+pub struct Applied {
+    pub version: u64,
+    pub name: String,
+    pub statements: usize,
+    /// False when the migration holds statements that commit implicitly
+    /// (DDL), so a failure can leave it half applied.
+    pub transactional: bool,
+}
+
+/// Applies and reverts the migrations of one directory.
+///
+/// ```no_run
+/// # fn run(conn: &mut mysql::Conn) -> rusql::error::Result<()> {
+/// let migrator = rusql::migrate::Migrator::new("./migrations");
+/// for applied in migrator.up(conn, None)? {
+///     println!("applied {} {}", applied.version, applied.name);
+/// }
+/// # Ok(()) }
+/// ```
+pub struct Migrator {
+    dir: PathBuf,
+    table: String,
+}
+
+impl Migrator {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Migrator { dir: dir.as_ref().to_path_buf(), table: "schema_migrations".to_string() }
+    }
+
+    /// Table recording applied versions, `schema_migrations` by default.
+    pub fn table(mut self, name: &str) -> Self {
+        self.table = name.to_string();
+        self
+    }
+
+    /// Migrations in the directory, by version.
+    pub fn migrations(&self) -> Result<Vec<Migration>> {
+        let mut found: BTreeMap<u64, Migration> = BTreeMap::new();
+        let entries = std::fs::read_dir(&self.dir)
+            .map_err(|e| RusqlError::Command(format!("Cannot read migrations from '{}': {}", self.dir.display(), e)))?;
+        for entry in entries {
+            let path = entry?.path();
+            let Some(file) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some(stem) = file.strip_suffix(".sql") else {
+                continue;
+            };
+            let (stem, down) = match stem.strip_suffix(".down") {
+                Some(stem) => (stem, true),
+                None => (stem.strip_suffix(".up").unwrap_or(stem), false),
+            };
+            let digits = stem.chars().take_while(char::is_ascii_digit).count();
+            let Ok(version) = stem[..digits].parse::<u64>() else {
+                continue;
+            };
+            let name = stem[digits..].trim_start_matches(['_', '-']).to_string();
+
+            let migration = found.entry(version).or_insert_with(|| Migration {
+                version,
+                name: name.clone(),
+                up: PathBuf::new(),
+                down: None,
+            });
+            let slot = if down { migration.down.get_or_insert_with(PathBuf::new) } else { &mut migration.up };
+            if !slot.as_os_str().is_empty() {
+                return Err(RusqlError::Command(format!("Two migrations with version {} in '{}'", version, self.dir.display())));
+            }
+            *slot = path.clone();
+        }
+
+        found.into_values()
+            .map(|migration| match migration.up.as_os_str().is_empty() {
+                true => Err(RusqlError::Command(format!("Migration {} has a down file but no up file", migration.version))),
+                false => Ok(migration),
+            })
+            .collect()
+    }
+
+    /// Every migration with when it was applied; applied versions no longer
+    /// in the directory are reported as an error.
+    pub fn status(&self, conn: &mut Conn) -> Result<Vec<MigrationStatus>> {
+        let migrations = self.migrations()?;
+        let applied = self.applied(conn)?;
+        if let Some(version) = applied.keys().find(|version| !migrations.iter().any(|m| m.version == **version)) {
+            return Err(RusqlError::Command(format!(
+                "Version {} is recorded in {} but has no file in '{}'",
+                version,
+                self.table,
+                self.dir.display()
+            )));
+        }
+        Ok(migrations.into_iter()
+            .map(|migration| {
+                let applied_at = applied.get(&migration.version).cloned();
+                MigrationStatus { migration, applied_at }
+            })
+            .collect())
+    }
+
+    /// Apply pending migrations in order, up to and including `target` when
+    /// given. Stops at the first failure, keeping what was applied before it.
+    pub fn up(&self, conn: &mut Conn, target: Option<u64>) -> Result<Vec<Applied>> {
+        let mut done = Vec::new();
+        for status in self.status(conn)? {
+            let migration = status.migration;
+            if status.applied_at.is_some() || target.is_some_and(|target| migration.version > target) {
+                continue;
+            }
+            let record = format!("INSERT INTO {} (version, name) VALUES (?, ?)", quote_table(&self.table));
+            let params = Params::from((migration.version, migration.name.clone()));
+            done.push(run(conn, &migration, &migration.up, &record, params)?);
+        }
+        Ok(done)
+    }
+
+    /// Revert the latest `steps` applied migrations, newest first.
+    pub fn down(&self, conn: &mut Conn, steps: usize) -> Result<Vec<Applied>> {
+        let mut applied: Vec<Migration> = self.status(conn)?
+            .into_iter()
+            .filter(|status| status.applied_at.is_some())
+            .map(|status| status.migration)
+            .collect();
+        applied.reverse();
+
+        let mut done = Vec::new();
+        for migration in applied.into_iter().take(steps) {
+            let down = migration.down.clone().ok_or_else(|| {
+                RusqlError::Command(format!("Migration {} {} has no .down.sql file", migration.version, migration.name))
+            })?;
+            let record = format!("DELETE FROM {} WHERE version = ?", quote_table(&self.table));
+            let params = Params::from((migration.version,));
+            done.push(run(conn, &migration, &down, &record, params)?);
+        }
+        Ok(done)
+    }
+
+    /// Applied versions with their time, creating the table on first use.
+    fn applied(&self, conn: &mut Conn) -> Result<BTreeMap<u64, String>> {
+        conn.query_drop(format!(
+            "CREATE TABLE IF NOT EXISTS {} (\
+             version BIGINT UNSIGNED NOT NULL PRIMARY KEY, \
+             name VARCHAR(255) NOT NULL, \
+             applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP)",
+            quote_table(&self.table)
+        ))?;
+        let rows: Vec<(u64, String)> = conn.query(format!(
+            "SELECT version, DATE_FORMAT(applied_at, '%Y-%m-%d %H:%i:%s') FROM {} ORDER BY version",
+            quote_table(&self.table)
+        ))?;
+        Ok(rows.into_iter().collect())
+    }
+}
+
+/// Run the statements of `file` and the bookkeeping statement, together in a
+/// transaction unless one of them commits implicitly.
+fn run(conn: &mut Conn, migration: &Migration, file: &Path, record: &str, params: Params) -> Result<Applied> {
+    let script = std::fs::read_to_string(file)?;
+    let statements: Vec<String> = Splitter::default()
+        .split(&script)
+        .into_iter()
+        // Pieces holding only comments are not statements to the server
+        .filter(|statement| !keywords(statement).is_empty())
+        .collect();
+    let transactional = !statements.iter()
+        .any(|statement| keywords(statement).first().is_some_and(|word| IMPLICIT_COMMIT.contains(&word.as_str())));
+
+    let failed = |e: mysql::Error, statement: &str| {
+        let error = RusqlError::from(e);
+        RusqlError::Command(format!("Migration {} {} failed at `{}`: {}", migration.version, migration.name, statement, error))
+    };
+    if transactional {
+        let mut tx = conn.start_transaction(TxOpts::default())?;
+        for statement in &statements {
+            tx.query_drop(statement).map_err(|e| failed(e, statement))?;
+        }
+        tx.exec_drop(record, params)?;
+        tx.commit()?;
+    } else {
+        for statement in &statements {
+            conn.query_drop(statement).map_err(|e| failed(e, statement))?;
+        }
+        conn.exec_drop(record, params)?;
+    }
+    Ok(Applied { version: migration.version, name: migration.name.clone(), statements: statements.len(), transactional })
+}
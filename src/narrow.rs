@@ -0,0 +1,208 @@
+//! Fitting result tables into the terminal width.
+//!
+//! Each column gets a truncation strategy from its type and contents. When a
+//! table is too wide, truncatable columns are shrunk (widest first) down to a
+//! minimum width, and if that is not enough, whole columns are hidden starting
+//! with the least important ones.
+
+use mysql::consts::ColumnType;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Columns are never shrunk below this many characters.
+pub const MIN_COLUMN_WIDTH: usize = 8;
+
+/// Numeric columns up to this width are never truncated.
+const SHORT_NUMERIC_WIDTH: usize = 20;
+
+/// How a column's values are shortened when it has to be narrowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Never truncated (short numbers).
+    Keep,
+    /// `long text…`
+    End,
+    /// `a1b2c3…9f8e` for UUIDs, hashes, and paths where both ends matter.
+    Middle,
+}
+
+/// Result of fitting a table: the width of every visible column, `None` for
+/// hidden ones.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    pub widths: Vec<Option<usize>>,
+    pub strategies: Vec<Strategy>,
+}
+
+impl Layout {
+    pub fn hidden(&self) -> impl Iterator<Item = usize> + '_ {
+        self.widths.iter().enumerate().filter(|(_, w)| w.is_none()).map(|(i, _)| i)
+    }
+}
+
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Pick a strategy from the column type and a sample of its values.
+pub fn strategy_for(column_type: ColumnType, values: &[&str]) -> Strategy {
+    let widest = values.iter().map(|v| display_width(v)).max().unwrap_or(0);
+    if is_numeric(column_type) && widest <= SHORT_NUMERIC_WIDTH {
+        return Strategy::Keep;
+    }
+
+    let identifiers = values.iter().filter(|v| looks_like_identifier(v)).count();
+    if !values.is_empty() && identifiers * 2 >= values.len() {
+        Strategy::Middle
+    } else {
+        Strategy::End
+    }
+}
+
+/// Compute a layout that fits `max_width` terminal columns.
+///
+/// `natural` holds each column's widest value (including the header) and
+/// `hide_order` lists column indexes in the order they may be hidden.
+pub fn fit(natural: &[usize], strategies: &[Strategy], hide_order: &[usize], max_width: usize) -> Layout {
+    let mut widths: Vec<Option<usize>> = natural.iter().map(|&w| Some(w)).collect();
+
+    // Shrink the widest truncatable column one step at a time
+    while total_width(&widths) > max_width {
+        let candidate = widths.iter()
+            .enumerate()
+            .filter(|(i, w)| strategies[*i] != Strategy::Keep && w.is_some_and(|w| w > MIN_COLUMN_WIDTH))
+            .max_by_key(|(_, w)| w.unwrap_or(0))
+            .map(|(i, _)| i);
+        match candidate {
+            Some(i) => widths[i] = widths[i].map(|w| w - 1),
+            None => break,
+        }
+    }
+
+    // Still too wide: hide columns, keeping at least one visible
+    for &i in hide_order {
+        if total_width(&widths) <= max_width || widths.iter().filter(|w| w.is_some()).count() <= 1 {
+            break;
+        }
+        widths[i] = None;
+    }
+
+    Layout { widths, strategies: strategies.to_vec() }
+}
+
+/// Width of the rendered table: `│ a │ b │` adds three characters per column plus one.
+pub fn total_width(widths: &[Option<usize>]) -> usize {
+    widths.iter().flatten().map(|w| w + 3).sum::<usize>() + 1
+}
+
+/// Shorten `text` to at most `width` display columns using `strategy`.
+pub fn truncate(text: &str, width: usize, strategy: Strategy) -> String {
+    if strategy == Strategy::Keep || display_width(text) <= width || width == 0 {
+        return text.to_string();
+    }
+
+    match strategy {
+        Strategy::Middle if width >= 5 => {
+            let tail_width = (width - 1) / 2;
+            let head = take_width(text, width - 1 - tail_width);
+            let tail = take_width_rev(text, tail_width);
+            format!("{}…{}", head, tail)
+        }
+        _ => format!("{}…", take_width(text, width - 1)),
+    }
+}
+
+fn take_width(text: &str, width: usize) -> String {
+    let mut used = 0;
+    text.chars()
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= width
+        })
+        .collect()
+}
+
+fn take_width_rev(text: &str, width: usize) -> String {
+    let mut used = 0;
+    let mut tail: Vec<char> = text.chars()
+        .rev()
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= width
+        })
+        .collect();
+    tail.reverse();
+    tail.into_iter().collect()
+}
+
+/// Order in which columns may be hidden: columns matching `low_priority`
+/// glob patterns first (in pattern order), then the rest from right to left.
+pub fn hide_order(names: &[String], low_priority: &[String]) -> Vec<usize> {
+    let mut order: Vec<usize> = Vec::new();
+    for pattern in low_priority {
+        for (i, name) in names.iter().enumerate() {
+            if !order.contains(&i) && glob_match(pattern, name) {
+                order.push(i);
+            }
+        }
+    }
+    for i in (0..names.len()).rev() {
+        if !order.contains(&i) {
+            order.push(i);
+        }
+    }
+    order
+}
+
+/// Case-insensitive match supporting `*` wildcards.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let name = name.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+
+    let mut rest = name.as_str();
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// Whether values of this type are numbers.
+pub fn is_numeric(column_type: ColumnType) -> bool {
+    matches!(
+        column_type,
+        ColumnType::MYSQL_TYPE_TINY
+            | ColumnType::MYSQL_TYPE_SHORT
+            | ColumnType::MYSQL_TYPE_INT24
+            | ColumnType::MYSQL_TYPE_LONG
+            | ColumnType::MYSQL_TYPE_LONGLONG
+            | ColumnType::MYSQL_TYPE_FLOAT
+            | ColumnType::MYSQL_TYPE_DOUBLE
+            | ColumnType::MYSQL_TYPE_DECIMAL
+            | ColumnType::MYSQL_TYPE_NEWDECIMAL
+            | ColumnType::MYSQL_TYPE_YEAR
+    )
+}
+
+/// UUIDs, long hex hashes, and filesystem paths or URLs.
+fn looks_like_identifier(value: &str) -> bool {
+    let hex_or_dash = value.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    let is_uuid = value.len() == 36 && hex_or_dash && value.matches('-').count() == 4;
+    let is_hash = value.len() >= 32 && value.chars().all(|c| c.is_ascii_hexdigit());
+    let is_path = !value.contains(char::is_whitespace) && value.matches(['/', '\\']).count() >= 2;
+    is_uuid || is_hash || is_path
+}
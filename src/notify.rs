@@ -0,0 +1,121 @@
+use crate::{format_duration, history, MySQLClient, QueryResult};
+use rusql::error::{Result, RusqlError};
+use rusql::statements::fingerprint;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: \\notify [on [seconds]|off]";
+
+/// Run time past which statements are reported when `[notify] after` is unset.
+const DEFAULT_AFTER: Duration = Duration::from_secs(60);
+
+/// Seconds a webhook call may take.
+const WEBHOOK_TIMEOUT: &str = "10";
+
+/// `\notify on [seconds]`: once a statement has run longer than `seconds`,
+/// ring the terminal bell, show a desktop notification and call the
+/// `[notify] webhook` when it finishes, with its run time and rows.
+pub fn set(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    match words.as_slice() {
+        [] => {}
+        ["on"] => client.notify_after = Some(client.notify.after.map_or(DEFAULT_AFTER, Duration::from_secs)),
+        ["on", seconds] => {
+            let seconds = seconds.parse().map_err(|_| RusqlError::Command(USAGE.into()))?;
+            client.notify_after = Some(Duration::from_secs(seconds));
+        }
+        ["off"] => client.notify_after = None,
+        _ => return Err(RusqlError::Command(USAGE.into())),
+    }
+    let message = match client.notify_after {
+        Some(after) => format!(
+            "Notifying when statements running over {} finish{}",
+            format_duration(after.as_secs()),
+            if client.notify.webhook.is_some() { " (with webhook)" } else { "" }
+        ),
+        None => "Notifications off".to_string(),
+    };
+    client.notice(&message);
+    Ok(None)
+}
+
+/// Report a statement that ran past the `\notify` threshold, with its
+/// summary line, the rows it affected or its error. Notifications leave the
+/// terminal, so the statement is sent with its literals replaced by `?`, and
+/// not at all when it looks like it carries credentials or sensitive columns.
+pub fn finished(client: &MySQLClient, statement: &str, elapsed: Duration, outcome: &Result<Option<QueryResult>>, rows_affected: Option<u64>) {
+    if client.notify_after.is_none_or(|after| elapsed < after) {
+        return;
+    }
+    let result = match (outcome, rows_affected) {
+        (Ok(Some(result)), _) => result.summary.clone(),
+        (Ok(None), Some(rows)) => format!("Query OK, {} {} affected", rows, if rows == 1 { "row" } else { "rows" }),
+        (Ok(None), None) => "Done".to_string(),
+        (Err(e), _) => e.to_string(),
+    };
+    let title = match outcome.is_ok() {
+        true => format!("Query finished on {} after {}", client.host, format_duration(elapsed.as_secs())),
+        false => format!("Query failed on {} after {}", client.host, format_duration(elapsed.as_secs())),
+    };
+    let statement = match history::is_sensitive(statement, &client.sensitive_columns) {
+        true => "<redacted>".to_string(),
+        false => fingerprint(statement),
+    };
+    let statement = match statement.char_indices().nth(100) {
+        Some((end, _)) => format!("{}...", &statement[..end]),
+        None => statement,
+    };
+    let body = format!("{}\n{}", statement, result);
+
+    eprint!("\x07");
+    let webhook = client.notify.webhook.clone();
+    // Notifiers can be slow or missing; neither should hold up the prompt
+    thread::spawn(move || {
+        desktop(&title, &body);
+        if let Some(url) = webhook {
+            let text = format!("{}\n{}", title, body);
+            if let Err(e) = post(&url, &serde_json::json!({ "text": text })) {
+                eprintln!("\nCould not call the notify webhook: {}", e);
+            }
+        }
+    });
+}
+
+fn desktop(title: &str, body: &str) {
+    let mut command = match std::env::consts::OS {
+        "macos" => {
+            let mut command = Command::new("osascript");
+            command.args(["-e", "on run argv", "-e", "display notification (item 2 of argv) with title (item 1 of argv)", "-e", "end run"]);
+            command.args([title, body]);
+            command
+        }
+        "windows" => return,
+        _ => {
+            let mut command = Command::new("notify-send");
+            command.args(["--app-name=Rusql", title, body]);
+            command
+        }
+    };
+    let _ = command.stdout(Stdio::null()).stderr(Stdio::null()).status();
+}
+
+/// POST a JSON payload, such as Slack's `{"text": ...}`, with curl.
+pub fn post(url: &str, payload: &serde_json::Value) -> Result<()> {
+    let mut child = Command::new("curl")
+        .args(["-sS", "-f", "-m", WEBHOOK_TIMEOUT, "-X", "POST", "-H", "Content-Type: application/json", "--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| RusqlError::Command(format!("Could not run curl: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(payload.to_string().as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(RusqlError::Command(format!("curl: {}", String::from_utf8_lossy(&output.stderr).trim()))),
+    }
+}
@@ -0,0 +1,311 @@
+use crate::chunked::{chunk_end, execute_with_retry, parse_duration};
+use crate::introspect::{primary_key, split_table};
+use crate::{interrupt, MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::Value;
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::error::{Result, RusqlError};
+use std::time::Duration;
+
+const USAGE: &str = "Usage: \\osc ALTER TABLE <table> <changes> [--chunk <rows>] [--sleep <200ms|2s>] [--dry-run] [--keep-old]";
+
+struct Options {
+    table: String,
+    changes: String,
+    chunk: usize,
+    sleep: Option<Duration>,
+    dry_run: bool,
+    keep_old: bool,
+}
+
+/// Names of the shadow objects of one change.
+struct Shadow {
+    db: String,
+    table: String,
+    key: String,
+    /// Columns in both tables, copied and mirrored.
+    columns: Vec<String>,
+}
+
+impl Shadow {
+    fn original(&self) -> String {
+        quote_table(&format!("{}.{}", self.db, self.table))
+    }
+
+    fn new_table(&self) -> String {
+        quote_table(&format!("{}._{}_new", self.db, self.table))
+    }
+
+    fn old_table(&self) -> String {
+        quote_table(&format!("{}._{}_old", self.db, self.table))
+    }
+
+    fn trigger(&self, event: &str) -> String {
+        quote_table(&format!("{}._osc_{}_{}", self.db, self.table, event))
+    }
+
+    /// Triggers keeping the new table in step with writes to the original
+    /// while rows are copied.
+    fn triggers(&self) -> Vec<String> {
+        let columns: Vec<String> = self.columns.iter().map(|c| quote_identifier(c)).collect();
+        let new_values: Vec<String> = columns.iter().map(|c| format!("NEW.{}", c)).collect();
+        let key = quote_identifier(&self.key);
+        let replace = format!("REPLACE INTO {} ({}) VALUES ({})", self.new_table(), columns.join(", "), new_values.join(", "));
+        let delete = format!("DELETE IGNORE FROM {} WHERE {} = OLD.{}", self.new_table(), key, key);
+        vec![
+            format!("CREATE TRIGGER {} AFTER INSERT ON {} FOR EACH ROW {}", self.trigger("ins"), self.original(), replace),
+            format!(
+                "CREATE TRIGGER {} AFTER UPDATE ON {} FOR EACH ROW BEGIN {}; {}; END",
+                self.trigger("upd"),
+                self.original(),
+                delete,
+                replace
+            ),
+            format!("CREATE TRIGGER {} AFTER DELETE ON {} FOR EACH ROW {}", self.trigger("del"), self.original(), delete),
+        ]
+    }
+
+    /// Copy of the rows with keys in `(?, ?]`; a NULL bound is open-ended.
+    fn copy(&self) -> String {
+        let columns: Vec<String> = self.columns.iter().map(|c| quote_identifier(c)).collect();
+        let key = quote_identifier(&self.key);
+        format!(
+            "INSERT IGNORE INTO {} ({cols}) SELECT {cols} FROM {} \
+             WHERE (? IS NULL OR {key} > ?) AND (? IS NULL OR {key} <= ?) LOCK IN SHARE MODE",
+            self.new_table(),
+            self.original(),
+            cols = columns.join(", "),
+            key = key,
+        )
+    }
+
+    fn drop_triggers(&self) -> Vec<String> {
+        ["ins", "upd", "del"].iter().map(|event| format!("DROP TRIGGER IF EXISTS {}", self.trigger(event))).collect()
+    }
+}
+
+/// `\osc ALTER TABLE t ...`: change a big table without locking it for the
+/// length of a plain ALTER. The change is applied to an empty copy, triggers
+/// mirror writes into it while the rows are copied over in key-range chunks,
+/// and the copy then replaces the table in one atomic RENAME.
+///
+/// `--dry-run` creates and alters the copy to check the change, shows the
+/// plan, and drops the copy again. The old table is dropped at the end
+/// unless `--keep-old` is given.
+pub fn osc(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let options = parse(args)?;
+    let (db, table) = split_table(client, &options.table)?;
+
+    let key = primary_key(client, &db, &table)?;
+    let [key] = key.as_slice() else {
+        return Err(RusqlError::Command(format!("\\osc needs a single-column primary key on '{}.{}'", db, table)));
+    };
+    // A RENAME leaves foreign keys of other tables pointing at the old table
+    let referenced_by: Vec<String> = client.conn.exec(
+        "SELECT DISTINCT CONCAT(TABLE_SCHEMA, '.', TABLE_NAME) FROM information_schema.KEY_COLUMN_USAGE \
+         WHERE REFERENCED_TABLE_SCHEMA = ? AND REFERENCED_TABLE_NAME = ?",
+        (&db, &table),
+    )?;
+    if !referenced_by.is_empty() {
+        return Err(RusqlError::Command(format!(
+            "'{}.{}' is referenced by foreign keys of {}; \\osc cannot swap it",
+            db,
+            table,
+            referenced_by.join(", ")
+        )));
+    }
+    let foreign_keys: Option<u64> = client.conn.exec_first(
+        "SELECT COUNT(*) FROM information_schema.TABLE_CONSTRAINTS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND CONSTRAINT_TYPE = 'FOREIGN KEY'",
+        (&db, &table),
+    )?;
+
+    let mut shadow = Shadow { db, table, key: key.clone(), columns: Vec::new() };
+    let exists: Option<String> = client.conn.exec_first(
+        "SELECT TABLE_NAME FROM information_schema.TABLES WHERE TABLE_SCHEMA = ? AND TABLE_NAME IN (?, ?)",
+        (&shadow.db, format!("_{}_new", shadow.table), format!("_{}_old", shadow.table)),
+    )?;
+    if let Some(name) = exists {
+        return Err(RusqlError::Command(format!("'{}.{}' is left from an earlier run; drop it first", shadow.db, name)));
+    }
+
+    // The new table is created and altered even for a dry run, which checks the change
+    client.conn.query_drop(format!("CREATE TABLE {} LIKE {}", shadow.new_table(), shadow.original()))?;
+    if let Err(e) = client.conn.query_drop(format!("ALTER TABLE {} {}", shadow.new_table(), options.changes)) {
+        let _ = client.conn.query_drop(format!("DROP TABLE {}", shadow.new_table()));
+        return Err(e.into());
+    }
+    shadow.columns = common_columns(client, &shadow)?;
+    // CREATE TABLE ... LIKE does not copy foreign keys; the ALTER must add them back
+    let new_foreign_keys: Option<u64> = client.conn.exec_first(
+        "SELECT COUNT(*) FROM information_schema.TABLE_CONSTRAINTS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND CONSTRAINT_TYPE = 'FOREIGN KEY'",
+        (&shadow.db, format!("_{}_new", shadow.table)),
+    )?;
+    let loses_foreign_keys = new_foreign_keys < foreign_keys;
+    let foreign_key_warning = format!(
+        "{} has foreign keys that the copy lacks; add them to the ALTER with ADD FOREIGN KEY",
+        shadow.original()
+    );
+
+    if options.dry_run {
+        client.conn.query_drop(format!("DROP TABLE {}", shadow.new_table()))?;
+        print_plan(client, &shadow, &options);
+        if loses_foreign_keys {
+            println!("{}", client.paint(&format!("Warning: {}", foreign_key_warning), client.theme.warning));
+        }
+        return Ok(None);
+    }
+    if loses_foreign_keys {
+        client.conn.query_drop(format!("DROP TABLE {}", shadow.new_table()))?;
+        return Err(RusqlError::Command(foreign_key_warning));
+    }
+
+    let outcome = copy(client, &shadow, &options);
+    if let Err(e) = outcome {
+        for statement in shadow.drop_triggers() {
+            let _ = client.conn.query_drop(statement);
+        }
+        let _ = client.conn.query_drop(format!("DROP TABLE IF EXISTS {}", shadow.new_table()));
+        return Err(e);
+    }
+
+    client.conn.query_drop(format!(
+        "RENAME TABLE {} TO {}, {} TO {}",
+        shadow.original(),
+        shadow.old_table(),
+        shadow.new_table(),
+        shadow.original()
+    ))?;
+    // The triggers moved with the original table to its old name
+    for statement in shadow.drop_triggers() {
+        client.conn.query_drop(statement)?;
+    }
+    if options.keep_old {
+        client.notice(&format!("Done; the old table is kept as {}", shadow.old_table()));
+    } else {
+        client.conn.query_drop(format!("DROP TABLE {}", shadow.old_table()))?;
+        client.notice(&format!("Done; {} has the new definition", shadow.original()));
+    }
+    Ok(None)
+}
+
+/// Install the triggers and copy the rows in chunks, with progress.
+fn copy(client: &mut MySQLClient, shadow: &Shadow, options: &Options) -> Result<()> {
+    for statement in shadow.triggers() {
+        client.conn.query_drop(statement)?;
+    }
+
+    let estimate: Option<u64> = client.conn.exec_first(
+        "SELECT TABLE_ROWS FROM information_schema.TABLES WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?",
+        (&shadow.db, &shadow.table),
+    )?;
+    let estimate = estimate.unwrap_or(0).max(1);
+    let mut throttle = client.throttle.clone();
+    if let Some(pause) = options.sleep {
+        throttle = throttle.sleep_every(Some(1)).pause(pause);
+    }
+    let mut throttle = throttle.start();
+
+    let table = format!("{}.{}", shadow.db, shadow.table);
+    let key = quote_identifier(&shadow.key);
+    let copy = shadow.copy();
+    let (mut chunks, mut copied) = (0u64, 0u64);
+    let mut lower: Option<Value> = None;
+    loop {
+        if interrupt::interrupted() {
+            eprintln!();
+            return Err(RusqlError::Cancelled);
+        }
+        let upper = chunk_end(client, &table, &key, lower.as_ref(), options.chunk)?;
+        copied += execute_with_retry(client, &copy, lower.as_ref(), upper.as_ref())?;
+        chunks += 1;
+        eprint!("\rCopied {} of ~{} rows ({:.0}%)", copied, estimate, (copied as f64 * 100.0 / estimate as f64).min(100.0));
+
+        let Some(upper) = upper else { break };
+        lower = Some(upper);
+        throttle.pace(chunks * options.chunk as u64);
+    }
+    eprintln!();
+    Ok(())
+}
+
+/// Columns of the new table that the original also has, in the new order.
+fn common_columns(client: &mut MySQLClient, shadow: &Shadow) -> Result<Vec<String>> {
+    let columns = |client: &mut MySQLClient, table: String| -> Result<Vec<String>> {
+        Ok(client.conn.exec(
+            "SELECT COLUMN_NAME FROM information_schema.COLUMNS \
+             WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND GENERATION_EXPRESSION = '' ORDER BY ORDINAL_POSITION",
+            (&shadow.db, table),
+        )?)
+    };
+    let original = columns(client, shadow.table.clone())?;
+    let new = columns(client, format!("_{}_new", shadow.table))?;
+    if !new.contains(&shadow.key) {
+        return Err(RusqlError::Command(format!("\\osc cannot drop or rename the key column {}", shadow.key)));
+    }
+    Ok(new.into_iter().filter(|column| original.contains(column)).collect())
+}
+
+fn print_plan(client: &MySQLClient, shadow: &Shadow, options: &Options) {
+    client.notice("Dry run: the change is valid; nothing was kept. The run would:");
+    println!("CREATE TABLE {} LIKE {};", shadow.new_table(), shadow.original());
+    println!("ALTER TABLE {} {};", shadow.new_table(), options.changes);
+    for statement in shadow.triggers() {
+        println!("{};", statement);
+    }
+    println!("-- in chunks of {} rows by {}:", options.chunk, shadow.key);
+    println!("{};", shadow.copy());
+    println!("RENAME TABLE {} TO {}, {} TO {};", shadow.original(), shadow.old_table(), shadow.new_table(), shadow.original());
+    for statement in shadow.drop_triggers() {
+        println!("{};", statement);
+    }
+    if !options.keep_old {
+        println!("DROP TABLE {};", shadow.old_table());
+    }
+}
+
+fn parse(args: &str) -> Result<Options> {
+    let (statement, flags) = match args.find(" --") {
+        Some(pos) => (&args[..pos], &args[pos..]),
+        None => (args, ""),
+    };
+    let statement = statement.trim().trim_end_matches(';').trim_end();
+    let word = |text: &str| -> (String, String) {
+        let text = text.trim_start();
+        let (word, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        (word.to_string(), rest.to_string())
+    };
+    let (alter, rest) = word(statement);
+    let (table_word, rest) = word(&rest);
+    let (table, changes) = word(&rest);
+    if !alter.eq_ignore_ascii_case("ALTER") || !table_word.eq_ignore_ascii_case("TABLE") {
+        return Err(RusqlError::Command(USAGE.into()));
+    }
+    let mut options = Options {
+        table,
+        changes: changes.trim().to_string(),
+        chunk: 1000,
+        sleep: None,
+        dry_run: false,
+        keep_old: false,
+    };
+
+    let mut words = flags.split_whitespace();
+    while let Some(flag) = words.next() {
+        match flag {
+            "--chunk" => {
+                options.chunk = words.next().and_then(|n| n.parse().ok()).filter(|&n| n > 0)
+                    .ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+            }
+            "--sleep" => options.sleep = Some(parse_duration(words.next().unwrap_or_default())?),
+            "--dry-run" => options.dry_run = true,
+            "--keep-old" => options.keep_old = true,
+            _ => return Err(RusqlError::Command(USAGE.into())),
+        }
+    }
+    if options.changes.is_empty() {
+        return Err(RusqlError::Command(USAGE.into()));
+    }
+    Ok(options)
+}
@@ -0,0 +1,178 @@
+use crate::notify;
+use rusql::otlp::{export_request, Span};
+use rusql::statements::fingerprint;
+use std::cell::Cell;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Spans sent in one export request at most.
+const BATCH_SIZE: usize = 128;
+
+/// Longest a span waits to be exported.
+const BATCH_DELAY: Duration = Duration::from_secs(2);
+
+/// How long exiting waits for the last spans to be exported.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+enum Message {
+    Span(Span),
+    Flush(Sender<()>),
+}
+
+/// Sends spans to the exporting thread, once `--otel-endpoint` is set.
+static EXPORTER: OnceLock<Sender<Message>> = OnceLock::new();
+
+thread_local! {
+    /// When the running statement's result header arrived, ending the
+    /// execute phase and starting the fetch.
+    static EXECUTED: Cell<Option<SystemTime>> = const { Cell::new(None) };
+    /// How many rows the running statement returned.
+    static FETCHED: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Export spans to the OTLP/HTTP collector at `endpoint` (`http://host:4318`
+/// or its full `/v1/traces` URL) from a background thread, in batches.
+pub fn init(endpoint: &str) {
+    let url = match endpoint.trim_end_matches('/') {
+        traces if traces.ends_with("/v1/traces") => traces.to_string(),
+        base => format!("{}/v1/traces", base),
+    };
+    let (sender, receiver) = mpsc::channel();
+    if EXPORTER.set(sender).is_ok() {
+        thread::spawn(move || export(&url, receiver));
+    }
+}
+
+/// Export the spans still queued, waiting a little for the collector.
+pub fn shutdown() {
+    let Some(exporter) = EXPORTER.get() else { return };
+    let (done, flushed) = mpsc::channel();
+    if exporter.send(Message::Flush(done)).is_ok() {
+        let _ = flushed.recv_timeout(SHUTDOWN_TIMEOUT);
+    }
+}
+
+fn enabled() -> bool {
+    EXPORTER.get().is_some()
+}
+
+fn send(spans: Vec<Span>) {
+    if let Some(exporter) = EXPORTER.get() {
+        for span in spans {
+            let _ = exporter.send(Message::Span(span));
+        }
+    }
+}
+
+fn export(url: &str, receiver: Receiver<Message>) {
+    let mut batch: Vec<Span> = Vec::new();
+    let mut deadline = Instant::now() + BATCH_DELAY;
+    let mut reported = false;
+    loop {
+        let message = receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()));
+        let flushed = match message {
+            Ok(Message::Span(span)) => {
+                if batch.is_empty() {
+                    deadline = Instant::now() + BATCH_DELAY;
+                }
+                batch.push(span);
+                if batch.len() < BATCH_SIZE {
+                    continue;
+                }
+                None
+            }
+            Ok(Message::Flush(done)) => Some(done),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        if !batch.is_empty() {
+            let request = export_request("rusql", &batch);
+            batch.clear();
+            // One complaint is enough when the collector is down
+            match notify::post(url, &request) {
+                Ok(()) => reported = false,
+                Err(e) if !reported => {
+                    eprintln!("\nCould not export traces to {}: {}", url, e);
+                    reported = true;
+                }
+                Err(_) => {}
+            }
+        }
+        deadline = Instant::now() + BATCH_DELAY;
+        if let Some(done) = flushed {
+            let _ = done.send(());
+        }
+    }
+}
+
+/// Mark the end of the running statement's execute phase.
+pub fn executed() {
+    if enabled() {
+        EXECUTED.with(|executed| executed.set(Some(SystemTime::now())));
+    }
+}
+
+/// Record how many rows the running statement returned.
+pub fn fetched(rows: usize) {
+    if enabled() {
+        FETCHED.with(|fetched| fetched.set(Some(rows)));
+    }
+}
+
+/// Where a span's operation ran, for its attributes.
+pub struct Target<'a> {
+    pub host: &'a str,
+    pub port: u16,
+    pub user: &'a str,
+    pub database: Option<&'a str>,
+}
+
+impl Target<'_> {
+    fn describe(&self, span: Span) -> Span {
+        let span = span.attribute("db.system", "mysql")
+            .attribute("server.address", self.host)
+            .attribute("server.port", i64::from(self.port))
+            .attribute("db.user", self.user);
+        match self.database {
+            Some(database) => span.attribute("db.name", database),
+            None => span,
+        }
+    }
+}
+
+/// Trace opening a connection.
+pub fn connect(target: &Target, start: SystemTime, error: Option<String>) {
+    if enabled() {
+        send(vec![target.describe(Span::root("connect", start, SystemTime::now())).error(error)]);
+    }
+}
+
+/// Trace a statement, with its literals replaced by `?`, and its execute and
+/// fetch phases when [`executed`] marked the boundary. `affected` counts for
+/// statements that returned no rows.
+pub fn statement(target: &Target, statement: &str, start: SystemTime, affected: Option<u64>, error: Option<String>) {
+    if !enabled() {
+        return;
+    }
+    let end = SystemTime::now();
+    let sanitized = fingerprint(statement);
+    let operation = sanitized.split_whitespace().next().unwrap_or("statement").to_uppercase();
+    let mut root = target.describe(Span::root(&operation, start, end))
+        .attribute("db.statement", sanitized)
+        .error(error);
+    match (FETCHED.with(|fetched| fetched.take()), affected) {
+        (Some(rows), _) => root = root.attribute("db.response.returned_rows", rows as u64),
+        (None, Some(rows)) => root = root.attribute("db.response.affected_rows", rows),
+        (None, None) => {}
+    }
+
+    let mut spans = Vec::new();
+    if let Some(executed) = EXECUTED.with(|executed| executed.take()).filter(|&executed| executed >= start) {
+        spans.push(root.child("execute", start, executed));
+        spans.push(root.child("fetch", executed, end));
+    }
+    spans.insert(0, root);
+    send(spans);
+}
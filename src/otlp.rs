@@ -0,0 +1,172 @@
+//! Trace spans encoded for the OpenTelemetry protocol (OTLP) over HTTP
+//! with JSON, which collectors accept on `/v1/traces`.
+
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The value of a span attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Attribute {
+    Text(String),
+    Int(i64),
+}
+
+impl From<&str> for Attribute {
+    fn from(value: &str) -> Self {
+        Attribute::Text(value.to_string())
+    }
+}
+
+impl From<String> for Attribute {
+    fn from(value: String) -> Self {
+        Attribute::Text(value)
+    }
+}
+
+impl From<i64> for Attribute {
+    fn from(value: i64) -> Self {
+        Attribute::Int(value)
+    }
+}
+
+impl From<u64> for Attribute {
+    fn from(value: u64) -> Self {
+        Attribute::Int(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+/// A finished operation.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub parent_span_id: Option<[u8; 8]>,
+    pub name: String,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(String, Attribute)>,
+    /// Error message of a failed operation.
+    pub error: Option<String>,
+}
+
+impl Span {
+    /// A span starting a new trace.
+    pub fn root(name: &str, start: SystemTime, end: SystemTime) -> Self {
+        Span {
+            trace_id: random_id(),
+            span_id: random_id(),
+            parent_span_id: None,
+            name: name.to_string(),
+            start,
+            end,
+            attributes: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// A span within this one's trace, below it.
+    pub fn child(&self, name: &str, start: SystemTime, end: SystemTime) -> Self {
+        let mut span = Span::root(name, start, end);
+        span.trace_id = self.trace_id;
+        span.parent_span_id = Some(self.span_id);
+        span
+    }
+
+    pub fn attribute(mut self, key: &str, value: impl Into<Attribute>) -> Self {
+        self.attributes.push((key.to_string(), value.into()));
+        self
+    }
+
+    pub fn error(mut self, message: Option<String>) -> Self {
+        self.error = message;
+        self
+    }
+
+    fn to_json(&self) -> Value {
+        let attributes: Vec<Value> = self.attributes.iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Attribute::Text(text) => json!({ "stringValue": text }),
+                    // 64-bit integers are strings in the JSON encoding
+                    Attribute::Int(number) => json!({ "intValue": number.to_string() }),
+                };
+                json!({ "key": key, "value": value })
+            })
+            .collect();
+        let mut span = json!({
+            "traceId": hex(&self.trace_id),
+            "spanId": hex(&self.span_id),
+            "name": self.name,
+            // SPAN_KIND_CLIENT
+            "kind": 3,
+            "startTimeUnixNano": nanos(self.start).to_string(),
+            "endTimeUnixNano": nanos(self.end).to_string(),
+            "attributes": attributes,
+            "status": match &self.error {
+                // STATUS_CODE_ERROR
+                Some(message) => json!({ "code": 2, "message": message }),
+                None => json!({}),
+            },
+        });
+        if let Some(parent) = &self.parent_span_id {
+            span["parentSpanId"] = hex(parent).into();
+        }
+        span
+    }
+}
+
+/// The body of an export request carrying `spans` of `service`.
+///
+/// ```
+/// use rusql::otlp::{export_request, Span};
+/// use std::time::{Duration, UNIX_EPOCH};
+/// let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let statement = Span::root("SELECT", start, start + Duration::from_millis(40))
+///     .attribute("db.system", "mysql")
+///     .attribute("db.response.returned_rows", 3_u64);
+/// let fetch = statement.child("fetch", start + Duration::from_millis(25), start + Duration::from_millis(40));
+///
+/// let body = export_request("rusql", &[statement.clone(), fetch]);
+/// let spans = &body["resourceSpans"][0]["scopeSpans"][0]["spans"];
+/// assert_eq!(spans[0]["startTimeUnixNano"], "1700000000000000000");
+/// assert_eq!(spans[0]["attributes"][1]["value"]["intValue"], "3");
+/// assert_eq!(spans[1]["traceId"], spans[0]["traceId"]);
+/// assert_eq!(spans[1]["parentSpanId"], spans[0]["spanId"]);
+/// ```
+pub fn export_request(service: &str, spans: &[Span]) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [{ "key": "service.name", "value": { "stringValue": service } }],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "rusql", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans.iter().map(Span::to_json).collect::<Vec<_>>(),
+            }],
+        }],
+    })
+}
+
+/// Random bytes for a trace or span id, never all zero.
+fn random_id<const N: usize>() -> [u8; N] {
+    static COUNTER: AtomicU64 = AtomicU64::new(1);
+    let mut id = [0u8; N];
+    for chunk in id.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        let bytes = hasher.finish().max(1).to_be_bytes();
+        chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+    id
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_nanos())
+}
@@ -0,0 +1,63 @@
+use mysql::{Column, Row};
+use rusql::error::Result;
+use rusql::sink::{write_rows, TableSink};
+use std::io::{self, BufRead, Write};
+use std::iter::Peekable;
+
+/// Lines a page leaves for the table frame, header and the `--More--` prompt.
+const FRAME_LINES: usize = 7;
+
+/// How a paged result ended.
+pub enum Paged {
+    /// Every row was shown.
+    Complete(usize),
+    /// The user quit after this many rows; the rest were not fetched.
+    Quit(usize),
+}
+
+/// Rows per page for the current terminal, if stdout is one.
+pub fn page_size() -> Option<usize> {
+    term_size::dimensions_stdout().map(|(_, height)| height.saturating_sub(FRAME_LINES).max(5))
+}
+
+/// Print rows a screenful at a time, fetching each page only when asked for.
+///
+/// Every page is its own table cloned from `template`, so column widths fit
+/// the rows on that page.
+pub fn page<I>(template: &TableSink, columns: &[Column], rows: &mut Peekable<I>, page_size: usize) -> Result<Paged>
+where
+    I: Iterator<Item = mysql::Result<Row>>,
+{
+    let mut shown = 0;
+    loop {
+        let page = rows.by_ref().take(page_size).collect::<mysql::Result<Vec<Row>>>()?;
+        shown += page.len();
+
+        let mut sink = template.clone();
+        write_rows(&mut sink, columns, page, "")?;
+        let (table, _) = sink.finish();
+        table.printstd();
+
+        if rows.peek().is_none() {
+            return Ok(Paged::Complete(shown));
+        }
+        if !more()? {
+            return Ok(Paged::Quit(shown));
+        }
+    }
+}
+
+/// Show the `--More--` prompt; false when the user asked to quit.
+fn more() -> Result<bool> {
+    let mut stdout = io::stdout();
+    write!(stdout, "--More-- (q to quit, Enter for next page) ")?;
+    stdout.flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer)? == 0 {
+        return Ok(false);
+    }
+    // Erase the prompt so pages follow each other directly
+    write!(stdout, "\x1B[1A\x1B[2K")?;
+    Ok(!answer.trim().eq_ignore_ascii_case("q"))
+}
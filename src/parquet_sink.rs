@@ -0,0 +1,305 @@
+//! Parquet output keeping column types: integers, decimals, dates and
+//! timestamps are written as their Arrow types rather than as text.
+
+use crate::format::format_value;
+use crate::sink::{OutputSink, BINARY_CHARSET};
+use arrow_array::types::{
+    Date32Type, Decimal128Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    TimestampMicrosecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{ArrayRef, ArrowPrimitiveType, BinaryArray, BooleanArray, PrimitiveArray, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{NaiveDate, NaiveDateTime};
+use mysql::consts::{ColumnFlags, ColumnType};
+use mysql::{Column, Value};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+/// Rows buffered before they are written as a row group.
+const BATCH_ROWS: usize = 8192;
+
+/// A Parquet file with one column per result column. Rows are buffered and
+/// written a batch at a time; the file is complete once
+/// [`OutputSink::summary`] has been called.
+///
+/// TIME values, which can exceed a day, are written as text, and
+/// zero dates (`0000-00-00`) as NULL.
+///
+/// ```
+/// use mysql::consts::ColumnType;
+/// use mysql::{Column, Value};
+/// use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+/// use rusql::parquet_sink::ParquetSink;
+/// use rusql::sink::OutputSink;
+///
+/// let path = std::env::temp_dir().join("rusql-doctest.parquet");
+/// let columns = [
+///     Column::new(ColumnType::MYSQL_TYPE_LONGLONG).with_name(b"id"),
+///     // DECIMAL(5,2): the display length adds the sign and the point
+///     Column::new(ColumnType::MYSQL_TYPE_NEWDECIMAL).with_name(b"price").with_column_length(7).with_decimals(2),
+///     Column::new(ColumnType::MYSQL_TYPE_DATETIME).with_name(b"sold_at"),
+/// ];
+/// let mut sink = ParquetSink::new(std::fs::File::create(&path)?);
+/// sink.begin(&columns)?;
+/// for row in [["1", "9.5", "2024-05-01 10:00:00"], ["2", "-120.25", "2024-05-02 11:30:00.250"]] {
+///     sink.row_began()?;
+///     for (index, text) in row.iter().enumerate() {
+///         sink.cell(index, &Value::from(*text))?;
+///     }
+///     sink.row_ended()?;
+/// }
+/// sink.summary("2 rows in set")?;
+///
+/// let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path)?)?;
+/// assert_eq!(reader.schema().field(1).data_type(), &arrow_schema::DataType::Decimal128(5, 2));
+/// let batch = reader.build()?.next().unwrap()?;
+/// assert_eq!(batch.num_rows(), 2);
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct ParquetSink<W: Write + Send> {
+    out: Option<W>,
+    writer: Option<ArrowWriter<W>>,
+    schema: SchemaRef,
+    /// Buffered values, one vector per column.
+    columns: Vec<Vec<Value>>,
+    rows: usize,
+}
+
+impl<W: Write + Send> ParquetSink<W> {
+    pub fn new(out: W) -> Self {
+        ParquetSink { out: Some(out), writer: None, schema: Arc::new(Schema::empty()), columns: Vec::new(), rows: 0 }
+    }
+
+    fn flush_batch(&mut self) -> io::Result<()> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let arrays = self.schema.fields().iter().zip(&mut self.columns)
+            .map(|(field, values)| array(field, &std::mem::take(values)))
+            .collect::<io::Result<Vec<ArrayRef>>>()?;
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays).map_err(io::Error::other)?;
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write(&batch).map_err(io::Error::other)?;
+        }
+        self.rows = 0;
+        Ok(())
+    }
+}
+
+impl<W: Write + Send> OutputSink for ParquetSink<W> {
+    fn begin(&mut self, columns: &[Column]) -> io::Result<()> {
+        let fields: Vec<Field> = columns.iter()
+            .map(|column| Field::new(column.name_str(), data_type(column), true))
+            .collect();
+        self.schema = Arc::new(Schema::new(fields));
+        self.columns = vec![Vec::new(); columns.len()];
+        let out = self.out.take().ok_or_else(|| io::Error::other("a Parquet file holds a single result"))?;
+        let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let writer = ArrowWriter::try_new(out, self.schema.clone(), Some(properties)).map_err(io::Error::other)?;
+        self.writer = Some(writer);
+        Ok(())
+    }
+
+    fn row_began(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn cell(&mut self, index: usize, value: &Value) -> io::Result<()> {
+        self.columns[index].push(value.clone());
+        Ok(())
+    }
+
+    fn row_ended(&mut self) -> io::Result<()> {
+        self.rows += 1;
+        match self.rows >= BATCH_ROWS {
+            true => self.flush_batch(),
+            false => Ok(()),
+        }
+    }
+
+    fn summary(&mut self, _summary: &str) -> io::Result<()> {
+        self.flush_batch()?;
+        if let Some(writer) = self.writer.take() {
+            writer.close().map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+/// The Arrow type of a result column.
+fn data_type(column: &Column) -> DataType {
+    let unsigned = column.flags().contains(ColumnFlags::UNSIGNED_FLAG);
+    match column.column_type() {
+        ColumnType::MYSQL_TYPE_TINY | ColumnType::MYSQL_TYPE_BIT if column.column_length() == 1 => DataType::Boolean,
+        ColumnType::MYSQL_TYPE_TINY if unsigned => DataType::UInt8,
+        ColumnType::MYSQL_TYPE_TINY => DataType::Int8,
+        ColumnType::MYSQL_TYPE_SHORT if unsigned => DataType::UInt16,
+        ColumnType::MYSQL_TYPE_SHORT => DataType::Int16,
+        ColumnType::MYSQL_TYPE_YEAR => DataType::UInt16,
+        ColumnType::MYSQL_TYPE_INT24 | ColumnType::MYSQL_TYPE_LONG if unsigned => DataType::UInt32,
+        ColumnType::MYSQL_TYPE_INT24 | ColumnType::MYSQL_TYPE_LONG => DataType::Int32,
+        ColumnType::MYSQL_TYPE_LONGLONG if unsigned => DataType::UInt64,
+        ColumnType::MYSQL_TYPE_LONGLONG => DataType::Int64,
+        ColumnType::MYSQL_TYPE_BIT => DataType::UInt64,
+        ColumnType::MYSQL_TYPE_FLOAT => DataType::Float32,
+        ColumnType::MYSQL_TYPE_DOUBLE => DataType::Float64,
+        ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => {
+            // The display length counts the sign and the decimal point
+            let scale = column.decimals();
+            let precision = (column.column_length() as u64)
+                .saturating_sub(u64::from(scale > 0) + u64::from(!unsigned));
+            match precision {
+                1..=38 => DataType::Decimal128(precision as u8, scale as i8),
+                _ => DataType::Utf8,
+            }
+        }
+        ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE => DataType::Date32,
+        ColumnType::MYSQL_TYPE_DATETIME
+        | ColumnType::MYSQL_TYPE_DATETIME2
+        | ColumnType::MYSQL_TYPE_TIMESTAMP
+        | ColumnType::MYSQL_TYPE_TIMESTAMP2 => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ColumnType::MYSQL_TYPE_TINY_BLOB
+        | ColumnType::MYSQL_TYPE_BLOB
+        | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+        | ColumnType::MYSQL_TYPE_LONG_BLOB
+        | ColumnType::MYSQL_TYPE_VARCHAR
+        | ColumnType::MYSQL_TYPE_VAR_STRING
+        | ColumnType::MYSQL_TYPE_STRING
+            if column.character_set() == BINARY_CHARSET =>
+        {
+            DataType::Binary
+        }
+        _ => DataType::Utf8,
+    }
+}
+
+/// Build one column of a batch from its buffered values.
+fn array(field: &Field, values: &[Value]) -> io::Result<ArrayRef> {
+    let name = field.name();
+    Ok(match field.data_type() {
+        DataType::Boolean => Arc::new(
+            values.iter()
+                .map(|value| integer(name, value).map(|n| n.map(|n| n != 0)))
+                .collect::<io::Result<BooleanArray>>()?,
+        ),
+        DataType::Int8 => primitive::<Int8Type>(name, values, |text| text.parse().ok())?,
+        DataType::Int16 => primitive::<Int16Type>(name, values, |text| text.parse().ok())?,
+        DataType::Int32 => primitive::<Int32Type>(name, values, |text| text.parse().ok())?,
+        DataType::Int64 => primitive::<Int64Type>(name, values, |text| text.parse().ok())?,
+        DataType::UInt8 => primitive::<UInt8Type>(name, values, |text| text.parse().ok())?,
+        DataType::UInt16 => primitive::<UInt16Type>(name, values, |text| text.parse().ok())?,
+        DataType::UInt32 => primitive::<UInt32Type>(name, values, |text| text.parse().ok())?,
+        DataType::UInt64 => Arc::new(
+            values.iter()
+                .map(|value| integer(name, value).map(|n| n.map(|n| n as u64)))
+                .collect::<io::Result<PrimitiveArray<UInt64Type>>>()?,
+        ),
+        DataType::Float32 => primitive::<Float32Type>(name, values, |text| text.parse().ok())?,
+        DataType::Float64 => primitive::<Float64Type>(name, values, |text| text.parse().ok())?,
+        DataType::Decimal128(precision, scale) => {
+            let scale = *scale as usize;
+            let array = parse_all::<Decimal128Type>(name, values, |text| decimal(text, scale))?;
+            Arc::new(array.with_precision_and_scale(*precision, scale as i8).map_err(io::Error::other)?)
+        }
+        DataType::Date32 => primitive::<Date32Type>(name, values, |text| {
+            let date = NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()?;
+            Some((date - NaiveDate::from_ymd_opt(1970, 1, 1)?).num_days() as i32)
+        })?,
+        DataType::Timestamp(..) => primitive::<TimestampMicrosecondType>(name, values, |text| {
+            let time = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").ok()?;
+            Some(time.and_utc().timestamp_micros())
+        })?,
+        DataType::Binary => Arc::new(
+            values.iter()
+                .map(|value| match value {
+                    Value::NULL => None,
+                    Value::Bytes(bytes) => Some(bytes.clone()),
+                    other => Some(format_value(other).into_bytes()),
+                })
+                .collect::<BinaryArray>(),
+        ),
+        _ => Arc::new(
+            values.iter()
+                .map(|value| match value {
+                    Value::NULL => None,
+                    other => Some(text(other)),
+                })
+                .collect::<StringArray>(),
+        ),
+    })
+}
+
+fn primitive<T: ArrowPrimitiveType>(
+    name: &str,
+    values: &[Value],
+    parse: impl Fn(&str) -> Option<T::Native>,
+) -> io::Result<ArrayRef> {
+    Ok(Arc::new(parse_all::<T>(name, values, parse)?))
+}
+
+/// Values parsed from their text; NULL and zero dates become nulls.
+fn parse_all<T: ArrowPrimitiveType>(
+    name: &str,
+    values: &[Value],
+    parse: impl Fn(&str) -> Option<T::Native>,
+) -> io::Result<PrimitiveArray<T>> {
+    values.iter()
+        .map(|value| {
+            let text = match value {
+                Value::NULL => return Ok(None),
+                other => text(other),
+            };
+            if text.starts_with("0000-00-00") {
+                return Ok(None);
+            }
+            parse(&text).map(Some).ok_or_else(|| unexpected(name, &text))
+        })
+        .collect()
+}
+
+/// An integer from text, or from the big-endian bytes of a BIT value.
+fn integer(name: &str, value: &Value) -> io::Result<Option<i128>> {
+    match value {
+        Value::NULL => Ok(None),
+        Value::Int(n) => Ok(Some(*n as i128)),
+        Value::UInt(n) => Ok(Some(*n as i128)),
+        Value::Bytes(bytes) => match std::str::from_utf8(bytes).ok().and_then(|text| text.parse().ok()) {
+            Some(n) => Ok(Some(n)),
+            None if bytes.len() <= 8 => Ok(Some(bytes.iter().fold(0, |n, &byte| (n << 8) | byte as i128))),
+            None => Err(unexpected(name, &String::from_utf8_lossy(bytes))),
+        },
+        other => Err(unexpected(name, &format_value(other))),
+    }
+}
+
+/// `-12.5` with scale 2 -> -1250.
+fn decimal(text: &str, scale: usize) -> Option<i128> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if fraction.len() > scale || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let unscaled: i128 = format!("{}{:0<scale$}", whole, fraction, scale = scale).parse().ok()?;
+    Some(if negative { -unscaled } else { unscaled })
+}
+
+/// The text of a value, keeping the microseconds of binary protocol dates.
+fn text(value: &Value) -> String {
+    match value {
+        Value::Date(y, m, d, h, i, s, micros) if *micros > 0 => {
+            format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}", y, m, d, h, i, s, micros)
+        }
+        other => format_value(other),
+    }
+}
+
+fn unexpected(column: &str, value: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("unexpected value '{}' in column '{}'", value, column))
+}
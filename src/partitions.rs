@@ -0,0 +1,193 @@
+use crate::{introspect, MySQLClient, QueryResult};
+use chrono::{Datelike, Local, NaiveDate};
+use mysql::prelude::*;
+use mysql::Params;
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::error::{Result, RusqlError};
+use rusql::statements::{tokens, Token};
+
+const USAGE: &str = "Usage: \\partitions <table> [add-month [YYYY-MM] | drop-before YYYY-MM-DD] [--apply]";
+
+/// One partition as listed in `information_schema.PARTITIONS`.
+struct Partition {
+    name: String,
+    method: String,
+    expression: String,
+    description: Option<String>,
+}
+
+/// `\partitions table [action] [--apply]`: show the partition layout, or
+/// generate maintenance statements for range-partitioned tables. Statements
+/// are only printed unless `--apply` is given.
+pub fn partitions(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut words: Vec<&str> = args.split_whitespace().collect();
+    let apply = words.contains(&"--apply");
+    words.retain(|w| *w != "--apply");
+
+    let Some((&name, action)) = words.split_first() else {
+        return Err(RusqlError::Command(USAGE.into()));
+    };
+    let (db, table) = introspect::split_table(client, name)?;
+
+    let statements = match action {
+        [] => return show_layout(client, &db, &table),
+        ["add-month"] => vec![add_month(client, &db, &table, next_month())?],
+        ["add-month", month] => vec![add_month(client, &db, &table, parse_month(month)?)?],
+        ["drop-before", date] => drop_before(client, &db, &table, date)?,
+        _ => return Err(RusqlError::Command(USAGE.into())),
+    };
+
+    if statements.is_empty() {
+        client.notice("Nothing to do");
+        return Ok(None);
+    }
+    for statement in &statements {
+        println!("{};", client.paint(statement, client.theme.cells.text));
+    }
+    if !apply {
+        client.notice("Preview only; add --apply to run");
+        return Ok(None);
+    }
+    for statement in &statements {
+        client.conn.query_drop(statement)?;
+    }
+    client.notice(&format!("{} statement(s) applied", statements.len()));
+    Ok(None)
+}
+
+fn show_layout(client: &mut MySQLClient, db: &str, table: &str) -> Result<Option<QueryResult>> {
+    let mut result = client.query_result(
+        "SELECT PARTITION_NAME AS `Partition`, \
+         CONCAT(PARTITION_METHOD, '(', PARTITION_EXPRESSION, ')') AS `Method`, \
+         PARTITION_DESCRIPTION AS `Bound`, TABLE_ROWS AS `Rows (est.)`, \
+         CONCAT(ROUND((DATA_LENGTH + INDEX_LENGTH) / 1048576, 1), ' MiB') AS `Size` \
+         FROM information_schema.PARTITIONS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND PARTITION_NAME IS NOT NULL \
+         ORDER BY PARTITION_ORDINAL_POSITION",
+        Params::Positional(vec![db.into(), table.into()]),
+    )?;
+    if result.table.len() <= 1 {
+        return Err(RusqlError::Command(format!("Table '{}.{}' is not partitioned", db, table)));
+    }
+    result.summary = format!("{} partitions in {}.{}", result.table.len() - 1, db, table);
+    Ok(Some(result))
+}
+
+fn load(client: &mut MySQLClient, db: &str, table: &str) -> Result<Vec<Partition>> {
+    let rows: Vec<(String, String, String, Option<String>)> = client.conn.exec(
+        "SELECT PARTITION_NAME, PARTITION_METHOD, PARTITION_EXPRESSION, PARTITION_DESCRIPTION \
+         FROM information_schema.PARTITIONS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND PARTITION_NAME IS NOT NULL \
+         ORDER BY PARTITION_ORDINAL_POSITION",
+        (db, table),
+    )?;
+    let partitions: Vec<Partition> = rows.into_iter()
+        .map(|(name, method, expression, description)| Partition { name, method, expression, description })
+        .collect();
+
+    match partitions.first() {
+        None => Err(RusqlError::Command(format!("Table '{}.{}' is not partitioned", db, table))),
+        Some(p) if !p.method.starts_with("RANGE") => {
+            Err(RusqlError::Command(format!("{}.{} is partitioned by {}, not RANGE", db, table, p.method)))
+        }
+        Some(_) => Ok(partitions),
+    }
+}
+
+/// The partitioning expression evaluated for a date, e.g. `to_days('2026-12-01')`
+/// for `RANGE (to_days(created_at))` or `'2026-12-01'` for `RANGE COLUMNS`.
+fn bound_for(partition: &Partition, date: NaiveDate) -> String {
+    let literal = format!("'{}'", date.format("%Y-%m-%d"));
+    if partition.method == "RANGE COLUMNS" {
+        return literal;
+    }
+    let expression = &partition.expression;
+    let tokens = tokens(expression);
+    let is_function = |end: usize| expression[end..].trim_start().starts_with('(');
+    let identifiers: Vec<&Token> = tokens.iter()
+        .filter(|token| token.quoted || (!is_function(token.end) && token.word.contains(char::is_alphabetic)))
+        .collect();
+    // The server backquotes the column; an unquoted word is the fallback
+    let Some(column) = identifiers.iter().rfind(|token| token.quoted).or(identifiers.last()) else {
+        return expression.clone();
+    };
+    let mut bound = expression.clone();
+    for token in identifiers.iter().rev().filter(|token| token.word == column.word) {
+        bound.replace_range(token.start..token.end, &literal);
+    }
+    bound
+}
+
+/// Partition holding `month`, split off a trailing `MAXVALUE` partition when
+/// there is one and added at the end otherwise.
+fn add_month(client: &mut MySQLClient, db: &str, table: &str, month: NaiveDate) -> Result<String> {
+    let partitions = load(client, db, table)?;
+    let name = format!("p{}", month.format("%Y%m"));
+    if partitions.iter().any(|p| p.name == name) {
+        return Err(RusqlError::Command(format!("Partition {} already exists", name)));
+    }
+
+    let next = month.checked_add_months(chrono::Months::new(1))
+        .ok_or_else(|| RusqlError::Command("Month out of range".into()))?;
+    let last = &partitions[partitions.len() - 1];
+    let new_partition = format!(
+        "PARTITION {} VALUES LESS THAN ({})",
+        quote_identifier(&name),
+        bound_for(last, next)
+    );
+    let target = quote_table(&format!("{}.{}", db, table));
+
+    if last.description.as_deref() == Some("MAXVALUE") {
+        Ok(format!(
+            "ALTER TABLE {} REORGANIZE PARTITION {} INTO ({}, PARTITION {} VALUES LESS THAN MAXVALUE)",
+            target,
+            quote_identifier(&last.name),
+            new_partition,
+            quote_identifier(&last.name)
+        ))
+    } else {
+        Ok(format!("ALTER TABLE {} ADD PARTITION ({})", target, new_partition))
+    }
+}
+
+/// Drop every partition whose rows all sort before `date`.
+fn drop_before(client: &mut MySQLClient, db: &str, table: &str, date: &str) -> Result<Vec<String>> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| RusqlError::Command(format!("Invalid date '{}', expected YYYY-MM-DD", date)))?;
+    let partitions = load(client, db, table)?;
+
+    let mut names = Vec::new();
+    for partition in &partitions {
+        let Some(description) = partition.description.as_deref().filter(|d| *d != "MAXVALUE") else {
+            continue;
+        };
+        // Let the server compare bounds so every expression type is handled alike
+        let query = format!("SELECT {} <= {}", description, bound_for(partition, date));
+        if client.conn.query_first::<u8, _>(query)?.unwrap_or(0) == 1 {
+            names.push(quote_identifier(&partition.name));
+        }
+    }
+
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+    if names.len() == partitions.len() {
+        return Err(RusqlError::Command("Refusing to drop every partition".into()));
+    }
+    Ok(vec![format!(
+        "ALTER TABLE {} DROP PARTITION {}",
+        quote_table(&format!("{}.{}", db, table)),
+        names.join(", ")
+    )])
+}
+
+fn next_month() -> NaiveDate {
+    let today = Local::now().date_naive();
+    let first = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap_or(today);
+    first.checked_add_months(chrono::Months::new(1)).unwrap_or(first)
+}
+
+fn parse_month(month: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| RusqlError::Command(format!("Invalid month '{}', expected YYYY-MM", month)))
+}
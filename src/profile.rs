@@ -0,0 +1,116 @@
+use crate::introspect::split_table;
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::{Row, Value};
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::error::{Result, RusqlError};
+use rusql::format::format_value;
+use rusql::mask::ColumnMasks;
+
+/// Most frequent values listed per column.
+const TOP_VALUES: usize = 5;
+
+/// Characters of a value shown in the report before it is cut.
+const VALUE_WIDTH: usize = 24;
+
+/// Aggregates computed for every column, in this order, in one scan.
+const STATS_PER_COLUMN: usize = 5;
+
+/// `\profile-table [db.]table`: per-column null percentage, distinct count,
+/// min/max, average length and most frequent values. Column statistics come
+/// from a single scan, followed by one GROUP BY per column for its top values.
+/// Values of sensitive columns are shown masked.
+pub fn profile_table(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        return Err(RusqlError::Command("Usage: \\profile-table [db.]table".into()));
+    }
+    let (db, table) = split_table(client, args)?;
+    let start_time = std::time::Instant::now();
+
+    let columns: Vec<(String, String, String)> = client.conn.exec(
+        "SELECT COLUMN_NAME, DATA_TYPE, COLUMN_TYPE FROM information_schema.COLUMNS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+        (&db, &table),
+    )?;
+    if columns.is_empty() {
+        return Err(RusqlError::Command(format!("Table '{}.{}' doesn't exist", db, table)));
+    }
+    let from = quote_table(&format!("{}.{}", db, table));
+
+    let aggregates: Vec<String> = columns.iter()
+        .map(|(name, data_type, _)| {
+            let column = quote_identifier(name);
+            // Comparing blobs, JSON or geometry values tells nothing useful
+            let (min, max) = if comparable(data_type) {
+                (format!("MIN({})", column), format!("MAX({})", column))
+            } else {
+                ("NULL".to_string(), "NULL".to_string())
+            };
+            format!(
+                "SUM({0} IS NULL), COUNT(DISTINCT {0}), {1}, {2}, AVG(LENGTH({0}))",
+                column, min, max,
+            )
+        })
+        .collect();
+    let stats: Row = client.conn
+        .query_first(format!("SELECT COUNT(*), {} FROM {}", aggregates.join(", "), from))?
+        .ok_or_else(|| RusqlError::Command(format!("Could not scan '{}.{}'", db, table)))?;
+    let total: u64 = stats.get(0).unwrap_or(0);
+
+    let mut rows = Vec::with_capacity(columns.len());
+    for (i, (name, data_type, column_type)) in columns.iter().enumerate() {
+        let stat = |n: usize| stats.as_ref(1 + i * STATS_PER_COLUMN + n).cloned().unwrap_or(Value::NULL);
+        let nulls: u64 = mysql::from_value_opt(stat(0)).unwrap_or(0);
+        let masks = client.sensitive_rules.columns(Some(&db), &table, &[name]);
+        let mut bounds = vec![vec![stat(2)], vec![stat(3)]];
+        masks.apply(&mut bounds);
+        let top = if comparable(data_type) { top_values(client, name, &from, &masks)? } else { String::new() };
+        rows.push(vec![
+            name.clone(),
+            column_type.clone(),
+            format!("{:.1}%", if total == 0 { 0.0 } else { nulls as f64 * 100.0 / total as f64 }),
+            format_value(&stat(1)),
+            shorten(&format_value(&bounds[0][0])),
+            shorten(&format_value(&bounds[1][0])),
+            mysql::from_value_opt::<f64>(stat(4)).map_or_else(|_| "NULL".to_string(), |avg| format!("{:.1}", avg)),
+            top,
+        ]);
+    }
+
+    let summary = format!("{} rows in {}.{} ({:.2} sec)", total, db, table, start_time.elapsed().as_secs_f64());
+    let headers = ["Column", "Type", "Null %", "Distinct", "Min", "Max", "Avg length", "Top values"];
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+/// Whether MIN, MAX and GROUP BY on the type give readable answers.
+fn comparable(data_type: &str) -> bool {
+    !matches!(
+        data_type.to_lowercase().as_str(),
+        "blob" | "tinyblob" | "mediumblob" | "longblob" | "binary" | "varbinary" | "json"
+            | "geometry" | "point" | "linestring" | "polygon" | "multipoint" | "multilinestring"
+            | "multipolygon" | "geometrycollection"
+    )
+}
+
+/// The most frequent non-NULL values as `value (count)`, masked by `masks`.
+fn top_values(client: &mut MySQLClient, name: &str, from: &str, masks: &ColumnMasks) -> Result<String> {
+    let column = quote_identifier(name);
+    let found: Vec<(Value, u64)> = client.conn.query(format!(
+        "SELECT {0}, COUNT(*) AS n FROM {1} WHERE {0} IS NOT NULL GROUP BY {0} ORDER BY n DESC LIMIT {2}",
+        column, from, TOP_VALUES,
+    ))?;
+    let mut values: Vec<Vec<Value>> = found.iter().map(|(value, _)| vec![value.clone()]).collect();
+    masks.apply(&mut values);
+    Ok(values.iter().zip(&found)
+        .map(|(value, (_, count))| format!("{} ({})", shorten(&format_value(&value[0])), count))
+        .collect::<Vec<_>>()
+        .join(", "))
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() <= VALUE_WIDTH {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(VALUE_WIDTH - 1).collect();
+    format!("{}…", cut)
+}
@@ -0,0 +1,119 @@
+use colored::*;
+use mysql::Row;
+use rusql::bulk::value_size;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Statements finishing faster than this never show the spinner.
+const SHOW_AFTER: Duration = Duration::from_millis(300);
+
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+const FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const PLAIN_FRAMES: &[&str] = &["|", "/", "-", "\\"];
+
+#[derive(Default)]
+struct Counters {
+    done: AtomicBool,
+    rows: AtomicU64,
+    bytes: AtomicU64,
+}
+
+/// Live status line on stderr for a running statement: spinner, elapsed time
+/// and, once rows stream in, rows fetched and bytes received.
+///
+/// Drawn from its own thread since the driver blocks the main one; only when
+/// stderr is a terminal, and without ANSI colors when colors are off.
+pub struct Spinner {
+    counters: Arc<Counters>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    /// Start the spinner, or a no-op one when `enabled` is false or stderr is not a TTY.
+    pub fn start(enabled: bool, use_colors: bool) -> Spinner {
+        let counters = Arc::new(Counters::default());
+        if !enabled || !io::stderr().is_terminal() {
+            return Spinner { counters, handle: None };
+        }
+
+        let shared = counters.clone();
+        let handle = thread::spawn(move || draw(&shared, use_colors));
+        Spinner { counters, handle: Some(handle) }
+    }
+
+    /// Count a fetched row.
+    pub fn row(&self, row: &Row) {
+        let bytes: usize = (0..row.len()).filter_map(|i| row.as_ref(i)).map(value_size).sum();
+        self.counters.rows.fetch_add(1, Ordering::Relaxed);
+        self.counters.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Stop and erase the status line; called before printing results.
+    pub fn finish(&mut self) {
+        self.counters.done.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+fn draw(counters: &Counters, use_colors: bool) {
+    let started = Instant::now();
+    let frames = if use_colors { FRAMES } else { PLAIN_FRAMES };
+    let mut drawn = false;
+    let mut frame = 0;
+
+    while !counters.done.load(Ordering::SeqCst) {
+        thread::park_timeout(REDRAW_INTERVAL);
+        if started.elapsed() < SHOW_AFTER || counters.done.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        let mut line = format!("{} {:.1} sec", frames[frame % frames.len()], started.elapsed().as_secs_f64());
+        let rows = counters.rows.load(Ordering::Relaxed);
+        if rows > 0 {
+            line.push_str(&format!(
+                "  {} {} fetched, {}",
+                rows,
+                if rows == 1 { "row" } else { "rows" },
+                human_bytes(counters.bytes.load(Ordering::Relaxed))
+            ));
+        }
+        let line = if use_colors { line.dimmed().to_string() } else { line };
+
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "\r\x1B[2K{}", line);
+        let _ = stderr.flush();
+        drawn = true;
+        frame += 1;
+    }
+
+    if drawn {
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "\r\x1B[2K");
+        let _ = stderr.flush();
+    }
+}
+
+/// Byte count in the largest binary unit that keeps it above 1, e.g. `1.5 MB`.
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", size, UNITS[unit]) }
+}
@@ -0,0 +1,97 @@
+//! Metrics in the Prometheus text exposition format.
+
+use std::fmt::Write;
+
+/// A metric's labels, by name, and value.
+pub type Sample = (Vec<(String, String)>, f64);
+
+/// The kind of a metric, as declared in its `# TYPE` line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MetricKind {
+    #[default]
+    Gauge,
+    Counter,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Gauge => "gauge",
+            MetricKind::Counter => "counter",
+        }
+    }
+}
+
+impl std::str::FromStr for MetricKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "gauge" => Ok(MetricKind::Gauge),
+            "counter" => Ok(MetricKind::Counter),
+            other => Err(format!("unknown metric type '{}'", other)),
+        }
+    }
+}
+
+/// A scrape's worth of metrics being written out.
+///
+/// ```
+/// use rusql::prometheus::{Exposition, MetricKind};
+/// let mut page = Exposition::default();
+/// page.metric("shop_open_orders", "Orders not shipped yet", MetricKind::Gauge, &[
+///     (vec![("country".to_string(), "NL".to_string())], 12.0),
+///     (vec![("country".to_string(), "say \"hi\"".to_string())], 3.5),
+/// ]);
+/// let text = page.finish();
+/// assert!(text.starts_with("# HELP shop_open_orders Orders not shipped yet\n# TYPE shop_open_orders gauge\n"));
+/// assert!(text.contains("shop_open_orders{country=\"NL\"} 12\n"));
+/// assert!(text.contains(r#"shop_open_orders{country="say \"hi\""} 3.5"#));
+/// ```
+#[derive(Debug, Default)]
+pub struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    /// Add a metric and its samples, each with its labels.
+    pub fn metric(&mut self, name: &str, help: &str, kind: MetricKind, samples: &[Sample]) {
+        let name = metric_name(name);
+        let _ = writeln!(self.text, "# HELP {} {}", name, help.replace('\\', "\\\\").replace('\n', "\\n"));
+        let _ = writeln!(self.text, "# TYPE {} {}", name, kind.as_str());
+        for (labels, value) in samples {
+            self.text.push_str(&name);
+            if !labels.is_empty() {
+                let labels: Vec<String> = labels.iter()
+                    .map(|(label, value)| format!("{}=\"{}\"", metric_name(label), escape_label(value)))
+                    .collect();
+                let _ = write!(self.text, "{{{}}}", labels.join(","));
+            }
+            let _ = writeln!(self.text, " {}", value);
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.text
+    }
+}
+
+/// A name made of the characters Prometheus allows, e.g. for a column name.
+///
+/// ```
+/// assert_eq!(rusql::prometheus::metric_name("Innodb rows-read"), "Innodb_rows_read");
+/// assert_eq!(rusql::prometheus::metric_name("2xx"), "_2xx");
+/// ```
+pub fn metric_name(name: &str) -> String {
+    let mut cleaned: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' })
+        .collect();
+    if cleaned.is_empty() || cleaned.starts_with(|c: char| c.is_ascii_digit()) {
+        cleaned.insert(0, '_');
+    }
+    cleaned
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,63 @@
+use chrono::Local;
+
+/// Values available to prompt escape sequences.
+pub struct PromptContext<'a> {
+    pub user: &'a str,
+    pub host: &'a str,
+    pub port: u16,
+    pub database: Option<&'a str>,
+    pub server_version: &'a str,
+    pub statements: u64,
+    pub in_transaction: bool,
+}
+
+/// Expand a MYSQL_PS1-style prompt template.
+///
+/// Supports `\u` user, `\h` host, `\p` port, `\d` database, `\U` user@host,
+/// `\v` server version, `\c` statement counter, `\x` (`*` while a transaction
+/// is open), the date and time escapes
+/// `\D \Y \y \o \O \w \R \r \m \s \P`, and `\n \t \_ \S \\`. Unknown escapes
+/// are printed without the backslash, as the mysql client does.
+pub fn expand(template: &str, ctx: &PromptContext) -> String {
+    let now = Local::now();
+    let mut result = String::new();
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('u') => result.push_str(ctx.user),
+            Some('h') => result.push_str(ctx.host),
+            Some('p') => result.push_str(&ctx.port.to_string()),
+            Some('d') => result.push_str(ctx.database.unwrap_or("(none)")),
+            Some('U') => result.push_str(&format!("{}@{}", ctx.user, ctx.host)),
+            Some('v') => result.push_str(ctx.server_version),
+            Some('c') => result.push_str(&ctx.statements.to_string()),
+            Some('x') if ctx.in_transaction => result.push('*'),
+            Some('x') => {}
+            Some('D') => result.push_str(&now.format("%a %b %e %H:%M:%S %Y").to_string()),
+            Some('Y') => result.push_str(&now.format("%Y").to_string()),
+            Some('y') => result.push_str(&now.format("%y").to_string()),
+            Some('o') => result.push_str(&now.format("%m").to_string()),
+            Some('O') => result.push_str(&now.format("%b").to_string()),
+            Some('w') => result.push_str(&now.format("%a").to_string()),
+            Some('R') => result.push_str(&now.format("%H").to_string()),
+            Some('r') => result.push_str(&now.format("%I").to_string()),
+            Some('m') => result.push_str(&now.format("%M").to_string()),
+            Some('s') => result.push_str(&now.format("%S").to_string()),
+            Some('P') => result.push_str(&now.format("%p").to_string()),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('_') | Some(' ') => result.push(' '),
+            Some('S') => result.push(';'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
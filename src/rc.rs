@@ -0,0 +1,46 @@
+use crate::{commands, execute_statements, print_error, MySQLClient};
+use dirs::home_dir;
+use std::path::PathBuf;
+
+/// Location of the startup file, `~/.rusqlrc`.
+pub fn rc_path() -> Option<PathBuf> {
+    home_dir().map(|mut path| {
+        path.push(".rusqlrc");
+        path
+    })
+}
+
+/// Execute the startup file line by line before the first prompt.
+///
+/// The file holds meta-commands (`\pager less -R`, `\format csv`,
+/// `\alias lag \run lag`, ...) and SQL statements. Blank lines and lines
+/// starting with `#` or `--` are skipped. Errors are reported with their line
+/// number and do not stop the remaining lines from running.
+pub fn run(client: &mut MySQLClient) {
+    let Some(path) = rc_path() else { return };
+    let Ok(contents) = std::fs::read_to_string(&path) else { return };
+
+    let mut buffer = String::new();
+    let mut start_line = 0;
+
+    for (number, line) in contents.lines().enumerate() {
+        let trimmed = line.trim();
+        if buffer.is_empty() && (trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("--")) {
+            continue;
+        }
+
+        if buffer.is_empty() {
+            start_line = number + 1;
+        }
+        buffer.push_str(line);
+        buffer.push('\n');
+
+        if commands::is_complete(&buffer, client.splitter.delimiter()) {
+            if let Err(e) = execute_statements(client, &buffer, true) {
+                eprint!("{}:{}: ", path.display(), start_line);
+                print_error(client.error_color(), &e);
+            }
+            buffer.clear();
+        }
+    }
+}
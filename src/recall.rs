@@ -0,0 +1,146 @@
+use crate::expand::{last_result, LastResult};
+use crate::progress::human_bytes;
+use crate::{MySQLClient, QueryResult};
+use chrono::{DateTime, Local};
+use rusql::error::{Result, RusqlError};
+use rusql::sink::OutputFormat;
+use std::collections::VecDeque;
+use std::io::IsTerminal;
+use std::rc::Rc;
+
+const USAGE: &str = "Usage: \\recall [n [format]]";
+
+/// Result sets kept for `\recall`.
+const MAX_RESULTS: usize = 10;
+/// Memory the kept result sets may hold; the newest is kept whatever its size.
+const MAX_BYTES: usize = 64 * 1024 * 1024;
+
+struct Cached {
+    query: String,
+    at: DateTime<Local>,
+    result: Rc<LastResult>,
+    bytes: usize,
+}
+
+/// The last few tables printed, newest first. The newest is the one
+/// `\expand`, `\filter`, `\transpose`, `\export last` and the like work on.
+#[derive(Default)]
+pub struct ResultCache {
+    entries: VecDeque<Cached>,
+    bytes: usize,
+    /// Set when the last query's rows were not kept (paged output), so
+    /// commands don't quietly act on an older result.
+    stale: bool,
+}
+
+impl ResultCache {
+    pub fn push(&mut self, query: &str, result: LastResult) {
+        let bytes = result.size() + query.len();
+        self.entries.push_front(Cached { query: query.to_string(), at: Local::now(), result: Rc::new(result), bytes });
+        self.bytes += bytes;
+        self.stale = false;
+        while self.entries.len() > MAX_RESULTS || (self.bytes > MAX_BYTES && self.entries.len() > 1) {
+            if let Some(evicted) = self.entries.pop_back() {
+                self.bytes -= evicted.bytes;
+            }
+        }
+    }
+
+    /// The result of the last query, if it was kept.
+    pub fn latest(&self) -> Option<&LastResult> {
+        self.entries.front().filter(|_| !self.stale).map(|cached| cached.result.as_ref())
+    }
+
+    /// Kept results, newest first.
+    #[cfg(feature = "duckdb")]
+    pub fn kept(&self) -> impl Iterator<Item = &Rc<LastResult>> {
+        self.entries.iter().map(|cached| &cached.result)
+    }
+
+    /// The last query printed no table to keep.
+    pub fn forget_latest(&mut self) {
+        self.stale = true;
+    }
+
+    /// Make result `n` (counting from 1, newest first) the latest again.
+    fn promote(&mut self, n: usize) -> Result<()> {
+        let cached = n.checked_sub(1)
+            .and_then(|i| self.entries.remove(i))
+            .ok_or_else(|| match self.entries.len() {
+                0 => RusqlError::Command("No results kept yet; run a query first".into()),
+                kept => RusqlError::Command(format!("Result {} is not kept (1-{})", n, kept)),
+            })?;
+        self.entries.push_front(cached);
+        self.stale = false;
+        Ok(())
+    }
+}
+
+/// `\recall`: list the kept results. `\recall <n> [format]`: show result
+/// `n` again, as a table or in another output format, and make it the
+/// last result for the commands that work on one.
+pub fn recall(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut words = args.split_whitespace();
+    let (n, format) = match (words.next(), words.next(), words.next()) {
+        (None, _, _) => return Ok(Some(list(client))),
+        (Some(n), format, None) => (n, format),
+        _ => return Err(RusqlError::Command(USAGE.into())),
+    };
+    let n: usize = n.parse().map_err(|_| RusqlError::Command(USAGE.into()))?;
+    let format = match format {
+        Some(name) => name.parse().map_err(RusqlError::Command)?,
+        None => client.format,
+    };
+
+    client.results.promote(n)?;
+    let cached = &client.results.entries[0];
+    let rows = cached.result.rows.len();
+    let summary = format!(
+        "{} {} from {} (kept result {})",
+        rows,
+        if rows == 1 { "row" } else { "rows" },
+        cached.at.format("%H:%M:%S"),
+        n
+    );
+    show_latest(client, format, &summary)
+}
+
+/// Print the latest kept result again in `format`.
+pub fn show_latest(client: &MySQLClient, format: OutputFormat, summary: &str) -> Result<Option<QueryResult>> {
+    if format.is_binary() && std::io::stdout().is_terminal() {
+        return Err(RusqlError::Command(
+            "Binary output is not shown on a terminal: write it to a file with \\export last".into(),
+        ));
+    }
+    let last = last_result(client)?;
+    if let Some(mut sink) = format.sink(std::io::stdout(), &client.sink_options) {
+        last.replay(sink.as_mut(), summary)?;
+        return Ok(None);
+    }
+    let mut sink = client.table_sink();
+    last.replay(&mut sink, summary)?;
+    let (table, summary) = sink.finish();
+    Ok(Some(QueryResult { table, summary }))
+}
+
+fn list(client: &MySQLClient) -> QueryResult {
+    let cache = &client.results;
+    let rows: Vec<Vec<String>> = cache.entries.iter()
+        .enumerate()
+        .map(|(i, cached)| vec![
+            (i + 1).to_string(),
+            cached.at.format("%H:%M:%S").to_string(),
+            cached.result.rows.len().to_string(),
+            human_bytes(cached.bytes as u64),
+            cached.query.split_whitespace().collect::<Vec<_>>().join(" "),
+        ])
+        .collect();
+    let summary = format!(
+        "{} of {} results kept ({} of {})",
+        rows.len(),
+        MAX_RESULTS,
+        human_bytes(cache.bytes as u64),
+        human_bytes(MAX_BYTES as u64)
+    );
+    QueryResult { table: client.build_table(&["#", "At", "Rows", "Size", "Query"], rows), summary }
+}
@@ -0,0 +1,71 @@
+use crate::spawn_shell;
+use prettytable::Table;
+use rusql::compress;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::Child;
+
+/// Where a result goes instead of the terminal.
+#[derive(Debug, Clone)]
+pub enum Redirect {
+    /// Standard input of a shell command (`; | cmd`, `\pipe`).
+    Pipe(String),
+    /// A file, truncated by `\g file` and appended to after `\o file`;
+    /// compressed when named `.gz` or `.zst`.
+    File { path: PathBuf, append: bool },
+}
+
+/// An open redirect target; finish it with [`Output::close`].
+pub struct Output {
+    writer: Box<dyn Write + Send>,
+    child: Option<Child>,
+}
+
+impl Redirect {
+    pub fn open(&self) -> io::Result<Output> {
+        match self {
+            Redirect::Pipe(command) => {
+                let mut child = spawn_shell(command)?;
+                let stdin = child.stdin.take()
+                    .ok_or_else(|| io::Error::other(format!("could not write to '{}'", command)))?;
+                Ok(Output { writer: Box::new(stdin), child: Some(child) })
+            }
+            Redirect::File { path, append } => Ok(Output { writer: compress::create(path, *append)?, child: None }),
+        }
+    }
+
+    /// Whether the summary line is shown on screen rather than sent along with the result.
+    pub fn summary_on_screen(&self) -> bool {
+        matches!(self, Redirect::File { .. })
+    }
+
+    /// Write a rendered table, and its summary when it is not shown on screen.
+    pub fn write_table(&self, table: &Table, summary: &str) -> io::Result<()> {
+        let mut output = self.open()?;
+        let mut written = table.print(&mut output.writer).map(|_| ());
+        if written.is_ok() && !self.summary_on_screen() && !summary.is_empty() {
+            written = writeln!(output.writer, "\n{}", summary);
+        }
+        // A pipe command may exit early (e.g. `head`), so only file errors count
+        if self.summary_on_screen() {
+            written?;
+        }
+        output.close()
+    }
+}
+
+impl Output {
+    pub fn writer(&mut self) -> &mut (dyn Write + Send) {
+        &mut self.writer
+    }
+
+    /// Flush the writer and wait for a pipe command to exit.
+    pub fn close(mut self) -> io::Result<()> {
+        let flushed = self.writer.flush();
+        drop(self.writer);
+        match self.child.as_mut() {
+            Some(child) => child.wait().map(|_| ()),
+            None => flushed,
+        }
+    }
+}
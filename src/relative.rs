@@ -0,0 +1,37 @@
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::Conn;
+use rusql::error::{Result, RusqlError};
+use rusql::sink::RelativeTime;
+
+/// Seconds the session's clock is ahead of UTC, so relative times can be
+/// measured against the session's own idea of now.
+pub fn session_offset(conn: &mut Conn) -> Result<i64> {
+    Ok(conn.query_first("SELECT TIMESTAMPDIFF(SECOND, UTC_TIMESTAMP(), NOW())")?.unwrap_or(0))
+}
+
+/// `\relative [off|beside|instead]`: show how long ago DATETIME and
+/// TIMESTAMP values were, next to or instead of the values themselves.
+pub fn set(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        client.notice(describe(client.relative_time));
+        return Ok(None);
+    }
+    let mode: RelativeTime = args.parse()
+        .map_err(|_| RusqlError::Command(format!("Usage: \\relative [{}]", RelativeTime::VARIANTS.join("|"))))?;
+    if mode != RelativeTime::Off {
+        // Picks up any SET time_zone since the last time
+        client.session_offset = session_offset(&mut client.conn)?;
+    }
+    client.relative_time = mode;
+    client.notice(describe(mode));
+    Ok(None)
+}
+
+fn describe(mode: RelativeTime) -> &'static str {
+    match mode {
+        RelativeTime::Off => "Relative times are off",
+        RelativeTime::Beside => "Dates and times are followed by how long ago they were",
+        RelativeTime::Instead => "Dates and times are shown as how long ago they were",
+    }
+}
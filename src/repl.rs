@@ -0,0 +1,181 @@
+use crate::{autolimit, commands, execute_statements, health, history, keepalive, print_error, prompt, rc, timeout, transaction, MySQLClient};
+use crate::cli::{connection_opts, Opts};
+use crate::config::Settings;
+use crate::helper::{AcceptHint, RusqlHelper};
+use crate::interrupt::{Interrupt, EXIT_INTERRUPT_WINDOW};
+use crate::keepalive::Idle;
+use crate::prompt::PromptContext;
+use mysql::prelude::*;
+use rusql::error::Result;
+use rustyline::{Config, Editor, EventHandler, KeyCode, KeyEvent, Modifiers};
+use rustyline::error::ReadlineError;
+use rustyline::history::FileHistory;
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+/// Run the interactive prompt until the user quits, saving the line history.
+pub fn run(mut client: MySQLClient, opts: &Opts, settings: &Settings) -> Result<()> {
+    client.at_prompt = true;
+    client.paging = !opts.no_paging && std::io::stdout().is_terminal();
+    client.auto_limit = Some(opts.auto_limit.or(settings.auto_limit).unwrap_or(autolimit::DEFAULT_AUTO_LIMIT))
+        .filter(|&limit| limit > 0);
+    let history_file = history::history_path(&client.host, client.current_db.as_deref());
+    let config = Config::builder()
+        .max_history_size(history::MAX_HISTORY_SIZE)?
+        .history_ignore_dups(true)?
+        .build();
+
+    let mut rl = Editor::<RusqlHelper, FileHistory>::with_config(config)?;
+    rl.set_helper(Some(RusqlHelper::new(client.use_colors)));
+    rl.bind_sequence(
+        KeyEvent(KeyCode::End, Modifiers::NONE),
+        EventHandler::Conditional(Box::new(AcceptHint)),
+    );
+    if rl.load_history(&history_file).is_err() {
+        println!("No previous history.");
+    }
+
+    print_welcome_message(&mut client);
+
+    let interrupt = Interrupt::install(connection_opts(opts), client.conn.connection_id())?;
+    client.interrupt = Some(interrupt.clone());
+
+    if !opts.no_rc {
+        rc::run(&mut client);
+    }
+
+    let keepalive = opts.keepalive.or(settings.keepalive).filter(|&seconds| seconds > 0).map(Duration::from_secs);
+    let mut query_buffer = String::new();
+    let mut exit_warned = false;
+    let mut last_interrupt: Option<Instant> = None;
+    loop {
+        #[cfg(feature = "jobs")]
+        for id in client.jobs.newly_finished() {
+            client.notice(&format!("[{}] done; \\fg {} shows the result", id, id));
+        }
+        let prompt = format_prompt(&client, !query_buffer.is_empty());
+
+        let (line, idle) = keepalive::while_idle(&mut client, keepalive, || rl.readline(&prompt));
+        match idle {
+            Idle::Alive => {}
+            Idle::Reconnected => {
+                interrupt.set_connection_id(client.conn.connection_id());
+                client.tracking = transaction::track(&mut client.conn);
+                let limit = client.max_execution_time;
+                if let Err(e) = timeout::apply(&mut client, limit) {
+                    eprintln!("{}", client.paint(&format!("Could not restore the statement time limit: {}", e), client.error_color()));
+                }
+                let mut message = "The connection dropped while idle and was re-established: session variables and temporary tables are gone".to_string();
+                if client.in_transaction {
+                    client.in_transaction = false;
+                    message.push_str(", and the open transaction was rolled back");
+                }
+                println!("{}", client.paint(&message, client.theme.warning));
+            }
+            Idle::Lost(e) => {
+                eprintln!("{}", client.paint(&format!("The connection dropped while idle: {}", e), client.error_color()));
+            }
+        }
+        match line {
+            Ok(line) => {
+                query_buffer.push_str(&line);
+                query_buffer.push('\n');
+
+                if commands::is_complete(&query_buffer, client.splitter.delimiter()) {
+                    // Whole statements go to history so recall and suggestions span every line
+                    if !history::is_sensitive(&query_buffer, &client.sensitive_columns) {
+                        rl.add_history_entry(query_buffer.trim_end())?;
+                    }
+                    let outcome = interrupt.while_executing(|| execute_statements(&mut client, &query_buffer, false));
+                    if let Err(e) = outcome {
+                        print_error(client.error_color(), &e);
+                    }
+                    query_buffer.clear();
+                }
+            }
+            Err(ReadlineError::Interrupted) => {
+                // A second Ctrl-C at an empty prompt within the window quits
+                let repeated = last_interrupt.is_some_and(|at| at.elapsed() < EXIT_INTERRUPT_WINDOW);
+                if query_buffer.is_empty() && repeated {
+                    println!("Bye");
+                    break;
+                }
+                if query_buffer.is_empty() {
+                    println!("^C (press Ctrl-C again to quit)");
+                } else {
+                    println!("^C");
+                }
+                last_interrupt = Some(Instant::now());
+                query_buffer.clear();
+            }
+            Err(ReadlineError::Eof) if client.in_transaction && !exit_warned => {
+                let warning = "Transaction still open: quitting rolls back uncommitted changes. \\commit or \\rollback first, or press Ctrl-D again to quit.";
+                println!("{}", client.paint(warning, client.theme.warning));
+                exit_warned = true;
+            }
+            Err(ReadlineError::Eof) => {
+                println!("Bye");
+                break;
+            }
+            Err(err) => {
+                println!("Error: {:?}", err);
+                break;
+            }
+        }
+    }
+
+    rl.save_history(&history_file)?;
+    Ok(())
+}
+
+fn print_welcome_message(client: &mut MySQLClient) {
+    if let Ok(Some(version)) = client.conn.query_first::<String, _>("SELECT VERSION()") {
+        let banner = format!(r#"
+Welcome to the MySQL monitor.  Commands end with ;
+
+Server version: {}
+Connection Id: {}
+
+Copyright (c) 2000, 2024, Oracle and/or its affiliates.
+Rust MySQL Monitor. A cross-platform MySQL client.
+
+Type 'help;' or '\h' for help. Type '\c' to clear the current input statement.
+"#, version, client.conn.connection_id());
+
+        println!("{}", client.paint(&banner, client.theme.banner));
+
+        let warnings = health::probes(&mut client.conn, &version);
+        for warning in &warnings {
+            let line = format!("Warning: {}", warning);
+            println!("{}", client.paint(&line, client.theme.warning));
+        }
+        if !warnings.is_empty() {
+            println!();
+        }
+    }
+}
+
+fn format_prompt(client: &MySQLClient, is_continuation: bool) -> String {
+    let prompt = if is_continuation {
+        "    -> ".to_string()
+    } else if let Some(template) = &client.prompt {
+        prompt::expand(template, &PromptContext {
+            user: &client.user,
+            host: &client.host,
+            port: client.port,
+            database: client.current_db.as_deref(),
+            server_version: &client.server_version,
+            statements: client.statements,
+            in_transaction: client.in_transaction,
+        })
+    } else {
+        let db_str = client.current_db
+            .as_ref()
+            .map(|db| format!("({})", db))
+            .unwrap_or_default();
+        let marker = if client.in_transaction { "*" } else { "" };
+        format!("mysql{}{} > ", db_str, marker)
+    };
+
+    client.paint(&prompt, client.theme.prompt)
+}
@@ -0,0 +1,199 @@
+use crate::health::LAG_WARNING_SECS;
+use crate::{interrupt, print_result, MySQLClient, QueryResult};
+use chrono::Local;
+use mysql::prelude::*;
+use mysql::{Conn, Row, Value};
+use rusql::error::{Result, RusqlError};
+use rusql::statements::keywords;
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: \\replication [full] [watch [seconds]]";
+
+/// Seconds between refreshes of `\replication watch`.
+const DEFAULT_WATCH_SECS: u64 = 2;
+
+/// Fields shown by `\replication`, by their MySQL 8.0.22+ names and the older
+/// `SHOW SLAVE STATUS` names. MariaDB-only fields have no newer name.
+const FIELDS: &[(&str, &str)] = &[
+    ("Channel_Name", "Channel_Name"),
+    ("Source_Host", "Master_Host"),
+    ("Source_Port", "Master_Port"),
+    ("Source_User", "Master_User"),
+    ("Replica_IO_State", "Slave_IO_State"),
+    ("Replica_IO_Running", "Slave_IO_Running"),
+    ("Replica_SQL_Running", "Slave_SQL_Running"),
+    ("Replica_SQL_Running_State", "Slave_SQL_Running_State"),
+    ("Seconds_Behind_Source", "Seconds_Behind_Master"),
+    ("Source_Log_File", "Master_Log_File"),
+    ("Read_Source_Log_Pos", "Read_Master_Log_Pos"),
+    ("Relay_Source_Log_File", "Relay_Master_Log_File"),
+    ("Exec_Source_Log_Pos", "Exec_Master_Log_Pos"),
+    ("Last_IO_Errno", "Last_IO_Errno"),
+    ("Last_IO_Error", "Last_IO_Error"),
+    ("Last_IO_Error_Timestamp", "Last_IO_Error_Timestamp"),
+    ("Last_SQL_Errno", "Last_SQL_Errno"),
+    ("Last_SQL_Error", "Last_SQL_Error"),
+    ("Last_SQL_Error_Timestamp", "Last_SQL_Error_Timestamp"),
+    ("Auto_Position", "Auto_Position"),
+    ("Using_Gtid", "Using_Gtid"),
+    ("Gtid_IO_Pos", "Gtid_IO_Pos"),
+    ("Retrieved_Gtid_Set", "Retrieved_Gtid_Set"),
+    ("Executed_Gtid_Set", "Executed_Gtid_Set"),
+];
+
+/// First keywords of statements that change data or schema.
+const WRITES: &[&str] = &[
+    "INSERT", "UPDATE", "DELETE", "REPLACE", "LOAD", "CREATE", "ALTER", "DROP", "TRUNCATE", "RENAME", "GRANT", "REVOKE",
+];
+
+/// Whether the server replicates from a source, so writes made here would
+/// diverge from it.
+pub fn is_replica(conn: &mut Conn) -> bool {
+    replica_status(conn).is_ok_and(|rows| !rows.is_empty())
+}
+
+/// Whether `statement` changes data or schema.
+pub fn is_write(statement: &str) -> bool {
+    keywords(statement).first().is_some_and(|word| WRITES.contains(&word.as_str()))
+}
+
+/// Shown before a write is sent to a replica.
+pub const WRITE_WARNING: &str = "Warning: this server is a replica; writes made here are not on its source and may break replication";
+
+/// `\replication [full] [watch [seconds]]`: `SHOW REPLICA STATUS` one field
+/// per line, with lag, thread state and errors highlighted. `full` lists every
+/// field; `watch` refreshes the report until Ctrl-C.
+pub fn replication(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut full = false;
+    let mut watch = None;
+    let mut words = args.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        match word {
+            "full" => full = true,
+            "watch" => {
+                let secs = match words.peek().and_then(|secs| secs.parse().ok()) {
+                    Some(secs) => {
+                        words.next();
+                        secs
+                    }
+                    None => DEFAULT_WATCH_SECS,
+                };
+                watch = Some(Duration::from_secs(secs).max(Duration::from_millis(500)));
+            }
+            _ => return Err(RusqlError::Command(USAGE.into())),
+        }
+    }
+
+    let Some(interval) = watch else {
+        let mut results = report(client, full)?;
+        let last = results.pop();
+        for result in results {
+            print_result(client, result);
+        }
+        return Ok(last);
+    };
+
+    loop {
+        let results = report(client, full)?;
+        print!("\x1B[2J\x1B[1;1H");  // Clear screen
+        client.notice(&format!(
+            "Every {:.1}s, Ctrl-C to stop: \\replication    {}",
+            interval.as_secs_f64(),
+            Local::now().format("%H:%M:%S")
+        ));
+        for result in results {
+            print_result(client, result);
+        }
+        let mut waited = Duration::ZERO;
+        while waited < interval {
+            if interrupt::interrupted() {
+                return Ok(None);
+            }
+            thread::sleep(Duration::from_millis(100));
+            waited += Duration::from_millis(100);
+        }
+    }
+}
+
+fn replica_status(conn: &mut Conn) -> Result<Vec<Row>> {
+    // SHOW SLAVE STATUS for servers before MySQL 8.0.22 and MariaDB
+    let rows = conn.query("SHOW REPLICA STATUS").or_else(|_| conn.query("SHOW SLAVE STATUS"))?;
+    Ok(rows)
+}
+
+/// One vertical table per replication channel.
+fn report(client: &mut MySQLClient, full: bool) -> Result<Vec<QueryResult>> {
+    let status = replica_status(&mut client.conn)?;
+    if status.is_empty() {
+        return Err(RusqlError::Command("Not a replica: SHOW REPLICA STATUS returned nothing".into()));
+    }
+
+    let results = status.iter()
+        .map(|row| {
+            let fields: Vec<(String, Value)> = row.columns_ref().iter()
+                .enumerate()
+                .map(|(i, column)| (column.name_str().into_owned(), row.as_ref(i).cloned().unwrap_or(Value::NULL)))
+                .collect();
+            let shown: Vec<&(String, Value)> = if full {
+                fields.iter().collect()
+            } else {
+                FIELDS.iter()
+                    .filter_map(|(name, old)| fields.iter().find(|(field, _)| field == name || field == old))
+                    .collect()
+            };
+            let rows = shown.into_iter()
+                .map(|(name, value)| vec![name.clone(), highlight(client, name, value)])
+                .collect();
+            QueryResult { table: client.build_table(&["Field", "Value"], rows), summary: summary(&fields) }
+        })
+        .collect();
+    Ok(results)
+}
+
+/// A field's value, colored when it tells whether replication is healthy.
+fn highlight(client: &MySQLClient, name: &str, value: &Value) -> String {
+    let text = match value {
+        Value::NULL => "NULL".to_string(),
+        value => mysql::from_value_opt::<String>(value.clone()).unwrap_or_else(|_| value.as_sql(true)),
+    };
+    let color = match name {
+        "Replica_IO_Running" | "Slave_IO_Running" | "Replica_SQL_Running" | "Slave_SQL_Running" => {
+            if text == "Yes" { client.theme.notice } else { client.theme.error }
+        }
+        "Seconds_Behind_Source" | "Seconds_Behind_Master" => match text.parse::<u64>() {
+            Ok(lag) if lag > LAG_WARNING_SECS => client.theme.warning,
+            Ok(_) => client.theme.notice,
+            Err(_) => client.theme.error,
+        },
+        "Last_IO_Errno" | "Last_SQL_Errno" | "Last_IO_Error" | "Last_SQL_Error" if !text.is_empty() && text != "0" => {
+            client.theme.error
+        }
+        _ => None,
+    };
+    client.paint(&text, color)
+}
+
+/// One line on the health of a channel: lag and which threads are stopped.
+fn summary(fields: &[(String, Value)]) -> String {
+    let field = |names: [&str; 2]| {
+        fields.iter()
+            .find(|(name, _)| names.contains(&name.as_str()))
+            .and_then(|(_, value)| mysql::from_value_opt::<String>(value.clone()).ok())
+    };
+    let stopped: Vec<&str> = [("IO", ["Replica_IO_Running", "Slave_IO_Running"]), ("SQL", ["Replica_SQL_Running", "Slave_SQL_Running"])]
+        .into_iter()
+        .filter(|(_, names)| field(*names).as_deref() != Some("Yes"))
+        .map(|(thread, _)| thread)
+        .collect();
+    let lag = field(["Seconds_Behind_Source", "Seconds_Behind_Master"]);
+    match (stopped.as_slice(), lag) {
+        ([], Some(lag)) => format!("Replicating, {} seconds behind the source", lag),
+        ([], None) => "Replicating".to_string(),
+        (stopped, _) => format!(
+            "Replication {} {} not running",
+            stopped.join(" and "),
+            if stopped.len() == 1 { "thread" } else { "threads" }
+        ),
+    }
+}
@@ -0,0 +1,80 @@
+use crate::expand::last_result;
+use crate::{MySQLClient, QueryResult};
+use rusql::error::{Result, RusqlError};
+
+/// `\transpose`: show the last result with its columns as rows, one column
+/// per original row.
+pub fn transpose(client: &mut MySQLClient, _args: &str) -> Result<Option<QueryResult>> {
+    let last = last_result(client)?;
+    let labels: Vec<String> = (1..=last.rows.len()).map(|n| format!("Row {}", n)).collect();
+    let mut headers = vec!["Column"];
+    headers.extend(labels.iter().map(String::as_str));
+
+    let rows: Vec<Vec<String>> = last.columns.iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let mut row = vec![name.clone()];
+            row.extend(last.rows.iter().map(|values| display(&values[i])));
+            row
+        })
+        .collect();
+    let summary = format!("{} columns x {} rows", last.columns.len(), last.rows.len());
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+/// `\pivot <row-col> <col-col> <value-col>`: crosstab of the last result,
+/// with one row per distinct `row-col` value and one column per distinct
+/// `col-col` value, both in order of first appearance.
+pub fn pivot(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut words = args.split_whitespace();
+    let (Some(row_col), Some(col_col), Some(value_col), None) = (words.next(), words.next(), words.next(), words.next()) else {
+        return Err(RusqlError::Command("Usage: \\pivot <row-col> <col-col> <value-col>".into()));
+    };
+    let last = last_result(client)?;
+    let (row_col, col_col, value_col) = (last.column(row_col)?, last.column(col_col)?, last.column(value_col)?);
+
+    let mut row_keys: Vec<String> = Vec::new();
+    let mut col_keys: Vec<String> = Vec::new();
+    let mut cells: Vec<(usize, usize, String)> = Vec::new();
+    for values in &last.rows {
+        let row = position_or_push(&mut row_keys, display(&values[row_col]));
+        let col = position_or_push(&mut col_keys, display(&values[col_col]));
+        cells.push((row, col, display(&values[value_col])));
+    }
+
+    let mut grid = vec![vec![None; col_keys.len()]; row_keys.len()];
+    let mut repeated = 0;
+    for (row, col, value) in cells {
+        if grid[row][col].replace(value).is_some() {
+            repeated += 1;
+        }
+    }
+
+    let mut headers = vec![last.columns[row_col].as_str()];
+    headers.extend(col_keys.iter().map(String::as_str));
+    let rows: Vec<Vec<String>> = row_keys.iter()
+        .zip(grid)
+        .map(|(key, cells)| {
+            let mut row = vec![key.clone()];
+            row.extend(cells.into_iter().map(Option::unwrap_or_default));
+            row
+        })
+        .collect();
+    let mut summary = format!("{} rows x {} columns", row_keys.len(), col_keys.len());
+    if repeated > 0 {
+        summary.push_str(&format!("; {} cells had more than one value, the last is shown", repeated));
+    }
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+fn display(value: &Option<String>) -> String {
+    value.as_deref().unwrap_or("NULL").to_string()
+}
+
+/// Index of `key` in `keys`, appending it if it is new.
+fn position_or_push(keys: &mut Vec<String>, key: String) -> usize {
+    keys.iter().position(|k| *k == key).unwrap_or_else(|| {
+        keys.push(key);
+        keys.len() - 1
+    })
+}
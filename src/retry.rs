@@ -0,0 +1,70 @@
+use crate::{commands, timeout, transaction, MySQLClient, QueryResult};
+use mysql::{Conn, OptsBuilder};
+use rusql::error::{Result, RusqlError};
+use std::thread;
+use std::time::Duration;
+
+/// ER_LOCK_WAIT_TIMEOUT, which only rolls back the statement that waited.
+const LOCK_WAIT_TIMEOUT: u16 = 1205;
+
+/// How script statements failing with a deadlock, a lock wait timeout or a
+/// dropped connection are run again (`--retry`, `--retry-backoff`).
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    pub attempts: u32,
+    /// Wait before the first retry, doubled for each one after.
+    pub backoff: Duration,
+}
+
+impl Retry {
+    /// Wait before retry number `attempt`, counted from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff.saturating_mul(2u32.saturating_pow(attempt - 1))
+    }
+}
+
+/// Run a script statement, retrying it while it fails transiently.
+///
+/// A deadlock or a dropped connection also undoes the statements before
+/// this one in an open transaction, so those are not retried there. Whether
+/// one was open is read before the statement runs, from the state the server
+/// reports (see [`transaction::refresh`]).
+pub fn execute(client: &mut MySQLClient, statement: &str) -> Result<Option<QueryResult>> {
+    let Some(retry) = client.retry.filter(|_| !commands::is_meta_command(statement)) else {
+        return client.execute_query(statement);
+    };
+    let mut attempt = 0;
+    loop {
+        let in_transaction = client.in_transaction;
+        let e = match client.execute_query(statement) {
+            Err(e) if e.is_transient() && attempt < retry.attempts => e,
+            outcome => return outcome,
+        };
+        if in_transaction && !matches!(e, RusqlError::Sql { code: LOCK_WAIT_TIMEOUT, .. }) {
+            eprintln!("{}", client.paint("Not retried, as the open transaction was rolled back with it", client.theme.warning));
+            return Err(e);
+        }
+
+        attempt += 1;
+        let wait = retry.delay(attempt);
+        eprintln!("{}; retrying in {} ms ({}/{})", e, wait.as_millis(), attempt, retry.attempts);
+        thread::sleep(wait);
+        if let RusqlError::Connection(_) = e
+            && let Err(e) = reconnect(client) {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+/// Replace a dropped connection, restoring the database and time limit and
+/// pointing Ctrl-C at it.
+fn reconnect(client: &mut MySQLClient) -> Result<()> {
+    let opts = OptsBuilder::from_opts(client.conn_opts.clone()).db_name(client.current_db.clone());
+    client.conn = Conn::new(opts)?;
+    if let Some(interrupt) = &client.interrupt {
+        interrupt.set_connection_id(client.conn.connection_id());
+    }
+    client.tracking = transaction::track(&mut client.conn);
+    let limit = client.max_execution_time;
+    timeout::apply(client, limit)
+}
@@ -0,0 +1,147 @@
+use crate::introspect::split_table;
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::{Params, Row, Value};
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::edit::RowUpdate;
+use rusql::error::{Result, RusqlError};
+use rusql::format::format_value;
+use std::fs;
+use std::process::Command;
+
+const USAGE: &str = "Usage: \\edit-row <[db.]table> <key>=<value> [<key>=<value> ...]";
+
+/// `\edit-row <table> <key>=<value> ...`: open the row with that key in
+/// `$VISUAL` or `$EDITOR` as `column = value` lines, then write back the
+/// columns that were changed. Sensitive columns show masked. The update only
+/// lands if the row still holds every value that was read, so a concurrent
+/// change is reported instead of overwritten.
+pub fn edit_row(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let mut words = args.split_whitespace();
+    let table = words.next().ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+    let key: Vec<(&str, &str)> = words
+        .map(|word| word.split_once('=').filter(|(column, _)| !column.is_empty()))
+        .collect::<Option<_>>()
+        .ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+    if key.is_empty() {
+        return Err(RusqlError::Command(USAGE.into()));
+    }
+
+    let conditions: Vec<String> = key.iter().map(|(column, _)| format!("{} = ?", quote_identifier(column))).collect();
+    let query = format!("SELECT * FROM {} WHERE {} LIMIT 2", quote_table(table), conditions.join(" AND "));
+    let params = Params::Positional(key.iter().map(|(_, value)| Value::from(*value)).collect());
+    let rows: Vec<Row> = client.conn.exec(query, params)?;
+    let row = match rows.as_slice() {
+        [row] => row,
+        [] => return Err(RusqlError::Command(format!("No row in {} with that key", table))),
+        _ => return Err(RusqlError::Command(format!("More than one row in {} has that key", table))),
+    };
+    let columns: Vec<String> = row.columns_ref().iter().map(|column| column.name_str().into_owned()).collect();
+    let values: Vec<Value> = (0..columns.len()).map(|index| row.as_ref(index).cloned().unwrap_or(Value::NULL)).collect();
+    // Sensitive columns open masked and are only written when replaced
+    let (db, name) = split_table(client, table)?;
+    let mut shown = vec![values.clone()];
+    client.sensitive_rules.columns(Some(&db), &name, &columns).apply(&mut shown);
+    let shown = shown.remove(0);
+
+    let before = write_lines(table, &columns, &shown);
+    let after = edit(&before)?;
+    let edited = parse_lines(&after)?;
+
+    let mut update = RowUpdate::new(table);
+    for (column, value) in &key {
+        update = update.key(column, *value);
+    }
+    let mut changed = Vec::new();
+    for ((column, value), shown) in columns.iter().zip(&values).zip(&shown) {
+        update = update.expect(column, value.clone());
+        let Some(new) = edited.iter().find(|(name, _)| name == column).map(|(_, new)| new) else { continue };
+        if *new != displayed(shown) {
+            update = update.set(column, parse_value(new));
+            changed.push(column.as_str());
+        }
+    }
+    if let Some((unknown, _)) = edited.iter().find(|(name, _)| !columns.contains(name)) {
+        return Err(RusqlError::Command(format!("{} has no column '{}'; nothing was updated", table, unknown)));
+    }
+    if changed.is_empty() {
+        client.notice("No changes");
+        return Ok(None);
+    }
+
+    update.execute(&mut client.conn)?;
+    client.notice(&format!("Updated {} in {}", changed.join(", "), table));
+    Ok(None)
+}
+
+/// How a value is shown for editing: one line, `NULL` for NULL, with
+/// backslashes and line breaks escaped.
+fn displayed(value: &Value) -> String {
+    format_value(value).replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+/// The edited text of a value back as one: `NULL` is NULL, anything else
+/// text the server converts to the column's type.
+fn parse_value(text: &str) -> Value {
+    if text == "NULL" {
+        return Value::NULL;
+    }
+    let mut value = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some(other) => value.push(other),
+            None => value.push('\\'),
+        }
+    }
+    Value::from(value)
+}
+
+fn write_lines(table: &str, columns: &[String], values: &[Value]) -> String {
+    let mut text = format!(
+        "# {}: change values and save to update the row; NULL sets NULL, \\n is a line break\n",
+        table
+    );
+    for (column, value) in columns.iter().zip(values) {
+        text.push_str(&format!("{} = {}\n", column, displayed(value)));
+    }
+    text
+}
+
+/// `column = value` lines, skipping blank lines and `#` comments.
+fn parse_lines(text: &str) -> Result<Vec<(String, String)>> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.split_once(" = ")
+                .or_else(|| line.split_once('='))
+                .map(|(column, value)| (column.trim().to_string(), value.to_string()))
+                .ok_or_else(|| RusqlError::Command(format!("Expected 'column = value', got '{}'; nothing was updated", line)))
+        })
+        .collect()
+}
+
+/// Let the user change `text` in their editor.
+fn edit(text: &str) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+    let path = std::env::temp_dir().join(format!("rusql-edit-row-{}.txt", std::process::id()));
+    fs::write(&path, text)?;
+    let status = match cfg!(windows) {
+        true => Command::new("cmd").arg("/C").arg(format!("{} \"{}\"", editor, path.display())).status(),
+        false => Command::new("sh").arg("-c").arg(format!("{} \"$1\"", editor)).arg("sh").arg(&path).status(),
+    };
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+    if !status?.success() {
+        return Err(RusqlError::Command("The editor failed; nothing was updated".into()));
+    }
+    Ok(edited?)
+}
@@ -0,0 +1,204 @@
+use crate::config::Settings;
+use crate::notify;
+use crate::retry::Retry;
+use chrono::{Local, NaiveDateTime};
+use mysql::{Column, Opts, OptsBuilder, Row};
+use rusql::compress;
+use rusql::cron::Schedule;
+use rusql::error::{Result, RusqlError};
+use rusql::job::{Job, Outcome};
+use rusql::sink::{write_rows, JsonSink, OutputFormat, SinkOptions};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest single sleep while waiting for the next run, so a changed clock
+/// or a suspended machine delays runs by at most this much.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// The jobs file of `rusql schedule`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobsFile {
+    #[serde(default)]
+    jobs: Vec<JobSettings>,
+}
+
+/// A `[[jobs]]` entry.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobSettings {
+    name: String,
+    /// Cron expression, e.g. `0 6 * * mon-fri`
+    cron: String,
+    /// Profile to connect with; the command-line connection if unset
+    profile: Option<String>,
+    query: String,
+    /// File written with the rows of every run; `{date}` and `{time}`
+    /// become the run's date and time
+    output: Option<String>,
+    /// Format of `output` (`csv`, `json`, ...); from its extension if unset, else CSV
+    format: Option<String>,
+    /// URL receiving the rows as JSON
+    webhook: Option<String>,
+    /// Retries of runs failing with a deadlock, lock wait timeout or dropped connection
+    #[serde(default)]
+    retry: u32,
+    /// Milliseconds before the first retry, doubled for each one after
+    retry_backoff: Option<u64>,
+}
+
+struct ScheduledJob {
+    settings: JobSettings,
+    schedule: Schedule,
+    opts: Opts,
+    format: OutputFormat,
+    next: Option<NaiveDateTime>,
+}
+
+/// `rusql schedule --config jobs.toml`: run the queries of a jobs file on
+/// their cron schedules until stopped, writing their rows to files or
+/// webhooks. Each run has its own connection; runs and failures are logged
+/// with a timestamp.
+pub fn run(path: &Path, settings: &Settings, connection: OptsBuilder) -> Result<()> {
+    let mut jobs = load(path, settings, connection)?;
+    for job in &jobs {
+        match job.next {
+            Some(next) => log(&format!("{}: next run at {}", job.settings.name, next.format("%Y-%m-%d %H:%M"))),
+            None => log(&format!("{}: its schedule never fires", job.settings.name)),
+        }
+    }
+
+    loop {
+        let Some(due) = jobs.iter().filter_map(|job| job.next).min() else {
+            return Err(RusqlError::Config("None of the jobs will ever run".into()));
+        };
+        sleep_until(due);
+        for job in jobs.iter_mut().filter(|job| job.next.is_some_and(|next| next <= due)) {
+            run_job(job, due);
+            job.next = job.schedule.next_after(Local::now().naive_local().max(due));
+        }
+    }
+}
+
+fn load(path: &Path, settings: &Settings, connection: OptsBuilder) -> Result<Vec<ScheduledJob>> {
+    let contents = compress::read_to_string(path)?;
+    let file: JobsFile = toml::from_str(&contents)
+        .map_err(|e| RusqlError::Config(format!("{}: {}", path.display(), e)))?;
+    if file.jobs.is_empty() {
+        return Err(RusqlError::Config(format!("{}: no [[jobs]]", path.display())));
+    }
+
+    let now = Local::now().naive_local();
+    let mut jobs = Vec::with_capacity(file.jobs.len());
+    for entry in file.jobs {
+        let invalid = |message: String| RusqlError::Config(format!("{}: job '{}': {}", path.display(), entry.name, message));
+        let schedule: Schedule = entry.cron.parse().map_err(invalid)?;
+        let opts = match &entry.profile {
+            Some(name) => settings.profiles.get(name)
+                .ok_or_else(|| invalid(format!("unknown profile '{}'", name)))?
+                .opts(),
+            None => connection.clone(),
+        };
+        let format = match (&entry.format, &entry.output) {
+            (Some(format), _) => match format.parse().map_err(invalid)? {
+                OutputFormat::Table => return Err(invalid("the table format can't be written to a file".into())),
+                format => format,
+            },
+            (None, Some(output)) => match compress::inner_path(Path::new(output)).extension().and_then(|ext| ext.to_str()) {
+                Some(extension) => extension.parse().ok().filter(|&format| format != OutputFormat::Table).unwrap_or(OutputFormat::Csv),
+                None => OutputFormat::Csv,
+            },
+            (None, None) => OutputFormat::Csv,
+        };
+        if entry.output.is_none() && entry.webhook.is_none() {
+            return Err(invalid("give an output file or a webhook for the rows".into()));
+        }
+        jobs.push(ScheduledJob { next: schedule.next_after(now), schedule, opts: opts.into(), format, settings: entry });
+    }
+    Ok(jobs)
+}
+
+fn sleep_until(due: NaiveDateTime) {
+    loop {
+        let Ok(left) = (due - Local::now().naive_local()).to_std() else { return };
+        if left.is_zero() {
+            return;
+        }
+        thread::sleep(left.min(MAX_SLEEP));
+    }
+}
+
+fn run_job(job: &ScheduledJob, at: NaiveDateTime) {
+    let start = Instant::now();
+    let outcome = execute(job).and_then(|Outcome { columns, rows, .. }| deliver(job, at, &columns, rows));
+    match outcome {
+        Ok(report) => log(&format!("{}: {} ({:.2} sec)", job.settings.name, report, start.elapsed().as_secs_f64())),
+        Err(e) => log_error(&format!("{}: failed: {}", job.settings.name, e)),
+    }
+}
+
+/// Run the job's query, retrying transient failures per its `retry`.
+fn execute(job: &ScheduledJob) -> Result<Outcome> {
+    let retry = Retry {
+        attempts: job.settings.retry,
+        backoff: Duration::from_millis(job.settings.retry_backoff.unwrap_or(500)),
+    };
+    let mut attempt = 0;
+    loop {
+        match Job::spawn(job.opts.clone(), &job.settings.query).and_then(Job::wait) {
+            Err(e) if e.is_transient() && attempt < retry.attempts => {
+                attempt += 1;
+                let wait = retry.delay(attempt);
+                log_error(&format!(
+                    "{}: {}; retrying in {} ms ({}/{})",
+                    job.settings.name,
+                    e,
+                    wait.as_millis(),
+                    attempt,
+                    retry.attempts
+                ));
+                thread::sleep(wait);
+            }
+            outcome => return outcome,
+        }
+    }
+}
+
+/// Write the rows to the job's file and webhook, describing where they went.
+fn deliver(job: &ScheduledJob, at: NaiveDateTime, columns: &[Column], rows: Vec<Row>) -> Result<String> {
+    let count = rows.len();
+    let mut report = vec![format!("{} {}", count, if count == 1 { "row" } else { "rows" })];
+
+    if let Some(output) = &job.settings.output {
+        let path = PathBuf::from(output.replace("{date}", &at.format("%Y-%m-%d").to_string())
+            .replace("{time}", &at.format("%H%M").to_string()));
+        let file = compress::create(&path, false)?;
+        let Some(mut sink) = job.format.sink(file, &SinkOptions::default()) else {
+            return Err(RusqlError::Command("The table format can't be written to a file".into()));
+        };
+        write_rows(&mut *sink, columns, rows.iter().cloned(), "")?;
+        report.push(format!("written to {}", path.display()));
+    }
+    if let Some(url) = &job.settings.webhook {
+        let mut json = Vec::new();
+        write_rows(&mut JsonSink::new(&mut json), columns, rows, "")?;
+        let rows: serde_json::Value = serde_json::from_slice(&json).map_err(|e| RusqlError::Data(e.to_string()))?;
+        notify::post(url, &serde_json::json!({
+            "text": format!("{}: {} at {}", job.settings.name, report[0], at.format("%Y-%m-%d %H:%M")),
+            "job": job.settings.name,
+            "rows": rows,
+        }))?;
+        report.push("sent to the webhook".to_string());
+    }
+    Ok(report.join(", "))
+}
+
+fn log(message: &str) {
+    println!("{} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+}
+
+fn log_error(message: &str) {
+    eprintln!("{} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+}
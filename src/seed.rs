@@ -0,0 +1,405 @@
+use crate::fk_graph::{self, ForeignKey};
+use chrono::{Datelike, Duration, Local, NaiveDateTime, Timelike};
+use mysql::prelude::*;
+use mysql::{Conn, Params, Value};
+use rusql::bulk::{quote_identifier, quote_table, BulkInsert, OnConflict};
+use rusql::error::{Result, RusqlError};
+use rusql::mask::Mask;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Share of NULLs, in percent, in nullable columns.
+const NULL_PERCENT: u64 = 10;
+
+/// Distinct parent keys sampled for each foreign key.
+const PARENT_SAMPLE: usize = 100_000;
+
+/// Generated dates and times fall within this many days before now.
+const DATE_SPAN_DAYS: u64 = 3 * 365;
+
+const WORDS: &[&str] = &[
+    "alpha", "amber", "basic", "bright", "cedar", "classic", "delta", "eastern", "echo", "forest", "golden",
+    "harbor", "island", "jade", "linear", "lunar", "maple", "metro", "north", "ocean", "prime", "quiet",
+    "rapid", "river", "silver", "solar", "summit", "terra", "urban", "valley", "vivid", "western",
+];
+
+const CITIES: &[&str] = &["Lisbon", "Osaka", "Denver", "Nairobi", "Krakow", "Lima", "Perth", "Tallinn", "Austin", "Porto"];
+
+const COUNTRIES: &[&str] = &["Portugal", "Japan", "United States", "Kenya", "Poland", "Peru", "Australia", "Estonia", "Canada"];
+
+const STATUSES: &[&str] = &["active", "inactive", "pending", "archived"];
+
+/// How many rows were written to a table, parents seeded first included.
+pub struct Seeded {
+    pub table: String,
+    pub rows: u64,
+    /// Generated rows dropped as duplicates of a unique key made only of
+    /// foreign key columns.
+    pub skipped: u64,
+}
+
+/// Sizes of a seeding run.
+pub struct Options {
+    pub rows: usize,
+    pub batch: usize,
+    /// Rows generated for referenced tables that are still empty.
+    pub parent_rows: usize,
+}
+
+/// A column as seen by the generator.
+struct Column {
+    name: String,
+    data_type: String,
+    column_type: String,
+    nullable: bool,
+    max_length: Option<u64>,
+    precision: Option<u64>,
+    scale: Option<u64>,
+    /// Gets distinct values, as part of a unique key.
+    unique: bool,
+    /// First value of a unique integer column.
+    next_id: i64,
+}
+
+/// `rusql seed`: fill `table` with `rows` rows of plausible fake data.
+/// Values follow each column's type, length and nullability, columns of
+/// unique keys get distinct values, and foreign key columns point at rows of
+/// the referenced table, which is seeded first when it is still empty.
+pub fn seed(conn: &mut Conn, db: &str, table: &str, options: &Options, progress: &mut dyn FnMut(&str, u64)) -> Result<Vec<Seeded>> {
+    let (tables, keys) = fk_graph::load(conn, db)?;
+    if !tables.iter().any(|name| name == table) {
+        return Err(RusqlError::Command(format!("Table '{}.{}' doesn't exist", db, table)));
+    }
+    let mut seeder = Seeder { conn, db, keys, options, path: Vec::new(), done: Vec::new(), progress };
+    seeder.table(table, options.rows)?;
+    Ok(seeder.done)
+}
+
+/// State of one seeding run, which may reach several tables.
+struct Seeder<'a> {
+    conn: &'a mut Conn,
+    db: &'a str,
+    keys: Vec<ForeignKey>,
+    options: &'a Options,
+    /// Tables whose parents are being seeded, to stop at reference cycles
+    path: Vec<String>,
+    done: Vec<Seeded>,
+    progress: &'a mut dyn FnMut(&str, u64),
+}
+
+impl Seeder<'_> {
+    fn table(&mut self, table: &str, rows: usize) -> Result<()> {
+        let (db, options) = (self.db, self.options);
+
+        // Referenced tables need rows to point at; ones in other databases or
+        // further up a reference cycle are left alone
+        self.path.push(table.to_string());
+        let parents: Vec<String> = self.keys.iter()
+            .filter(|key| key.table == table)
+            .map(|key| key.parent.clone())
+            .collect();
+        for parent in parents {
+            if parent == table || parent.contains('.') || self.path.contains(&parent) || self.done.iter().any(|d| d.table == parent) {
+                continue;
+            }
+            let probe = format!("SELECT 1 FROM {} LIMIT 1", quote_table(&format!("{}.{}", db, parent)));
+            if self.conn.query_first::<u8, _>(probe)?.is_none() {
+                self.table(&parent, options.parent_rows)?;
+            }
+        }
+        self.path.pop();
+
+        let conn = &mut *self.conn;
+        let own_keys: Vec<&ForeignKey> = self.keys.iter().filter(|key| key.table == table).collect();
+        let full_name = format!("{}.{}", db, table);
+        let (mut columns, skip_duplicates) = load_columns(conn, db, table, &own_keys)?;
+        let mut pools = Vec::new();
+        for key in &own_keys {
+            pools.push(parent_sample(conn, db, table, key, &columns)?);
+        }
+        for column in columns.iter_mut().filter(|c| c.unique && is_integer(&c.data_type)) {
+            let max: Option<i64> = conn.query_first(format!(
+                "SELECT COALESCE(MAX({}), 0) FROM {}",
+                quote_identifier(&column.name),
+                quote_table(&full_name)
+            ))?;
+            column.next_id = max.unwrap_or(0) + 1;
+        }
+
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        let mut rng = Rng::new();
+        let run = format!("{:04x}", rng.next() % 0x10000);
+        let mut written = 0;
+        let mut generated = 0;
+        while generated < rows {
+            let count = options.batch.min(rows - generated);
+            let mut batch = Vec::with_capacity(count);
+            for n in generated..generated + count {
+                let mut row: Vec<Value> = Vec::with_capacity(columns.len());
+                for column in &columns {
+                    row.push(generate(&mut rng, column, n as u64, &run)?);
+                }
+                // Foreign key columns take a sampled parent key as a whole
+                for (key, pool) in own_keys.iter().zip(&pools) {
+                    let parent_key = match pool {
+                        Some(pool) => &pool[rng.below(pool.len() as u64) as usize],
+                        None => &vec![Value::NULL; key.columns.len()],
+                    };
+                    for (column, value) in key.columns.iter().zip(parent_key) {
+                        if let Some(index) = names.iter().position(|name| name == column) {
+                            row[index] = value.clone();
+                        }
+                    }
+                }
+                batch.push(Params::Positional(row));
+            }
+            written += BulkInsert::new(&full_name, &names)
+                .max_rows_per_batch(options.batch)
+                .on_conflict(if skip_duplicates { OnConflict::Skip } else { OnConflict::Fail })
+                .execute(conn, batch)?;
+            generated += count;
+            (self.progress)(table, generated as u64);
+        }
+
+        self.done.push(Seeded { table: table.to_string(), rows: written, skipped: rows as u64 - written });
+        Ok(())
+    }
+}
+
+/// The columns to fill, leaving out auto-increment and generated ones, and
+/// whether duplicates must be skipped because a unique key is made only of
+/// foreign key columns.
+fn load_columns(conn: &mut Conn, db: &str, table: &str, keys: &[&ForeignKey]) -> Result<(Vec<Column>, bool)> {
+    type Row = (String, String, String, String, Option<u64>, Option<u64>, Option<u64>, String);
+    let rows: Vec<Row> = conn.exec(
+        "SELECT COLUMN_NAME, DATA_TYPE, COLUMN_TYPE, IS_NULLABLE, CHARACTER_MAXIMUM_LENGTH, \
+         NUMERIC_PRECISION, NUMERIC_SCALE, EXTRA FROM information_schema.COLUMNS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+        (db, table),
+    )?;
+    let unique_keys: Vec<(String, String)> = conn.exec(
+        "SELECT INDEX_NAME, COLUMN_NAME FROM information_schema.STATISTICS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND NON_UNIQUE = 0 ORDER BY INDEX_NAME, SEQ_IN_INDEX",
+        (db, table),
+    )?;
+
+    let skipped: Vec<String> = rows.iter()
+        .filter(|row| {
+            let extra = row.7.to_lowercase();
+            extra.contains("auto_increment") || extra.contains("virtual generated") || extra.contains("stored generated")
+        })
+        .map(|row| row.0.clone())
+        .collect();
+    let references = |column: &str| keys.iter().any(|key| key.columns.iter().any(|c| c == column));
+
+    // One distinct column makes a whole unique key distinct
+    let mut unique = Vec::new();
+    let mut skip_duplicates = false;
+    let mut index_names: Vec<&str> = unique_keys.iter().map(|(index, _)| index.as_str()).collect();
+    index_names.dedup();
+    for index in index_names {
+        let index_columns: Vec<&str> = unique_keys.iter().filter(|(i, _)| i == index).map(|(_, c)| c.as_str()).collect();
+        if index_columns.iter().any(|c| skipped.iter().any(|s| s == c)) {
+            continue;
+        }
+        match index_columns.iter().find(|c| !references(c)) {
+            Some(column) => unique.push(column.to_string()),
+            None => skip_duplicates = true,
+        }
+    }
+
+    let columns = rows.into_iter()
+        .filter(|row| !skipped.contains(&row.0))
+        .map(|(name, data_type, column_type, nullable, max_length, precision, scale, _)| Column {
+            unique: unique.contains(&name),
+            name,
+            data_type: data_type.to_lowercase(),
+            column_type,
+            nullable: nullable == "YES",
+            max_length,
+            precision,
+            scale,
+            next_id: 1,
+        })
+        .collect();
+    Ok((columns, skip_duplicates))
+}
+
+/// Existing keys of the table `key` references, or `None` when there are
+/// none and the key's columns may all be NULL.
+fn parent_sample(conn: &mut Conn, db: &str, table: &str, key: &ForeignKey, columns: &[Column]) -> Result<Option<Vec<Vec<Value>>>> {
+    let parent = if key.parent.contains('.') { key.parent.clone() } else { format!("{}.{}", db, key.parent) };
+    let selected: Vec<String> = key.parent_columns.iter().map(|c| quote_identifier(c)).collect();
+    let sample: Vec<mysql::Row> = conn.query(format!(
+        "SELECT DISTINCT {} FROM {} WHERE {} LIMIT {}",
+        selected.join(", "),
+        quote_table(&parent),
+        selected.iter().map(|c| format!("{} IS NOT NULL", c)).collect::<Vec<_>>().join(" AND "),
+        PARENT_SAMPLE
+    ))?;
+    if !sample.is_empty() {
+        return Ok(Some(sample.into_iter().map(mysql::Row::unwrap).collect()));
+    }
+    let nullable = key.columns.iter().all(|name| columns.iter().any(|c| &c.name == name && c.nullable));
+    match nullable {
+        true => Ok(None),
+        false => Err(RusqlError::Command(format!(
+            "Cannot seed {}: {} has no rows for foreign key {}",
+            table, parent, key.name
+        ))),
+    }
+}
+
+/// A plausible value for `column` in row `n` of this run.
+fn generate(rng: &mut Rng, column: &Column, n: u64, run: &str) -> Result<Value> {
+    if column.nullable && !column.unique && rng.below(100) < NULL_PERCENT {
+        return Ok(Value::NULL);
+    }
+    let column_type = column.column_type.to_lowercase();
+    let unsigned = column_type.contains("unsigned");
+    let value = match column.data_type.as_str() {
+        _ if column_type.starts_with("tinyint(1)") || column.data_type == "bit" => Value::UInt(rng.below(2)),
+        data_type if is_integer(data_type) && column.unique => Value::Int(column.next_id + n as i64),
+        data_type if is_integer(data_type) => {
+            let max = match (data_type, unsigned) {
+                ("tinyint", false) => 127,
+                ("tinyint", true) => 255,
+                ("smallint", false) => 32_767,
+                ("smallint", true) => 65_535,
+                _ => 1_000_000,
+            };
+            Value::UInt(rng.below(max) + 1)
+        }
+        "decimal" => {
+            let scale = column.scale.unwrap_or(0).min(6);
+            let digits = column.precision.unwrap_or(10).saturating_sub(scale).clamp(1, 6);
+            let whole = rng.below(10u64.pow(digits as u32));
+            match scale {
+                0 => Value::from(whole.to_string()),
+                scale => Value::from(format!("{}.{:0width$}", whole, rng.below(10u64.pow(scale as u32)), width = scale as usize)),
+            }
+        }
+        "float" | "double" => Value::Double(rng.below(100_000) as f64 / 100.0),
+        "date" => {
+            let time = moment(rng, column.unique, n);
+            Value::Date(time.year() as u16, time.month() as u8, time.day() as u8, 0, 0, 0, 0)
+        }
+        "datetime" | "timestamp" => {
+            let time = moment(rng, column.unique, n);
+            Value::Date(
+                time.year() as u16,
+                time.month() as u8,
+                time.day() as u8,
+                time.hour() as u8,
+                time.minute() as u8,
+                time.second() as u8,
+                0,
+            )
+        }
+        "time" => Value::Time(false, 0, rng.below(24) as u8, rng.below(60) as u8, rng.below(60) as u8, 0),
+        "year" => Value::UInt(2000 + rng.below(26)),
+        "enum" | "set" => {
+            let options = choices(&column.column_type);
+            match options.is_empty() {
+                true => Value::NULL,
+                false => Value::from(options[rng.below(options.len() as u64) as usize].clone()),
+            }
+        }
+        "json" => Value::from(format!("{{\"{}\": {}}}", WORDS[rng.below(WORDS.len() as u64) as usize], rng.below(1000))),
+        "char" | "varchar" | "tinytext" | "text" | "mediumtext" | "longtext" => Value::from(text(rng, column, n, run)),
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => {
+            let length = column.max_length.unwrap_or(16).min(16) as usize;
+            Value::Bytes((0..length).map(|_| rng.below(256) as u8).collect())
+        }
+        _ if column.nullable => Value::NULL,
+        other => {
+            return Err(RusqlError::Command(format!(
+                "Cannot generate values for {} of type {}",
+                column.name, other
+            )));
+        }
+    };
+    Ok(value)
+}
+
+fn is_integer(data_type: &str) -> bool {
+    matches!(data_type, "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint")
+}
+
+/// A random moment in the last few years; distinct ones step back a second per row.
+fn moment(rng: &mut Rng, unique: bool, n: u64) -> NaiveDateTime {
+    let seconds = if unique { n } else { rng.below(DATE_SPAN_DAYS * 86_400) };
+    Local::now().naive_local() - Duration::seconds(seconds as i64)
+}
+
+/// Text fitting the column's name, length and uniqueness.
+fn text(rng: &mut Rng, column: &Column, n: u64, run: &str) -> String {
+    let name = column.name.to_lowercase();
+    // The input is random already, so no secret is needed
+    let fake = |mask: Mask, rng: &mut Rng| rusql::format::format_value(&mask.apply(&Value::UInt(rng.next()), &[]));
+    let pick = |list: &[&str], rng: &mut Rng| list[rng.below(list.len() as u64) as usize].to_string();
+    let mut text = if name.contains("email") {
+        fake(Mask::Email, rng)
+    } else if name.contains("first_name") || name.contains("firstname") {
+        fake(Mask::FirstName, rng)
+    } else if name.contains("last_name") || name.contains("lastname") || name.contains("surname") {
+        fake(Mask::LastName, rng)
+    } else if name.contains("name") {
+        fake(Mask::Name, rng)
+    } else if name.contains("phone") || name.contains("mobile") {
+        fake(Mask::Phone, rng)
+    } else if name.contains("url") || name.contains("website") {
+        format!("https://example.com/{}", pick(WORDS, rng))
+    } else if name.contains("city") {
+        pick(CITIES, rng)
+    } else if name.contains("country") {
+        pick(COUNTRIES, rng)
+    } else if name.contains("status") || name.contains("state") {
+        pick(STATUSES, rng)
+    } else {
+        let words = if column.data_type.ends_with("text") { 8 + rng.below(12) } else { 1 + rng.below(3) };
+        let words: Vec<String> = (0..words).map(|_| pick(WORDS, rng)).collect();
+        words.join(" ")
+    };
+
+    let max = column.max_length.map_or(usize::MAX, |max| max as usize);
+    if column.unique {
+        // The run tag keeps values distinct from earlier seeding runs too
+        let suffix = format!("-{}{}", run, n);
+        let (local, domain) = match text.split_once('@') {
+            Some((local, domain)) => (local.to_string(), format!("@{}", domain)),
+            None => (text.clone(), String::new()),
+        };
+        let room = max.saturating_sub(suffix.len() + domain.len());
+        text = format!("{}{}{}", local.chars().take(room).collect::<String>(), suffix, domain);
+    }
+    text.chars().take(max).collect()
+}
+
+/// The quoted options of an `enum(...)` or `set(...)` column type.
+fn choices(column_type: &str) -> Vec<String> {
+    let inner = column_type.split_once('(').map_or("", |(_, rest)| rest.trim_end_matches(')'));
+    inner.split("','")
+        .map(|option| option.trim_matches('\'').replace("''", "'"))
+        .filter(|option| !option.is_empty())
+        .collect()
+}
+
+/// Xorshift generator seeded from the clock; fake data needs no better.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        Rng(SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |t| t.as_nanos() as u64) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound.max(1)
+    }
+}
@@ -0,0 +1,117 @@
+use crate::users::ask;
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use rusql::error::{Result, RusqlError};
+use std::collections::BTreeMap;
+
+/// Where a variable's global value came from, when the server says.
+const COMPILED: &str = "COMPILED";
+
+/// `\variables [pattern]`: session and global values side by side. Session
+/// values differing from the global one and global values changed from the
+/// compiled-in default are highlighted. The pattern is a LIKE pattern, or a
+/// substring when it has no `%` or `_`.
+pub fn variables(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let pattern = match args.trim_matches(|c| c == '\'' || c == '"') {
+        "" => "%".to_string(),
+        pattern if pattern.contains(['%', '_']) => pattern.to_string(),
+        pattern => format!("%{}%", pattern),
+    };
+
+    let session: Vec<(String, String)> = client.conn.exec("SHOW SESSION VARIABLES LIKE ?", (&pattern,))?;
+    let global: BTreeMap<String, String> = client.conn.exec("SHOW GLOBAL VARIABLES LIKE ?", (&pattern,))?
+        .into_iter()
+        .collect();
+    let sources = sources(client, &pattern);
+
+    let mut names: Vec<&String> = global.keys().chain(session.iter().map(|(name, _)| name)).collect();
+    names.sort();
+    names.dedup();
+
+    let mut differing = 0;
+    let mut changed = 0;
+    let rows: Vec<Vec<String>> = names.into_iter()
+        .map(|name| {
+            let session_value = session.iter().find(|(n, _)| n == name).map(|(_, value)| value);
+            let global_value = global.get(name);
+            let source = sources.get(name).cloned().unwrap_or_default();
+
+            let session_cell = match (session_value, global_value) {
+                (Some(value), Some(global)) if value != global => {
+                    differing += 1;
+                    client.paint(value, client.theme.warning)
+                }
+                (Some(value), _) => value.clone(),
+                (None, _) => String::new(),
+            };
+            let global_cell = match global_value {
+                Some(value) if !source.is_empty() && source != COMPILED => {
+                    changed += 1;
+                    client.paint(value, client.theme.notice)
+                }
+                Some(value) => value.clone(),
+                None => String::new(),
+            };
+            vec![name.clone(), session_cell, global_cell, source.to_lowercase()]
+        })
+        .collect();
+
+    let mut summary = format!("{} variables, {} with a session value differing from the global one", rows.len(), differing);
+    if !sources.is_empty() {
+        summary.push_str(&format!(", {} changed from the default", changed));
+    }
+    let headers = ["Variable", "Session", "Global", "Source"];
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+/// Origin of each global value: `COMPILED`, `GLOBAL` (set at runtime),
+/// `PERSISTED`, `EXPLICIT` (option file), ... Empty on servers that do not
+/// track it.
+fn sources(client: &mut MySQLClient, pattern: &str) -> BTreeMap<String, String> {
+    // MySQL 8.0+; MariaDB keeps the origin in information_schema
+    let sources: Vec<(String, String)> = client.conn
+        .exec(
+            "SELECT VARIABLE_NAME, VARIABLE_SOURCE FROM performance_schema.variables_info WHERE VARIABLE_NAME LIKE ?",
+            (pattern,),
+        )
+        .or_else(|_| {
+            client.conn.exec(
+                "SELECT LOWER(VARIABLE_NAME), REPLACE(GLOBAL_VALUE_ORIGIN, 'COMPILE-TIME', 'COMPILED') \
+                 FROM information_schema.SYSTEM_VARIABLES WHERE VARIABLE_NAME LIKE ?",
+                (pattern,),
+            )
+        })
+        .unwrap_or_default();
+    sources.into_iter().collect()
+}
+
+/// `\set-global <variable> <value>`: `SET GLOBAL` after showing the current
+/// value and asking for confirmation. The value is SQL, so strings need quotes.
+pub fn set_global(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let usage = || RusqlError::Command("Usage: \\set-global <variable> <value>".into());
+    let (name, value) = args.split_once(char::is_whitespace).ok_or_else(usage)?;
+    let (name, value) = (name.trim_start_matches("@@").trim_start_matches("global."), value.trim());
+    if value.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+        return Err(usage());
+    }
+
+    // `_` is a LIKE wildcard; escaped, only the variable itself matches
+    let like = name.replace('_', "\\_");
+    let current: Option<(String, String)> = client.conn.exec_first("SHOW GLOBAL VARIABLES LIKE ?", (&like,))?;
+    let Some((name, current)) = current else {
+        return Err(RusqlError::Command(format!("Unknown system variable '{}'", name)));
+    };
+    let statement = format!("SET GLOBAL {} = {}", name, value);
+    println!("{} is {} for every new session; this runs:", name, client.paint(&current, client.theme.notice));
+    println!("{};", statement);
+    if !ask("Change it? [y/N] ")?.eq_ignore_ascii_case("y") {
+        client.notice("Nothing was changed");
+        return Ok(None);
+    }
+
+    client.conn.query_drop(&statement)?;
+    let new: Option<(String, String)> = client.conn.exec_first("SHOW GLOBAL VARIABLES LIKE ?", (&like,))?;
+    let new = new.map(|(_, value)| value).unwrap_or_default();
+    client.notice(&format!("{} changed from {} to {}; it is lost on restart unless also set in the option file", name, current, new));
+    Ok(None)
+}
@@ -0,0 +1,95 @@
+//! Routing of a sharding key's values to the profile of the server that
+//! holds them, by key range or by hash.
+
+use crate::error::{Result, RusqlError};
+use flate2::Crc;
+use std::str::FromStr;
+
+/// How a hashed shard map spreads keys over its profiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardHash {
+    /// CRC32 of the key's text, for keys of any type.
+    Crc32,
+    /// The integer key modulo the number of profiles.
+    Modulo,
+}
+
+impl ShardHash {
+    pub const VARIANTS: &'static [&'static str] = &["crc32", "modulo"];
+}
+
+impl FromStr for ShardHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "crc32" => Ok(ShardHash::Crc32),
+            "modulo" => Ok(ShardHash::Modulo),
+            other => Err(format!("unknown shard hash '{}'", other)),
+        }
+    }
+}
+
+/// Where the values of one sharding key live.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShardMap {
+    /// Profiles by ascending upper bound, inclusive; a last range without
+    /// one takes every key above the others.
+    Ranges(Vec<(Option<i64>, String)>),
+    /// Keys hashed over the profiles, in order.
+    Hash(ShardHash, Vec<String>),
+}
+
+impl ShardMap {
+    /// The profile holding `key`.
+    ///
+    /// ```
+    /// use rusql::shard::{ShardHash, ShardMap};
+    /// let ranges = ShardMap::Ranges(vec![
+    ///     (Some(999_999), "shard01".to_string()),
+    ///     (None, "shard02".to_string()),
+    /// ]);
+    /// assert_eq!(ranges.route("12345")?, "shard01");
+    /// assert_eq!(ranges.route("1000000")?, "shard02");
+    ///
+    /// let hashed = ShardMap::Hash(ShardHash::Modulo, vec!["even".to_string(), "odd".to_string()]);
+    /// assert_eq!(hashed.route("7")?, "odd");
+    /// assert!(hashed.route("acme").is_err());
+    /// # Ok::<(), rusql::error::RusqlError>(())
+    /// ```
+    pub fn route(&self, key: &str) -> Result<&str> {
+        let integer = || key.parse::<i64>()
+            .map_err(|_| RusqlError::Command(format!("Shard key '{}' is not an integer", key)));
+        match self {
+            ShardMap::Ranges(ranges) => {
+                let key = integer()?;
+                ranges.iter()
+                    .find(|(to, _)| to.is_none_or(|to| key <= to))
+                    .map(|(_, profile)| profile.as_str())
+                    .ok_or_else(|| RusqlError::Command(format!("No shard range holds {}", key)))
+            }
+            ShardMap::Hash(_, profiles) if profiles.is_empty() => {
+                Err(RusqlError::Config("A hashed shard map needs at least one profile".into()))
+            }
+            ShardMap::Hash(hash, profiles) => {
+                let bucket = match hash {
+                    ShardHash::Crc32 => {
+                        let mut crc = Crc::new();
+                        crc.update(key.as_bytes());
+                        crc.sum() as u64 % profiles.len() as u64
+                    }
+                    ShardHash::Modulo => integer()?.rem_euclid(profiles.len() as i64) as u64,
+                };
+                Ok(&profiles[bucket as usize])
+            }
+        }
+    }
+
+    /// Every profile the map routes to.
+    pub fn profiles(&self) -> Vec<&str> {
+        match self {
+            ShardMap::Ranges(ranges) => ranges.iter().map(|(_, profile)| profile.as_str()).collect(),
+            ShardMap::Hash(_, profiles) => profiles.iter().map(String::as_str).collect(),
+        }
+    }
+}
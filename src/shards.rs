@@ -0,0 +1,79 @@
+use crate::expand::LastResult;
+use crate::recall::show_latest;
+use crate::{interrupt, variables, MySQLClient, QueryResult};
+use rusql::error::{Result, RusqlError};
+use rusql::job::{Job, Outcome};
+use rusql::shard::{ShardHash, ShardMap};
+use rusql::sink::{write_rows, TableSink};
+
+const USAGE: &str = "Usage: \\shard [key=value [statement]]";
+
+/// `\shard key=value <statement>`: run a statement on the profile that
+/// holds `value` of a sharding key from the `[shards]` config, on its own
+/// connection. Without a statement, show which profile that is; without
+/// arguments, list the shard maps.
+pub fn shard(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let args = args.trim();
+    if args.is_empty() {
+        return Ok(Some(list(client)));
+    }
+    let (route, statement) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let (key, value) = route.split_once('=').ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+    let value = value.trim_matches(|c| c == '\'' || c == '"');
+    let map = client.shards.get(key).ok_or_else(|| RusqlError::Command(format!(
+        "No [shards.{}] map; known keys: {}",
+        key,
+        client.shards.keys().cloned().collect::<Vec<_>>().join(", ")
+    )))?;
+    let name = map.route(value)?.to_string();
+
+    let statement = statement.trim().trim_end_matches(';').trim_end();
+    if statement.is_empty() {
+        client.notice(&format!("{} = {} is on {}", key, value, name));
+        return Ok(None);
+    }
+    let profile = client.profiles.get(&name)
+        .ok_or_else(|| RusqlError::Config(format!("Shard profile '{}' is not in [profiles]", name)))?;
+    let statement = variables::interpolate(statement, &client.variables);
+    let Outcome { columns, mut rows, affected_rows, elapsed } = Job::spawn(profile.opts(), &statement)?.wait_unless(interrupt::interrupted)?;
+
+    if columns.is_empty() {
+        client.notice(&format!(
+            "Query OK on {}, {} {} affected ({:.2} sec)",
+            name,
+            affected_rows,
+            if affected_rows == 1 { "row" } else { "rows" },
+            elapsed.as_secs_f64()
+        ));
+        return Ok(None);
+    }
+    client.sensitive_rules.result_columns(&columns).apply_rows(&mut rows);
+    let mut sink = TableSink::new(false);
+    write_rows(&mut sink, &columns, rows, "")?;
+    let result = LastResult::from_sink(&sink);
+    let count = result.rows.len();
+    client.results.push(&format!("\\shard {}", args), result);
+    let summary = format!(
+        "{} {} in set on {} ({:.2} sec)",
+        count,
+        if count == 1 { "row" } else { "rows" },
+        name,
+        elapsed.as_secs_f64()
+    );
+    show_latest(client, client.format, &summary)
+}
+
+fn list(client: &MySQLClient) -> QueryResult {
+    let rows: Vec<Vec<String>> = client.shards.iter()
+        .map(|(key, map)| {
+            let routing = match map {
+                ShardMap::Ranges(_) => "ranges",
+                ShardMap::Hash(ShardHash::Crc32, _) => "crc32 hash",
+                ShardMap::Hash(ShardHash::Modulo, _) => "modulo hash",
+            };
+            vec![key.clone(), routing.to_string(), map.profiles().join(", ")]
+        })
+        .collect();
+    let summary = format!("{} shard maps", rows.len());
+    QueryResult { table: client.build_table(&["Key", "Routing", "Profiles"], rows), summary }
+}
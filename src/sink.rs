@@ -0,0 +1,901 @@
+use crate::format::{format_value, relative_duration, value_to_json, NumberFormat};
+use crate::totals::{column_totals, Total};
+use chrono::NaiveDateTime;
+use crate::narrow::{self, Strategy};
+use colored::*;
+use mysql::consts::ColumnType;
+use mysql::{Column, Row, Value};
+use prettytable::{format, Cell, Row as PrettyRow, Table};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Receives a result set one piece at a time.
+///
+/// The execution path only talks to this trait, so new output formats can be
+/// added by implementing it without touching how statements are run.
+pub trait OutputSink {
+    /// Called once with the result's columns before any row.
+    fn begin(&mut self, columns: &[Column]) -> io::Result<()>;
+
+    fn row_began(&mut self) -> io::Result<()>;
+
+    fn cell(&mut self, index: usize, value: &Value) -> io::Result<()>;
+
+    fn row_ended(&mut self) -> io::Result<()>;
+
+    /// Called once after the last row with the human readable summary.
+    fn summary(&mut self, summary: &str) -> io::Result<()>;
+}
+
+/// Feed a result set through a sink.
+pub fn write_rows<S, I>(sink: &mut S, columns: &[Column], rows: I, summary: &str) -> io::Result<()>
+where
+    S: OutputSink + ?Sized,
+    I: IntoIterator<Item = Row>,
+{
+    sink.begin(columns)?;
+    for row in rows {
+        sink.row_began()?;
+        for index in 0..columns.len() {
+            sink.cell(index, row.as_ref(index).unwrap_or(&Value::NULL))?;
+        }
+        sink.row_ended()?;
+    }
+    sink.summary(summary)
+}
+
+/// Output formats selectable with `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    Json,
+    Sql,
+    #[cfg(feature = "export-parquet")]
+    Parquet,
+    #[cfg(feature = "xlsx")]
+    Xlsx,
+}
+
+impl OutputFormat {
+    pub const VARIANTS: &'static [&'static str] = &[
+        "table",
+        "csv",
+        "json",
+        "sql",
+        #[cfg(feature = "export-parquet")]
+        "parquet",
+        #[cfg(feature = "xlsx")]
+        "xlsx",
+    ];
+
+    /// Streaming sink writing this format to `out`, or `None` for tables,
+    /// which are built in memory and returned to the caller.
+    pub fn sink<'a, W: Write + Send + 'a>(self, out: W, options: &SinkOptions) -> Option<Box<dyn OutputSink + 'a>> {
+        match self {
+            OutputFormat::Table => None,
+            OutputFormat::Csv => Some(Box::new(CsvSink::new(out).spreadsheet_safe(options.spreadsheet_safe))),
+            OutputFormat::Json => Some(Box::new(JsonSink::new(out))),
+            OutputFormat::Sql => Some(Box::new(SqlSink::new(out).table(options.sql_table.as_deref()))),
+            #[cfg(feature = "export-parquet")]
+            OutputFormat::Parquet => Some(Box::new(crate::parquet_sink::ParquetSink::new(out))),
+            #[cfg(feature = "xlsx")]
+            OutputFormat::Xlsx => Some(Box::new(crate::xlsx_sink::XlsxSink::new(out))),
+        }
+    }
+
+    /// The name given to `--format`.
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Table => "table",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Json => "json",
+            OutputFormat::Sql => "sql",
+            #[cfg(feature = "export-parquet")]
+            OutputFormat::Parquet => "parquet",
+            #[cfg(feature = "xlsx")]
+            OutputFormat::Xlsx => "xlsx",
+        }
+    }
+
+    /// Binary formats, which make no sense on a terminal and can't be
+    /// appended to.
+    pub fn is_binary(self) -> bool {
+        match self {
+            #[cfg(feature = "export-parquet")]
+            OutputFormat::Parquet => true,
+            #[cfg(feature = "xlsx")]
+            OutputFormat::Xlsx => true,
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(OutputFormat::Table),
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            "sql" => Ok(OutputFormat::Sql),
+            #[cfg(feature = "export-parquet")]
+            "parquet" => Ok(OutputFormat::Parquet),
+            #[cfg(feature = "xlsx")]
+            "xlsx" => Ok(OutputFormat::Xlsx),
+            other => Err(format!("unknown format '{}'", other)),
+        }
+    }
+}
+
+/// Settings that tune how the streaming sinks render values.
+#[derive(Debug, Clone, Default)]
+pub struct SinkOptions {
+    /// Neutralize CSV cells that spreadsheets would evaluate as formulas.
+    pub spreadsheet_safe: bool,
+    /// Table named in `INSERT` statements, instead of the one the columns
+    /// were read from.
+    pub sql_table: Option<String>,
+}
+
+/// Kind of value a column holds, used to style its cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Number,
+    Date,
+    Boolean,
+    Text,
+}
+
+impl ValueKind {
+    /// `TINYINT(1)` and `BIT(1)` count as booleans.
+    pub fn of(column: &Column) -> Self {
+        match column.column_type() {
+            ColumnType::MYSQL_TYPE_TINY | ColumnType::MYSQL_TYPE_BIT if column.column_length() == 1 => ValueKind::Boolean,
+            column_type if narrow::is_numeric(column_type) => ValueKind::Number,
+            ColumnType::MYSQL_TYPE_DATE
+            | ColumnType::MYSQL_TYPE_NEWDATE
+            | ColumnType::MYSQL_TYPE_DATETIME
+            | ColumnType::MYSQL_TYPE_DATETIME2
+            | ColumnType::MYSQL_TYPE_TIMESTAMP
+            | ColumnType::MYSQL_TYPE_TIMESTAMP2
+            | ColumnType::MYSQL_TYPE_TIME
+            | ColumnType::MYSQL_TYPE_TIME2 => ValueKind::Date,
+            _ => ValueKind::Text,
+        }
+    }
+}
+
+/// Colors of table cells by kind of value, applied when colors are enabled.
+/// `None` leaves text in the terminal's own foreground color.
+#[derive(Debug, Clone, Copy)]
+pub struct CellColors {
+    pub header: Option<Color>,
+    pub number: Option<Color>,
+    pub date: Option<Color>,
+    pub boolean: Option<Color>,
+    pub text: Option<Color>,
+    pub null: Option<Color>,
+}
+
+impl Default for CellColors {
+    fn default() -> Self {
+        CellColors {
+            header: Some(Color::BrightCyan),
+            number: Some(Color::BrightYellow),
+            date: Some(Color::BrightMagenta),
+            boolean: Some(Color::BrightGreen),
+            text: Some(Color::BrightWhite),
+            null: Some(Color::BrightRed),
+        }
+    }
+}
+
+impl CellColors {
+    pub fn for_kind(&self, kind: ValueKind) -> Option<Color> {
+        match kind {
+            ValueKind::Number => self.number,
+            ValueKind::Date => self.date,
+            ValueKind::Boolean => self.boolean,
+            ValueKind::Text => self.text,
+        }
+    }
+}
+
+/// `text` in `color`, or unchanged for the terminal's default color.
+pub fn paint(text: &str, color: Option<Color>) -> String {
+    match color {
+        Some(color) => text.color(color).to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// Glyphs drawn around and between table cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Borders {
+    /// Box-drawing characters.
+    #[default]
+    Unicode,
+    /// `+---+` frames as printed by the mysql client.
+    Ascii,
+    /// Columns separated by spaces only.
+    None,
+    /// Inner separators and a rule under the header, without an outer frame.
+    Compact,
+}
+
+impl Borders {
+    pub const VARIANTS: &'static [&'static str] = &["unicode", "ascii", "none", "compact"];
+}
+
+impl FromStr for Borders {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unicode" => Ok(Borders::Unicode),
+            "ascii" => Ok(Borders::Ascii),
+            "none" => Ok(Borders::None),
+            "compact" => Ok(Borders::Compact),
+            other => Err(format!("unknown border style '{}'", other)),
+        }
+    }
+}
+
+/// Whether DATETIME and TIMESTAMP cells show how long ago they were.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RelativeTime {
+    #[default]
+    Off,
+    /// `2024-05-01 09:00:00 (3h ago)`
+    Beside,
+    /// `3h ago`
+    Instead,
+}
+
+impl RelativeTime {
+    pub const VARIANTS: &'static [&'static str] = &["off", "beside", "instead"];
+}
+
+impl FromStr for RelativeTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(RelativeTime::Off),
+            "beside" => Ok(RelativeTime::Beside),
+            "instead" => Ok(RelativeTime::Instead),
+            other => Err(format!("unknown relative time mode '{}'", other)),
+        }
+    }
+}
+
+/// How long ago a DATETIME or TIMESTAMP value was; zero dates have none.
+fn relative(text: &str, column_type: ColumnType, now: NaiveDateTime) -> Option<String> {
+    let temporal = matches!(
+        column_type,
+        ColumnType::MYSQL_TYPE_DATETIME
+            | ColumnType::MYSQL_TYPE_DATETIME2
+            | ColumnType::MYSQL_TYPE_TIMESTAMP
+            | ColumnType::MYSQL_TYPE_TIMESTAMP2
+    );
+    if !temporal {
+        return None;
+    }
+    let then = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").ok()?;
+    Some(relative_duration(then, now))
+}
+
+/// Table format shared by every result table.
+pub fn table_format(borders: Borders) -> format::TableFormat {
+    match borders {
+        Borders::Unicode => format::FormatBuilder::new()
+            .column_separator('│')
+            .borders('│')
+            .separator(format::LinePosition::Top, format::LineSeparator::new('─', '┌', '┐', '┬'))
+            .separator(format::LinePosition::Bottom, format::LineSeparator::new('─', '└', '┘', '┴'))
+            .separator(format::LinePosition::Title, format::LineSeparator::new('─', '├', '┤', '┼'))
+            .padding(1, 1)
+            .build(),
+        Borders::Ascii => format::FormatBuilder::new()
+            .column_separator('|')
+            .borders('|')
+            .separators(
+                &[format::LinePosition::Top, format::LinePosition::Title, format::LinePosition::Bottom],
+                format::LineSeparator::new('-', '+', '+', '+'),
+            )
+            .padding(1, 1)
+            .build(),
+        Borders::None => format::FormatBuilder::new()
+            .column_separator(' ')
+            .padding(0, 1)
+            .build(),
+        Borders::Compact => format::FormatBuilder::new()
+            .column_separator('|')
+            .separator(format::LinePosition::Title, format::LineSeparator::new('-', '+', '+', '+'))
+            .padding(1, 1)
+            .build(),
+    }
+}
+
+/// Builds the pretty-printed result table.
+///
+/// Rows are buffered so the table can be narrowed to the terminal width once
+/// every value is known.
+#[derive(Clone)]
+pub struct TableSink {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Option<String>>>,
+    current: Vec<Option<String>>,
+    use_colors: bool,
+    summary: String,
+    max_width: Option<usize>,
+    max_column_width: Option<usize>,
+    auto_vertical: bool,
+    colors: CellColors,
+    borders: Borders,
+    number_format: NumberFormat,
+    relative_time: Option<(RelativeTime, NaiveDateTime)>,
+    timestamp_label: Option<String>,
+    totals: bool,
+    low_priority: Vec<String>,
+}
+
+impl TableSink {
+    pub fn new(use_colors: bool) -> Self {
+        TableSink {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            current: Vec::new(),
+            use_colors,
+            summary: String::new(),
+            max_width: None,
+            max_column_width: None,
+            auto_vertical: false,
+            colors: CellColors::default(),
+            borders: Borders::default(),
+            number_format: NumberFormat::default(),
+            relative_time: None,
+            timestamp_label: None,
+            totals: false,
+            low_priority: Vec::new(),
+        }
+    }
+
+    /// Narrow the table to `width` terminal columns, hiding columns matching
+    /// `low_priority` patterns first if truncation is not enough.
+    pub fn fit_to(mut self, width: Option<usize>, low_priority: &[String]) -> Self {
+        self.max_width = width;
+        self.low_priority = low_priority.to_vec();
+        self
+    }
+
+    /// Cut values longer than `width` characters with an ellipsis. Short
+    /// numbers are never cut.
+    pub fn max_column_width(mut self, width: Option<usize>) -> Self {
+        self.max_column_width = width.filter(|&w| w > 0);
+        self
+    }
+
+    /// Print one `column: value` line per cell instead of narrowing the
+    /// table when it is wider than the width given to [`TableSink::fit_to`].
+    pub fn auto_vertical(mut self, enabled: bool) -> Self {
+        self.auto_vertical = enabled;
+        self
+    }
+
+    /// Colors used for cells when colors are enabled.
+    pub fn cell_colors(mut self, colors: CellColors) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Glyphs drawn around and between cells.
+    pub fn borders(mut self, borders: Borders) -> Self {
+        self.borders = borders;
+        self
+    }
+
+    /// Grouping, decimal places and notation for numeric columns. Values
+    /// returned by [`TableSink::rows`] are left as the server sent them.
+    pub fn number_format(mut self, format: NumberFormat) -> Self {
+        self.number_format = format;
+        self
+    }
+
+    /// Show DATETIME and TIMESTAMP values relative to `now`, which should be
+    /// the current time in the session's time zone.
+    pub fn relative_time(mut self, mode: RelativeTime, now: NaiveDateTime) -> Self {
+        self.relative_time = (mode != RelativeTime::Off).then_some((mode, now));
+        self
+    }
+
+    /// Follow TIMESTAMP values with the session time zone they are shown in,
+    /// e.g. `+02:00`. DATETIME values carry no zone and are left alone.
+    pub fn timestamp_label(mut self, label: Option<String>) -> Self {
+        self.timestamp_label = label;
+        self
+    }
+
+    /// End the table with a sum and average of every numeric column and the
+    /// number of distinct values of the others.
+    pub fn totals(mut self, enabled: bool) -> Self {
+        self.totals = enabled;
+        self
+    }
+
+    /// Columns of the result received so far.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Column names of the result received so far.
+    pub fn column_names(&self) -> Vec<String> {
+        self.columns.iter().map(|c| c.name_str().into_owned()).collect()
+    }
+
+    /// Untruncated values received so far, `None` for NULL.
+    pub fn rows(&self) -> &[Vec<Option<String>>] {
+        &self.rows
+    }
+
+    /// The finished table and its summary line.
+    pub fn finish(mut self) -> (Table, String) {
+        let footer = (self.totals && !self.rows.is_empty()).then(|| self.footer());
+        if !self.number_format.is_plain() {
+            for row in &mut self.rows {
+                for (value, column) in row.iter_mut().zip(&self.columns) {
+                    if let Some(text) = value {
+                        *text = self.number_format.apply(text, column.column_type());
+                    }
+                }
+            }
+        }
+        if let Some((mode, now)) = self.relative_time {
+            for row in &mut self.rows {
+                for (value, column) in row.iter_mut().zip(&self.columns) {
+                    if let Some(text) = value && let Some(relative) = relative(text, column.column_type(), now) {
+                        *text = match mode {
+                            RelativeTime::Instead => relative,
+                            _ => format!("{} ({})", text, relative),
+                        };
+                    }
+                }
+            }
+        }
+        let relative_only = self.relative_time.is_some_and(|(mode, _)| mode == RelativeTime::Instead);
+        if let Some(label) = self.timestamp_label.as_ref().filter(|_| !relative_only) {
+            let timestamps: Vec<bool> = self.columns.iter()
+                .map(|c| matches!(c.column_type(), ColumnType::MYSQL_TYPE_TIMESTAMP | ColumnType::MYSQL_TYPE_TIMESTAMP2))
+                .collect();
+            for row in &mut self.rows {
+                for (value, _) in row.iter_mut().zip(&timestamps).filter(|(_, timestamp)| **timestamp) {
+                    if let Some(text) = value {
+                        // Relative times already follow the value in parentheses
+                        match text.find(" (") {
+                            Some(at) => text.insert_str(at, &format!(" {}", label)),
+                            None => text.push_str(&format!(" {}", label)),
+                        }
+                    }
+                }
+            }
+        }
+        let footer_rows = footer.as_ref().map_or(0, Vec::len);
+        self.rows.extend(footer.into_iter().flatten());
+        let names = self.column_names();
+        // A column cap without a terminal width still needs a layout to truncate
+        let fit_width = self.max_width.or(self.max_column_width.map(|_| usize::MAX));
+        let layout = fit_width.map(|max_width| {
+            let mut natural: Vec<usize> = names.iter().map(|n| narrow::display_width(n)).collect();
+            let strategies: Vec<Strategy> = self.columns.iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    let values: Vec<&str> = self.rows.iter().filter_map(|row| row[i].as_deref()).collect();
+                    for value in &values {
+                        natural[i] = natural[i].max(narrow::display_width(value));
+                    }
+                    narrow::strategy_for(column.column_type(), &values)
+                })
+                .collect();
+            if let Some(cap) = self.max_column_width {
+                for (width, strategy) in natural.iter_mut().zip(&strategies) {
+                    if *strategy != Strategy::Keep {
+                        *width = (*width).min(cap);
+                    }
+                }
+            }
+            let too_wide = narrow::total_width(&natural.iter().map(|&w| Some(w)).collect::<Vec<_>>()) > max_width;
+            (narrow::fit(&natural, &strategies, &narrow::hide_order(&names, &self.low_priority), max_width), too_wide)
+        });
+        if self.auto_vertical && layout.as_ref().is_some_and(|(_, too_wide)| *too_wide) {
+            return self.finish_vertical(&names, footer_rows);
+        }
+        let layout = layout.map(|(layout, _)| layout);
+
+        let visible = |i: usize| layout.as_ref().is_none_or(|l| l.widths[i].is_some());
+        let shorten = |i: usize, text: &str| match &layout {
+            Some(l) => narrow::truncate(text, l.widths[i].unwrap_or(0), l.strategies[i]),
+            None => text.to_string(),
+        };
+
+        let mut table = Table::new();
+        table.set_format(table_format(self.borders));
+
+        // Numbers are right-aligned so their digits line up
+        let kinds: Vec<ValueKind> = self.columns.iter().map(ValueKind::of).collect();
+        let align = |i: usize| if kinds[i] == ValueKind::Number { "r" } else { "l" };
+
+        let headers: Vec<Cell> = names.iter()
+            .enumerate()
+            .filter(|(i, _)| visible(*i))
+            .map(|(i, name)| {
+                let name = shorten(i, name);
+                let header = if self.use_colors { paint(&name, self.colors.header) } else { name };
+                Cell::new(&header).style_spec(&format!("b{}", align(i)))
+            })
+            .collect();
+        table.add_row(PrettyRow::new(headers));
+
+        let body_rows = self.rows.len() - footer_rows;
+        for (number, row) in self.rows.iter().enumerate() {
+            if number >= body_rows {
+                let cells: Vec<Cell> = row.iter()
+                    .enumerate()
+                    .filter(|(i, _)| visible(*i))
+                    .map(|(i, value)| {
+                        let text = shorten(i, value.as_deref().unwrap_or_default());
+                        let text = if self.use_colors { paint(&text, self.colors.header) } else { text };
+                        Cell::new(&text).style_spec(&format!("b{}", align(i)))
+                    })
+                    .collect();
+                table.add_row(PrettyRow::new(cells));
+                continue;
+            }
+            let cells: Vec<Cell> = row.iter()
+                .enumerate()
+                .filter(|(i, _)| visible(*i))
+                .map(|(i, value)| {
+                    let formatted = match (value, self.use_colors) {
+                        (None, true) => paint("NULL", self.colors.null),
+                        (None, false) => "NULL".to_string(),
+                        (Some(text), true) => paint(&shorten(i, text), self.colors.for_kind(kinds[i])),
+                        (Some(text), false) => shorten(i, text),
+                    };
+                    Cell::new(&formatted).style_spec(align(i))
+                })
+                .collect();
+            table.add_row(PrettyRow::new(cells));
+        }
+
+        let mut summary = self.summary;
+        if let Some(layout) = &layout {
+            let hidden: Vec<&str> = layout.hidden().map(|i| names[i].as_str()).collect();
+            if !hidden.is_empty() {
+                summary.push_str(&format!(" [hidden to fit: {}]", hidden.join(", ")));
+            }
+        }
+
+        (table, summary)
+    }
+
+    /// `\G`-style output: a header line per row, then one line per column
+    /// with the names right-aligned.
+    /// Footer rows for [`TableSink::totals`]: sums with counts of distinct
+    /// values, then averages.
+    fn footer(&self) -> Vec<Vec<Option<String>>> {
+        let totals = column_totals(&self.columns, &self.rows);
+        let (sums, averages) = totals.iter()
+            .zip(&self.columns)
+            .map(|(total, column)| match total {
+                Total::Numeric { sum, average } => (
+                    format!("sum {}", self.number_format.apply(sum, column.column_type())),
+                    // Averages of integers have decimals too
+                    format!("avg {}", self.number_format.apply(average, ColumnType::MYSQL_TYPE_NEWDECIMAL)),
+                ),
+                Total::Distinct(count) => (format!("{} distinct", count), String::new()),
+                Total::Empty => (String::new(), String::new()),
+            })
+            .map(|(sum, average)| (Some(sum), Some(average)))
+            .unzip();
+        vec![sums, averages]
+    }
+
+    /// Rows as `column: value` blocks; the last `footer_rows` rows are totals.
+    fn finish_vertical(self, names: &[String], footer_rows: usize) -> (Table, String) {
+        let mut table = Table::new();
+        table.set_format(format::FormatBuilder::new().column_separator(' ').padding(0, 0).build());
+
+        let kinds: Vec<ValueKind> = self.columns.iter().map(ValueKind::of).collect();
+        let name_width = names.iter().map(|n| narrow::display_width(n)).max().unwrap_or(0);
+        let body_rows = self.rows.len() - footer_rows;
+        for (number, row) in self.rows.iter().enumerate() {
+            // Footer rows follow a single banner
+            let banner = match number.checked_sub(body_rows) {
+                None => Some(format!("{0} {1}. row {0}", "*".repeat(27), number + 1)),
+                Some(0) => Some(format!("{0} totals {0}", "*".repeat(27))),
+                Some(_) => None,
+            };
+            if let Some(banner) = banner {
+                table.add_row(PrettyRow::new(vec![Cell::new(&banner).with_hspan(2)]));
+            }
+
+            for ((name, value), kind) in names.iter().zip(row).zip(&kinds) {
+                let name = format!("{:>width$}:", name, width = name_width);
+                let (name, value) = match (value, self.use_colors) {
+                    (None, true) => (paint(&name, self.colors.header), paint("NULL", self.colors.null)),
+                    (None, false) => (name, "NULL".to_string()),
+                    (Some(text), true) => (paint(&name, self.colors.header), paint(text, self.colors.for_kind(*kind))),
+                    (Some(text), false) => (name, text.clone()),
+                };
+                table.add_row(PrettyRow::new(vec![Cell::new(&name), Cell::new(&value)]));
+            }
+        }
+        (table, self.summary)
+    }
+}
+
+impl OutputSink for TableSink {
+    fn begin(&mut self, columns: &[Column]) -> io::Result<()> {
+        self.columns = columns.to_vec();
+        Ok(())
+    }
+
+    fn row_began(&mut self) -> io::Result<()> {
+        self.current = Vec::with_capacity(self.columns.len());
+        Ok(())
+    }
+
+    fn cell(&mut self, _index: usize, value: &Value) -> io::Result<()> {
+        self.current.push(match value {
+            Value::NULL => None,
+            other => Some(format_value(other)),
+        });
+        Ok(())
+    }
+
+    fn row_ended(&mut self) -> io::Result<()> {
+        self.rows.push(std::mem::take(&mut self.current));
+        Ok(())
+    }
+
+    fn summary(&mut self, summary: &str) -> io::Result<()> {
+        self.summary = summary.to_string();
+        Ok(())
+    }
+}
+
+/// RFC 4180 CSV with a header line. NULL is written as an empty field.
+pub struct CsvSink<W: Write> {
+    out: W,
+    first: bool,
+    spreadsheet_safe: bool,
+}
+
+impl<W: Write> CsvSink<W> {
+    pub fn new(out: W) -> Self {
+        CsvSink { out, first: true, spreadsheet_safe: false }
+    }
+
+    /// Prefix cells starting with `=`, `+`, `-`, `@`, tab, or carriage return
+    /// with `'` so spreadsheets show them as text instead of evaluating them
+    /// (OWASP CSV injection guidance). Numbers, including negative ones and
+    /// ones using a decimal comma, are left untouched.
+    pub fn spreadsheet_safe(mut self, enabled: bool) -> Self {
+        self.spreadsheet_safe = enabled;
+        self
+    }
+
+    fn text_field(&mut self, text: &str) -> io::Result<()> {
+        if self.spreadsheet_safe && is_formula(text) {
+            self.field(&format!("'{}", text))
+        } else {
+            self.field(text)
+        }
+    }
+
+    fn field(&mut self, text: &str) -> io::Result<()> {
+        if !self.first {
+            self.out.write_all(b",")?;
+        }
+        self.first = false;
+
+        if text.contains([',', '"', '\n', '\r']) {
+            write!(self.out, "\"{}\"", text.replace('"', "\"\""))
+        } else {
+            self.out.write_all(text.as_bytes())
+        }
+    }
+}
+
+impl<W: Write> OutputSink for CsvSink<W> {
+    fn begin(&mut self, columns: &[Column]) -> io::Result<()> {
+        self.first = true;
+        for column in columns {
+            self.text_field(&column.name_str())?;
+        }
+        self.out.write_all(b"\r\n")
+    }
+
+    fn row_began(&mut self) -> io::Result<()> {
+        self.first = true;
+        Ok(())
+    }
+
+    fn cell(&mut self, _index: usize, value: &Value) -> io::Result<()> {
+        match value {
+            Value::NULL => self.field(""),
+            other => self.text_field(&format_value(other)),
+        }
+    }
+
+    fn row_ended(&mut self) -> io::Result<()> {
+        self.out.write_all(b"\r\n")
+    }
+
+    fn summary(&mut self, _summary: &str) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn is_formula(text: &str) -> bool {
+    if !text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        return false;
+    }
+    let numeric = text.trim().replacen(',', ".", 1);
+    numeric.parse::<f64>().is_err()
+}
+
+/// A JSON array with one object per row, keyed by column name.
+pub struct JsonSink<W: Write> {
+    out: W,
+    columns: Vec<(String, ColumnType)>,
+    row: serde_json::Map<String, serde_json::Value>,
+    rows: usize,
+}
+
+impl<W: Write> JsonSink<W> {
+    pub fn new(out: W) -> Self {
+        JsonSink { out, columns: Vec::new(), row: serde_json::Map::new(), rows: 0 }
+    }
+}
+
+impl<W: Write> OutputSink for JsonSink<W> {
+    fn begin(&mut self, columns: &[Column]) -> io::Result<()> {
+        self.columns = columns.iter()
+            .map(|c| (c.name_str().into_owned(), c.column_type()))
+            .collect();
+        self.rows = 0;
+        self.out.write_all(b"[")
+    }
+
+    fn row_began(&mut self) -> io::Result<()> {
+        self.row.clear();
+        Ok(())
+    }
+
+    fn cell(&mut self, index: usize, value: &Value) -> io::Result<()> {
+        let (name, column_type) = &self.columns[index];
+        self.row.insert(name.clone(), value_to_json(value, *column_type));
+        Ok(())
+    }
+
+    fn row_ended(&mut self) -> io::Result<()> {
+        if self.rows > 0 {
+            self.out.write_all(b",")?;
+        }
+        self.rows += 1;
+        self.out.write_all(b"\n  ")?;
+        serde_json::to_writer(&mut self.out, &self.row)?;
+        Ok(())
+    }
+
+    fn summary(&mut self, _summary: &str) -> io::Result<()> {
+        self.out.write_all(if self.rows > 0 { b"\n]\n" } else { b"]\n" })?;
+        self.out.flush()
+    }
+}
+
+/// Character set number of binary strings and BLOBs.
+pub const BINARY_CHARSET: u16 = 63;
+
+/// One `INSERT` statement per row, into the table given with
+/// [`SqlSink::table`] or else the one the columns were read from. Numbers
+/// are written bare, binary values as hex literals, everything else as
+/// quoted strings.
+///
+/// ```
+/// use mysql::consts::ColumnType;
+/// use mysql::{Column, Value};
+/// use rusql::sink::{OutputSink, SqlSink};
+///
+/// let columns = [
+///     Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id").with_org_table(b"countries"),
+///     Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"name").with_org_table(b"countries"),
+/// ];
+/// let mut out = Vec::new();
+/// let mut sink = SqlSink::new(&mut out);
+/// sink.begin(&columns)?;
+/// for row in [[Value::from("1"), Value::from("Côte d'Ivoire")], [Value::from("2"), Value::NULL]] {
+///     sink.row_began()?;
+///     sink.cell(0, &row[0])?;
+///     sink.cell(1, &row[1])?;
+///     sink.row_ended()?;
+/// }
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "INSERT INTO `countries` (`id`, `name`) VALUES (1, 'Côte d\\'Ivoire');\n\
+///      INSERT INTO `countries` (`id`, `name`) VALUES (2, NULL);\n"
+/// );
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct SqlSink<W: Write> {
+    out: W,
+    table: Option<String>,
+    /// `INSERT INTO ... VALUES (`, built once the columns are known.
+    prefix: String,
+    kinds: Vec<(ValueKind, bool)>,
+    values: Vec<String>,
+}
+
+impl<W: Write> SqlSink<W> {
+    pub fn new(out: W) -> Self {
+        SqlSink { out, table: None, prefix: String::new(), kinds: Vec::new(), values: Vec::new() }
+    }
+
+    /// Table to insert into, possibly `db.table`.
+    pub fn table(mut self, table: Option<&str>) -> Self {
+        self.table = table.map(str::to_string);
+        self
+    }
+}
+
+impl<W: Write> OutputSink for SqlSink<W> {
+    fn begin(&mut self, columns: &[Column]) -> io::Result<()> {
+        // Without a name given, the table every column came from, if they share one
+        let table = self.table.clone().unwrap_or_else(|| {
+            let mut tables = columns.iter().map(|c| c.org_table_str().into_owned());
+            match tables.next() {
+                Some(first) if !first.is_empty() && tables.all(|table| table == first) => first,
+                _ => "result".to_string(),
+            }
+        });
+        let names: Vec<String> = columns.iter().map(|c| crate::bulk::quote_identifier(&c.name_str())).collect();
+        self.prefix = format!("INSERT INTO {} ({}) VALUES (", crate::bulk::quote_table(&table), names.join(", "));
+        self.kinds = columns.iter().map(|c| (ValueKind::of(c), c.character_set() == BINARY_CHARSET)).collect();
+        Ok(())
+    }
+
+    fn row_began(&mut self) -> io::Result<()> {
+        self.values.clear();
+        Ok(())
+    }
+
+    fn cell(&mut self, index: usize, value: &Value) -> io::Result<()> {
+        let (kind, binary) = self.kinds[index];
+        let literal = match value {
+            Value::NULL => "NULL".to_string(),
+            Value::Bytes(bytes) if binary && kind == ValueKind::Text => {
+                match bytes.is_empty() {
+                    true => "''".to_string(),
+                    false => format!("X'{}'", bytes.iter().map(|b| format!("{:02X}", b)).collect::<String>()),
+                }
+            }
+            Value::Bytes(bytes) if kind == ValueKind::Number || kind == ValueKind::Boolean => {
+                String::from_utf8_lossy(bytes).into_owned()
+            }
+            Value::Bytes(_) => value.as_sql(false),
+            other => Value::from(format_value(other)).as_sql(false),
+        };
+        self.values.push(literal);
+        Ok(())
+    }
+
+    fn row_ended(&mut self) -> io::Result<()> {
+        writeln!(self.out, "{}{});", self.prefix, self.values.join(", "))
+    }
+
+    fn summary(&mut self, _summary: &str) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,55 @@
+use crate::progress::human_bytes;
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use rusql::error::{Result, RusqlError};
+
+/// One row of information_schema.TABLES; sizes are NULL for some engines.
+struct TableSize {
+    name: String,
+    engine: Option<String>,
+    rows: Option<u64>,
+    data: Option<u64>,
+    index: Option<u64>,
+    free: Option<u64>,
+}
+
+/// `\sizes [db]`: data and index size, estimated rows, engine and free space
+/// of every table in a database, largest first.
+pub fn sizes(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let db = match args {
+        "" => client.current_db.clone().ok_or_else(|| RusqlError::Command("No database selected; use \\sizes <db>".into()))?,
+        db => db.replace('`', ""),
+    };
+
+    let tables: Vec<TableSize> = client.conn.exec_map(
+        "SELECT TABLE_NAME, ENGINE, TABLE_ROWS, DATA_LENGTH, INDEX_LENGTH, DATA_FREE \
+         FROM information_schema.TABLES \
+         WHERE TABLE_SCHEMA = ? AND TABLE_TYPE = 'BASE TABLE' \
+         ORDER BY COALESCE(DATA_LENGTH, 0) + COALESCE(INDEX_LENGTH, 0) DESC, TABLE_NAME",
+        (&db,),
+        |(name, engine, rows, data, index, free)| TableSize { name, engine, rows, data, index, free },
+    )?;
+    if tables.is_empty() {
+        return Err(RusqlError::Command(format!("No tables in database '{}'", db)));
+    }
+
+    let mut total = 0;
+    let rows: Vec<Vec<String>> = tables.into_iter()
+        .map(|table| {
+            let (data, index) = (table.data.unwrap_or(0), table.index.unwrap_or(0));
+            total += data + index;
+            vec![
+                table.name,
+                table.engine.unwrap_or_default(),
+                table.rows.map_or_else(String::new, |n| n.to_string()),
+                human_bytes(data),
+                human_bytes(index),
+                human_bytes(data + index),
+                table.free.map_or_else(String::new, human_bytes),
+            ]
+        })
+        .collect();
+    let summary = format!("{} tables in {}, {} in total (row counts and sizes are estimates)", rows.len(), db, human_bytes(total));
+    let headers = ["Table", "Engine", "Rows (est.)", "Data", "Index", "Total", "Free"];
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
@@ -0,0 +1,202 @@
+use mysql::prelude::*;
+use mysql::Conn;
+use prettytable::{Cell, Row, Table};
+use rusql::error::Result;
+use rusql::sink::{table_format, Borders};
+use rusql::statements::fingerprint;
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Characters of a fingerprint shown in the report.
+const MAX_FINGERPRINT_WIDTH: usize = 120;
+
+/// One statement recorded in the slow query log.
+#[derive(Debug, Default)]
+pub struct SlowQuery {
+    query_time: f64,
+    lock_time: f64,
+    rows_sent: u64,
+    rows_examined: u64,
+    statement: String,
+}
+
+/// Statements sharing a fingerprint, with their summed metrics.
+struct Digest {
+    fingerprint: String,
+    count: u64,
+    total_time: f64,
+    max_time: f64,
+    lock_time: f64,
+    rows_sent: u64,
+    rows_examined: u64,
+}
+
+/// What `slowlog analyze` ranks fingerprints by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OrderBy {
+    #[default]
+    Total,
+    Count,
+    Rows,
+}
+
+impl OrderBy {
+    pub const VARIANTS: &'static [&'static str] = &["total", "count", "rows"];
+}
+
+impl FromStr for OrderBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "total" => Ok(OrderBy::Total),
+            "count" => Ok(OrderBy::Count),
+            "rows" => Ok(OrderBy::Rows),
+            other => Err(format!("unknown ranking '{}'", other)),
+        }
+    }
+}
+
+/// Statements of a slow log file as written with `log_output=FILE`.
+pub fn read_file(path: &Path) -> Result<Vec<SlowQuery>> {
+    let bytes = std::fs::read(path)?;
+    Ok(parse(&String::from_utf8_lossy(&bytes)))
+}
+
+/// Statements of `mysql.slow_log`, written with `log_output=TABLE`.
+pub fn read_table(conn: &mut Conn) -> Result<Vec<SlowQuery>> {
+    let queries = conn.query_map(
+        "SELECT TIME_TO_SEC(query_time) + MICROSECOND(query_time) / 1000000, \
+         TIME_TO_SEC(lock_time) + MICROSECOND(lock_time) / 1000000, \
+         rows_sent, rows_examined, CONVERT(sql_text USING utf8mb4) \
+         FROM mysql.slow_log",
+        |(query_time, lock_time, rows_sent, rows_examined, statement): (f64, f64, u64, u64, String)| SlowQuery {
+            query_time,
+            lock_time,
+            rows_sent,
+            rows_examined,
+            statement,
+        },
+    )?;
+    Ok(queries)
+}
+
+/// Split a slow log into its entries. An entry is a block of `# ` header
+/// lines followed by the statement; the `SET timestamp` and `use` lines the
+/// server adds before it are not part of the statement.
+fn parse(log: &str) -> Vec<SlowQuery> {
+    let mut queries = Vec::new();
+    let mut current: Option<SlowQuery> = None;
+
+    for line in log.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            // A header after statement lines starts the next entry
+            if current.as_ref().is_some_and(|query| !query.statement.is_empty()) {
+                queries.extend(current.take());
+            }
+            if header.starts_with("Query_time:") {
+                let query = current.get_or_insert_with(SlowQuery::default);
+                let mut words = header.split_whitespace();
+                while let (Some(name), Some(value)) = (words.next(), words.next()) {
+                    match name {
+                        "Query_time:" => query.query_time = value.parse().unwrap_or(0.0),
+                        "Lock_time:" => query.lock_time = value.parse().unwrap_or(0.0),
+                        "Rows_sent:" => query.rows_sent = value.parse().unwrap_or(0),
+                        "Rows_examined:" => query.rows_examined = value.parse().unwrap_or(0),
+                        _ => {}
+                    }
+                }
+            }
+            continue;
+        }
+        // The banner the server writes whenever it (re)opens the log
+        if line.contains(", Version: ") || line.starts_with("Tcp port:") || line.starts_with("Time  ") {
+            continue;
+        }
+        let Some(query) = current.as_mut() else {
+            continue;
+        };
+        let lower = line.trim_start().to_lowercase();
+        if query.statement.is_empty() && (lower.starts_with("set timestamp=") || lower.starts_with("use ")) {
+            continue;
+        }
+        if !query.statement.is_empty() {
+            query.statement.push('\n');
+        }
+        query.statement.push_str(line);
+    }
+    queries.extend(current.filter(|query| !query.statement.is_empty()));
+    queries
+}
+
+/// Group statements by fingerprint, best ranked first.
+fn digest(queries: &[SlowQuery], order_by: OrderBy) -> Vec<Digest> {
+    let mut digests: HashMap<String, Digest> = HashMap::new();
+    for query in queries {
+        let fingerprint = fingerprint(&query.statement);
+        let digest = digests.entry(fingerprint.clone()).or_insert_with(|| Digest {
+            fingerprint,
+            count: 0,
+            total_time: 0.0,
+            max_time: 0.0,
+            lock_time: 0.0,
+            rows_sent: 0,
+            rows_examined: 0,
+        });
+        digest.count += 1;
+        digest.total_time += query.query_time;
+        digest.max_time = digest.max_time.max(query.query_time);
+        digest.lock_time += query.lock_time;
+        digest.rows_sent += query.rows_sent;
+        digest.rows_examined += query.rows_examined;
+    }
+
+    let mut digests: Vec<Digest> = digests.into_values().collect();
+    digests.sort_by(|a, b| match order_by {
+        OrderBy::Total => b.total_time.total_cmp(&a.total_time),
+        OrderBy::Count => b.count.cmp(&a.count),
+        OrderBy::Rows => b.rows_examined.cmp(&a.rows_examined),
+    });
+    digests
+}
+
+/// The ranked report: the top `limit` fingerprints and what share of the
+/// total time each accounts for.
+pub fn report(queries: &[SlowQuery], order_by: OrderBy, limit: usize, borders: Borders) -> (Table, String) {
+    let digests = digest(queries, order_by);
+    let total: f64 = digests.iter().map(|digest| digest.total_time).sum();
+
+    let mut table = Table::new();
+    table.set_format(table_format(borders));
+    let headers = ["Rank", "Total time", "Share", "Calls", "Avg time", "Max time", "Lock time", "Rows examined", "Rows sent", "Fingerprint"];
+    table.add_row(Row::new(headers.iter().map(|header| Cell::new(header).style_spec("b")).collect()));
+    for (rank, digest) in digests.iter().take(limit).enumerate() {
+        let share = if total > 0.0 { digest.total_time * 100.0 / total } else { 0.0 };
+        let mut shape = digest.fingerprint.clone();
+        if shape.chars().count() > MAX_FINGERPRINT_WIDTH {
+            shape = shape.chars().take(MAX_FINGERPRINT_WIDTH - 1).chain(['…']).collect();
+        }
+        let cells = [
+            (rank + 1).to_string(),
+            format!("{:.3}s", digest.total_time),
+            format!("{:.1}%", share),
+            digest.count.to_string(),
+            format!("{:.3}s", digest.total_time / digest.count as f64),
+            format!("{:.3}s", digest.max_time),
+            format!("{:.3}s", digest.lock_time),
+            digest.rows_examined.to_string(),
+            digest.rows_sent.to_string(),
+            shape,
+        ];
+        table.add_row(Row::new(cells.iter().map(|cell| Cell::new(cell)).collect()));
+    }
+
+    let summary = format!(
+        "{} statements, {} fingerprints, {:.3}s in total",
+        queries.len(),
+        digests.len(),
+        total
+    );
+    (table, summary)
+}
@@ -0,0 +1,123 @@
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use rusql::error::{Result, RusqlError};
+
+const USAGE: &str = "Usage: \\sqlmode [+MODE|-MODE ...|default]";
+
+/// The modes listed by `\sqlmode`, with what each changes. Combination modes
+/// such as ANSI and TRADITIONAL are left out: the server expands them.
+const MODES: &[(&str, &str)] = &[
+    ("ALLOW_INVALID_DATES", "Dates are only checked for a month of 1-12 and a day of 1-31"),
+    ("ANSI_QUOTES", "\" quotes identifiers instead of strings"),
+    ("ERROR_FOR_DIVISION_BY_ZERO", "Division by zero in a write is an error in strict mode"),
+    ("HIGH_NOT_PRECEDENCE", "NOT a BETWEEN b AND c means (NOT a) BETWEEN b AND c"),
+    ("IGNORE_SPACE", "Spaces are allowed between a function name and ("),
+    ("NO_AUTO_VALUE_ON_ZERO", "Inserting 0 into an AUTO_INCREMENT column stores 0"),
+    ("NO_BACKSLASH_ESCAPES", "\\ is an ordinary character in strings"),
+    ("NO_DIR_IN_CREATE", "DATA DIRECTORY and INDEX DIRECTORY table options are ignored"),
+    ("NO_ENGINE_SUBSTITUTION", "CREATE TABLE fails when its engine is unavailable"),
+    ("NO_UNSIGNED_SUBTRACTION", "Subtracting unsigned integers gives a signed result"),
+    ("NO_ZERO_DATE", "'0000-00-00' is rejected in strict mode"),
+    ("NO_ZERO_IN_DATE", "Dates with a zero month or day are rejected in strict mode"),
+    ("ONLY_FULL_GROUP_BY", "Selected columns must be grouped or depend on the grouped ones"),
+    ("PAD_CHAR_TO_FULL_LENGTH", "CHAR values keep their trailing spaces"),
+    ("PIPES_AS_CONCAT", "|| concatenates strings instead of meaning OR"),
+    ("REAL_AS_FLOAT", "REAL means FLOAT instead of DOUBLE"),
+    ("STRICT_ALL_TABLES", "Invalid or missing values are errors in every table"),
+    ("STRICT_TRANS_TABLES", "Invalid or missing values are errors in transactional tables"),
+    ("TIME_TRUNCATE_FRACTIONAL", "Extra fractional seconds are truncated instead of rounded"),
+];
+
+/// `\sqlmode [+MODE|-MODE ...|default]`: show the session's SQL modes as a
+/// checklist, or switch modes on (`+`) and off (`-`) for the session, sparing
+/// the comma-separated `SET sql_mode` string. `default` goes back to the
+/// global modes.
+pub fn sqlmode(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let args = args.trim();
+    if args.is_empty() {
+        return Ok(Some(checklist(client)?));
+    }
+    if args.eq_ignore_ascii_case("default") {
+        client.conn.query_drop("SET SESSION sql_mode = @@GLOBAL.sql_mode")?;
+        let modes = current(client)?;
+        client.notice(&format!("sql_mode is back to the global {}", describe(&modes)));
+        return Ok(None);
+    }
+
+    let mut modes = current(client)?;
+    let before = modes.clone();
+    for toggle in args.split(|c: char| c.is_whitespace() || c == ',').filter(|toggle| !toggle.is_empty()) {
+        let (on, mode) = match (toggle.strip_prefix('+'), toggle.strip_prefix('-')) {
+            (Some(mode), _) => (true, mode),
+            (_, Some(mode)) => (false, mode),
+            (None, None) => return Err(RusqlError::Command(USAGE.into())),
+        };
+        if mode.is_empty() || !mode.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(RusqlError::Command(format!("Invalid SQL mode '{}'", mode)));
+        }
+        let mode = mode.to_uppercase();
+        match (on, modes.contains(&mode)) {
+            (true, false) => modes.push(mode),
+            (false, true) => modes.retain(|set| *set != mode),
+            (true, true) => client.notice(&format!("{} is already on", mode)),
+            (false, false) => client.notice(&format!("{} is already off", mode)),
+        }
+    }
+    if modes == before {
+        return Ok(None);
+    }
+
+    client.conn.exec_drop("SET SESSION sql_mode = ?", (modes.join(","),))?;
+    let after = current(client)?;
+    let added: Vec<&str> = after.iter().filter(|mode| !before.contains(mode)).map(String::as_str).collect();
+    let removed: Vec<&str> = before.iter().filter(|mode| !after.contains(mode)).map(String::as_str).collect();
+    let mut message = Vec::new();
+    if !added.is_empty() {
+        message.push(format!("on: {}", added.join(", ")));
+    }
+    if !removed.is_empty() {
+        message.push(format!("off: {}", removed.join(", ")));
+    }
+    message.push(format!("sql_mode is now {}", describe(&after)));
+    client.notice(&message.join("; "));
+    Ok(None)
+}
+
+/// The session's modes, in the server's order.
+fn current(client: &mut MySQLClient) -> Result<Vec<String>> {
+    let modes: Option<String> = client.conn.query_first("SELECT @@SESSION.sql_mode")?;
+    Ok(modes.unwrap_or_default()
+        .split(',')
+        .filter(|mode| !mode.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn describe(modes: &[String]) -> String {
+    match modes.is_empty() {
+        true => "empty".to_string(),
+        false => format!("'{}'", modes.join(",")),
+    }
+}
+
+/// Every known mode, checked when set, followed by any other mode the
+/// server reports.
+fn checklist(client: &mut MySQLClient) -> Result<QueryResult> {
+    let modes = current(client)?;
+    let mut rows: Vec<Vec<String>> = MODES.iter()
+        .map(|(mode, effect)| {
+            let set = modes.iter().any(|set| set == mode);
+            let mode = match set {
+                true => client.paint(mode, client.theme.notice),
+                false => mode.to_string(),
+            };
+            vec![if set { "[x]" } else { "[ ]" }.to_string(), mode, effect.to_string()]
+        })
+        .collect();
+    rows.extend(modes.iter()
+        .filter(|mode| !MODES.iter().any(|(known, _)| known == mode))
+        .map(|mode| vec!["[x]".to_string(), client.paint(mode, client.theme.notice), String::new()]));
+
+    let summary = format!("{} modes set; toggle them with \\sqlmode +MODE or -MODE", modes.len());
+    Ok(QueryResult { table: client.build_table(&["Set", "Mode", "Effect"], rows), summary })
+}
@@ -0,0 +1,301 @@
+//! Splitting scripts into individual statements.
+
+/// Splits input into statements, honoring `DELIMITER` commands.
+///
+/// Delimiters inside quoted strings, backquoted identifiers, and comments
+/// (`-- `, `#`, `/* */`) are ignored. A line `DELIMITER //` at the start of a
+/// statement switches the delimiter for the rest of the input and for later
+/// calls, so routine bodies containing `;` can be written as one statement.
+///
+/// ```
+/// let mut splitter = rusql::statements::Splitter::default();
+/// let statements = splitter.split("DELIMITER //\nCREATE PROCEDURE p() BEGIN SELECT 1; END //\nDELIMITER ;\nCALL p();");
+/// assert_eq!(statements, ["CREATE PROCEDURE p() BEGIN SELECT 1; END", "CALL p();"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Splitter {
+    delimiter: String,
+}
+
+impl Default for Splitter {
+    fn default() -> Self {
+        Splitter { delimiter: ";".to_string() }
+    }
+}
+
+impl Splitter {
+    /// The active statement delimiter.
+    pub fn delimiter(&self) -> &str {
+        &self.delimiter
+    }
+
+    /// Split `input` into statements. A `;` terminator is kept; a custom
+    /// delimiter is removed since the server does not understand it.
+    /// Unterminated text at the end is returned as the last statement, and
+    /// pieces holding only comments are dropped, as the server rejects them
+    /// as empty queries. Versioned comments (`/*!40101 ... */`) are
+    /// statements to the server and are kept.
+    ///
+    /// ```
+    /// let statements = rusql::statements::split("SELECT 1; -- done\n/* end */");
+    /// assert_eq!(statements, ["SELECT 1;"]);
+    /// let statements = rusql::statements::split("/*!40101 SET NAMES utf8 */;\n;");
+    /// assert_eq!(statements, ["/*!40101 SET NAMES utf8 */;"]);
+    /// ```
+    pub fn split(&mut self, input: &str) -> Vec<String> {
+        let mut statements = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+
+        while i < input.len() {
+            let rest = &input[i..];
+
+            if input[start..i].trim().is_empty()
+                && let Some(delimiter) = delimiter_command(rest.lines().next().unwrap_or_default())
+            {
+                self.delimiter = delimiter.to_string();
+                i += line_len(rest);
+                start = i;
+                continue;
+            }
+
+            if rest.starts_with(self.delimiter.as_str()) {
+                let end = i + self.delimiter.len();
+                let statement = if self.delimiter == ";" { &input[start..end] } else { &input[start..i] };
+                statements.push(statement.trim().to_string());
+                i = end;
+                start = end;
+                continue;
+            }
+
+            let c = rest.chars().next().unwrap_or_default();
+            i += match c {
+                '\'' | '"' | '`' => quoted_len(rest, c),
+                '#' => line_len(rest),
+                '-' if rest.starts_with("--") && rest[2..].chars().next().is_none_or(char::is_whitespace) => {
+                    line_len(rest)
+                }
+                '/' if rest.starts_with("/*") => rest[2..].find("*/").map_or(rest.len(), |n| n + 4),
+                _ => c.len_utf8(),
+            };
+        }
+        statements.push(input[start..].trim().to_string());
+
+        statements.retain(|statement| !keywords(statement).is_empty() || statement.contains("/*!"));
+        statements
+    }
+}
+
+/// Split `input` on `;` with a fresh [`Splitter`].
+pub fn split(input: &str) -> Vec<String> {
+    Splitter::default().split(input)
+}
+
+/// Upper-cased keywords and identifiers of a statement, skipping string
+/// literals, quoted identifiers and comments.
+///
+/// ```
+/// let words = rusql::statements::keywords("SELECT 'a limit' FROM t -- LIMIT\n");
+/// assert_eq!(words, ["SELECT", "FROM", "T"]);
+/// ```
+pub fn keywords(statement: &str) -> Vec<String> {
+    tokens(statement).into_iter()
+        .filter(|token| !token.quoted)
+        .map(|token| token.word)
+        .collect()
+}
+
+/// A keyword or identifier found by [`tokens`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    /// Byte range in the statement, including any backquotes.
+    pub start: usize,
+    pub end: usize,
+    /// Upper-cased, and without backquotes when `quoted`.
+    pub word: String,
+    /// A backquoted identifier.
+    pub quoted: bool,
+    /// Parentheses around the token, 0 outside any subquery or list.
+    pub depth: usize,
+}
+
+/// The keywords and identifiers of a statement with where they are, skipping
+/// string literals and comments, for finding or rewriting clauses in the
+/// original text.
+///
+/// ```
+/// let tokens = rusql::statements::tokens("DELETE FROM t\nWHERE id IN (SELECT id FROM u WHERE `where`) AND s = 'WHERE'");
+/// let wheres: Vec<_> = tokens.iter()
+///     .filter(|token| token.word == "WHERE")
+///     .map(|token| (token.start, token.quoted, token.depth))
+///     .collect();
+/// assert_eq!(wheres, [(14, false, 0), (44, false, 1), (50, true, 1)]);
+/// ```
+pub fn tokens(statement: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < statement.len() {
+        let rest = &statement[i..];
+        let c = rest.chars().next().unwrap_or_default();
+        if c.is_alphanumeric() || c == '_' || c == '$' {
+            start.get_or_insert(i);
+            i += c.len_utf8();
+            continue;
+        }
+        if let Some(start) = start.take() {
+            tokens.push(Token { start, end: i, word: statement[start..i].to_uppercase(), quoted: false, depth });
+        }
+        i += match c {
+            '`' => {
+                let len = quoted_len(rest, c);
+                let name = rest[1..len].strip_suffix('`').unwrap_or(&rest[1..len]);
+                tokens.push(Token { start: i, end: i + len, word: name.to_uppercase(), quoted: true, depth });
+                len
+            }
+            '\'' | '"' => quoted_len(rest, c),
+            '#' => line_len(rest),
+            '-' if rest.starts_with("--") && rest[2..].chars().next().is_none_or(char::is_whitespace) => {
+                line_len(rest)
+            }
+            '/' if rest.starts_with("/*") => rest[2..].find("*/").map_or(rest.len(), |n| n + 4),
+            '(' => {
+                depth += 1;
+                1
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                1
+            }
+            _ => c.len_utf8(),
+        };
+    }
+    if let Some(start) = start {
+        tokens.push(Token { start, end: statement.len(), word: statement[start..].to_uppercase(), quoted: false, depth });
+    }
+    tokens
+}
+
+/// Shape of a statement shared by every run of it with other values, for
+/// grouping like pt-query-digest: literals become `?`, lists of them `?+`,
+/// comments are dropped, whitespace collapses and everything outside
+/// backquotes is lower-cased.
+///
+/// ```
+/// let shape = rusql::statements::fingerprint("SELECT * FROM t WHERE id IN (1, 2, 3)\n  AND name = 'x'; -- hi");
+/// assert_eq!(shape, "select * from t where id in (?+) and name = ?");
+/// assert_eq!(rusql::statements::fingerprint("select * from t where id in (7)"), shape.replace(" and name = ?", ""));
+/// ```
+pub fn fingerprint(statement: &str) -> String {
+    let mut shape = String::new();
+    let mut i = 0;
+
+    while i < statement.len() {
+        let rest = &statement[i..];
+        let c = rest.chars().next().unwrap_or_default();
+        let word_len = |rest: &str| rest.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$' || c == '.')).unwrap_or(rest.len());
+        i += match c {
+            '\'' | '"' => {
+                placeholder(&mut shape);
+                quoted_len(rest, c)
+            }
+            '`' => {
+                let len = quoted_len(rest, c);
+                shape.push_str(&rest[..len]);
+                len
+            }
+            '#' => {
+                space(&mut shape);
+                line_len(rest)
+            }
+            '-' if rest.starts_with("--") && rest[2..].chars().next().is_none_or(char::is_whitespace) => {
+                space(&mut shape);
+                line_len(rest)
+            }
+            '/' if rest.starts_with("/*") => {
+                space(&mut shape);
+                rest[2..].find("*/").map_or(rest.len(), |n| n + 4)
+            }
+            c if c.is_whitespace() => {
+                space(&mut shape);
+                c.len_utf8()
+            }
+            // Numbers, including `1.5`, `1e3` and `0x1F`
+            c if c.is_ascii_digit() => {
+                placeholder(&mut shape);
+                word_len(rest)
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '$' => {
+                let len = word_len(rest);
+                shape.push_str(&rest[..len].to_lowercase());
+                len
+            }
+            c => {
+                shape.extend(c.to_lowercase());
+                c.len_utf8()
+            }
+        };
+    }
+
+    let mut shape = shape.trim().trim_end_matches(';').trim_end().replace("in (?)", "in (?+)");
+    // Multi-row VALUES lists
+    for row in ["(?+)", "(?)"] {
+        let repeated = format!("{}, {}", row, row);
+        while shape.contains(&repeated) {
+            shape = shape.replace(&repeated, row);
+        }
+    }
+    shape
+}
+
+/// Append `?` for a literal, or turn a preceding `?, ` into `?+`.
+fn placeholder(shape: &mut String) {
+    for list in ["?, ", "?,", "?+, ", "?+,"] {
+        if shape.ends_with(list) {
+            shape.truncate(shape.len() - list.len() + 1);
+            if !shape.ends_with('+') {
+                shape.push('+');
+            }
+            return;
+        }
+    }
+    shape.push('?');
+}
+
+/// Append a single space unless one is already there.
+fn space(shape: &mut String) {
+    if !shape.is_empty() && !shape.ends_with(' ') {
+        shape.push(' ');
+    }
+}
+
+/// The new delimiter if `line` is a `DELIMITER x` command.
+pub fn delimiter_command(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let keyword = line.get(..10)?;
+    if !keyword.eq_ignore_ascii_case("delimiter ") {
+        return None;
+    }
+    line[10..].split_whitespace().next()
+}
+
+/// Length of a quoted string or identifier starting at the front of `rest`,
+/// including both quotes. Backslash escapes apply to strings only.
+fn quoted_len(rest: &str, quote: char) -> usize {
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' && quote != '`' {
+            chars.next();
+        } else if c == quote {
+            return index + c.len_utf8();
+        }
+    }
+    rest.len()
+}
+
+/// Length of the rest of the line, including its newline.
+fn line_len(rest: &str) -> usize {
+    rest.find('\n').map_or(rest.len(), |n| n + 1)
+}
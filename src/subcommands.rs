@@ -0,0 +1,288 @@
+use crate::{codegen, config, diagram, seed, slowlog};
+#[cfg(feature = "bulk")]
+use crate::chunked;
+use crate::cli::{connection_opts, Command, Opts, SlowlogCommand};
+#[cfg(feature = "migrate")]
+use crate::cli::MigrateCommand;
+#[cfg(feature = "bulk")]
+use crate::cli::throttle;
+use crate::config::Settings;
+use mysql::*;
+#[cfg(feature = "bulk")]
+use mysql::prelude::*;
+#[cfg(feature = "migrate")]
+use prettytable::{Table, Row as PrettyRow, Cell};
+#[cfg(feature = "bulk")]
+use rusql::bulk::{quote_table, OnConflict};
+#[cfg(feature = "bulk")]
+use rusql::copy::TableCopy;
+use rusql::error::{Result, RusqlError};
+use rusql::export::TableExport;
+#[cfg(feature = "bulk")]
+use rusql::mask::MaskRules;
+#[cfg(feature = "migrate")]
+use rusql::migrate::{self, Migrator};
+#[cfg(feature = "migrate")]
+use rusql::sink::table_format;
+use rusql::sink::SinkOptions;
+#[cfg(feature = "bulk")]
+use rusql::sync::TableSync;
+
+#[cfg(feature = "bulk")]
+pub fn run_sync(opts: &Opts, settings: &Settings) -> Result<()> {
+    let Some(Command::Sync { source, target, table, key, chunk_size, dry_run }) = &opts.command else {
+        return Ok(());
+    };
+    let profile = |name: &str| {
+        settings.profiles.get(name)
+            .ok_or_else(|| RusqlError::Config(format!("Unknown profile '{}'", name)))
+    };
+    let mut source_conn = profile(source)?.connect()?;
+    let mut target_conn = profile(target)?.connect()?;
+
+    let report = TableSync::new(table, key)
+        .chunk_size(*chunk_size)
+        .dry_run(*dry_run)
+        .throttle(throttle(opts))
+        .on_progress(|p| eprint!("\r{} chunks compared, {} differing", p.chunks, p.differing_chunks))
+        .execute(&mut source_conn, &mut target_conn)?;
+    eprintln!();
+
+    if *dry_run {
+        println!("{} of {} chunks differ between '{}' and '{}'", report.differing_chunks, report.chunks, source, target);
+    } else {
+        println!(
+            "{} of {} chunks differed; {} rows copied from '{}' to '{}'",
+            report.differing_chunks, report.chunks, report.rows_copied, source, target
+        );
+    }
+    Ok(())
+}
+
+/// `copy`: stream rows between two connections in key-ordered batches,
+/// recording the last key written so a repeated command resumes after it.
+#[cfg(feature = "bulk")]
+pub fn run_copy(opts: &Opts, settings: &Settings) -> Result<()> {
+    let Some(Command::Copy { from, to, condition, on_conflict, batch, fresh, mask }) = &opts.command else {
+        return Ok(());
+    };
+    let endpoint = |spec: &str| -> Result<(Conn, String)> {
+        let (profile, table) = spec.split_once('/')
+            .filter(|(profile, table)| !profile.is_empty() && !table.is_empty())
+            .ok_or_else(|| RusqlError::Command(format!("Expected profile/db.table, got '{}'", spec)))?;
+        let conn = match profile {
+            "." => Conn::new(connection_opts(opts))?,
+            name => settings.profiles.get(name)
+                .ok_or_else(|| RusqlError::Config(format!("Unknown profile '{}'", name)))?
+                .connect()?,
+        };
+        Ok((conn, table.to_string()))
+    };
+    let (mut source, source_table) = endpoint(from)?;
+    let (mut target, target_table) = endpoint(to)?;
+
+    let job = format!("{}\n{}\n{}", from, to, condition.as_deref().unwrap_or_default());
+    let server = chunked::server_key(&opts.host, opts.port, opts.database.as_deref());
+    let state = chunked::state_path("copy", &server, &job);
+    let resume_after = match (&state, fresh) {
+        (Some(path), false) => std::fs::read_to_string(path).ok()
+            .and_then(|contents| contents.lines().nth(3).map(|key| Value::from(key.to_string()))),
+        _ => None,
+    };
+    match &resume_after {
+        Some(key) => eprintln!("Resuming after key {}", rusql::format::format_value(key)),
+        None if on_conflict == "truncate" => target.query_drop(format!("TRUNCATE TABLE {}", quote_table(&target_table)))?,
+        None => {}
+    }
+    let mode = match on_conflict.as_str() {
+        "upsert" => OnConflict::Update,
+        "skip" => OnConflict::Skip,
+        _ => OnConflict::Fail,
+    };
+
+    let report = TableCopy::new(&source_table, &target_table)
+        .condition(condition.as_deref())
+        .batch_size(*batch)
+        .on_conflict(mode)
+        .resume_after(resume_after)
+        .mask(if *mask { settings.mask_rules()? } else { MaskRules::default() })
+        .throttle(throttle(opts))
+        .on_progress(|p| {
+            eprint!("\r{} rows copied", p.rows);
+            if let (Some(path), Some(key)) = (&state, &p.last_key) {
+                let _ = std::fs::write(path, format!("{}\n{}\n", job, rusql::format::format_value(key)));
+            }
+        })
+        .execute(&mut source, &mut target)?;
+    eprintln!();
+
+    if let Some(path) = &state {
+        let _ = std::fs::remove_file(path);
+    }
+    println!("{} rows copied from '{}' to '{}' in {} batches", report.rows, from, to, report.batches);
+    Ok(())
+}
+
+/// `config check`: print every problem found and fail if any is an error.
+pub fn run_config_check(probe: bool) -> Result<()> {
+    let diagnostics = config::check(probe);
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    match diagnostics.iter().filter(|d| d.error).count() {
+        0 => {
+            println!("Configuration OK ({} warnings)", diagnostics.len());
+            Ok(())
+        }
+        errors => Err(RusqlError::Config(format!("{} errors in the configuration", errors))),
+    }
+}
+
+/// `slowlog analyze`: digest a slow log file, or mysql.slow_log over a connection.
+pub fn run_slowlog(opts: &Opts, settings: &Settings) -> Result<()> {
+    let Some(Command::Slowlog(SlowlogCommand::Analyze { file, from_table, order_by, limit })) = &opts.command else {
+        return Ok(());
+    };
+    let queries = match file {
+        Some(path) => slowlog::read_file(path)?,
+        None if *from_table => slowlog::read_table(&mut Conn::new(connection_opts(opts))?)?,
+        None => return Err(RusqlError::Command("Give a slow log file or --from-table".into())),
+    };
+    let borders = opts.table_style.unwrap_or(settings.theme()?.borders);
+    let (table, summary) = slowlog::report(&queries, *order_by, *limit, borders);
+    table.printstd();
+    println!("\n{}", summary);
+    Ok(())
+}
+
+#[cfg(feature = "migrate")]
+pub fn run_migrate(opts: &Opts, settings: &Settings) -> Result<()> {
+    let Some(Command::Migrate(command)) = &opts.command else {
+        return Ok(());
+    };
+    if opts.database.is_none() {
+        return Err(RusqlError::Command("Give the database to migrate with -D".into()));
+    }
+    let mut conn = Conn::new(connection_opts(opts))?;
+    let report = |applied: Vec<migrate::Applied>, verb: &str| {
+        for migration in &applied {
+            let note = if migration.transactional { "" } else { " (not in a transaction: holds DDL)" };
+            println!("{} {} {}: {} statements{}", verb, migration.version, migration.name, migration.statements, note);
+        }
+        if applied.is_empty() {
+            println!("Nothing to do");
+        }
+    };
+
+    match command {
+        MigrateCommand::Up { dir, to } => report(Migrator::new(dir).up(&mut conn, *to)?, "Applied"),
+        MigrateCommand::Down { dir, steps } => report(Migrator::new(dir).down(&mut conn, *steps)?, "Reverted"),
+        MigrateCommand::Status { dir } => {
+            let status = Migrator::new(dir).status(&mut conn)?;
+            let mut table = Table::new();
+            table.set_format(table_format(opts.table_style.unwrap_or(settings.theme()?.borders)));
+            table.add_row(PrettyRow::new(["Version", "Name", "Applied at", "Down"].iter().map(|h| Cell::new(h).style_spec("b")).collect()));
+            for entry in &status {
+                table.add_row(PrettyRow::new(vec![
+                    Cell::new(&entry.migration.version.to_string()),
+                    Cell::new(&entry.migration.name),
+                    Cell::new(entry.applied_at.as_deref().unwrap_or("pending")),
+                    Cell::new(if entry.migration.down.is_some() { "yes" } else { "" }),
+                ]));
+            }
+            table.printstd();
+            let pending = status.iter().filter(|entry| entry.applied_at.is_none()).count();
+            println!("\n{} migrations, {} pending", status.len(), pending);
+        }
+    }
+    Ok(())
+}
+
+pub fn run_diagram(opts: &Opts) -> Result<()> {
+    let Some(Command::Diagram { db, out, include, exclude }) = &opts.command else {
+        return Ok(());
+    };
+    let format = diagram::Format::of(out)?;
+    let db = db.clone().or_else(|| opts.database.clone())
+        .ok_or_else(|| RusqlError::Command("Give the database to draw with --db".into()))?;
+    let mut conn = Conn::new(connection_opts(opts))?;
+    let (diagram, tables, relationships) = diagram::render(&mut conn, &db, include, exclude, format)?;
+    std::fs::write(out, diagram)?;
+    println!("{} tables and {} relationships of '{}' written to '{}'", tables, relationships, db, out.display());
+    Ok(())
+}
+
+pub fn run_export(opts: &Opts) -> Result<()> {
+    let Some(Command::Export { table, dir, format, compress, chunk_rows, condition, resume }) = &opts.command else {
+        return Ok(());
+    };
+    let table = match (table.contains('.'), &opts.database) {
+        (false, Some(db)) => format!("{}.{}", db, table),
+        (false, None) => return Err(RusqlError::Command("Give the table as db.table or the database with -D".into())),
+        (true, _) => table.clone(),
+    };
+    let mut conn = Conn::new(connection_opts(opts))?;
+    let manifest = TableExport::new(&table, dir)
+        .format(*format)
+        .compression(compress.as_deref())
+        .chunk_rows(*chunk_rows)
+        .condition(condition.as_deref())
+        .resume(*resume)
+        .sink_options(SinkOptions { spreadsheet_safe: opts.spreadsheet_safe, sql_table: opts.sql_table.clone() })
+        .on_chunk(|manifest| eprint!("\r{} rows exported in {} files", manifest.rows(), manifest.chunks.len()))
+        .execute(&mut conn)?;
+    eprintln!();
+    println!(
+        "{} rows of {} exported in {} files to '{}'",
+        manifest.rows(),
+        table,
+        manifest.chunks.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+pub fn run_codegen(opts: &Opts) -> Result<()> {
+    let Some(Command::Codegen { db, table, style, out }) = &opts.command else {
+        return Ok(());
+    };
+    let db = db.clone().or_else(|| opts.database.clone())
+        .ok_or_else(|| RusqlError::Command("Give the database with --db".into()))?;
+    let mut conn = Conn::new(connection_opts(opts))?;
+    let (source, structs) = codegen::render(&mut conn, &db, table, *style)?;
+    match out {
+        Some(out) => {
+            std::fs::write(out, source)?;
+            println!("{} structs for '{}' written to '{}'", structs, db, out.display());
+        }
+        None => print!("{}", source),
+    }
+    Ok(())
+}
+
+pub fn run_seed(opts: &Opts) -> Result<()> {
+    let Some(Command::Seed { table, rows, batch, parent_rows }) = &opts.command else {
+        return Ok(());
+    };
+    let (db, table) = match table.split_once('.') {
+        Some((db, table)) => (db.to_string(), table.to_string()),
+        None => (
+            opts.database.clone().ok_or_else(|| RusqlError::Command("Give the table as db.table or the database with -D".into()))?,
+            table.clone(),
+        ),
+    };
+    let mut conn = Conn::new(connection_opts(opts))?;
+    let options = seed::Options { rows: *rows, batch: (*batch).max(1), parent_rows: *parent_rows };
+    let seeded = seed::seed(&mut conn, &db, &table, &options, &mut |table, rows| eprint!("\r{}: {} rows generated", table, rows))?;
+    eprintln!();
+    for table in &seeded {
+        match table.skipped {
+            0 => println!("{} rows inserted into {}.{}", table.rows, db, table.table),
+            skipped => println!(
+                "{} rows inserted into {}.{} ({} duplicates of a unique key skipped)",
+                table.rows, db, table.table, skipped
+            ),
+        }
+    }
+    Ok(())
+}
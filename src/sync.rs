@@ -0,0 +1,199 @@
+//! Incremental table sync between two servers.
+//!
+//! The source table is walked in primary key order in chunks. Each chunk is
+//! checksummed on both sides and only chunks whose row count or checksum
+//! differ are copied, so repeated syncs of a mostly unchanged table are cheap.
+
+use crate::bulk::{quote_identifier, quote_table, BulkInsert};
+use crate::error::{Result, RusqlError};
+use crate::throttle::Throttle;
+use mysql::prelude::*;
+use mysql::{Conn, Params, Row, TxOpts, Value};
+
+/// Progress reported after every chunk compared by [`TableSync`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncProgress {
+    pub chunks: u64,
+    pub differing_chunks: u64,
+    pub rows_copied: u64,
+}
+
+/// Checksum-based sync of one table from a source to a target connection.
+///
+/// Differing chunks are replaced on the target inside a transaction: rows in
+/// the chunk's key range are deleted and the source rows written back with
+/// `REPLACE`, so rows removed on the source disappear from the target too.
+///
+/// ```no_run
+/// # fn run(source: &mut mysql::Conn, target: &mut mysql::Conn) -> rusql::error::Result<()> {
+/// let report = rusql::sync::TableSync::new("shop.orders", "id")
+///     .chunk_size(5000)
+///     .execute(source, target)?;
+/// println!("{} of {} chunks copied", report.differing_chunks, report.chunks);
+/// # Ok(()) }
+/// ```
+pub struct TableSync<'a> {
+    table: String,
+    key: String,
+    chunk_size: usize,
+    dry_run: bool,
+    throttle: Throttle,
+    progress: Option<Box<dyn FnMut(SyncProgress) + 'a>>,
+}
+
+impl<'a> TableSync<'a> {
+    pub fn new(table: &str, key: &str) -> Self {
+        TableSync {
+            table: table.to_string(),
+            key: key.to_string(),
+            chunk_size: 1000,
+            dry_run: false,
+            throttle: Throttle::default(),
+            progress: None,
+        }
+    }
+
+    /// Number of source rows per compared chunk.
+    pub fn chunk_size(mut self, rows: usize) -> Self {
+        self.chunk_size = rows.max(1);
+        self
+    }
+
+    /// Compare chunks without writing to the target.
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled;
+        self
+    }
+
+    /// Pace chunk comparisons to spare the servers.
+    pub fn throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Called after every chunk with the running totals.
+    pub fn on_progress<F: FnMut(SyncProgress) + 'a>(mut self, callback: F) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    pub fn execute(mut self, source: &mut Conn, target: &mut Conn) -> Result<SyncProgress> {
+        let columns = self.columns(source)?;
+        let mut progress = SyncProgress::default();
+        let mut lower: Option<Value> = None;
+        let mut throttle = self.throttle.start();
+        let mut rows_seen = 0;
+
+        loop {
+            let upper = self.chunk_end(source, lower.as_ref())?;
+            let (condition, params) = self.range(lower.as_ref(), upper.as_ref());
+
+            let checksum = self.checksum_query(&columns, &condition);
+            let source_sum: Option<(u64, u64)> = source.exec_first(&checksum, params.clone())?;
+            let target_sum: Option<(u64, u64)> = target.exec_first(&checksum, params.clone())?;
+
+            progress.chunks += 1;
+            rows_seen += source_sum.map_or(0, |(rows, _)| rows);
+            if source_sum != target_sum {
+                progress.differing_chunks += 1;
+                if !self.dry_run {
+                    progress.rows_copied += self.copy_chunk(source, target, &columns, &condition, params)?;
+                }
+            }
+            if let Some(callback) = self.progress.as_mut() {
+                callback(progress);
+            }
+            throttle.pace(rows_seen);
+
+            match upper {
+                Some(upper) => lower = Some(upper),
+                None => return Ok(progress),
+            }
+        }
+    }
+
+    fn columns(&self, source: &mut Conn) -> Result<Vec<String>> {
+        let result = source.query_iter(format!("SELECT * FROM {} LIMIT 0", quote_table(&self.table)))?;
+        let columns: Vec<String> = result.columns().as_ref().iter().map(|c| c.name_str().into_owned()).collect();
+        if !columns.iter().any(|c| c.eq_ignore_ascii_case(&self.key)) {
+            return Err(RusqlError::Command(format!("column '{}' not found in {}", self.key, self.table)));
+        }
+        Ok(columns)
+    }
+
+    /// Key of the last row in the chunk starting after `lower`, or `None`
+    /// when fewer than a full chunk of rows remain.
+    fn chunk_end(&self, source: &mut Conn, lower: Option<&Value>) -> Result<Option<Value>> {
+        let (condition, params) = self.range(lower, None);
+        let query = format!(
+            "SELECT {key} FROM {table} WHERE {condition} ORDER BY {key} LIMIT 1 OFFSET {offset}",
+            key = quote_identifier(&self.key),
+            table = quote_table(&self.table),
+            condition = condition,
+            offset = self.chunk_size - 1,
+        );
+        let row: Option<Row> = source.exec_first(query, params)?;
+        Ok(row.and_then(|row| row.as_ref(0).cloned()))
+    }
+
+    /// `WHERE` condition for keys in `(lower, upper]`; both ends are optional.
+    fn range(&self, lower: Option<&Value>, upper: Option<&Value>) -> (String, Params) {
+        let key = quote_identifier(&self.key);
+        let mut conditions = vec!["1 = 1".to_string()];
+        let mut params = Vec::new();
+        if let Some(lower) = lower {
+            conditions.push(format!("{} > ?", key));
+            params.push(lower.clone());
+        }
+        if let Some(upper) = upper {
+            conditions.push(format!("{} <= ?", key));
+            params.push(upper.clone());
+        }
+        let params = if params.is_empty() { Params::Empty } else { Params::Positional(params) };
+        (conditions.join(" AND "), params)
+    }
+
+    /// Row count and an order-independent checksum of the rows in a range.
+    fn checksum_query(&self, columns: &[String], condition: &str) -> String {
+        let quoted: Vec<String> = columns.iter().map(|c| quote_identifier(c)).collect();
+        let nulls: Vec<String> = quoted.iter().map(|c| format!("ISNULL({})", c)).collect();
+        format!(
+            "SELECT COUNT(*), COALESCE(SUM(CRC32(CONCAT_WS('#', {}, CONCAT({})))), 0) FROM {} WHERE {}",
+            quoted.join(", "),
+            nulls.join(", "),
+            quote_table(&self.table),
+            condition
+        )
+    }
+
+    fn copy_chunk(
+        &self,
+        source: &mut Conn,
+        target: &mut Conn,
+        columns: &[String],
+        condition: &str,
+        params: Params,
+    ) -> Result<u64> {
+        let quoted: Vec<String> = columns.iter().map(|c| quote_identifier(c)).collect();
+        let rows: Vec<Row> = source.exec(
+            format!(
+                "SELECT {} FROM {} WHERE {} ORDER BY {}",
+                quoted.join(", "),
+                quote_table(&self.table),
+                condition,
+                quote_identifier(&self.key)
+            ),
+            params.clone(),
+        )?;
+        let count = rows.len() as u64;
+
+        let mut tx = target.start_transaction(TxOpts::default())?;
+        tx.exec_drop(format!("DELETE FROM {} WHERE {}", quote_table(&self.table), condition), params)?;
+        BulkInsert::new(&self.table, columns)
+            .replace(true)
+            .execute(&mut tx, rows.into_iter().map(|row| Params::Positional(row.unwrap())))?;
+        tx.commit()?;
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,86 @@
+use colored::Color;
+use rusql::sink::{Borders, CellColors};
+
+/// Names accepted by `theme.name`.
+pub const THEMES: &[&str] = &["default", "solarized", "plain"];
+
+/// Colors and table borders for everything the client prints.
+/// A `None` color leaves text in the terminal's own foreground color.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub prompt: Option<Color>,
+    pub error: Option<Color>,
+    pub warning: Option<Color>,
+    pub notice: Option<Color>,
+    pub banner: Option<Color>,
+    pub cells: CellColors,
+    pub borders: Borders,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            prompt: Some(Color::BrightGreen),
+            error: Some(Color::BrightRed),
+            warning: Some(Color::Yellow),
+            notice: Some(Color::Green),
+            banner: Some(Color::BrightBlue),
+            cells: CellColors::default(),
+            borders: Borders::Unicode,
+        }
+    }
+}
+
+impl Theme {
+    /// One of the built-in themes listed in [`THEMES`].
+    pub fn builtin(name: &str) -> Option<Theme> {
+        match name.to_lowercase().as_str() {
+            "default" => Some(Theme::default()),
+            "solarized" => Some(solarized()),
+            "plain" => Some(plain()),
+            _ => None,
+        }
+    }
+}
+
+/// Solarized accent colors, which read on light and dark backgrounds alike.
+fn solarized() -> Theme {
+    const YELLOW: Color = Color::TrueColor { r: 0xb5, g: 0x89, b: 0x00 };
+    const ORANGE: Color = Color::TrueColor { r: 0xcb, g: 0x4b, b: 0x16 };
+    const RED: Color = Color::TrueColor { r: 0xdc, g: 0x32, b: 0x2f };
+    const MAGENTA: Color = Color::TrueColor { r: 0xd3, g: 0x36, b: 0x82 };
+    const VIOLET: Color = Color::TrueColor { r: 0x6c, g: 0x71, b: 0xc4 };
+    const BLUE: Color = Color::TrueColor { r: 0x26, g: 0x8b, b: 0xd2 };
+    const CYAN: Color = Color::TrueColor { r: 0x2a, g: 0xa1, b: 0x98 };
+    const GREEN: Color = Color::TrueColor { r: 0x85, g: 0x99, b: 0x00 };
+
+    Theme {
+        prompt: Some(GREEN),
+        error: Some(RED),
+        warning: Some(YELLOW),
+        notice: Some(CYAN),
+        banner: Some(BLUE),
+        cells: CellColors {
+            header: Some(BLUE),
+            number: Some(ORANGE),
+            date: Some(VIOLET),
+            boolean: Some(MAGENTA),
+            text: None,
+            null: Some(RED),
+        },
+        borders: Borders::Unicode,
+    }
+}
+
+/// No colors at all, with plain ASCII frames.
+fn plain() -> Theme {
+    Theme {
+        prompt: None,
+        error: None,
+        warning: None,
+        notice: None,
+        banner: None,
+        cells: CellColors { header: None, number: None, date: None, boolean: None, text: None, null: None },
+        borders: Borders::Ascii,
+    }
+}
@@ -0,0 +1,85 @@
+//! Pacing for client-driven bulk jobs.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Pause taken by [`Throttle::sleep_every`] unless set with [`Throttle::pause`].
+const DEFAULT_PAUSE: Duration = Duration::from_secs(1);
+
+/// Keeps a job from starving application traffic: caps the rows processed
+/// per second and/or pauses after every few chunks.
+///
+/// Call [`Throttle::pace`] after each chunk with the running row count.
+///
+/// ```
+/// use std::time::Duration;
+/// let mut throttle = rusql::throttle::Throttle::default()
+///     .rows_per_sec(Some(50_000))
+///     .sleep_every(Some(10))
+///     .pause(Duration::from_millis(500))
+///     .start();
+/// throttle.pace(1000);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    rows_per_sec: Option<u64>,
+    sleep_every: Option<u64>,
+    pause: Duration,
+    started: Instant,
+    chunks: u64,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Throttle {
+            rows_per_sec: None,
+            sleep_every: None,
+            pause: DEFAULT_PAUSE,
+            started: Instant::now(),
+            chunks: 0,
+        }
+    }
+}
+
+impl Throttle {
+    /// Allow at most `rows` rows per second, or no limit for `None`.
+    pub fn rows_per_sec(mut self, rows: Option<u64>) -> Self {
+        self.rows_per_sec = rows.filter(|&rate| rate > 0);
+        self
+    }
+
+    /// Pause after every `chunks` chunks, or never for `None`.
+    pub fn sleep_every(mut self, chunks: Option<u64>) -> Self {
+        self.sleep_every = chunks.filter(|&n| n > 0);
+        self
+    }
+
+    /// How long [`Throttle::sleep_every`] pauses (one second by default).
+    pub fn pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    /// The same limits with the clock and chunk count reset, for a new job.
+    pub fn start(&self) -> Self {
+        Throttle { started: Instant::now(), chunks: 0, ..self.clone() }
+    }
+
+    /// Record a finished chunk and sleep as long as the limits require.
+    /// `rows_done` is the total number of rows processed since [`Throttle::start`].
+    pub fn pace(&mut self, rows_done: u64) {
+        self.chunks += 1;
+        if let Some(every) = self.sleep_every
+            && self.chunks.is_multiple_of(every)
+        {
+            thread::sleep(self.pause);
+        }
+
+        if let Some(rate) = self.rows_per_sec {
+            let due = Duration::from_secs_f64(rows_done as f64 / rate as f64);
+            if let Some(wait) = due.checked_sub(self.started.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::interrupt::kill_query;
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::Queryable;
+use mysql::Opts;
+use rusql::error::{Result, RusqlError};
+use rusql::statements::keywords;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const USAGE: &str = "Usage: \\timeout [<ms>|off [statement]]";
+
+/// The session variable, if any, through which the server enforces a time
+/// limit on statements itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerLimit {
+    /// MySQL 5.7.8+ `max_execution_time`, which only covers SELECTs.
+    Selects,
+    /// MariaDB 10.1+ `max_statement_time`, which covers every statement.
+    Statements,
+    None,
+}
+
+impl ServerLimit {
+    /// What the server reporting `server_version` (from `SELECT VERSION()`) offers.
+    pub fn of(server_version: &str) -> Self {
+        let version: Vec<u32> = server_version.split('-').next().unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect();
+        match server_version.contains("MariaDB") {
+            true if version.as_slice() >= [10, 1].as_slice() => ServerLimit::Statements,
+            false if version.as_slice() >= [5, 7, 8].as_slice() => ServerLimit::Selects,
+            _ => ServerLimit::None,
+        }
+    }
+
+    /// Whether the server stops `statement` by itself once the limit is set.
+    pub fn covers(self, statement: &str) -> bool {
+        match self {
+            ServerLimit::Statements => true,
+            ServerLimit::Selects => keywords(statement).first().is_some_and(|word| word == "SELECT"),
+            ServerLimit::None => false,
+        }
+    }
+}
+
+/// Set the statement time limit for the session: the server variable where
+/// there is one, and the limit the client enforces on what it doesn't cover.
+pub fn apply(client: &mut MySQLClient, limit: Option<Duration>) -> Result<()> {
+    let millis = limit.map_or(0, |limit| limit.as_millis());
+    match client.server_limit {
+        ServerLimit::Selects => client.conn.query_drop(format!("SET SESSION max_execution_time = {}", millis))?,
+        ServerLimit::Statements => {
+            client.conn.query_drop(format!("SET SESSION max_statement_time = {}", millis as f64 / 1000.0))?
+        }
+        ServerLimit::None => {}
+    }
+    client.max_execution_time = limit;
+    Ok(())
+}
+
+/// Kills the running query from a second connection if it is still running
+/// once the limit passes.
+pub struct Watchdog {
+    done: Sender<()>,
+    thread: JoinHandle<bool>,
+}
+
+impl Watchdog {
+    pub fn start(opts: Opts, connection_id: u32, limit: Duration) -> Watchdog {
+        let (done, finished) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || match finished.recv_timeout(limit) {
+            Err(RecvTimeoutError::Timeout) => {
+                if let Err(e) = kill_query(opts, connection_id) {
+                    eprintln!("\nCould not stop the query at its time limit: {}", e);
+                }
+                true
+            }
+            _ => false,
+        });
+        Watchdog { done, thread }
+    }
+
+    /// Disarm the watchdog, returning whether it killed the query.
+    pub fn finish(self) -> bool {
+        let _ = self.done.send(());
+        self.thread.join().unwrap_or(false)
+    }
+}
+
+/// The error for a statement the watchdog killed.
+pub fn exceeded(limit: Duration) -> RusqlError {
+    RusqlError::Command(format!("The statement exceeded the {} ms time limit and was killed", limit.as_millis()))
+}
+
+/// `\timeout [<ms>|off [statement]]`: show or set the time limit on each
+/// statement. With a statement, the limit applies to it alone.
+pub fn timeout(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let args = args.trim();
+    if args.is_empty() {
+        let message = match client.max_execution_time {
+            Some(limit) => format!("Statements are killed after {} ms", limit.as_millis()),
+            None => "Statements have no time limit".to_string(),
+        };
+        client.notice(&message);
+        return Ok(None);
+    }
+    let (value, statement) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let limit = match value {
+        "off" | "0" => None,
+        millis => Some(Duration::from_millis(millis.parse().map_err(|_| RusqlError::Command(USAGE.into()))?)),
+    };
+
+    let statement = statement.trim();
+    if statement.is_empty() {
+        apply(client, limit)?;
+        client.notice(&match limit {
+            Some(limit) => format!("Statements are now killed after {} ms", limit.as_millis()),
+            None => "Statement time limit off".to_string(),
+        });
+        return Ok(None);
+    }
+    let previous = client.max_execution_time;
+    apply(client, limit)?;
+    let result = client.execute_query(statement.trim_end_matches(';'));
+    let restored = apply(client, previous);
+    result.and_then(|result| restored.map(|_| result))
+}
@@ -0,0 +1,31 @@
+use rusql::error::RusqlError;
+
+/// Server error for a zone it does not know.
+const UNKNOWN_TIME_ZONE: u16 = 1298;
+
+/// Parse a zone given to `--timezone`: a name such as `Europe/Berlin` or
+/// `UTC`, `SYSTEM`, or an offset such as `+02:00`.
+pub fn parse_zone(s: &str) -> Result<String, String> {
+    let valid = !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || "/_+-:".contains(c));
+    if !valid {
+        return Err(format!("invalid time zone `{}`", s));
+    }
+    Ok(s.to_string())
+}
+
+/// Statement run on every new connection to switch its time zone.
+pub fn set_statement(zone: &str) -> String {
+    format!("SET time_zone = '{}'", zone)
+}
+
+/// Replace the server's bare "unknown time zone" error with what to do about it.
+pub fn explain(error: RusqlError, zone: &str) -> RusqlError {
+    match error {
+        RusqlError::Sql { code: UNKNOWN_TIME_ZONE, .. } => RusqlError::Config(format!(
+            "Unknown time zone '{}': named zones need the server's time zone tables \
+             (mysql_tzinfo_to_sql), or use an offset such as '+02:00'",
+            zone,
+        )),
+        other => other,
+    }
+}
@@ -0,0 +1,97 @@
+//! Per-column aggregates for the footer of a result table.
+
+use crate::sink::ValueKind;
+use mysql::consts::ColumnType;
+use mysql::Column;
+use std::collections::HashSet;
+
+/// Extra places the average keeps beyond the values themselves.
+const AVERAGE_PLACES: u32 = 2;
+
+/// Aggregate of one column: sum and average of numbers, or the number of
+/// distinct values of anything else. NULLs are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Total {
+    Numeric { sum: String, average: String },
+    Distinct(usize),
+    /// Only NULLs, or a column such as YEAR that has nothing to add up.
+    Empty,
+}
+
+/// Totals of every column of `rows`.
+pub fn column_totals(columns: &[Column], rows: &[Vec<Option<String>>]) -> Vec<Total> {
+    columns.iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let values = rows.iter().filter_map(|row| row[i].as_deref());
+            match ValueKind::of(column) {
+                ValueKind::Number if column.column_type() != ColumnType::MYSQL_TYPE_YEAR => sum(values).unwrap_or(Total::Empty),
+                ValueKind::Number => Total::Empty,
+                _ => Total::Distinct(values.collect::<HashSet<_>>().len()),
+            }
+        })
+        .collect()
+}
+
+/// Sum and average of numeric text, or `None` when there are no values.
+///
+/// Numbers are summed exactly as decimals so money columns do not pick up
+/// floating point noise; values with exponents fall back to `f64`.
+///
+/// ```
+/// use rusql::totals::{sum, Total};
+///
+/// let total = sum(["0.10", "0.20", "-0.05"]);
+/// assert_eq!(total, Some(Total::Numeric { sum: "0.25".into(), average: "0.0833".into() }));
+/// ```
+pub fn sum<'a>(values: impl IntoIterator<Item = &'a str>) -> Option<Total> {
+    let values: Vec<&str> = values.into_iter().collect();
+    if values.is_empty() {
+        return None;
+    }
+    let count = values.len() as i128;
+    if let Some((sum, scale)) = exact_sum(&values) && let Some(scaled) = sum.checked_mul(10i128.pow(AVERAGE_PLACES)) {
+        let mut average = scaled / count;
+        // Round half away from zero
+        if (scaled % count).abs() * 2 >= count {
+            average += scaled.signum();
+        }
+        return Some(Total::Numeric {
+            sum: decimal_text(sum, scale),
+            average: decimal_text(average, scale + AVERAGE_PLACES),
+        });
+    }
+    let sum: f64 = values.iter().filter_map(|v| v.parse::<f64>().ok()).sum();
+    Some(Total::Numeric { sum: sum.to_string(), average: (sum / count as f64).to_string() })
+}
+
+/// Sum as a scaled integer, or `None` if a value is not a plain decimal or
+/// the sum overflows.
+fn exact_sum(values: &[&str]) -> Option<(i128, u32)> {
+    let parsed: Vec<(i128, u32)> = values.iter().map(|v| parse_decimal(v)).collect::<Option<_>>()?;
+    let scale = parsed.iter().map(|(_, scale)| *scale).max()?;
+    parsed.iter().try_fold(0i128, |sum, (mantissa, own)| {
+        sum.checked_add(mantissa.checked_mul(10i128.checked_pow(scale - own)?)?)
+    })
+    .map(|sum| (sum, scale))
+}
+
+fn parse_decimal(text: &str) -> Option<(i128, u32)> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty() || !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mantissa: i128 = format!("{}{}", whole, fraction).parse().ok()?;
+    Some((if negative { -mantissa } else { mantissa }, fraction.len() as u32))
+}
+
+fn decimal_text(mantissa: i128, scale: u32) -> String {
+    let digits = format!("{:0>width$}", mantissa.unsigned_abs(), width = scale as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - scale as usize);
+    let sign = if mantissa < 0 { "-" } else { "" };
+    if fraction.is_empty() { format!("{}{}", sign, whole) } else { format!("{}{}.{}", sign, whole, fraction) }
+}
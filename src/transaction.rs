@@ -0,0 +1,180 @@
+use crate::{commands, print_result, retry, MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::session_state_change::SessionStateChange;
+use mysql::Conn;
+use rusql::error::{Result, RusqlError};
+use rusql::statements::keywords;
+
+/// ER_LOCK_DEADLOCK, which rolls back the whole transaction.
+const DEADLOCK: u16 = 1213;
+
+/// Statements that commit the open transaction on their own, so a script
+/// holding one can't be undone as a whole.
+const IMPLICIT_COMMITS: &[&str] = &[
+    "ALTER", "CREATE", "DROP", "RENAME", "TRUNCATE", "BEGIN", "START", "COMMIT", "ROLLBACK", "LOCK", "UNLOCK",
+    "GRANT", "REVOKE", "ANALYZE", "OPTIMIZE", "REPAIR", "LOAD",
+];
+
+/// Whether `statement` ends the transaction it runs in. Temporary tables
+/// are the exception to DDL committing.
+fn commits_implicitly(statement: &str) -> bool {
+    let words = keywords(statement);
+    match words.first().map(String::as_str) {
+        Some("CREATE" | "DROP") => words.get(1).is_none_or(|word| word != "TEMPORARY"),
+        Some("LOAD") => words.get(1).is_some_and(|word| word == "INDEX"),
+        Some(word) => IMPLICIT_COMMITS.contains(&word),
+        None => false,
+    }
+}
+
+/// How the open transaction is followed for the prompt's `*`, the exit
+/// warning and `--single-transaction` nesting, without querying the server
+/// after each statement.
+#[derive(Debug, Clone, Copy)]
+pub struct Tracking {
+    /// The server reports the transaction state in its OK packets
+    /// (`session_track_transaction_info`).
+    server: bool,
+    autocommit: bool,
+}
+
+/// Ask the server to report transaction state changes, and read whether
+/// autocommit is on, once per connection. Servers without session tracking
+/// leave the state to be inferred from the statements run.
+pub fn track(conn: &mut Conn) -> Tracking {
+    let server = conn.query_drop("SET SESSION session_track_transaction_info = 'STATE'").is_ok();
+    let autocommit = conn.query_first::<u8, _>("SELECT @@autocommit").ok().flatten().is_none_or(|on| on != 0);
+    Tracking { server, autocommit }
+}
+
+/// Update whether a transaction is open after `statement` ran. The server's
+/// report is used when the statement's OK packet carried one; result sets
+/// don't, so the statement itself is read otherwise.
+pub fn refresh(client: &mut MySQLClient, statement: &str, outcome: &Result<Option<QueryResult>>) {
+    if let Err(e) = outcome {
+        // Other errors only undo the failed statement
+        if matches!(e, RusqlError::Connection(_) | RusqlError::Sql { code: DEADLOCK, .. }) {
+            client.in_transaction = false;
+        }
+        return;
+    }
+    if let Some(open) = reported(&client.conn, &mut client.tracking) {
+        client.in_transaction = open;
+        return;
+    }
+
+    let words = keywords(statement);
+    let has = |word: &str| words.iter().any(|w| w == word);
+    if let Some(on) = autocommit_setting(&words) {
+        // Switching autocommit on commits the open transaction
+        if on {
+            client.in_transaction = false;
+        }
+        client.tracking.autocommit = on;
+        return;
+    }
+    match (words.first().map(String::as_str), words.get(1).map(String::as_str)) {
+        (Some("BEGIN"), _) | (Some("START"), Some("TRANSACTION")) => client.in_transaction = true,
+        // Rolling back to a savepoint keeps the transaction open
+        (Some("ROLLBACK"), _) if has("TO") => {}
+        (Some("COMMIT" | "ROLLBACK"), _) => client.in_transaction = has("CHAIN") && !has("NO"),
+        _ if commits_implicitly(statement) => client.in_transaction = false,
+        // Without autocommit every statement runs in a transaction
+        _ if !client.tracking.autocommit => client.in_transaction = true,
+        _ => {}
+    }
+}
+
+/// The transaction state in the last OK packet, noting autocommit changes
+/// reported alongside it.
+fn reported(conn: &Conn, tracking: &mut Tracking) -> Option<bool> {
+    if !tracking.server {
+        return None;
+    }
+    let mut open = None;
+    for info in conn.session_state_changes().unwrap_or_default() {
+        match info.decode() {
+            Ok(SessionStateChange::TransactionState(state)) => {
+                // `_` for none, `T` for explicit and `I` for implicit ones
+                open = state.as_bytes().first().map(|&state| state != b'_');
+            }
+            Ok(SessionStateChange::SystemVariables(variables)) => {
+                for variable in variables.iter().filter(|variable| variable.name_str().eq_ignore_ascii_case("autocommit")) {
+                    tracking.autocommit = matches!(variable.value_str().to_ascii_uppercase().as_str(), "ON" | "1");
+                }
+            }
+            _ => {}
+        }
+    }
+    open
+}
+
+/// The value a `SET [SESSION] autocommit = ...` statement gives, from its
+/// keywords.
+fn autocommit_setting(words: &[String]) -> Option<bool> {
+    if words.first().is_none_or(|word| word != "SET") {
+        return None;
+    }
+    let at = words.iter().position(|word| word == "AUTOCOMMIT")?;
+    match words.get(at + 1).map(String::as_str) {
+        Some("1" | "ON" | "TRUE") => Some(true),
+        Some("0" | "OFF" | "FALSE") => Some(false),
+        _ => None,
+    }
+}
+
+/// Run a script's statements in one transaction, committed once all of them
+/// succeed and rolled back at the first error (`--single-transaction`,
+/// `\source --single-transaction`). Inside a transaction that is already
+/// open the statements simply become part of it.
+pub fn run_script(client: &mut MySQLClient, statements: &[String]) -> Result<()> {
+    if let Some(statement) = statements.iter().find(|s| !commands::is_meta_command(s) && commits_implicitly(s)) {
+        return Err(RusqlError::Command(format!(
+            "'{}' commits implicitly, so the script can't run as a single transaction",
+            first_line(statement)
+        )));
+    }
+    let nested = client.in_transaction;
+    if !nested {
+        client.execute_query("START TRANSACTION")?;
+    }
+
+    for (done, statement) in statements.iter().enumerate() {
+        match retry::execute(client, statement) {
+            Ok(Some(result)) => print_result(client, result),
+            Ok(None) => {}
+            Err(e) if nested => return Err(e),
+            Err(e) => {
+                // A dropped connection has already rolled back on the server
+                let _ = client.execute_query("ROLLBACK");
+                let mut report = format!(
+                    "Statement {} of {} failed; rolled back {} {}",
+                    done + 1,
+                    statements.len(),
+                    done,
+                    if done == 1 { "statement" } else { "statements" }
+                );
+                for statement in &statements[..done] {
+                    report.push_str("\n  ");
+                    report.push_str(&first_line(statement));
+                }
+                eprintln!("{}", client.paint(&report, client.theme.warning));
+                return Err(e);
+            }
+        }
+    }
+    if !nested {
+        client.execute_query("COMMIT")?;
+    }
+    Ok(())
+}
+
+/// The first line of a statement, shortened for a report.
+fn first_line(statement: &str) -> String {
+    let line = statement.trim().lines().next().unwrap_or_default();
+    match line.char_indices().nth(80) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None if statement.trim().lines().nth(1).is_some() => format!("{} ...", line),
+        None => line.to_string(),
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::{Conn, Params};
+use rusql::bulk::{quote_table, BulkInsert};
+use rusql::error::{Result, RusqlError};
+use rusql::mask::MaskRules;
+use rusql::throttle::Throttle;
+use std::cell::RefCell;
+
+const USAGE: &str = "Usage: \\transfer <profile>.<table> -> <profile>.<table> [--where <condition>] \
+                     [--batch <rows>] [--throttle-rows-per-sec <n>] [--sleep-every-n-chunks <n>] [--no-fk-checks] [--mask]";
+
+/// Options of a `\transfer` command.
+struct Transfer<'a> {
+    source: (&'a str, &'a str),
+    target: (&'a str, &'a str),
+    condition: Option<String>,
+    batch: usize,
+    throttle: Throttle,
+    no_fk_checks: bool,
+    mask: bool,
+}
+
+/// `\transfer prod.db.table -> dev.db.table [--where ...]`: stream rows from
+/// one connection into batched inserts on another. `.` is the current connection.
+/// `--mask` applies the `[masking]` rules of config.toml to the rows on the way.
+pub fn transfer(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let options = parse(args, client.throttle.clone())?;
+    let rules = if options.mask { client.mask_rules.clone() } else { MaskRules::default() };
+    let mut source_conn = connect(client, options.source.0)?;
+    let mut target_conn = connect(client, options.target.0)?;
+
+    let (source, target): (&mut Conn, &mut Conn) = match (source_conn.as_mut(), target_conn.as_mut()) {
+        (Some(source), Some(target)) => (source, target),
+        (Some(source), None) => (source, &mut client.conn),
+        (None, Some(target)) => (&mut client.conn, target),
+        (None, None) => {
+            return Err(RusqlError::Command("Source and target are the same connection; use INSERT ... SELECT".into()));
+        }
+    };
+
+    if options.no_fk_checks {
+        target.query_drop("SET SESSION foreign_key_checks = 0")?;
+    }
+    let outcome = copy_rows(source, target, &options, &rules);
+    if options.no_fk_checks {
+        target.query_drop("SET SESSION foreign_key_checks = 1")?;
+    }
+    let rows = outcome?;
+
+    eprintln!();
+    client.notice(&format!(
+        "{} rows transferred from {}.{} to {}.{}",
+        rows, options.source.0, options.source.1, options.target.0, options.target.1
+    ));
+    Ok(None)
+}
+
+fn copy_rows(source: &mut Conn, target: &mut Conn, options: &Transfer, rules: &MaskRules) -> Result<u64> {
+    let query = format!(
+        "SELECT * FROM {}{}",
+        quote_table(options.source.1),
+        options.condition.as_ref().map(|c| format!(" WHERE {}", c)).unwrap_or_default()
+    );
+    let result = source.query_iter(query)?;
+    let columns: Vec<String> = result.columns().as_ref().iter().map(|c| c.name_str().into_owned()).collect();
+    let masks = rules.result_columns(result.columns().as_ref());
+
+    // Stop at the first read error and report it once the batches written so far are in
+    let read_error = RefCell::new(None);
+    let rows = result.map_while(|row| match row {
+        Ok(row) => Some(row.unwrap()),
+        Err(e) => {
+            *read_error.borrow_mut() = Some(e);
+            None
+        }
+    });
+    let rows = masks.stream(rows, options.batch).map(Params::Positional);
+
+    let mut throttle = options.throttle.start();
+    let mut transferred = 0;
+    BulkInsert::new(options.target.1, &columns)
+        .max_rows_per_batch(options.batch)
+        .on_progress(|progress| {
+            transferred = progress.rows;
+            eprint!("\r{} rows transferred", progress.rows);
+            throttle.pace(progress.rows);
+        })
+        .execute(target, rows)?;
+
+    match read_error.into_inner() {
+        Some(e) => Err(e.into()),
+        None => Ok(transferred),
+    }
+}
+
+/// A connection for `profile`, or `None` for the current one (`.`).
+pub fn connect(client: &MySQLClient, profile: &str) -> Result<Option<Conn>> {
+    if profile == "." {
+        return Ok(None);
+    }
+    let profile = client.profiles.get(profile)
+        .ok_or_else(|| RusqlError::Command(format!("Unknown profile '{}'", profile)))?;
+    profile.connect().map(Some)
+}
+
+/// Parse the command's arguments; throttle flags override the `defaults`
+/// given on the command line.
+fn parse(args: &str, defaults: Throttle) -> Result<Transfer<'_>> {
+    let (source, rest) = args.split_once("->").ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+    let mut words = rest.split_whitespace();
+    let target = words.next().ok_or_else(|| RusqlError::Command(USAGE.into()))?;
+
+    let mut options = Transfer {
+        source: split_endpoint(source.trim())?,
+        target: split_endpoint(target)?,
+        condition: None,
+        batch: 1000,
+        throttle: defaults,
+        no_fk_checks: false,
+        mask: false,
+    };
+
+    let words: Vec<&str> = words.collect();
+    let mut i = 0;
+    while i < words.len() {
+        match words[i] {
+            "--where" => {
+                let end = words[i + 1..].iter().position(|w| w.starts_with("--")).map_or(words.len(), |n| i + 1 + n);
+                options.condition = Some(words[i + 1..end].join(" ")).filter(|c| !c.is_empty());
+                i = end;
+                continue;
+            }
+            "--batch" => options.batch = number(words.get(i + 1))?,
+            "--throttle-rows-per-sec" => {
+                let rate = number(words.get(i + 1))? as u64;
+                options.throttle = options.throttle.rows_per_sec(Some(rate));
+            }
+            "--sleep-every-n-chunks" => {
+                let chunks = number(words.get(i + 1))? as u64;
+                options.throttle = options.throttle.sleep_every(Some(chunks));
+            }
+            "--no-fk-checks" => {
+                options.no_fk_checks = true;
+                i += 1;
+                continue;
+            }
+            "--mask" => {
+                options.mask = true;
+                i += 1;
+                continue;
+            }
+            _ => return Err(RusqlError::Command(USAGE.into())),
+        }
+        i += 2;
+    }
+    Ok(options)
+}
+
+/// Split `profile.db.table` into the profile and the (possibly qualified)
+/// table; `.db.table` names the current connection.
+fn split_endpoint(spec: &str) -> Result<(&str, &str)> {
+    let (profile, table) = match spec.strip_prefix('.') {
+        Some(table) => (".", table),
+        None => spec.split_once('.').ok_or_else(|| RusqlError::Command(USAGE.into()))?,
+    };
+    if profile.is_empty() || table.is_empty() {
+        return Err(RusqlError::Command(USAGE.into()));
+    }
+    Ok((profile, table))
+}
+
+fn number(word: Option<&&str>) -> Result<usize> {
+    word.and_then(|w| w.parse().ok()).ok_or_else(|| RusqlError::Command(USAGE.into()))
+}
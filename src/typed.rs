@@ -0,0 +1,225 @@
+use mysql::prelude::*;
+use mysql::{Row, Value};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use crate::error::{self, RusqlError};
+use std::error::Error;
+use std::fmt;
+
+/// Error raised when a row cannot be mapped onto the requested type.
+#[derive(Debug)]
+pub struct RowError(String);
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for RowError {}
+
+impl From<RowError> for RusqlError {
+    fn from(e: RowError) -> Self {
+        RusqlError::Data(e.0)
+    }
+}
+
+impl de::Error for RowError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        RowError(msg.to_string())
+    }
+}
+
+/// Run a query and deserialize every row into `T`, matching struct fields to
+/// column names.
+///
+/// ```no_run
+/// # fn run(conn: &mut mysql::Conn) -> Result<(), Box<dyn std::error::Error>> {
+/// #[derive(serde::Deserialize)]
+/// struct User {
+///     id: u64,
+///     email: Option<String>,
+/// }
+///
+/// let users: Vec<User> = rusql::typed::query_as(conn, "SELECT id, email FROM users")?;
+/// # Ok(()) }
+/// ```
+pub fn query_as<T, C, Q>(conn: &mut C, query: Q) -> error::Result<Vec<T>>
+where
+    T: DeserializeOwned,
+    C: Queryable,
+    Q: AsRef<str>,
+{
+    let rows: Vec<Row> = conn.query(query)?;
+    Ok(rows.iter().map(from_row).collect::<Result<_, _>>()?)
+}
+
+/// Deserialize a single row into `T`.
+pub fn from_row<T: DeserializeOwned>(row: &Row) -> Result<T, RowError> {
+    let columns = row.columns_ref();
+    let entries = columns.iter()
+        .enumerate()
+        .map(|(i, column)| (column.name_str().into_owned(), row.as_ref(i).unwrap_or(&Value::NULL)))
+        .collect();
+
+    T::deserialize(RowDeserializer { entries })
+}
+
+struct RowDeserializer<'a> {
+    entries: Vec<(String, &'a Value)>,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for RowDeserializer<'a> {
+    type Error = RowError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RowError> {
+        visitor.visit_map(RowAccess { entries: self.entries.into_iter(), value: None })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct RowAccess<'a> {
+    entries: std::vec::IntoIter<(String, &'a Value)>,
+    value: Option<&'a Value>,
+}
+
+impl<'de, 'a> MapAccess<'de> for RowAccess<'a> {
+    type Error = RowError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, RowError> {
+        match self.entries.next() {
+            Some((name, value)) => {
+                self.value = Some(value);
+                seed.deserialize(name.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, RowError> {
+        let value = self.value.take().ok_or_else(|| RowError("value requested before key".into()))?;
+        seed.deserialize(CellDeserializer(value))
+    }
+}
+
+/// Deserializer for a single cell.
+///
+/// The text protocol sends every non-NULL value as bytes, so numeric and
+/// boolean targets parse the textual representation.
+struct CellDeserializer<'a>(&'a Value);
+
+impl CellDeserializer<'_> {
+    fn text(&self) -> Result<String, RowError> {
+        match self.0 {
+            Value::Bytes(bytes) => String::from_utf8(bytes.clone())
+                .map_err(|_| RowError("column is not valid UTF-8".into())),
+            Value::NULL => Err(RowError("unexpected NULL".into())),
+            other => Ok(crate::format::format_value(other)),
+        }
+    }
+
+    fn parse<T: std::str::FromStr>(&self, kind: &str) -> Result<T, RowError> {
+        let text = self.text()?;
+        text.trim().parse().map_err(|_| RowError(format!("invalid {} value '{}'", kind, text)))
+    }
+}
+
+macro_rules! deserialize_number {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RowError> {
+            let value: $ty = match *self.0 {
+                Value::Int(n) => <$ty>::try_from(n).map_err(de::Error::custom)?,
+                Value::UInt(n) => <$ty>::try_from(n).map_err(de::Error::custom)?,
+                _ => self.parse(stringify!($ty))?,
+            };
+            visitor.$visit(value)
+        }
+    };
+}
+
+macro_rules! deserialize_float {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RowError> {
+            let value: $ty = match *self.0 {
+                Value::Int(n) => n as $ty,
+                Value::UInt(n) => n as $ty,
+                Value::Float(f) => f as $ty,
+                Value::Double(d) => d as $ty,
+                _ => self.parse(stringify!($ty))?,
+            };
+            visitor.$visit(value)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for CellDeserializer<'_> {
+    type Error = RowError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RowError> {
+        match self.0 {
+            Value::NULL => visitor.visit_none(),
+            Value::Int(n) => visitor.visit_i64(*n),
+            Value::UInt(n) => visitor.visit_u64(*n),
+            Value::Float(f) => visitor.visit_f32(*f),
+            Value::Double(d) => visitor.visit_f64(*d),
+            Value::Bytes(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => visitor.visit_string(text.to_string()),
+                Err(_) => visitor.visit_byte_buf(bytes.clone()),
+            },
+            other => visitor.visit_string(crate::format::format_value(other)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RowError> {
+        match self.0 {
+            Value::NULL => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RowError> {
+        match *self.0 {
+            Value::Int(n) => visitor.visit_bool(n != 0),
+            Value::UInt(n) => visitor.visit_bool(n != 0),
+            _ => match self.text()?.trim().to_lowercase().as_str() {
+                "1" | "true" => visitor.visit_bool(true),
+                "0" | "false" => visitor.visit_bool(false),
+                other => Err(RowError(format!("invalid bool value '{}'", other))),
+            },
+        }
+    }
+
+    deserialize_number!(deserialize_i8, visit_i8, i8);
+    deserialize_number!(deserialize_i16, visit_i16, i16);
+    deserialize_number!(deserialize_i32, visit_i32, i32);
+    deserialize_number!(deserialize_i64, visit_i64, i64);
+    deserialize_number!(deserialize_u8, visit_u8, u8);
+    deserialize_number!(deserialize_u16, visit_u16, u16);
+    deserialize_number!(deserialize_u32, visit_u32, u32);
+    deserialize_number!(deserialize_u64, visit_u64, u64);
+    deserialize_float!(deserialize_f32, visit_f32, f32);
+    deserialize_float!(deserialize_f64, visit_f64, f64);
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, RowError> {
+        visitor.visit_enum(self.text()?.into_deserializer())
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, RowError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
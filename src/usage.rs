@@ -0,0 +1,128 @@
+use crate::history::is_sensitive;
+use chrono::{DateTime, Local, Timelike};
+use dirs::data_local_dir;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One executed statement as recorded in the local usage log.
+pub struct UsageEntry {
+    pub time: DateTime<Local>,
+    pub connection: String,
+    pub ok: bool,
+    pub statement: String,
+}
+
+/// Aggregates shown by `\stats`.
+pub struct UsageStats {
+    pub per_connection: Vec<(String, usize, usize)>,
+    pub top_statements: Vec<(String, usize)>,
+    pub per_hour: [usize; 24],
+    pub total: usize,
+    pub errors: usize,
+}
+
+/// Size past which the usage log is rotated, keeping a single older file,
+/// so the log and what `\stats` reads stay bounded.
+const MAX_LOG_BYTES: u64 = 4 * 1024 * 1024;
+
+/// The usage log lives next to the history files and never leaves the machine.
+fn usage_path() -> Option<PathBuf> {
+    let mut path = data_local_dir()?;
+    path.push("rusql");
+    fs::create_dir_all(&path).ok()?;
+    path.push("usage.tsv");
+    Some(path)
+}
+
+/// The previous usage log, replaced at each rotation.
+fn rotated_path(path: &Path) -> PathBuf {
+    path.with_extension("tsv.1")
+}
+
+/// Append a statement typed at the prompt to the usage log. Failures are
+/// ignored so statistics can never get in the way of running queries.
+pub fn record(connection: &str, statement: &str, ok: bool, sensitive_columns: &[String]) {
+    let Some(path) = usage_path() else { return };
+    if fs::metadata(&path).is_ok_and(|metadata| metadata.len() >= MAX_LOG_BYTES) {
+        let _ = fs::rename(&path, rotated_path(&path));
+    }
+    let statement = if is_sensitive(statement, sensitive_columns) {
+        "<redacted>".to_string()
+    } else {
+        normalize(statement)
+    };
+
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(
+            file,
+            "{}\t{}\t{}\t{}",
+            Local::now().to_rfc3339(),
+            connection,
+            if ok { 1 } else { 0 },
+            statement
+        );
+    }
+}
+
+/// Read every entry recorded at or after `since`, from the current and the
+/// rotated log.
+pub fn load(since: Option<DateTime<Local>>) -> Vec<UsageEntry> {
+    let Some(path) = usage_path() else { return Vec::new() };
+    let contents: String = [rotated_path(&path), path].iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .collect();
+
+    contents.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            let time = DateTime::parse_from_rfc3339(fields.next()?).ok()?.with_timezone(&Local);
+            let connection = fields.next()?.to_string();
+            let ok = fields.next()? == "1";
+            let statement = fields.next()?.to_string();
+            Some(UsageEntry { time, connection, ok, statement })
+        })
+        .filter(|entry| since.is_none_or(|since| entry.time >= since))
+        .collect()
+}
+
+pub fn aggregate(entries: &[UsageEntry], top: usize) -> UsageStats {
+    let mut connections: HashMap<&str, (usize, usize)> = HashMap::new();
+    let mut statements: HashMap<&str, usize> = HashMap::new();
+    let mut per_hour = [0; 24];
+    let mut errors = 0;
+
+    for entry in entries {
+        let counts = connections.entry(&entry.connection).or_default();
+        counts.0 += 1;
+        if !entry.ok {
+            counts.1 += 1;
+            errors += 1;
+        }
+        *statements.entry(&entry.statement).or_default() += 1;
+        per_hour[entry.time.hour() as usize] += 1;
+    }
+
+    let mut per_connection: Vec<(String, usize, usize)> = connections.into_iter()
+        .map(|(name, (total, errors))| (name.to_string(), total, errors))
+        .collect();
+    per_connection.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut top_statements: Vec<(String, usize)> = statements.into_iter()
+        .map(|(statement, count)| (statement.to_string(), count))
+        .collect();
+    top_statements.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_statements.truncate(top);
+
+    UsageStats { per_connection, top_statements, per_hour, total: entries.len(), errors }
+}
+
+fn normalize(statement: &str) -> String {
+    statement.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(';')
+        .trim_end()
+        .to_string()
+}
@@ -0,0 +1,294 @@
+use crate::{MySQLClient, QueryResult};
+use mysql::prelude::*;
+use mysql::Value;
+use rusql::bulk::{quote_identifier, quote_table};
+use rusql::error::{Result, RusqlError};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// `\users`: accounts with their authentication plugin, whether they are
+/// locked and when their password expires.
+pub fn users(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if !args.is_empty() {
+        return Err(RusqlError::Command("Usage: \\users".into()));
+    }
+    // A per-account password_lifetime of NULL means the server default applies
+    let accounts: Vec<(String, String, String, String, String, Option<String>)> = client.conn.query(
+        "SELECT User, Host, plugin, account_locked, password_expired, \
+         CASE WHEN password_expired = 'Y' THEN NULL \
+              WHEN COALESCE(password_lifetime, @@default_password_lifetime) > 0 \
+              THEN CAST(DATE_ADD(password_last_changed, \
+                   INTERVAL COALESCE(password_lifetime, @@default_password_lifetime) DAY) AS CHAR) \
+              ELSE 'never' END \
+         FROM mysql.user ORDER BY User, Host",
+    )?;
+
+    let rows: Vec<Vec<String>> = accounts.iter()
+        .map(|(user, host, plugin, locked, expired, expires)| {
+            let locked = match locked.as_str() {
+                "Y" => client.paint("locked", client.theme.warning),
+                _ => String::new(),
+            };
+            let expires = match (expired.as_str(), expires) {
+                ("Y", _) => client.paint("expired", client.theme.error),
+                (_, Some(expires)) => expires.clone(),
+                (_, None) => String::new(),
+            };
+            vec![user.clone(), host.clone(), plugin.clone(), locked, expires]
+        })
+        .collect();
+    let locked = accounts.iter().filter(|account| account.3 == "Y").count();
+    let expired = accounts.iter().filter(|account| account.4 == "Y").count();
+    let summary = format!("{} accounts, {} locked, {} with an expired password", accounts.len(), locked, expired);
+    let headers = ["User", "Host", "Plugin", "Locked", "Password expires"];
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+/// One `GRANT ... ON object TO ...` line of `SHOW GRANTS`.
+struct Grant {
+    privileges: Vec<String>,
+    /// `*.*`, `db.*`, `db.table`, or `PROCEDURE db.name`, unquoted.
+    object: String,
+    grant_option: bool,
+}
+
+/// `\grants <user>[@host]`: `SHOW GRANTS` as a tree of global, database and
+/// table or routine privileges, with granted roles listed last.
+pub fn grants(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    if args.is_empty() {
+        return Err(RusqlError::Command("Usage: \\grants <user>[@host]".into()));
+    }
+    let (user, host) = parse_account(args);
+    let account = account_literal(client, &user, &host);
+    let lines: Vec<String> = client.conn.query(format!("SHOW GRANTS FOR {}", account))?;
+
+    let mut global = Vec::new();
+    let mut databases: BTreeMap<String, (Vec<Grant>, Vec<Grant>)> = BTreeMap::new();
+    let mut roles = Vec::new();
+    for line in &lines {
+        match parse_grant(line) {
+            // `*.*`, and `PROXY ON ''@''` which names an account, not a table
+            Some(grant) if grant.object == "*.*" || !grant.object.contains('.') => global.push(grant),
+            Some(grant) => {
+                let (routine, name) = grant.object.split_once(' ')
+                    .map_or(("", grant.object.as_str()), |(kind, name)| (kind, name));
+                let (db, object) = name.split_once('.').unwrap_or((name, "*"));
+                let entry = databases.entry(db.to_string()).or_default();
+                if object == "*" && routine.is_empty() {
+                    entry.0.push(grant);
+                } else {
+                    entry.1.push(grant);
+                }
+            }
+            None => roles.extend(granted_roles(line)),
+        }
+    }
+
+    let mut rows = Vec::new();
+    for grant in &global {
+        let object = if grant.object == "*.*" { "*.* (global)".to_string() } else { grant.object.clone() };
+        rows.push(vec![object, grant.privileges.join(", "), option(grant)]);
+    }
+    for (db, (on_db, on_objects)) in &databases {
+        match on_db.as_slice() {
+            [] => rows.push(vec![db.clone(), String::new(), String::new()]),
+            grants => {
+                for grant in grants {
+                    rows.push(vec![db.clone(), grant.privileges.join(", "), option(grant)]);
+                }
+            }
+        }
+        for grant in on_objects {
+            let object = grant.object.split_once('.').map_or(grant.object.as_str(), |(_, object)| object);
+            let kind = grant.object.split_once(' ').map_or(String::new(), |(kind, _)| format!("{} ", kind.to_lowercase()));
+            rows.push(vec![format!("└─ {}{}", kind, object), grant.privileges.join(", "), option(grant)]);
+        }
+    }
+    if !roles.is_empty() {
+        rows.push(vec!["roles".to_string(), roles.join(", "), String::new()]);
+    }
+
+    let summary = format!("'{}'@'{}': {} grants, {} databases, {} roles", user, host, lines.len(), databases.len(), roles.len());
+    let headers = ["Object", "Privileges", "Grant option"];
+    Ok(Some(QueryResult { table: client.build_table(&headers, rows), summary }))
+}
+
+fn option(grant: &Grant) -> String {
+    if grant.grant_option { "yes".to_string() } else { String::new() }
+}
+
+/// `name`, `name@host` or `'name'@'host'`; the host defaults to `%`.
+fn parse_account(text: &str) -> (String, String) {
+    let unquote = |part: &str| part.trim().trim_matches(|c| c == '\'' || c == '"' || c == '`').to_string();
+    match text.rsplit_once('@') {
+        Some((user, host)) => (unquote(user), unquote(host)),
+        None => (unquote(text), "%".to_string()),
+    }
+}
+
+/// An account as written in account management statements, `'name'@'host'`.
+fn account_literal(client: &mut MySQLClient, user: &str, host: &str) -> String {
+    let no_backslash_escape = client.conn.no_backslash_escape();
+    format!(
+        "{}@{}",
+        Value::from(user).as_sql(no_backslash_escape),
+        Value::from(host).as_sql(no_backslash_escape)
+    )
+}
+
+/// Split a `GRANT privileges ON object TO account` line; `None` for role
+/// grants, which have no `ON`.
+fn parse_grant(line: &str) -> Option<Grant> {
+    let rest = line.strip_prefix("GRANT ")?;
+    let (privileges, rest) = split_outside_quotes(rest, " ON ")?;
+    let (object, rest) = split_outside_quotes(rest, " TO ")?;
+    Some(Grant {
+        privileges: split_outside_quotes_all(privileges, ',').into_iter().map(|p| p.trim().replace('`', "")).collect(),
+        object: object.replace('`', ""),
+        grant_option: rest.ends_with("WITH GRANT OPTION"),
+    })
+}
+
+/// Roles of a `GRANT role, ... TO account` line.
+fn granted_roles(line: &str) -> Vec<String> {
+    line.strip_prefix("GRANT ")
+        .and_then(|rest| split_outside_quotes(rest, " TO "))
+        .map(|(roles, _)| split_outside_quotes_all(roles, ',').into_iter().map(|role| role.trim().replace('`', "")).collect())
+        .unwrap_or_default()
+}
+
+/// Split at the first `separator` outside backticks, quotes and parentheses.
+fn split_outside_quotes<'a>(text: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    let mut quote = None;
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '`' | '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, _) if depth == 0 && text[i..].starts_with(separator) => {
+                return Some((&text[..i], &text[i + separator.len()..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split at every `separator` outside backticks, quotes and parentheses, so
+/// `SELECT (a, b), INSERT` stays two privileges.
+fn split_outside_quotes_all(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    let separator = separator.to_string();
+    while let Some((part, after)) = split_outside_quotes(rest, &separator) {
+        parts.push(part);
+        rest = after;
+    }
+    parts.push(rest);
+    parts
+}
+
+/// `\create-user`: ask for the account, password, plugin and privileges,
+/// show the statements and run them once confirmed.
+pub fn create_user(client: &mut MySQLClient, args: &str) -> Result<Option<QueryResult>> {
+    let (user, host) = match args {
+        "" => {
+            let user = ask("User name: ")?;
+            if user.is_empty() {
+                return Err(RusqlError::Cancelled);
+            }
+            (user, ask_or("Host", "%")?)
+        }
+        account => parse_account(account),
+    };
+    let password = ask_secret("Password (blank for a random one): ")?;
+    let plugin = ask_or("Authentication plugin", "server default")?;
+    let db = ask("Database to grant on, db or db.table (blank for none): ")?;
+    let privileges = if db.is_empty() { String::new() } else { ask_or("Privileges", "SELECT")? };
+
+    let no_backslash_escape = client.conn.no_backslash_escape();
+    let account = account_literal(client, &user, &host);
+    let mut create = format!("CREATE USER {}", account);
+    if plugin != "server default" {
+        create.push_str(&format!(" IDENTIFIED WITH {}", quote_identifier(&plugin)));
+    } else {
+        create.push_str(" IDENTIFIED");
+    }
+    if password.is_empty() {
+        // The server generates the password and returns it (MySQL 8.0.18+)
+        create.push_str(" BY RANDOM PASSWORD");
+    } else {
+        create.push_str(&format!(" BY {}", Value::from(password.as_str()).as_sql(no_backslash_escape)));
+    }
+    let mut statements = vec![create];
+    if !db.is_empty() {
+        let object = match db.replace('`', "").split_once('.') {
+            Some((db, "*")) => format!("{}.*", quote_identifier(db)),
+            Some(_) => quote_table(&db),
+            None => format!("{}.*", quote_identifier(&db)),
+        };
+        statements.push(format!("GRANT {} ON {} TO {}", privileges.to_uppercase(), object, account));
+    }
+
+    println!();
+    for statement in &statements {
+        // Never echo the typed password
+        let shown = match statement.find(" BY '") {
+            Some(pos) if !password.is_empty() => format!("{} BY '***'", &statement[..pos]),
+            _ => statement.clone(),
+        };
+        println!("{};", shown);
+    }
+    if !ask("Run these statements? [y/N] ")?.eq_ignore_ascii_case("y") {
+        client.notice("Nothing was run");
+        return Ok(None);
+    }
+
+    let mut generated = None;
+    for statement in &statements {
+        let row: Option<mysql::Row> = client.conn.query_first(statement)?;
+        // CREATE USER ... RANDOM PASSWORD returns user, host, password, auth factor
+        generated = generated.or(row.and_then(|row| row.get_opt::<String, _>(2).and_then(|password| password.ok())));
+    }
+    client.notice(&format!("Created '{}'@'{}'", user, host));
+    if let Some(password) = generated {
+        println!("Generated password (shown once): {}", client.paint(&password, client.theme.warning));
+    }
+    Ok(None)
+}
+
+/// One line of input after `prompt`, trimmed; Ctrl-D cancels.
+pub fn ask(prompt: &str) -> Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer)? == 0 {
+        return Err(RusqlError::Cancelled);
+    }
+    Ok(answer.trim().to_string())
+}
+
+fn ask_or(prompt: &str, default: &str) -> Result<String> {
+    let answer = ask(&format!("{} [{}]: ", prompt, default))?;
+    Ok(if answer.is_empty() { default.to_string() } else { answer })
+}
+
+/// Like [`ask`], without echoing what is typed when stdin is a terminal.
+fn ask_secret(prompt: &str) -> Result<String> {
+    let terminal = io::stdin().is_terminal();
+    let stty = |arg: &str| {
+        let _ = std::process::Command::new("stty").arg(arg).stdin(std::process::Stdio::inherit()).status();
+    };
+    if terminal {
+        stty("-echo");
+    }
+    let answer = ask(prompt);
+    if terminal {
+        stty("echo");
+        println!();
+    }
+    answer
+}
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+/// Client-side variables set with `\set` or `--var`.
+pub type Variables = BTreeMap<String, String>;
+
+/// Parse a `name=value` pair given to `--var`.
+pub fn parse_var(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s.split_once('=')
+        .ok_or_else(|| format!("invalid name=value: no `=` found in `{}`", s))?;
+    if !is_valid_name(name) {
+        return Err(format!("invalid variable name `{}`", name));
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_name_char)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Substitute `${var}` and `:var` references in a statement.
+///
+/// `${var}` is replaced everywhere, including inside string literals, so it
+/// can be used to template quoted values. `:var` is only recognised outside
+/// quotes so time literals and `:=` assignments are left alone. Unknown
+/// variables are kept verbatim.
+pub fn interpolate(sql: &str, vars: &Variables) -> String {
+    if vars.is_empty() {
+        return sql.to_string();
+    }
+
+    let chars: Vec<char> = sql.chars().collect();
+    let mut result = String::with_capacity(sql.len());
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '$'
+            && chars.get(i + 1) == Some(&'{')
+            && let Some(end) = chars[i + 2..].iter().position(|&c| c == '}')
+        {
+            let name: String = chars[i + 2..i + 2 + end].iter().collect();
+            if let Some(value) = vars.get(&name) {
+                result.push_str(value);
+                i += end + 3;
+                continue;
+            }
+        }
+
+        match quote {
+            Some(q) => {
+                if c == '\\' && q != '`' {
+                    result.push(c);
+                    if let Some(&next) = chars.get(i + 1) {
+                        result.push(next);
+                    }
+                    i += 2;
+                    continue;
+                }
+                if c == q {
+                    quote = None;
+                }
+            }
+            None if c == '\'' || c == '"' || c == '`' => quote = Some(c),
+            None if c == ':' && i + 1 < chars.len() && is_name_char(chars[i + 1]) => {
+                let len = chars[i + 1..].iter().take_while(|&&c| is_name_char(c)).count();
+                let name: String = chars[i + 1..i + 1 + len].iter().collect();
+                if let Some(value) = vars.get(&name) {
+                    result.push_str(value);
+                    i += len + 1;
+                    continue;
+                }
+            }
+            None => {}
+        }
+
+        result.push(c);
+        i += 1;
+    }
+
+    result
+}
@@ -0,0 +1,163 @@
+//! Excel output: a worksheet with a bold, frozen header row and cells typed
+//! after their columns, so numbers and dates open as numbers and dates.
+
+use crate::format::format_value;
+use crate::sink::{OutputSink, ValueKind};
+use mysql::consts::ColumnType;
+use mysql::{Column, Value};
+use rust_xlsxwriter::{ExcelDateTime, Format, FormatBorder, Workbook, Worksheet, XlsxError};
+use std::io::{self, Write};
+
+/// Integers beyond this lose digits as Excel numbers and are written as text.
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// How the cells of a column are written.
+#[derive(Debug, Clone)]
+enum CellKind {
+    Boolean,
+    Integer,
+    /// DECIMAL with its scale.
+    Decimal(u8),
+    Float,
+    Date,
+    DateTime,
+    Time,
+    Text,
+}
+
+impl CellKind {
+    fn of(column: &Column) -> Self {
+        match (ValueKind::of(column), column.column_type()) {
+            (ValueKind::Boolean, _) => CellKind::Boolean,
+            (ValueKind::Number, ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL) => {
+                CellKind::Decimal(column.decimals())
+            }
+            (ValueKind::Number, ColumnType::MYSQL_TYPE_FLOAT | ColumnType::MYSQL_TYPE_DOUBLE) => CellKind::Float,
+            (ValueKind::Number, _) => CellKind::Integer,
+            (ValueKind::Date, ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE) => CellKind::Date,
+            (ValueKind::Date, ColumnType::MYSQL_TYPE_TIME | ColumnType::MYSQL_TYPE_TIME2) => CellKind::Time,
+            (ValueKind::Date, _) => CellKind::DateTime,
+            (ValueKind::Text, _) => CellKind::Text,
+        }
+    }
+
+    /// Number format of the column's cells.
+    fn format(&self) -> Format {
+        let format = Format::new();
+        match self {
+            CellKind::Integer => format.set_num_format("0"),
+            CellKind::Decimal(0) => format.set_num_format("#,##0"),
+            CellKind::Decimal(scale) => format.set_num_format(format!("#,##0.{}", "0".repeat(*scale as usize))),
+            CellKind::Date => format.set_num_format("yyyy-mm-dd"),
+            CellKind::DateTime => format.set_num_format("yyyy-mm-dd hh:mm:ss"),
+            CellKind::Time => format.set_num_format("hh:mm:ss"),
+            CellKind::Boolean | CellKind::Float | CellKind::Text => format,
+        }
+    }
+}
+
+/// An `.xlsx` workbook with the result on one sheet. Values that don't fit
+/// their column's type (zero dates, TIME beyond a day, huge integers) are
+/// written as text. The workbook is built in memory and written to `out`
+/// by [`OutputSink::summary`]; a sheet holds at most 1,048,575 rows.
+///
+/// ```
+/// use mysql::consts::ColumnType;
+/// use mysql::{Column, Value};
+/// use rusql::sink::OutputSink;
+/// use rusql::xlsx_sink::XlsxSink;
+///
+/// let columns = [
+///     Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"customer"),
+///     Column::new(ColumnType::MYSQL_TYPE_DATE).with_name(b"ordered"),
+/// ];
+/// let mut out = Vec::new();
+/// let mut sink = XlsxSink::new(&mut out);
+/// sink.begin(&columns)?;
+/// sink.row_began()?;
+/// sink.cell(0, &Value::from("ACME"))?;
+/// sink.cell(1, &Value::from("2024-05-01"))?;
+/// sink.row_ended()?;
+/// sink.summary("1 row in set")?;
+/// // An .xlsx file is a zip archive
+/// assert!(out.starts_with(b"PK"));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct XlsxSink<W: Write> {
+    out: W,
+    sheet: Worksheet,
+    kinds: Vec<CellKind>,
+    formats: Vec<Format>,
+    row: u32,
+}
+
+impl<W: Write> XlsxSink<W> {
+    pub fn new(out: W) -> Self {
+        XlsxSink { out, sheet: Worksheet::new(), kinds: Vec::new(), formats: Vec::new(), row: 0 }
+    }
+
+    fn write_cell(&mut self, index: usize, value: &Value) -> Result<(), XlsxError> {
+        let (row, column) = (self.row, index as u16);
+        let text = match value {
+            Value::NULL => return Ok(()),
+            other => format_value(other),
+        };
+        let format = &self.formats[index];
+        match self.kinds[index] {
+            CellKind::Boolean => match text.parse::<i64>() {
+                Ok(n) => self.sheet.write_boolean(row, column, n != 0)?,
+                Err(_) => self.sheet.write_string(row, column, text)?,
+            },
+            CellKind::Integer | CellKind::Decimal(_) | CellKind::Float => match text.parse::<f64>() {
+                Ok(n) if n.is_finite() && n.abs() < MAX_EXACT_INTEGER => {
+                    self.sheet.write_number_with_format(row, column, n, format)?
+                }
+                _ => self.sheet.write_string(row, column, text)?,
+            },
+            CellKind::Date | CellKind::DateTime | CellKind::Time => match ExcelDateTime::parse_from_str(&text) {
+                Ok(datetime) if !text.starts_with("0000") => {
+                    self.sheet.write_datetime_with_format(row, column, &datetime, format)?
+                }
+                _ => self.sheet.write_string(row, column, text)?,
+            },
+            CellKind::Text => self.sheet.write_string(row, column, text)?,
+        };
+        Ok(())
+    }
+}
+
+impl<W: Write> OutputSink for XlsxSink<W> {
+    fn begin(&mut self, columns: &[Column]) -> io::Result<()> {
+        self.kinds = columns.iter().map(CellKind::of).collect();
+        self.formats = self.kinds.iter().map(CellKind::format).collect();
+        let header = Format::new().set_bold().set_border_bottom(FormatBorder::Thin);
+        for (index, column) in columns.iter().enumerate() {
+            self.sheet.write_string_with_format(0, index as u16, column.name_str(), &header).map_err(io::Error::other)?;
+        }
+        self.sheet.set_freeze_panes(1, 0).map_err(io::Error::other)?;
+        self.row = 0;
+        Ok(())
+    }
+
+    fn row_began(&mut self) -> io::Result<()> {
+        self.row += 1;
+        Ok(())
+    }
+
+    fn cell(&mut self, index: usize, value: &Value) -> io::Result<()> {
+        self.write_cell(index, value).map_err(io::Error::other)
+    }
+
+    fn row_ended(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn summary(&mut self, _summary: &str) -> io::Result<()> {
+        self.sheet.autofit();
+        let mut workbook = Workbook::new();
+        workbook.push_worksheet(std::mem::take(&mut self.sheet));
+        let bytes = workbook.save_to_buffer().map_err(io::Error::other)?;
+        self.out.write_all(&bytes)?;
+        self.out.flush()
+    }
+}